@@ -0,0 +1,2 @@
+-- 用户记录版本号，每次修改递增，用于ETag及乐观并发控制
+ALTER TABLE users ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,16 @@
+-- 幂等键：记录请求指纹及完整响应，重试时重放
+CREATE TABLE idempotency_keys (
+    idempotency_key TEXT    PRIMARY KEY,
+    fingerprint     TEXT    NOT NULL,
+    -- 请求处理完成前为空，表示处理中
+    status          INTEGER,
+    headers         TEXT,
+    body            BLOB,
+    created_at      TEXT    NOT NULL,
+    -- 过期时间（Unix时间戳，秒）
+    expires_at      INTEGER NOT NULL,
+    -- 租约到期时间（Unix时间戳，秒），处理中的请求崩溃后到期的键可以重新占用
+    locked_until    INTEGER
+);
+
+CREATE INDEX idx_idempotency_keys_expires_at ON idempotency_keys (expires_at);
@@ -0,0 +1,4 @@
+-- 软删除时间，为空表示未删除
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX idx_users_deleted_at ON users (deleted_at);
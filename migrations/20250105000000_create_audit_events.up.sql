@@ -0,0 +1,31 @@
+-- 审计事件：记录每次数据变更的操作者、动作及变更内容
+CREATE TABLE audit_events (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    occurred_at TEXT    NOT NULL,
+    -- 操作者类型：anonymous、system、user
+    actor_type  TEXT    NOT NULL,
+    actor_id    TEXT,
+    actor_name  TEXT,
+    action      TEXT    NOT NULL,
+    entity_type TEXT    NOT NULL,
+    entity_id   TEXT    NOT NULL,
+    -- 变更字段（JSON），格式为 {"字段": {"before": 旧值, "after": 新值}}
+    changes     TEXT    NOT NULL,
+    request_id  TEXT,
+    ip          TEXT
+);
+
+CREATE INDEX idx_audit_events_entity ON audit_events (entity_type, entity_id);
+CREATE INDEX idx_audit_events_actor ON audit_events (actor_id);
+CREATE INDEX idx_audit_events_occurred_at ON audit_events (occurred_at);
+
+-- 审计记录只允许追加，拒绝修改和删除
+CREATE TRIGGER audit_events_no_update BEFORE UPDATE ON audit_events
+BEGIN
+    SELECT RAISE(ABORT, 'audit_events is append-only');
+END;
+
+CREATE TRIGGER audit_events_no_delete BEFORE DELETE ON audit_events
+BEGIN
+    SELECT RAISE(ABORT, 'audit_events is append-only');
+END;
@@ -0,0 +1,26 @@
+-- 事务性发件箱：与业务数据在同一个事务中写入，由后台任务投递给事件订阅者
+CREATE TABLE outbox_events (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type      TEXT    NOT NULL,
+    -- 事件内容（JSON）
+    payload         TEXT    NOT NULL,
+    occurred_at     TEXT    NOT NULL,
+    -- 已领取投递的次数
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    -- 最早可领取的时间（Unix时间戳，秒），领取后推迟到租约到期，失败后推迟到下次重试
+    next_attempt_at INTEGER NOT NULL,
+    last_error      TEXT
+);
+
+CREATE INDEX idx_outbox_events_next_attempt_at ON outbox_events (next_attempt_at);
+
+-- 死信：超过最大投递次数仍失败的事件，保留原事件ID，可重新放回发件箱
+CREATE TABLE dead_letter_events (
+    id          INTEGER PRIMARY KEY,
+    event_type  TEXT    NOT NULL,
+    payload     TEXT    NOT NULL,
+    occurred_at TEXT    NOT NULL,
+    attempts    INTEGER NOT NULL,
+    last_error  TEXT,
+    failed_at   TEXT    NOT NULL
+);
@@ -0,0 +1,45 @@
+-- Webhook订阅
+CREATE TABLE webhooks (
+    id                   INTEGER PRIMARY KEY AUTOINCREMENT,
+    url                  TEXT    NOT NULL,
+    -- 订阅的事件类型（JSON数组）
+    event_types          TEXT    NOT NULL,
+    -- 签名密钥，投递时计算HMAC需要原文
+    secret               TEXT    NOT NULL,
+    description          TEXT,
+    enabled              INTEGER NOT NULL DEFAULT 1,
+    -- 连续投递失败次数，成功后清零
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    disabled_reason      TEXT,
+    created_at           TEXT    NOT NULL,
+    updated_at           TEXT    NOT NULL
+);
+
+-- Webhook投递记录
+CREATE TABLE webhook_deliveries (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    webhook_id      INTEGER NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    -- 对应的领域事件ID
+    event_id        INTEGER NOT NULL,
+    event_type      TEXT    NOT NULL,
+    -- 请求体（JSON）
+    payload         TEXT    NOT NULL,
+    -- pending、succeeded、failed
+    status          TEXT    NOT NULL DEFAULT 'pending',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    -- 最早可投递的时间（Unix时间戳，秒），投递中推迟到租约到期，结束后为空
+    next_attempt_at INTEGER,
+    response_status INTEGER,
+    response_body   TEXT,
+    error           TEXT,
+    -- 手动重新投递时为原投递记录ID
+    redelivery_of   INTEGER,
+    created_at      TEXT    NOT NULL,
+    delivered_at    TEXT
+);
+
+-- 领域事件重复投递时不重复创建投递记录
+CREATE UNIQUE INDEX uq_webhook_deliveries_event ON webhook_deliveries (webhook_id, event_id)
+    WHERE redelivery_of IS NULL;
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries (status, next_attempt_at);
+CREATE INDEX idx_webhook_deliveries_webhook ON webhook_deliveries (webhook_id, id);
@@ -0,0 +1,28 @@
+-- 后台任务
+CREATE TABLE jobs (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- 任务类型，对应 `Job::KIND`
+    kind          TEXT    NOT NULL,
+    -- 任务参数（JSON）
+    payload       TEXT    NOT NULL,
+    -- scheduled、running、succeeded、failed、cancelled
+    status        TEXT    NOT NULL DEFAULT 'scheduled',
+    attempts      INTEGER NOT NULL DEFAULT 0,
+    -- 最大执行次数，为空时使用配置的默认值
+    max_attempts  INTEGER,
+    -- 最早可执行的时间（Unix时间戳，秒）
+    run_at        INTEGER NOT NULL,
+    -- 执行租约到期时间（Unix时间戳，秒），到期仍未结束的任务可被重新领取
+    locked_until  INTEGER,
+    -- 唯一键，同一键同时只能存在一个等待或执行中的任务
+    unique_key    TEXT,
+    last_error    TEXT,
+    created_at    TEXT    NOT NULL,
+    updated_at    TEXT    NOT NULL,
+    finished_at   TEXT
+);
+
+CREATE UNIQUE INDEX uq_jobs_unique_key ON jobs (unique_key)
+    WHERE unique_key IS NOT NULL AND status IN ('scheduled', 'running');
+CREATE INDEX idx_jobs_due ON jobs (status, run_at);
+CREATE INDEX idx_jobs_kind ON jobs (kind, id);
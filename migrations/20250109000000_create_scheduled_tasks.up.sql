@@ -0,0 +1,18 @@
+-- 定时任务的执行状态，同时作为多实例间的执行锁
+CREATE TABLE scheduled_tasks (
+    name             TEXT    PRIMARY KEY,
+    -- 当前的cron表达式，未按计划执行（只能手动触发）时为空
+    cron             TEXT,
+    -- 下次执行时间（Unix时间戳，秒），为空时不会自动执行
+    next_run_at      INTEGER,
+    -- 执行锁：持有锁的实例及锁到期时间（Unix时间戳，秒）
+    locked_by        TEXT,
+    locked_until     INTEGER,
+    last_started_at  TEXT,
+    last_finished_at TEXT,
+    -- 最后一次执行的结果：succeeded、failed
+    last_status      TEXT,
+    last_error       TEXT,
+    last_duration_ms INTEGER,
+    updated_at       TEXT    NOT NULL
+);
@@ -0,0 +1,21 @@
+-- 邮箱验证时间，为空表示未验证；修改邮箱后清空
+ALTER TABLE users ADD COLUMN email_verified_at TEXT;
+
+-- 邮箱验证、密码重置等一次性令牌
+CREATE TABLE user_tokens (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id    INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- 用途：verify_email、reset_password
+    purpose    TEXT    NOT NULL,
+    -- 令牌的SHA-256摘要，不保存原文
+    token_hash TEXT    NOT NULL UNIQUE,
+    -- 签发时的邮箱，邮箱修改后验证令牌失效
+    email      TEXT    NOT NULL,
+    -- 过期时间（Unix时间戳，秒）
+    expires_at INTEGER NOT NULL,
+    -- 使用时间，为空表示未使用
+    used_at    TEXT,
+    created_at TEXT    NOT NULL
+);
+
+CREATE INDEX idx_user_tokens_user ON user_tokens (user_id, purpose);
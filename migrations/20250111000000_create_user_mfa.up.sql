@@ -0,0 +1,24 @@
+-- 两步验证（TOTP）
+CREATE TABLE user_mfa (
+    user_id        INTEGER PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    -- TOTP密钥（Base32），校验验证码时需要原文
+    secret         TEXT    NOT NULL,
+    -- 启用时间，为空表示已开始绑定但尚未确认
+    enabled_at     TEXT,
+    -- 最后一次通过校验的步数，同一步内的验证码不能重复使用
+    last_used_step INTEGER,
+    created_at     TEXT    NOT NULL,
+    updated_at     TEXT    NOT NULL
+);
+
+-- 两步验证恢复码，每个只能使用一次，重新生成时全部替换
+CREATE TABLE mfa_recovery_codes (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id    INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- 恢复码的SHA-256摘要，不保存原文
+    code_hash  TEXT    NOT NULL,
+    used_at    TEXT,
+    created_at TEXT    NOT NULL
+);
+
+CREATE UNIQUE INDEX uq_mfa_recovery_codes ON mfa_recovery_codes (user_id, code_hash);
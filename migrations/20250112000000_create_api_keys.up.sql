@@ -0,0 +1,16 @@
+-- API密钥
+CREATE TABLE api_keys (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    name         TEXT    NOT NULL,
+    -- 密钥的前缀部分，用于识别密钥，可以公开显示
+    prefix       TEXT    NOT NULL UNIQUE,
+    -- 完整密钥的SHA-256摘要，不保存原文
+    key_hash     TEXT    NOT NULL UNIQUE,
+    -- 授权范围（JSON数组）
+    scopes       TEXT    NOT NULL,
+    expires_at   TEXT,
+    last_used_at TEXT,
+    revoked_at   TEXT,
+    created_at   TEXT    NOT NULL,
+    updated_at   TEXT    NOT NULL
+);
@@ -0,0 +1,29 @@
+-- 外部身份提供方（OpenID Connect）登录中的授权请求，回调时使用一次后删除
+CREATE TABLE oidc_login_states (
+    -- 授权请求的 state 参数
+    state         TEXT    PRIMARY KEY,
+    provider      TEXT    NOT NULL,
+    -- ID令牌中应携带的 nonce
+    nonce         TEXT    NOT NULL,
+    -- PKCE校验码，兑换授权码时发送给身份提供方
+    code_verifier TEXT    NOT NULL,
+    -- 过期时间（Unix时间戳，秒）
+    expires_at    INTEGER NOT NULL,
+    created_at    TEXT    NOT NULL
+);
+
+-- 用户在外部身份提供方中的身份，同一提供方的同一身份只能关联一个用户
+CREATE TABLE user_identities (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id       INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    provider      TEXT    NOT NULL,
+    -- ID令牌中的 sub
+    subject       TEXT    NOT NULL,
+    -- 最后一次登录时身份提供方返回的邮箱
+    email         TEXT,
+    created_at    TEXT    NOT NULL,
+    last_login_at TEXT    NOT NULL,
+    UNIQUE (provider, subject)
+);
+
+CREATE INDEX idx_user_identities_user ON user_identities (user_id);
@@ -0,0 +1,19 @@
+-- 浏览器会话（存储方式为 database 时使用）
+CREATE TABLE sessions (
+    -- 会话ID，用于列出及注销会话，可以公开显示
+    id           TEXT    PRIMARY KEY,
+    -- 会话Cookie中令牌的SHA-256摘要，不保存原文
+    token_hash   TEXT    NOT NULL UNIQUE,
+    user_id      INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- 双重提交校验使用的CSRF令牌
+    csrf_token   TEXT    NOT NULL,
+    ip           TEXT,
+    user_agent   TEXT,
+    -- 以下时间均为Unix时间戳（秒）
+    created_at   INTEGER NOT NULL,
+    last_seen_at INTEGER NOT NULL,
+    -- 绝对过期时间
+    expires_at   INTEGER NOT NULL
+);
+
+CREATE INDEX idx_sessions_user ON sessions (user_id);
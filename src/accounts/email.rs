@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::TokenPurpose;
+use crate::events::{DomainEvent, EventEnvelope, EventHandler};
+use crate::jobs::{Job, JobContext, JobHandler, JobOptions, JobQueue};
+use crate::models::user::User;
+use crate::services::AccountService;
+
+/// 账号邮件（邮箱验证、密码重置）发送任务
+///
+/// 只保存用户ID，执行时才签发令牌并发送到用户当前的邮箱
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEmail {
+    /// 用户ID
+    pub user_id: u64,
+    /// 邮件用途
+    pub purpose: TokenPurpose,
+}
+
+impl AccountEmail {
+    /// 入队选项：同一用户同一用途同时只保留一个等待中的任务
+    pub fn options(&self) -> JobOptions {
+        JobOptions::new().unique(format!("{}:{}", self.purpose.as_str(), self.user_id))
+    }
+}
+
+impl Job for AccountEmail {
+    const KIND: &'static str = "account_email";
+}
+
+/// 账号邮件发送任务的处理器
+pub struct AccountEmailHandler {
+    accounts: AccountService,
+}
+
+impl AccountEmailHandler {
+    /// 使用指定的账号服务签发令牌并发送
+    pub fn new(accounts: AccountService) -> Self {
+        Self { accounts }
+    }
+}
+
+#[async_trait]
+impl JobHandler for AccountEmailHandler {
+    type Job = AccountEmail;
+
+    async fn handle(&self, job: AccountEmail, _ctx: &JobContext) -> anyhow::Result<()> {
+        self.accounts.send_email(job.user_id, job.purpose).await
+    }
+}
+
+/// 新用户创建或修改邮箱后发送验证邮件
+///
+/// 只负责将发送任务入队，事件重复投递时以用户ID作为唯一键，避免同时存在多个发送任务
+pub struct VerificationEmail {
+    jobs: JobQueue,
+}
+
+impl VerificationEmail {
+    /// 发送任务放入指定的任务队列
+    pub fn new(jobs: JobQueue) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl EventHandler for VerificationEmail {
+    fn name(&self) -> &str {
+        "verification-email"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> anyhow::Result<()> {
+        let user: &User = match &envelope.event {
+            DomainEvent::UserCreated(created) => &created.user,
+            DomainEvent::UserUpdated(updated) if updated.before.email != updated.after.email => &updated.after,
+            _ => return Ok(()),
+        };
+        let (Some(user_id), None, None) = (user.id, &user.email_verified_at, &user.deleted_at) else {
+            return Ok(());
+        };
+
+        let job = AccountEmail {
+            user_id,
+            purpose: TokenPurpose::VerifyEmail,
+        };
+        self.jobs.enqueue(&job, job.options()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! 账号令牌
+//!
+//! 邮箱验证、密码重置通过邮件中的一次性令牌完成。令牌格式为 `{用户ID}.{过期时间}.{随机数}.{签名}`，
+//! 签名为以 `security.token_secret` 为密钥、对用途及前三段计算的HMAC-SHA256，伪造或篡改的令牌不必查询数据库即可拒绝；
+//! 数据库只保存令牌的SHA-256摘要，使用时原子地标记为已使用，保证每个令牌只能使用一次。
+//!
+//! 邮件由 `AccountEmail` 任务在执行时签发令牌并发送，令牌原文不会写入任务参数
+
+mod email;
+
+pub use email::{AccountEmail, AccountEmailHandler, VerificationEmail};
+
+use std::sync::Arc;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// 令牌用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenPurpose {
+    /// 邮箱验证
+    VerifyEmail,
+    /// 密码重置
+    ResetPassword,
+}
+
+impl TokenPurpose {
+    /// 数据库中存储的用途标识，同时参与签名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::VerifyEmail => "verify_email",
+            Self::ResetPassword => "reset_password",
+        }
+    }
+}
+
+/// 签名校验通过的令牌
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedToken {
+    /// 用户ID
+    pub user_id: u64,
+    /// 过期时间（Unix时间戳，秒）
+    pub expires_at: i64,
+}
+
+/// 令牌签名器
+///
+/// 克隆开销很小，可在各组件间共享
+#[derive(Clone)]
+pub struct TokenSigner {
+    key: Arc<[u8]>,
+}
+
+impl TokenSigner {
+    /// 使用配置的密钥，未配置时随机生成，重启后已发出的令牌全部失效
+    pub fn new(secret: Option<&str>) -> Self {
+        let key: Arc<[u8]> = match secret.filter(|secret| !secret.is_empty()) {
+            Some(secret) => Arc::from(secret.as_bytes()),
+            None => {
+                tracing::warn!("未配置 security.token_secret，使用随机密钥，重启后已发出的验证及重置链接失效");
+                let mut key = [0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                Arc::from(key.as_slice())
+            }
+        };
+        Self { key }
+    }
+
+    /// 签发令牌
+    pub fn issue(&self, purpose: TokenPurpose, user_id: u64, expires_at: i64) -> String {
+        let mut nonce = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let payload = format!("{}.{}.{}", user_id, expires_at, hex::encode(nonce));
+        let signature = hex::encode(self.mac(purpose, &payload).finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    /// 校验令牌格式及签名，不检查是否过期或已使用；比较耗时与签名内容无关
+    pub fn verify(&self, purpose: TokenPurpose, token: &str) -> Option<SignedToken> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        let signature = hex::decode(signature).ok()?;
+        self.mac(purpose, payload).verify_slice(&signature).ok()?;
+
+        let mut parts = payload.splitn(3, '.');
+        let user_id = parts.next()?.parse().ok()?;
+        let expires_at = parts.next()?.parse().ok()?;
+        Some(SignedToken { user_id, expires_at })
+    }
+
+    /// 以 `"{purpose}:{payload}"` 为内容的HMAC，不同用途的令牌不能互换
+    fn mac(&self, purpose: TokenPurpose, payload: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC接受任意长度的密钥");
+        mac.update(purpose.as_str().as_bytes());
+        mac.update(b":");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+/// 令牌的SHA-256摘要（十六进制），数据库中只保存摘要
+pub fn token_digest(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,81 @@
+use super::ApiKeySecurity;
+use crate::audit::AuditContext;
+use crate::auth::Principal;
+use crate::config::tags::ApiTags;
+use crate::models::api_key::{ApiKey, ApiKeyListResponse, CreateApiKeyRequest, CreatedApiKey};
+use crate::services::ApiKeyService;
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+
+/// API密钥控制器
+///
+/// 各版本共用，管理供机器调用方使用的API密钥；使用API密钥调用这些接口时需要 `admin` 授权范围
+#[derive(Default)]
+pub struct ApiKeyController;
+
+#[OpenApi]
+impl ApiKeyController {
+    /// 创建API密钥
+    ///
+    /// 密钥原文只在响应中返回这一次，服务端只保存摘要
+    #[oai(path = "/api-keys", method = "post", operation_id = "createApiKey", tag = ApiTags::ApiKey)]
+    async fn create_api_key(
+        &self,
+        service: Data<&ApiKeyService>,
+        audit: AuditContext,
+        req: Json<CreateApiKeyRequest>,
+    ) -> Result<Json<ApiResponse<CreatedApiKey>>> {
+        result_json(service.create(&audit, req.0).await)
+    }
+
+    /// 获取API密钥列表
+    ///
+    /// 包括已吊销的密钥
+    #[oai(path = "/api-keys", method = "get", operation_id = "listApiKeys", tag = ApiTags::ApiKey)]
+    async fn list_api_keys(
+        &self,
+        service: Data<&ApiKeyService>,
+        principal: Principal,
+    ) -> Result<Json<ApiResponse<ApiKeyListResponse>>> {
+        result_json(service.list(&principal).await.map(|items| ApiKeyListResponse { items }))
+    }
+
+    /// 获取当前API密钥
+    ///
+    /// 返回请求所使用的API密钥，可用于检查密钥的授权范围及过期时间
+    #[oai(path = "/api-keys/current", method = "get", operation_id = "getCurrentApiKey", tag = ApiTags::ApiKey)]
+    async fn get_current_api_key(
+        &self,
+        service: Data<&ApiKeyService>,
+        auth: ApiKeySecurity,
+    ) -> Result<Json<ApiResponse<ApiKey>>> {
+        let principal = auth.principal();
+        let id = principal.id().unwrap_or_default();
+        result_json(service.get(principal, id).await)
+    }
+
+    /// 获取API密钥详情
+    #[oai(path = "/api-keys/:id", method = "get", operation_id = "getApiKey", tag = ApiTags::ApiKey)]
+    async fn get_api_key(
+        &self,
+        service: Data<&ApiKeyService>,
+        principal: Principal,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<ApiKey>>> {
+        result_json(service.get(&principal, id.0).await)
+    }
+
+    /// 吊销API密钥
+    ///
+    /// 吊销后立即失效且不能恢复，密钥记录保留；已吊销时返回409
+    #[oai(path = "/api-keys/:id", method = "delete", operation_id = "revokeApiKey", tag = ApiTags::ApiKey)]
+    async fn revoke_api_key(
+        &self,
+        service: Data<&ApiKeyService>,
+        audit: AuditContext,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<ApiKey>>> {
+        result_json(service.revoke(&audit, id.0).await)
+    }
+}
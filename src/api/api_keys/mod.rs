@@ -0,0 +1,5 @@
+mod controller;
+mod security;
+
+pub use controller::ApiKeyController;
+pub use security::{ApiKeyAuthorization, ApiKeyHeader, ApiKeySecurity};
@@ -0,0 +1,43 @@
+//! API密钥的OpenAPI安全方案
+//!
+//! 密钥由 `middlewares::ApiKeyAuth` 认证，这里只读取认证结果，用于在规范中声明需要API密钥的接口
+
+use poem::Request;
+use poem_openapi::{auth::ApiKey, SecurityScheme};
+
+use crate::auth::Principal;
+
+/// API密钥，请求头 `X-API-Key: <密钥>`
+#[derive(SecurityScheme)]
+#[oai(ty = "api_key", key_name = "X-API-Key", key_in = "header", checker = "authenticated_key")]
+pub struct ApiKeyHeader(pub Principal);
+
+/// API密钥，请求头 `Authorization: ApiKey <密钥>`
+#[derive(SecurityScheme)]
+#[oai(ty = "api_key", key_name = "Authorization", key_in = "header", checker = "authenticated_key")]
+pub struct ApiKeyAuthorization(pub Principal);
+
+/// 任一请求头携带的API密钥
+#[derive(SecurityScheme)]
+pub enum ApiKeySecurity {
+    Header(ApiKeyHeader),
+    Authorization(ApiKeyAuthorization),
+}
+
+impl ApiKeySecurity {
+    /// 密钥对应的调用方身份
+    pub fn principal(&self) -> &Principal {
+        match self {
+            Self::Header(auth) => &auth.0,
+            Self::Authorization(auth) => &auth.0,
+        }
+    }
+}
+
+/// 认证中间件写入的API密钥身份，未携带有效密钥时返回 `None`（401）
+async fn authenticated_key(req: &Request, _key: ApiKey) -> Option<Principal> {
+    req.extensions()
+        .get::<Principal>()
+        .filter(|principal| matches!(principal, Principal::ApiKey { .. }))
+        .cloned()
+}
@@ -0,0 +1,80 @@
+use crate::audit::{AuditFilter, AuditLog};
+use crate::auth::Admin;
+use crate::config::tags::ApiTags;
+use crate::models::audit::{AuditAction, AuditPageResponse};
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{param::Query, payload::Json, OpenApi};
+
+/// 审计日志API控制器
+///
+/// 各版本共用，只提供查询，审计记录由数据变更自动写入；需要管理权限
+#[derive(Default)]
+pub struct AuditController;
+
+#[OpenApi]
+impl AuditController {
+    /// 查询审计事件
+    ///
+    /// 按时间倒序分页返回数据变更记录，过滤条件均为精确匹配
+    #[oai(path = "/audit", method = "get", operation_id = "listAuditEvents", tag = ApiTags::Audit)]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_events(
+        &self,
+        _admin: Admin,
+        audit: Data<&AuditLog>,
+        /// 操作者用户ID
+        actor_id: Query<Option<String>>,
+        /// 动作
+        action: Query<Option<AuditAction>>,
+        /// 实体类型，例如 `user`
+        entity_type: Query<Option<String>>,
+        /// 实体ID
+        entity_id: Query<Option<String>>,
+        /// 请求ID（响应头 `X-Request-Id`）
+        request_id: Query<Option<String>>,
+        /// 起始时间（RFC 3339，包含），例如 `2025-01-01T00:00:00Z`
+        since: Query<Option<String>>,
+        /// 截止时间（RFC 3339，不包含）
+        until: Query<Option<String>>,
+        /// 分页：页码，从1开始
+        #[oai(default = "default_page", validator(minimum(value = "1")))]
+        page: Query<u32>,
+        /// 分页：每页记录数，最大100
+        #[oai(default = "default_page_size", validator(minimum(value = "1"), maximum(value = "100")))]
+        page_size: Query<u32>,
+    ) -> Result<Json<ApiResponse<AuditPageResponse>>> {
+        let filter = AuditFilter {
+            actor_id: actor_id.0,
+            action: action.0,
+            entity_type: entity_type.0,
+            entity_id: entity_id.0,
+            request_id: request_id.0,
+            since: since.0,
+            until: until.0,
+        };
+
+        let result = audit
+            .list(&filter, page.0, page_size.0)
+            .await
+            .map(|(items, total)| AuditPageResponse {
+                items,
+                total,
+                page: page.0,
+                page_size: page_size.0,
+                total_pages: total.div_ceil(page_size.0 as u64) as u32,
+            });
+
+        result_json(result)
+    }
+}
+
+/// 默认页码
+fn default_page() -> u32 {
+    1
+}
+
+/// 默认每页记录数
+fn default_page_size() -> u32 {
+    20
+}
@@ -0,0 +1,3 @@
+mod controller;
+
+pub use controller::AuditController;
@@ -0,0 +1,56 @@
+use crate::audit::AuditContext;
+use crate::config::tags::ApiTags;
+use crate::models::auth::{ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest};
+use crate::models::user::User;
+use crate::services::AccountService;
+use crate::utils::response::{empty, result_json, ApiResponse, EmptyResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{payload::Json, OpenApi};
+
+/// 账号API控制器
+///
+/// 各版本共用，通过邮件中的一次性令牌验证邮箱、找回及重置密码
+#[derive(Default)]
+pub struct AuthController;
+
+#[OpenApi]
+impl AuthController {
+    /// 验证邮箱
+    ///
+    /// 使用验证邮件中的令牌验证邮箱，返回验证后的用户；令牌无效、已过期或已使用时返回400
+    #[oai(path = "/auth/verify-email", method = "post", operation_id = "verifyEmail", tag = ApiTags::Auth)]
+    async fn verify_email(
+        &self,
+        service: Data<&AccountService>,
+        audit: AuditContext,
+        req: Json<VerifyEmailRequest>,
+    ) -> Result<Json<ApiResponse<User>>> {
+        result_json(service.verify_email(&audit, &req.0.token).await)
+    }
+
+    /// 找回密码
+    ///
+    /// 邮箱对应的用户存在时发送密码重置邮件；无论邮箱是否存在都返回成功
+    #[oai(path = "/auth/forgot-password", method = "post", operation_id = "forgotPassword", tag = ApiTags::Auth)]
+    async fn forgot_password(
+        &self,
+        service: Data<&AccountService>,
+        req: Json<ForgotPasswordRequest>,
+    ) -> Result<Json<ApiResponse<EmptyResponse>>> {
+        result_json(service.forgot_password(&req.0.email).await.map(|_| empty()))
+    }
+
+    /// 重置密码
+    ///
+    /// 使用密码重置邮件中的令牌设置新密码，成功后该用户其余未使用的重置令牌一并失效；
+    /// 令牌无效、已过期或已使用时返回400
+    #[oai(path = "/auth/reset-password", method = "post", operation_id = "resetPassword", tag = ApiTags::Auth)]
+    async fn reset_password(
+        &self,
+        service: Data<&AccountService>,
+        audit: AuditContext,
+        req: Json<ResetPasswordRequest>,
+    ) -> Result<Json<ApiResponse<EmptyResponse>>> {
+        result_json(service.reset_password(&audit, req.0).await.map(|_| empty()))
+    }
+}
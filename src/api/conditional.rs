@@ -0,0 +1,151 @@
+//! 条件请求
+//!
+//! 带版本号的资源以版本号作为强ETag：读取时支持 `If-None-Match`（命中返回304），
+//! 修改时支持 `If-Match`（版本不一致返回412，配置要求携带而未携带时返回428）
+
+use poem_openapi::{
+    payload::Json,
+    types::{Example, ParseFromJSON, ToJSON, Type},
+};
+use serde::Serialize;
+
+use crate::config::ApiConfig;
+use crate::models::user::User;
+use crate::services::ServiceError;
+use crate::utils::response::{ApiResponse, EmptyResponse, ErrorResponse};
+
+/// 带版本号的资源
+pub trait Versioned {
+    /// 当前版本号，无版本号的资源不返回ETag
+    fn version(&self) -> Option<u64>;
+}
+
+impl Versioned for User {
+    fn version(&self) -> Option<u64> {
+        self.version
+    }
+}
+
+impl Versioned for EmptyResponse {
+    fn version(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// 支持条件请求的响应
+#[derive(poem_openapi::ApiResponse)]
+pub enum ConditionalResponse<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON + Example> {
+    /// 处理结果，认证、授权以外的业务错误同样以200返回，错误码见响应体
+    #[oai(status = 200)]
+    Ok(
+        Json<ApiResponse<T>>,
+        /// 资源当前版本的强ETag，可用于 `If-None-Match`、`If-Match`
+        #[oai(header = "ETag")]
+        Option<String>,
+    ),
+
+    /// 资源未修改（`If-None-Match` 与当前版本一致）
+    #[oai(status = 304)]
+    NotModified(
+        /// 资源当前版本的强ETag
+        #[oai(header = "ETag")]
+        String,
+    ),
+
+    /// 未登录或缺少认证信息
+    #[oai(status = 401)]
+    Unauthorized(Json<ErrorResponse>),
+
+    /// 调用方无权操作该资源
+    #[oai(status = 403)]
+    Forbidden(Json<ErrorResponse>),
+
+    /// 资源已被修改（`If-Match` 与当前版本不一致）
+    #[oai(status = 412)]
+    PreconditionFailed(Json<ErrorResponse>),
+
+    /// 缺少 `If-Match` 请求头（配置 `api.require_if_match` 时）
+    #[oai(status = 428)]
+    PreconditionRequired(Json<ErrorResponse>),
+}
+
+impl<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON + Example + Versioned> ConditionalResponse<T> {
+    /// 转换业务结果，成功时附带ETag，认证、授权错误返回401、403，版本不一致返回412，其余错误沿用统一响应格式
+    pub fn from_result(result: Result<T, ServiceError>) -> Self {
+        match result {
+            Ok(data) => {
+                let etag = data.version().map(etag);
+                Self::Ok(Json(ApiResponse::success(data)), etag)
+            }
+            Err(err @ ServiceError::Unauthorized(_)) => {
+                Self::Unauthorized(Json(ErrorResponse::new(err.status_code(), err.to_string())))
+            }
+            Err(err @ ServiceError::Forbidden(_)) => {
+                Self::Forbidden(Json(ErrorResponse::new(err.status_code(), err.to_string())))
+            }
+            Err(err @ ServiceError::PreconditionFailed(_)) => {
+                Self::PreconditionFailed(Json(ErrorResponse::new(err.status_code(), err.to_string())))
+            }
+            Err(err) => Self::Ok(Json(ApiResponse::error(err.status_code(), err.to_string())), None),
+        }
+    }
+
+    /// 转换读取结果，`If-None-Match` 与当前版本一致时返回304
+    pub fn read(result: Result<T, ServiceError>, if_none_match: Option<&str>) -> Self {
+        if let (Ok(data), Some(if_none_match)) = (&result, if_none_match) {
+            if let Some(etag) = data.version().map(etag) {
+                if none_match(if_none_match, &etag) {
+                    return Self::NotModified(etag);
+                }
+            }
+        }
+        Self::from_result(result)
+    }
+}
+
+/// 根据版本号生成强ETag
+pub fn etag(version: u64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// 解析 `If-Match` 请求头，返回调用方期望的版本号
+///
+/// `*` 匹配任意版本；只支持单个强ETag，弱ETag及无法识别的值按RFC 9110视为不匹配
+pub fn expected_version<T>(if_match: Option<&str>, config: &ApiConfig) -> Result<Option<u64>, ConditionalResponse<T>>
+where
+    T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON + Example,
+{
+    let Some(if_match) = if_match.map(str::trim) else {
+        if config.require_if_match {
+            return Err(ConditionalResponse::PreconditionRequired(Json(ErrorResponse::new(
+                428,
+                "修改资源时必须携带 If-Match 请求头".to_string(),
+            ))));
+        }
+        return Ok(None);
+    };
+
+    if if_match == "*" {
+        return Ok(None);
+    }
+
+    if_match
+        .strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .and_then(|tag| tag.parse().ok())
+        .map(Some)
+        .ok_or_else(|| {
+            ConditionalResponse::PreconditionFailed(Json(ErrorResponse::new(
+                412,
+                format!("无法识别的 If-Match 值: {}", if_match),
+            )))
+        })
+}
+
+/// `If-None-Match` 是否与当前ETag匹配，按RFC 9110使用弱比较
+fn none_match(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
@@ -0,0 +1,71 @@
+//! API文档路由
+//!
+//! 每个版本提供规范端点（`/{version}/json`、`/{version}/yaml`）及配置中启用的文档UI
+//! （`/{version}/<ui>`），根路径下的文档对应默认版本
+
+use poem::{endpoint::BoxEndpoint, EndpointExt, Route};
+use poem_openapi::{OpenApi, OpenApiService};
+
+use super::{v1, v2, ApiVersion};
+use crate::config::{DocsConfig, DocsUi};
+
+/// 创建API文档路由
+pub fn create_docs_route(config: &DocsConfig) -> Route {
+    let mut route = Route::new();
+    for version in ApiVersion::ALL {
+        route = route.nest(format!("/{}", version), version_route(version, config));
+    }
+
+    // 默认版本，兼容原有的 /api/docs 与 /api/docs/json
+    route.nest("/", version_route(ApiVersion::DEFAULT, config))
+}
+
+/// 生成嵌入了规范的独立HTML文档页面，可作为静态文件发布
+pub fn render_html(version: ApiVersion, ui: DocsUi, config: &DocsConfig) -> String {
+    match version {
+        ApiVersion::V1 => html(&v1::create_api_service(&config.servers), ui),
+        ApiVersion::V2 => html(&v2::create_api_service(&config.servers), ui),
+    }
+}
+
+fn version_route(version: ApiVersion, config: &DocsConfig) -> Route {
+    match version {
+        ApiVersion::V1 => service_route(&v1::create_api_service(&config.servers), &config.ui),
+        ApiVersion::V2 => service_route(&v2::create_api_service(&config.servers), &config.ui),
+    }
+}
+
+fn service_route<T: OpenApi>(service: &OpenApiService<T, ()>, uis: &[DocsUi]) -> Route {
+    let mut route = Route::new()
+        .at("/json", service.spec_endpoint())
+        .at("/yaml", service.spec_endpoint_yaml());
+
+    for ui in uis {
+        route = route.nest(format!("/{}", ui.as_str()), ui_endpoint(service, *ui));
+    }
+    if let Some(ui) = uis.first() {
+        route = route.nest("/", ui_endpoint(service, *ui));
+    }
+
+    route
+}
+
+fn ui_endpoint<T: OpenApi>(service: &OpenApiService<T, ()>, ui: DocsUi) -> BoxEndpoint<'static> {
+    match ui {
+        DocsUi::SwaggerUi => service.swagger_ui().map_to_response().boxed(),
+        DocsUi::Redoc => service.redoc().map_to_response().boxed(),
+        DocsUi::Rapidoc => service.rapidoc().map_to_response().boxed(),
+        DocsUi::Scalar => service.scalar().map_to_response().boxed(),
+        DocsUi::OpenapiExplorer => service.openapi_explorer().map_to_response().boxed(),
+    }
+}
+
+fn html<T: OpenApi>(service: &OpenApiService<T, ()>, ui: DocsUi) -> String {
+    match ui {
+        DocsUi::SwaggerUi => service.swagger_ui_html(),
+        DocsUi::Redoc => service.redoc_html(),
+        DocsUi::Rapidoc => service.rapidoc_html(),
+        DocsUi::Scalar => service.scalar_html(),
+        DocsUi::OpenapiExplorer => service.openapi_explorer_html(),
+    }
+}
@@ -0,0 +1,105 @@
+use crate::auth::Admin;
+use crate::config::tags::ApiTags;
+use crate::jobs::{JobFilter, JobQueue};
+use crate::models::job::{JobPageResponse, JobRecord, JobStatus};
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+
+/// 后台任务API控制器
+///
+/// 各版本共用，查看任务执行情况并手动重试或取消；任务由业务代码入队。所有接口都需要管理权限
+#[derive(Default)]
+pub struct JobController;
+
+#[OpenApi]
+impl JobController {
+    /// 查询后台任务
+    ///
+    /// 按创建时间倒序分页返回任务，可按状态和类型过滤
+    #[oai(path = "/jobs", method = "get", operation_id = "listJobs", tag = ApiTags::Job)]
+    async fn list_jobs(
+        &self,
+        _admin: Admin,
+        jobs: Data<&JobQueue>,
+        /// 任务状态
+        status: Query<Option<JobStatus>>,
+        /// 任务类型
+        kind: Query<Option<String>>,
+        /// 分页：页码，从1开始
+        #[oai(default = "default_page", validator(minimum(value = "1")))]
+        page: Query<u32>,
+        /// 分页：每页记录数，最大100
+        #[oai(default = "default_page_size", validator(minimum(value = "1"), maximum(value = "100")))]
+        page_size: Query<u32>,
+    ) -> Result<Json<ApiResponse<JobPageResponse>>> {
+        let filter = JobFilter {
+            status: status.0,
+            kind: kind.0,
+        };
+
+        let result = jobs
+            .list(&filter, page.0, page_size.0)
+            .await
+            .map(|(items, total)| JobPageResponse {
+                items,
+                total,
+                page: page.0,
+                page_size: page_size.0,
+                total_pages: total.div_ceil(page_size.0 as u64) as u32,
+            });
+
+        result_json(result)
+    }
+
+    /// 获取后台任务详情
+    #[oai(path = "/jobs/:id", method = "get", operation_id = "getJob", tag = ApiTags::Job)]
+    async fn get_job(
+        &self,
+        _admin: Admin,
+        jobs: Data<&JobQueue>,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<JobRecord>>> {
+        result_json(jobs.get(id.0).await)
+    }
+
+    /// 重试后台任务
+    ///
+    /// 重新执行失败或已取消的任务，执行次数从零开始；其他状态返回409
+    #[oai(path = "/jobs/:id/retry", method = "post", operation_id = "retryJob", tag = ApiTags::Job)]
+    async fn retry_job(
+        &self,
+        _admin: Admin,
+        jobs: Data<&JobQueue>,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<JobRecord>>> {
+        result_json(jobs.retry(id.0).await)
+    }
+
+    /// 取消后台任务
+    ///
+    /// 只能取消等待执行的任务（包括等待重试的任务），执行中或已结束的任务返回409
+    #[oai(path = "/jobs/:id/cancel", method = "post", operation_id = "cancelJob", tag = ApiTags::Job)]
+    async fn cancel_job(
+        &self,
+        _admin: Admin,
+        jobs: Data<&JobQueue>,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<JobRecord>>> {
+        result_json(jobs.cancel(id.0).await)
+    }
+}
+
+/// 默认页码
+fn default_page() -> u32 {
+    1
+}
+
+/// 默认每页记录数
+fn default_page_size() -> u32 {
+    20
+}
@@ -0,0 +1,89 @@
+use crate::audit::AuditContext;
+use crate::auth::Principal;
+use crate::config::tags::ApiTags;
+use crate::models::mfa::{MfaCodeRequest, MfaEnrollment, MfaStatus, RecoveryCodesResponse};
+use crate::services::MfaService;
+use crate::utils::response::{empty, result_json, ApiResponse, EmptyResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+
+/// 两步验证API控制器
+///
+/// 各版本共用，管理用户的TOTP绑定与恢复码；只允许用户本人或管理员调用，未登录时返回401，
+/// 操作其他用户时返回403；验证码错误时返回400，状态不允许时返回409
+#[derive(Default)]
+pub struct MfaController;
+
+#[OpenApi]
+impl MfaController {
+    /// 获取两步验证状态
+    #[oai(path = "/users/:id/mfa", method = "get", operation_id = "getMfaStatus", tag = ApiTags::Mfa)]
+    async fn get_status(
+        &self,
+        service: Data<&MfaService>,
+        principal: Principal,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<MfaStatus>>> {
+        result_json(service.status(&principal, id.0).await)
+    }
+
+    /// 开始绑定两步验证
+    ///
+    /// 生成新的TOTP密钥及二维码内容，提交验证码启用前重复调用会替换密钥；已启用时返回409
+    #[oai(path = "/users/:id/mfa/enroll", method = "post", operation_id = "enrollMfa", tag = ApiTags::Mfa)]
+    async fn enroll(
+        &self,
+        service: Data<&MfaService>,
+        principal: Principal,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<MfaEnrollment>>> {
+        result_json(service.enroll(&principal, id.0).await)
+    }
+
+    /// 启用两步验证
+    ///
+    /// 提交验证器应用显示的验证码确认绑定，返回恢复码；恢复码只返回这一次
+    #[oai(path = "/users/:id/mfa/enable", method = "post", operation_id = "enableMfa", tag = ApiTags::Mfa)]
+    async fn enable(
+        &self,
+        service: Data<&MfaService>,
+        audit: AuditContext,
+        id: Path<u64>,
+        req: Json<MfaCodeRequest>,
+    ) -> Result<Json<ApiResponse<RecoveryCodesResponse>>> {
+        result_json(service.enable(&audit, id.0, &req.0.code).await)
+    }
+
+    /// 停用两步验证
+    ///
+    /// 需要验证码或恢复码；用户的角色必须启用两步验证时返回409
+    #[oai(path = "/users/:id/mfa/disable", method = "post", operation_id = "disableMfa", tag = ApiTags::Mfa)]
+    async fn disable(
+        &self,
+        service: Data<&MfaService>,
+        audit: AuditContext,
+        id: Path<u64>,
+        req: Json<MfaCodeRequest>,
+    ) -> Result<Json<ApiResponse<EmptyResponse>>> {
+        result_json(service.disable(&audit, id.0, &req.0.code).await.map(|_| empty()))
+    }
+
+    /// 重新生成恢复码
+    ///
+    /// 需要验证码或恢复码，原有的恢复码全部失效
+    #[oai(
+        path = "/users/:id/mfa/recovery-codes",
+        method = "post",
+        operation_id = "regenerateRecoveryCodes",
+        tag = ApiTags::Mfa
+    )]
+    async fn regenerate_recovery_codes(
+        &self,
+        service: Data<&MfaService>,
+        audit: AuditContext,
+        id: Path<u64>,
+        req: Json<MfaCodeRequest>,
+    ) -> Result<Json<ApiResponse<RecoveryCodesResponse>>> {
+        result_json(service.regenerate_recovery_codes(&audit, id.0, &req.0.code).await)
+    }
+}
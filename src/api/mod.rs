@@ -1,40 +1,118 @@
 //! API模块
-//! 
-//! 包含所有API接口的定义，每个子模块代表一个功能域
+//!
+//! 包含所有API接口的定义。每个版本（`v1`、`v2`）是一个独立的OpenAPI服务，
+//! 拥有各自的规范文档和Swagger UI；版本内的每个子模块代表一个功能域，
+//! 各版本行为一致的功能域（如 `api_keys`、`audit`、`auth`、`jobs`、`mfa`、`oidc`、`scheduler`、`sessions`、`users`、`webhooks`）放在版本之外共用，
+//! 用户列表的分页方式各版本不同，`/users` 上的接口由版本内的 `user` 模块提供
 
-pub mod user;
+pub mod api_keys;
+pub mod audit;
+pub mod auth;
+pub mod conditional;
+pub mod docs;
+pub mod jobs;
+pub mod mfa;
+pub mod oidc;
+pub mod scheduler;
+pub mod sessions;
+pub mod users;
+pub mod v1;
+pub mod v2;
+pub mod version;
+pub mod webhooks;
 
-use poem_openapi::{OpenApiService, ContactObject, LicenseObject};
+pub use docs::create_docs_route;
+pub use version::ApiVersion;
 
+use poem::{middleware::SetHeader, EndpointExt, Route};
+use poem_openapi::{
+    ContactObject, ExternalDocumentObject, ExtraHeader, LicenseObject, OpenApi, OpenApiService,
+    ServerObject,
+};
+
+use crate::config::DocsServer;
+use version::{NegotiateVersion, API_VERSION};
+
+const DOC_TITLE: &str = "{{ doc_title }}";
 const AUTHOR: &str = "{{ author }}";
 const GITHUB: &str = "{{ github }}";
 
-/// 创建OpenAPI服务
-/// 
-/// 聚合所有API模块，并配置OpenAPI文档
-pub fn create_api_service() -> OpenApiService<(user::UserController), ()> {
+/// 创建某个版本的OpenAPI服务，并设置通用的文档信息
+fn new_service<T: OpenApi>(api: T, version: ApiVersion, servers: &[DocsServer]) -> OpenApiService<T, ()> {
     let mut service = OpenApiService::new(
-        (
-            user::UserController::default() // 用户管理API控制器
-        ),
-        "{{ doc_title }}", // API文档标题
+        api,
+        format!("{} {}", DOC_TITLE, version), // API文档标题
         env!("CARGO_PKG_VERSION"), // API版本（从Cargo.toml获取）
     )
-    .server("/api") // API基础路径
     .description("{{ project_description }}") // API描述
     .license(
         LicenseObject::new("MIT")
             .url("https://opensource.org/licenses/MIT"),
-    ); // 许可证
+    ) // 许可证
+    // 所有接口都会返回的响应头
+    .extra_response_header::<String, _>(
+        ExtraHeader::new("X-Request-Id").description("请求ID，未提供时由服务端生成，排查问题时请附带"),
+    );
 
-    if !AUTHOR.trim().is_empty() && !GITHUB.trim().is_empty() {
-        service = service.contact(
-            ContactObject::new()
-                .name(AUTHOR)
-                .url(GITHUB),
-        );
+    // API基础路径，未配置服务器地址时使用相对路径
+    if servers.is_empty() {
+        service = service.server(format!("/api/{}", version));
+    }
+    for server in servers {
+        let mut object = ServerObject::new(format!("{}/api/{}", server.url.trim_end_matches('/'), version));
+        if let Some(description) = &server.description {
+            object = object.description(description);
+        }
+        service = service.server(object);
     }
 
+    if !AUTHOR.trim().is_empty() && !GITHUB.trim().is_empty() {
+        service = service
+            .contact(
+                ContactObject::new()
+                    .name(AUTHOR)
+                    .url(GITHUB),
+            )
+            .external_document(
+                ExternalDocumentObject::new(GITHUB).description("项目主页"),
+            );
+    }
 
     service
-}
\ No newline at end of file
+}
+
+/// 创建API路由
+///
+/// - `/v1/...`、`/v2/...`：通过路径前缀指定版本
+/// - `/...`：通过 `Accept-Version` 请求头指定版本，未指定时使用默认版本
+pub fn create_api_route() -> Route {
+    let negotiated = NegotiateVersion::new()
+        .version(ApiVersion::V1, v1::create_api_service(&[]))
+        .version(ApiVersion::V2, v2::create_api_service(&[]));
+
+    Route::new()
+        .nest(
+            "/v1",
+            v1::create_api_service(&[]).with(version_header(ApiVersion::V1)),
+        )
+        .nest(
+            "/v2",
+            v2::create_api_service(&[]).with(version_header(ApiVersion::V2)),
+        )
+        .nest("/", negotiated)
+}
+
+/// 导出某个版本的OpenAPI规范，`yaml` 为 `false` 时输出JSON
+pub fn export_spec(version: ApiVersion, yaml: bool, servers: &[DocsServer]) -> String {
+    match (version, yaml) {
+        (ApiVersion::V1, false) => v1::create_api_service(servers).spec(),
+        (ApiVersion::V1, true) => v1::create_api_service(servers).spec_yaml(),
+        (ApiVersion::V2, false) => v2::create_api_service(servers).spec(),
+        (ApiVersion::V2, true) => v2::create_api_service(servers).spec_yaml(),
+    }
+}
+
+/// 在响应中标明实际使用的API版本
+fn version_header(version: ApiVersion) -> SetHeader {
+    SetHeader::new().overriding(API_VERSION, version.as_str())
+}
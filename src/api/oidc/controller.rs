@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use crate::audit::AuditContext;
+use crate::config::tags::ApiTags;
+use crate::models::oidc::{OidcAuthorization, OidcLoginResponse, OidcProviderListResponse};
+use crate::services::{MfaRequirement, OidcService, ServiceError, SessionService};
+use crate::sessions;
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{
+    web::{cookie::CookieJar, Data},
+    Request, Result,
+};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+
+/// 单点登录API控制器
+///
+/// 各版本共用，通过外部身份提供方（OpenID Connect）登录。前端调用 `authorize` 获取授权地址并重定向，
+/// 身份提供方重定向回前端页面后，前端将地址中的 `code`、`state` 转交 `callback` 完成登录；
+/// 两次请求需要来自同一个浏览器，登录状态通过Cookie绑定
+#[derive(Default)]
+pub struct OidcController;
+
+#[OpenApi]
+impl OidcController {
+    /// 获取身份提供方列表
+    #[oai(path = "/auth/oidc/providers", method = "get", operation_id = "listOidcProviders", tag = ApiTags::Oidc)]
+    async fn list_providers(&self, service: Data<&OidcService>) -> Result<Json<ApiResponse<OidcProviderListResponse>>> {
+        result_json(Ok(OidcProviderListResponse {
+            items: service.providers(),
+        }))
+    }
+
+    /// 发起登录
+    ///
+    /// 返回身份提供方的授权地址，需要在 `expires_in` 秒内完成登录；身份提供方不存在时返回404。
+    /// 同时将 `state` 写入 `HttpOnly` 的Cookie，回调时校验登录由同一个浏览器发起
+    #[oai(path = "/auth/oidc/:provider/authorize", method = "get", operation_id = "authorizeOidc", tag = ApiTags::Oidc)]
+    async fn authorize(
+        &self,
+        service: Data<&OidcService>,
+        session_service: Data<&SessionService>,
+        cookies: &CookieJar,
+        provider: Path<String>,
+    ) -> Result<Json<ApiResponse<OidcAuthorization>>> {
+        let result = service.authorize(&provider.0).await;
+        if let Ok(authorization) = &result {
+            sessions::set_private_cookie(
+                cookies,
+                session_service.config(),
+                service.state_cookie_name(),
+                &authorization.state,
+                Duration::from_secs(authorization.expires_in),
+            );
+        }
+        result_json(result)
+    }
+
+    /// 完成登录
+    ///
+    /// 兑换授权码并校验ID令牌，返回对应的本地用户；身份首次登录时关联邮箱相同的用户或创建新用户。
+    /// 启用会话登录时同时创建会话并写入会话Cookie；用户已启用两步验证时需要同时提交 `mfa_code`，
+    /// 缺少时返回401，由于 `state` 只能使用一次，需要重新发起登录。
+    /// 登录状态无效或与浏览器Cookie中的不一致、授权码被拒绝、ID令牌无效或验证码错误时返回400，
+    /// 身份提供方未开放注册时返回403
+    #[oai(path = "/auth/oidc/:provider/callback", method = "get", operation_id = "completeOidcLogin", tag = ApiTags::Oidc)]
+    #[allow(clippy::too_many_arguments)]
+    async fn callback(
+        &self,
+        service: Data<&OidcService>,
+        session_service: Data<&SessionService>,
+        audit: AuditContext,
+        request: &Request,
+        cookies: &CookieJar,
+        provider: Path<String>,
+        /// 身份提供方返回的授权码
+        code: Query<Option<String>>,
+        /// 发起登录时返回的 `state`
+        state: Query<Option<String>>,
+        /// 身份提供方返回的错误码，例如用户拒绝授权时为 `access_denied`
+        error: Query<Option<String>>,
+        /// 身份提供方返回的错误描述
+        error_description: Query<Option<String>>,
+        /// 已启用两步验证的用户需要提交的验证码或恢复码
+        mfa_code: Query<Option<String>>,
+    ) -> Result<Json<ApiResponse<OidcLoginResponse>>> {
+        // 登录状态只能使用一次，无论登录是否成功都删除Cookie
+        let browser_state = cookies
+            .get(service.state_cookie_name())
+            .map(|cookie| cookie.value_str().to_string());
+        sessions::remove_cookie(cookies, session_service.config(), service.state_cookie_name());
+
+        let result = match (error.0, code.0, state.0) {
+            (Some(error), _, _) => Err(ServiceError::Validation(match error_description.0 {
+                Some(description) => format!("身份提供方返回错误: {}（{}）", error, description),
+                None => format!("身份提供方返回错误: {}", error),
+            })),
+            (None, Some(code), Some(state)) => {
+                service
+                    .complete(&audit, &provider.0, &code, &state, browser_state.as_deref())
+                    .await
+            }
+            _ => Err(ServiceError::Validation("缺少 code 或 state 参数".to_string())),
+        };
+        let result = match result {
+            Ok(mut response) if session_service.config().enabled => {
+                let user_agent = sessions::user_agent(request);
+                match session_service.verify_mfa(&response.user, mfa_code.0.as_deref()).await {
+                    Ok(requirement) => session_service.start(&audit, &response.user, user_agent).await.map(|issued| {
+                        sessions::set_cookies(cookies, session_service.config(), &issued.token, &issued.csrf_token);
+                        response.session = Some(issued.session);
+                        response.mfa_enrollment_required = requirement == MfaRequirement::Enroll;
+                        response
+                    }),
+                    Err(err) => Err(err),
+                }
+            }
+            result => result,
+        };
+        result_json(result)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::auth::Admin;
+use crate::config::tags::ApiTags;
+use crate::models::scheduler::{ScheduledTaskListResponse, ScheduledTaskStatus};
+use crate::scheduler::Scheduler;
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+
+/// 定时任务API控制器
+///
+/// 各版本共用，查看定时任务的执行计划与最近一次执行结果，并可手动触发。所有接口都需要管理权限
+#[derive(Default)]
+pub struct SchedulerController;
+
+#[OpenApi]
+impl SchedulerController {
+    /// 查询定时任务
+    ///
+    /// 返回所有定时任务的cron表达式、下次执行时间及最后一次执行的结果
+    #[oai(path = "/scheduler/tasks", method = "get", operation_id = "listScheduledTasks", tag = ApiTags::Scheduler)]
+    async fn list_tasks(
+        &self,
+        _admin: Admin,
+        scheduler: Data<&Scheduler>,
+    ) -> Result<Json<ApiResponse<ScheduledTaskListResponse>>> {
+        result_json(
+            scheduler
+                .list()
+                .await
+                .map(|items| ScheduledTaskListResponse { items }),
+        )
+    }
+
+    /// 手动触发定时任务
+    ///
+    /// 将下次执行时间设为现在，由启用了定时任务的实例尽快执行，之后恢复按计划执行；正在执行时返回409
+    #[oai(path = "/scheduler/tasks/:name/run", method = "post", operation_id = "runScheduledTask", tag = ApiTags::Scheduler)]
+    async fn run_task(
+        &self,
+        _admin: Admin,
+        scheduler: Data<&Scheduler>,
+        name: Path<String>,
+    ) -> Result<Json<ApiResponse<ScheduledTaskStatus>>> {
+        result_json(scheduler.trigger(&name.0).await)
+    }
+}
@@ -0,0 +1,124 @@
+use crate::audit::AuditContext;
+use crate::config::tags::ApiTags;
+use crate::models::session::{LoginRequest, LoginResponse, RevokedSessions, Session, SessionListResponse};
+use crate::services::SessionService;
+use crate::sessions::{self, CurrentSession};
+use crate::utils::response::{empty, result_json, ApiResponse, EmptyResponse};
+use poem::{
+    web::{cookie::CookieJar, Data},
+    Request, Result,
+};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+
+/// 会话API控制器
+///
+/// 各版本共用，供浏览器管理后台使用Cookie登录。登录成功后通过 `Set-Cookie` 写入会话Cookie及CSRF令牌Cookie，
+/// 之后修改数据的请求需要将CSRF令牌Cookie的值放在 `X-CSRF-Token` 请求头中提交（名称可配置）
+#[derive(Default)]
+pub struct SessionController;
+
+#[OpenApi]
+impl SessionController {
+    /// 登录
+    ///
+    /// 使用用户名（或邮箱）及密码登录并创建会话；已启用两步验证的用户需要同时提交验证码或恢复码。
+    /// 用户名或密码错误、缺少两步验证码时返回401，验证码错误时返回400
+    #[oai(path = "/auth/login", method = "post", operation_id = "login", tag = ApiTags::Session)]
+    async fn login(
+        &self,
+        service: Data<&SessionService>,
+        audit: AuditContext,
+        request: &Request,
+        cookies: &CookieJar,
+        req: Json<LoginRequest>,
+    ) -> Result<Json<ApiResponse<LoginResponse>>> {
+        let result = service.login(&audit, req.0, sessions::user_agent(request)).await.map(|(issued, response)| {
+            sessions::set_cookies(cookies, service.config(), &issued.token, &issued.csrf_token);
+            response
+        });
+        result_json(result)
+    }
+
+    /// 退出登录
+    ///
+    /// 注销当前会话并删除会话Cookie；未登录时同样返回成功
+    #[oai(path = "/auth/logout", method = "post", operation_id = "logout", tag = ApiTags::Session)]
+    async fn logout(
+        &self,
+        service: Data<&SessionService>,
+        audit: AuditContext,
+        current: Option<Data<&CurrentSession>>,
+        cookies: &CookieJar,
+    ) -> Result<Json<ApiResponse<EmptyResponse>>> {
+        let result = service.logout(&audit, current.map(|data| data.0)).await;
+        sessions::clear_cookies(cookies, service.config());
+        result_json(result.map(|_| empty()))
+    }
+
+    /// 获取当前会话
+    ///
+    /// 可用于前端检查登录状态；未登录或会话已失效时返回401
+    #[oai(path = "/sessions/current", method = "get", operation_id = "getCurrentSession", tag = ApiTags::Session)]
+    async fn get_current_session(
+        &self,
+        service: Data<&SessionService>,
+        current: Option<Data<&CurrentSession>>,
+    ) -> Result<Json<ApiResponse<Session>>> {
+        result_json(service.current(current.map(|data| data.0)).await)
+    }
+
+    /// 获取会话列表
+    ///
+    /// 返回当前用户所有未过期的会话，`current` 标记本次请求使用的会话；未登录时返回401
+    #[oai(path = "/sessions", method = "get", operation_id = "listSessions", tag = ApiTags::Session)]
+    async fn list_sessions(
+        &self,
+        service: Data<&SessionService>,
+        current: Option<Data<&CurrentSession>>,
+    ) -> Result<Json<ApiResponse<SessionListResponse>>> {
+        result_json(
+            service
+                .list(current.map(|data| data.0))
+                .await
+                .map(|items| SessionListResponse { items }),
+        )
+    }
+
+    /// 注销其他会话
+    ///
+    /// 注销当前用户除本次请求使用的会话以外的所有会话，例如在其他设备上退出登录；未登录时返回401
+    #[oai(path = "/sessions", method = "delete", operation_id = "revokeOtherSessions", tag = ApiTags::Session)]
+    async fn revoke_other_sessions(
+        &self,
+        service: Data<&SessionService>,
+        audit: AuditContext,
+        current: Option<Data<&CurrentSession>>,
+    ) -> Result<Json<ApiResponse<RevokedSessions>>> {
+        result_json(
+            service
+                .revoke_others(&audit, current.map(|data| data.0))
+                .await
+                .map(|count| RevokedSessions { count }),
+        )
+    }
+
+    /// 注销会话
+    ///
+    /// 只能注销当前用户的会话，注销本次请求使用的会话时同时删除会话Cookie；
+    /// 会话不存在或属于其他用户时返回404，未登录时返回401
+    #[oai(path = "/sessions/:id", method = "delete", operation_id = "revokeSession", tag = ApiTags::Session)]
+    async fn revoke_session(
+        &self,
+        service: Data<&SessionService>,
+        audit: AuditContext,
+        current: Option<Data<&CurrentSession>>,
+        cookies: &CookieJar,
+        id: Path<String>,
+    ) -> Result<Json<ApiResponse<Session>>> {
+        let result = service.revoke(&audit, current.map(|data| data.0), &id.0).await;
+        if result.as_ref().is_ok_and(|session| session.current) {
+            sessions::clear_cookies(cookies, service.config());
+        }
+        result_json(result)
+    }
+}
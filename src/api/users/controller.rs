@@ -0,0 +1,134 @@
+use crate::models::user::{PatchUserPayload, UpdateUserRequest, User};
+use crate::api::conditional::{self, ConditionalResponse};
+use crate::audit::AuditContext;
+use crate::auth::Principal;
+use crate::config::ApiConfig;
+use crate::services::UserService;
+use crate::utils::response::{EmptyResponse, empty};
+use poem::{web::Data, Result};
+use poem_openapi::{
+    param::{Header, Path},
+    payload::Json,
+    OpenApi,
+};
+use crate::config::tags::ApiTags;
+
+/// 用户管理API控制器
+///
+/// 各版本共用的单个用户接口（`/users/{id}`）；`/users` 上的创建及列表接口由各版本的 `user` 模块提供，
+/// 同一路径的接口需要在同一个控制器中声明，否则规范文档中只保留其中一个控制器的接口
+#[derive(Default)]
+pub struct UserController;
+
+#[OpenApi]
+impl UserController {
+    /// 获取用户详情
+    ///
+    /// 根据用户ID获取用户详细信息，响应头 `ETag` 标识当前版本；只能获取自己的账户，管理员可以获取任意用户
+    #[oai(path = "/users/:id", method = "get", operation_id = "getUserById", tag = ApiTags::User)]
+    async fn get_user(
+        &self,
+        service: Data<&UserService>,
+        principal: Principal,
+        id: Path<u64>,
+        /// 上次读取时返回的ETag，与当前版本一致时返回304
+        #[oai(name = "If-None-Match")]
+        if_none_match: Header<Option<String>>,
+    ) -> Result<ConditionalResponse<User>> {
+        Ok(ConditionalResponse::read(service.get_as(&principal, id.0).await, if_none_match.0.as_deref()))
+    }
+
+    /// 更新用户信息
+    ///
+    /// 根据用户ID更新用户信息，携带 `If-Match` 时只在版本一致时更新；只能修改自己的账户，管理员可以修改任意用户
+    #[oai(path = "/users/:id", method = "put", operation_id = "updateUser", tag = ApiTags::User)]
+    async fn update_user(
+        &self,
+        service: Data<&UserService>,
+        audit: AuditContext,
+        config: Data<&ApiConfig>,
+        id: Path<u64>,
+        /// 上次读取时返回的ETag，与当前版本不一致时返回412
+        #[oai(name = "If-Match")]
+        if_match: Header<Option<String>>,
+        req: Json<UpdateUserRequest>,
+    ) -> Result<ConditionalResponse<User>> {
+        let expected_version = match conditional::expected_version(if_match.0.as_deref(), &config) {
+            Ok(version) => version,
+            Err(resp) => return Ok(resp),
+        };
+        Ok(ConditionalResponse::from_result(service.update(&audit, id.0, req.0, expected_version).await))
+    }
+
+    /// 部分更新用户信息
+    ///
+    /// 支持 `application/merge-patch+json`（RFC 7396）与 `application/json-patch+json`（RFC 6902），
+    /// 未涉及的字段保持不变，字段校验规则及权限与更新接口一致
+    #[oai(path = "/users/:id", method = "patch", operation_id = "patchUser", tag = ApiTags::User)]
+    async fn patch_user(
+        &self,
+        service: Data<&UserService>,
+        audit: AuditContext,
+        config: Data<&ApiConfig>,
+        id: Path<u64>,
+        /// 上次读取时返回的ETag，与当前版本不一致时返回412
+        #[oai(name = "If-Match")]
+        if_match: Header<Option<String>>,
+        req: PatchUserPayload,
+    ) -> Result<ConditionalResponse<User>> {
+        let expected_version = match conditional::expected_version(if_match.0.as_deref(), &config) {
+            Ok(version) => version,
+            Err(resp) => return Ok(resp),
+        };
+        let result = match req {
+            PatchUserPayload::MergePatch(req) => service.patch(&audit, id.0, req.0, expected_version).await,
+            PatchUserPayload::JsonPatch(ops) => service.apply_json_patch(&audit, id.0, ops.0, expected_version).await,
+        };
+        Ok(ConditionalResponse::from_result(result))
+    }
+
+    /// 删除用户
+    ///
+    /// 根据用户ID软删除用户，删除后可通过恢复接口恢复，超过保留期后彻底清除；
+    /// 携带 `If-Match` 时只在版本一致时删除；需要管理权限
+    #[oai(path = "/users/:id", method = "delete", operation_id = "deleteUser", tag = ApiTags::User)]
+    async fn delete_user(
+        &self,
+        service: Data<&UserService>,
+        audit: AuditContext,
+        config: Data<&ApiConfig>,
+        id: Path<u64>,
+        /// 上次读取时返回的ETag，与当前版本不一致时返回412
+        #[oai(name = "If-Match")]
+        if_match: Header<Option<String>>,
+    ) -> Result<ConditionalResponse<EmptyResponse>> {
+        let expected_version = match conditional::expected_version(if_match.0.as_deref(), &config) {
+            Ok(version) => version,
+            Err(resp) => return Ok(resp),
+        };
+        let result = service.delete(&audit, id.0, expected_version).await;
+        Ok(ConditionalResponse::from_result(result.map(|_| empty())))
+    }
+
+    /// 恢复已删除的用户
+    ///
+    /// 恢复被软删除且尚未彻底清除的用户，用户未被删除时返回409；
+    /// 携带 `If-Match` 时只在版本一致时恢复；需要管理权限
+    #[oai(path = "/users/:id/restore", method = "post", operation_id = "restoreUser", tag = ApiTags::User)]
+    async fn restore_user(
+        &self,
+        service: Data<&UserService>,
+        audit: AuditContext,
+        config: Data<&ApiConfig>,
+        id: Path<u64>,
+        /// 删除后返回的ETag，与当前版本不一致时返回412
+        #[oai(name = "If-Match")]
+        if_match: Header<Option<String>>,
+    ) -> Result<ConditionalResponse<User>> {
+        let expected_version = match conditional::expected_version(if_match.0.as_deref(), &config) {
+            Ok(version) => version,
+            Err(resp) => return Ok(resp),
+        };
+        Ok(ConditionalResponse::from_result(service.restore(&audit, id.0, expected_version).await))
+    }
+}
@@ -1,5 +1,3 @@
 mod controller;
-mod dto;
 
 pub use controller::UserController;
-pub use dto::*;
@@ -0,0 +1,60 @@
+//! API v1
+//!
+//! 第一版API，部分接口已弃用，新功能请在 `v2` 中开发
+
+pub mod user;
+
+use chrono::{DateTime, TimeZone, Utc};
+use poem_openapi::OpenApiService;
+
+use super::{
+    api_keys::ApiKeyController, audit::AuditController, auth::AuthController, jobs::JobController,
+    mfa::MfaController, new_service, oidc::OidcController, scheduler::SchedulerController,
+    sessions::SessionController, users::UserController, webhooks::WebhookController, ApiVersion,
+};
+use crate::config::DocsServer;
+
+/// v1 中已弃用接口的下线时间
+pub fn sunset_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2027, 6, 30, 0, 0, 0).unwrap()
+}
+
+/// v1包含的API控制器
+pub type Controllers = (
+    UserController,
+    user::UserCollectionController,
+    AuditController,
+    WebhookController,
+    JobController,
+    SchedulerController,
+    AuthController,
+    MfaController,
+    ApiKeyController,
+    OidcController,
+    SessionController,
+);
+
+/// 创建v1版本的OpenAPI服务
+///
+/// 聚合v1的所有API模块，并配置OpenAPI文档
+///
+/// `servers` 为规范中列出的服务器地址，为空时使用相对路径
+pub fn create_api_service(servers: &[DocsServer]) -> OpenApiService<Controllers, ()> {
+    new_service(
+        (
+            UserController,                 // 用户管理API控制器（各版本共用）
+            user::UserCollectionController, // 用户创建及列表API控制器
+            AuditController,                // 审计日志API控制器（各版本共用）
+            WebhookController,              // Webhook订阅API控制器（各版本共用）
+            JobController,                  // 后台任务API控制器（各版本共用）
+            SchedulerController,            // 定时任务API控制器（各版本共用）
+            AuthController,                 // 账号API控制器（各版本共用）
+            MfaController,                  // 两步验证API控制器（各版本共用）
+            ApiKeyController,               // API密钥控制器（各版本共用）
+            OidcController,                 // 单点登录API控制器（各版本共用）
+            SessionController,              // 会话API控制器（各版本共用）
+        ),
+        ApiVersion::V1,
+        servers,
+    )
+}
@@ -0,0 +1,97 @@
+use crate::api::v1::sunset_at;
+use crate::middlewares::Deprecation;
+use crate::models::user::{CreateUserRequest, User, UserListResponse};
+use crate::audit::AuditContext;
+use crate::auth::Principal;
+use crate::services::{UserFilter, UserService};
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{web::Data, Endpoint, EndpointExt, Result};
+use poem_openapi::{
+    param::{Header, Query},
+    payload::Json,
+    OpenApi,
+};
+use  crate::config::tags::ApiTags;
+
+/// 用户创建及列表API控制器（v1）
+///
+/// 提供 `/users` 上的接口，单个用户的接口各版本共用，见 `api::users`
+#[derive(Default)]
+pub struct UserCollectionController;
+
+#[OpenApi]
+impl UserCollectionController {
+    /// 创建新用户
+    ///
+    /// 根据提供的用户信息创建一个新用户
+    #[oai(path = "/users", method = "post", operation_id = "createUser", tag = ApiTags::User)]
+    async fn create_user(
+        &self,
+        service: Data<&UserService>,
+        audit: AuditContext,
+        /// 幂等键，超时重试时使用同一个值可避免重复创建，有效期内直接返回首次的响应
+        #[oai(name = "Idempotency-Key")]
+        _idempotency_key: Header<Option<String>>,
+        req: Json<CreateUserRequest>,
+    ) -> Result<Json<ApiResponse<User>>> {
+        result_json(service.create(&audit, req.0).await)
+    }
+
+    /// 获取用户列表
+    ///
+    /// 根据查询条件获取用户列表，需要管理权限，其他调用方返回401或403。
+    ///
+    /// 已弃用：请使用 `GET /api/v2/users`，其分页响应包含总页数
+    #[oai(
+        path = "/users",
+        method = "get",
+        operation_id = "listUsers",
+        tag = ApiTags::User,
+        deprecated,
+        transform = "deprecate_list_users"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_users(
+        &self,
+        service: Data<&UserService>,
+        principal: Principal,
+        /// 用户名模糊匹配
+        #[oai(name = "username")] username: Query<Option<String>>,
+        /// 邮箱模糊匹配
+        #[oai(name = "email")] email: Query<Option<String>>,
+        /// 分页：页码，从1开始
+        #[oai(name = "page")] page: Query<Option<u32>>,
+        /// 分页：每页记录数
+        #[oai(name = "page_size")] page_size: Query<Option<u32>>,
+    ) -> Result<Json<ApiResponse<UserListResponse>>> {
+        let page = page.0.unwrap_or(1);
+        let page_size = page_size.0.unwrap_or(10);
+        let filter = UserFilter {
+            username: username.0,
+            email: email.0,
+            ..Default::default()
+        };
+
+        let result = service
+            .list(&principal, &filter, page, page_size)
+            .await
+            .map(|(users, total)| UserListResponse {
+                users,
+                total,
+                page,
+                page_size,
+            });
+
+        // 返回统一格式的响应
+        result_json(result)
+    }
+}
+
+/// `listUsers` 已弃用，响应中附带 `Deprecation`、`Sunset` 及替代接口地址
+fn deprecate_list_users(ep: impl Endpoint) -> impl Endpoint {
+    ep.with(
+        Deprecation::new()
+            .sunset(sunset_at())
+            .successor("/api/v2/users"),
+    )
+}
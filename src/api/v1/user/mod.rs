@@ -0,0 +1,4 @@
+mod controller;
+mod dto;
+
+pub use controller::UserCollectionController;
@@ -0,0 +1,54 @@
+//! API v2
+//!
+//! 第二版API，与v1并行提供服务
+
+pub mod user;
+
+use poem_openapi::OpenApiService;
+
+use super::{
+    api_keys::ApiKeyController, audit::AuditController, auth::AuthController, jobs::JobController,
+    mfa::MfaController, new_service, oidc::OidcController, scheduler::SchedulerController,
+    sessions::SessionController, users::UserController, webhooks::WebhookController, ApiVersion,
+};
+use crate::config::DocsServer;
+
+/// v2包含的API控制器
+pub type Controllers = (
+    UserController,
+    user::UserCollectionController,
+    AuditController,
+    WebhookController,
+    JobController,
+    SchedulerController,
+    AuthController,
+    MfaController,
+    ApiKeyController,
+    OidcController,
+    SessionController,
+);
+
+/// 创建v2版本的OpenAPI服务
+///
+/// 聚合v2的所有API模块，并配置OpenAPI文档
+///
+/// `servers` 为规范中列出的服务器地址，为空时使用相对路径
+pub fn create_api_service(servers: &[DocsServer]) -> OpenApiService<Controllers, ()> {
+    new_service(
+        (
+            UserController,                 // 用户管理API控制器（各版本共用）
+            user::UserCollectionController, // 用户创建及列表API控制器
+            AuditController,                // 审计日志API控制器（各版本共用）
+            WebhookController,              // Webhook订阅API控制器（各版本共用）
+            JobController,                  // 后台任务API控制器（各版本共用）
+            SchedulerController,            // 定时任务API控制器（各版本共用）
+            AuthController,                 // 账号API控制器（各版本共用）
+            MfaController,                  // 两步验证API控制器（各版本共用）
+            ApiKeyController,               // API密钥控制器（各版本共用）
+            OidcController,                 // 单点登录API控制器（各版本共用）
+            SessionController,              // 会话API控制器（各版本共用）
+        ),
+        ApiVersion::V2,
+        servers,
+    )
+}
@@ -0,0 +1,92 @@
+use crate::models::user::{CreateUserRequest, User, UserPageResponse};
+use crate::audit::AuditContext;
+use crate::auth::Principal;
+use crate::services::{UserFilter, UserService};
+use crate::utils::response::{result_json, ApiResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{
+    param::{Header, Query},
+    payload::Json,
+    OpenApi,
+};
+use crate::config::tags::ApiTags;
+
+/// 用户创建及列表API控制器（v2）
+///
+/// 提供 `/users` 上的接口，与v1相比，用户列表改为包含总页数的分页响应，并限制每页记录数上限；
+/// 单个用户的接口各版本共用，见 `api::users`
+#[derive(Default)]
+pub struct UserCollectionController;
+
+#[OpenApi]
+impl UserCollectionController {
+    /// 创建新用户
+    ///
+    /// 根据提供的用户信息创建一个新用户
+    #[oai(path = "/users", method = "post", operation_id = "createUser", tag = ApiTags::User)]
+    async fn create_user(
+        &self,
+        service: Data<&UserService>,
+        audit: AuditContext,
+        /// 幂等键，超时重试时使用同一个值可避免重复创建，有效期内直接返回首次的响应
+        #[oai(name = "Idempotency-Key")]
+        _idempotency_key: Header<Option<String>>,
+        req: Json<CreateUserRequest>,
+    ) -> Result<Json<ApiResponse<User>>> {
+        result_json(service.create(&audit, req.0).await)
+    }
+
+    /// 分页获取用户列表
+    ///
+    /// 根据查询条件分页获取用户列表，响应中包含总记录数和总页数；
+    /// 需要管理权限，其他调用方返回401或403
+    #[oai(path = "/users", method = "get", operation_id = "listUsers", tag = ApiTags::User)]
+    #[allow(clippy::too_many_arguments)]
+    async fn list_users(
+        &self,
+        service: Data<&UserService>,
+        principal: Principal,
+        /// 用户名模糊匹配
+        username: Query<Option<String>>,
+        /// 邮箱模糊匹配
+        email: Query<Option<String>>,
+        /// 分页：页码，从1开始
+        #[oai(default = "default_page", validator(minimum(value = "1")))]
+        page: Query<u32>,
+        /// 分页：每页记录数，最大100
+        #[oai(default = "default_page_size", validator(minimum(value = "1"), maximum(value = "100")))]
+        page_size: Query<u32>,
+        /// 是否包含已删除的用户
+        #[oai(default)]
+        include_deleted: Query<bool>,
+    ) -> Result<Json<ApiResponse<UserPageResponse>>> {
+        let filter = UserFilter {
+            username: username.0,
+            email: email.0,
+            include_deleted: include_deleted.0,
+        };
+
+        let result = service
+            .list(&principal, &filter, page.0, page_size.0)
+            .await
+            .map(|(items, total)| UserPageResponse {
+                items,
+                total,
+                page: page.0,
+                page_size: page_size.0,
+                total_pages: total.div_ceil(page_size.0 as u64) as u32,
+            });
+
+        result_json(result)
+    }
+}
+
+/// 默认页码
+fn default_page() -> u32 {
+    1
+}
+
+/// 默认每页记录数
+fn default_page_size() -> u32 {
+    10
+}
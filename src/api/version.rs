@@ -0,0 +1,150 @@
+//! API版本协商
+//!
+//! 除了 `/api/v1`、`/api/v2` 路径前缀外，也可以请求不带版本前缀的 `/api/...`，
+//! 并通过 `Accept-Version` 请求头选择版本，未指定时使用默认版本
+
+use std::{fmt, str::FromStr};
+
+use poem::{
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    web::Json,
+    Endpoint, EndpointExt, IntoEndpoint, IntoResponse, Request, Response, Result,
+};
+
+use crate::utils::response::{ApiResponse, EmptyResponse};
+
+/// 请求版本的请求头
+pub const ACCEPT_VERSION: HeaderName = HeaderName::from_static("accept-version");
+
+/// 响应实际使用版本的响应头
+pub const API_VERSION: HeaderName = HeaderName::from_static("api-version");
+
+/// API版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// 第一版
+    V1,
+    /// 第二版
+    V2,
+}
+
+impl ApiVersion {
+    /// 所有支持的版本
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    /// 未指定版本时使用的默认版本，保持对旧客户端的兼容
+    pub const DEFAULT: ApiVersion = ApiVersion::V1;
+
+    /// 版本标识，同时也是路径前缀
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+
+    /// 解析版本标识，支持 `v2`、`V2`、`2` 等写法
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value
+            .strip_prefix('v')
+            .or_else(|| value.strip_prefix('V'))
+            .unwrap_or(value);
+        match value {
+            "1" => Some(Self::V1),
+            "2" => Some(Self::V2),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(value).ok_or_else(|| format!("不支持的API版本: {}", value))
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 根据 `Accept-Version` 请求头分发到对应版本的端点
+pub struct NegotiateVersion {
+    endpoints: Vec<(ApiVersion, poem::endpoint::BoxEndpoint<'static, Response>)>,
+}
+
+impl NegotiateVersion {
+    /// 创建空的版本分发端点
+    pub fn new() -> Self {
+        Self {
+            endpoints: Vec::new(),
+        }
+    }
+
+    /// 注册某个版本的端点
+    pub fn version<E>(mut self, version: ApiVersion, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.endpoints
+            .push((version, ep.into_endpoint().map_to_response().boxed()));
+        self
+    }
+}
+
+impl Default for NegotiateVersion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Endpoint for NegotiateVersion {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let requested = req
+            .headers()
+            .get(ACCEPT_VERSION)
+            .and_then(|value| value.to_str().ok());
+
+        let version = match requested {
+            None => ApiVersion::DEFAULT,
+            Some(value) => match ApiVersion::parse(value) {
+                Some(version) => version,
+                None => return Ok(unsupported_version(value)),
+            },
+        };
+
+        let Some((_, ep)) = self.endpoints.iter().find(|(v, _)| *v == version) else {
+            return Ok(unsupported_version(version.as_str()));
+        };
+
+        let mut resp = ep.call(req).await?;
+        let headers = resp.headers_mut();
+        headers.insert(API_VERSION, HeaderValue::from_static(version.as_str()));
+        // 响应内容随请求头变化，提示缓存按版本区分
+        headers.append(header::VARY, HeaderValue::from_static("accept-version"));
+        Ok(resp)
+    }
+}
+
+/// 不支持的版本，返回400及支持的版本列表
+fn unsupported_version(requested: &str) -> Response {
+    let supported = ApiVersion::ALL
+        .iter()
+        .map(ApiVersion::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Json(ApiResponse::<EmptyResponse>::error(
+        400,
+        format!("不支持的API版本: {}，可用版本: {}", requested, supported),
+    ))
+    .with_status(StatusCode::BAD_REQUEST)
+    .into_response()
+}
@@ -0,0 +1,146 @@
+use crate::auth::Admin;
+use crate::config::tags::ApiTags;
+use crate::models::webhook::{
+    CreateWebhookRequest, DeliveryStatus, UpdateWebhookRequest, Webhook, WebhookDelivery,
+    WebhookDeliveryPageResponse, WebhookListResponse,
+};
+use crate::services::WebhookService;
+use crate::utils::response::{empty, result_json, ApiResponse, EmptyResponse};
+use poem::{web::Data, Result};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+
+/// Webhook订阅API控制器
+///
+/// 各版本共用，管理订阅及查看投递记录，需要管理权限；请求体签名方式见 `webhooks` 模块
+#[derive(Default)]
+pub struct WebhookController;
+
+#[OpenApi]
+impl WebhookController {
+    /// 创建Webhook订阅
+    ///
+    /// 订阅的事件发生后以POST请求推送到指定地址，请求头 `X-Webhook-Signature` 为使用 `secret`
+    /// 对 `"{X-Webhook-Timestamp}.{请求体}"` 计算的HMAC-SHA256签名
+    #[oai(path = "/webhooks", method = "post", operation_id = "createWebhook", tag = ApiTags::Webhook)]
+    async fn create_webhook(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+        req: Json<CreateWebhookRequest>,
+    ) -> Result<Json<ApiResponse<Webhook>>> {
+        result_json(service.create(req.0).await)
+    }
+
+    /// 获取Webhook订阅列表
+    #[oai(path = "/webhooks", method = "get", operation_id = "listWebhooks", tag = ApiTags::Webhook)]
+    async fn list_webhooks(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+    ) -> Result<Json<ApiResponse<WebhookListResponse>>> {
+        result_json(service.list().await.map(|items| WebhookListResponse { items }))
+    }
+
+    /// 获取Webhook订阅详情
+    #[oai(path = "/webhooks/:id", method = "get", operation_id = "getWebhook", tag = ApiTags::Webhook)]
+    async fn get_webhook(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<Webhook>>> {
+        result_json(service.get(id.0).await)
+    }
+
+    /// 修改Webhook订阅
+    ///
+    /// 只修改提交的字段，可用于轮换密钥；自动停用的订阅修复后将 `enabled` 设为 `true` 重新启用
+    #[oai(path = "/webhooks/:id", method = "put", operation_id = "updateWebhook", tag = ApiTags::Webhook)]
+    async fn update_webhook(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+        id: Path<u64>,
+        req: Json<UpdateWebhookRequest>,
+    ) -> Result<Json<ApiResponse<Webhook>>> {
+        result_json(service.update(id.0, req.0).await)
+    }
+
+    /// 删除Webhook订阅
+    ///
+    /// 同时删除订阅的投递记录，尚未投递的事件不再投递
+    #[oai(path = "/webhooks/:id", method = "delete", operation_id = "deleteWebhook", tag = ApiTags::Webhook)]
+    async fn delete_webhook(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+        id: Path<u64>,
+    ) -> Result<Json<ApiResponse<EmptyResponse>>> {
+        result_json(service.delete(id.0).await.map(|_| empty()))
+    }
+
+    /// 查询投递记录
+    ///
+    /// 按创建时间倒序分页返回订阅的投递记录，包含最后一次响应的状态码及内容
+    #[oai(path = "/webhooks/:id/deliveries", method = "get", operation_id = "listWebhookDeliveries", tag = ApiTags::Webhook)]
+    async fn list_deliveries(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+        id: Path<u64>,
+        /// 投递状态
+        status: Query<Option<DeliveryStatus>>,
+        /// 分页：页码，从1开始
+        #[oai(default = "default_page", validator(minimum(value = "1")))]
+        page: Query<u32>,
+        /// 分页：每页记录数，最大100
+        #[oai(default = "default_page_size", validator(minimum(value = "1"), maximum(value = "100")))]
+        page_size: Query<u32>,
+    ) -> Result<Json<ApiResponse<WebhookDeliveryPageResponse>>> {
+        let result = service
+            .deliveries(id.0, status.0, page.0, page_size.0)
+            .await
+            .map(|(items, total)| WebhookDeliveryPageResponse {
+                items,
+                total,
+                page: page.0,
+                page_size: page_size.0,
+                total_pages: total.div_ceil(page_size.0 as u64) as u32,
+            });
+
+        result_json(result)
+    }
+
+    /// 重新投递
+    ///
+    /// 以原请求体创建一条新的投递记录并尽快投递，事件ID不变，接收方可据此去重；订阅已停用时返回409
+    #[oai(
+        path = "/webhooks/:id/deliveries/:delivery_id/redeliver",
+        method = "post",
+        operation_id = "redeliverWebhookDelivery",
+        tag = ApiTags::Webhook
+    )]
+    async fn redeliver(
+        &self,
+        _admin: Admin,
+        service: Data<&WebhookService>,
+        id: Path<u64>,
+        delivery_id: Path<u64>,
+    ) -> Result<Json<ApiResponse<WebhookDelivery>>> {
+        result_json(service.redeliver(id.0, delivery_id.0).await)
+    }
+}
+
+/// 默认页码
+fn default_page() -> u32 {
+    1
+}
+
+/// 默认每页记录数
+fn default_page_size() -> u32 {
+    20
+}
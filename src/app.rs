@@ -0,0 +1,268 @@
+//! 应用组装
+//!
+//! 根据配置和共享状态构建完整的路由及中间件，服务启动与集成测试共用同一套组装逻辑
+
+use std::net::SocketAddr;
+
+use poem::{
+    middleware::{CookieJarManager, Cors, RequestId, ReuseId, Tracing},
+    Endpoint, EndpointExt, Response, Route,
+};
+
+use crate::accounts::{AccountEmailHandler, TokenSigner, VerificationEmail};
+use crate::audit::AuditLog;
+use crate::config::AppConfig;
+use crate::db::{self, DbPool};
+use crate::events::{EventBus, Outbox};
+use crate::health::HealthRegistry;
+use crate::jobs::JobQueue;
+use crate::mailer::{self, Mailer, SendEmailHandler, WelcomeEmail};
+use crate::scheduler::{PurgeDeletedUsers, PurgeExpiredSessions, Scheduler};
+use crate::services::{
+    AccountService, ApiKeyService, MfaService, OidcService, SessionService, UserService, WebhookService,
+};
+use crate::shutdown::ShutdownToken;
+use crate::webhooks::{WebhookDispatcher, WebhookSender};
+use crate::{api, graphql, health, middlewares, sessions};
+
+/// 应用共享状态
+///
+/// 各字段克隆开销都很小，通过 `.data()` 注入后在处理函数中用 `Data<&T>` 获取
+#[derive(Clone)]
+pub struct AppState {
+    /// 数据库连接池
+    pub pool: DbPool,
+    /// 用户服务
+    pub users: UserService,
+    /// 账号服务（邮箱验证、密码重置）
+    pub accounts: AccountService,
+    /// 两步验证服务
+    pub mfa: MfaService,
+    /// API密钥服务
+    pub api_keys: ApiKeyService,
+    /// 外部身份提供方登录服务
+    pub oidc: OidcService,
+    /// 浏览器会话服务
+    pub sessions: SessionService,
+    /// 审计日志查询
+    pub audit: AuditLog,
+    /// Webhook订阅服务
+    pub webhooks: WebhookService,
+    /// Webhook投递，由后台任务定期调用
+    pub webhook_sender: WebhookSender,
+    /// 领域事件总线，订阅者在启动时注册
+    pub events: EventBus,
+    /// 领域事件发件箱，由后台任务投递到事件总线
+    pub outbox: Outbox,
+    /// 后台任务队列，任务处理器在启动时注册
+    pub jobs: JobQueue,
+    /// 定时任务调度器，任务在启动时注册
+    pub scheduler: Scheduler,
+    /// 邮件发送器，业务流程中通过后台任务发送
+    pub mailer: Mailer,
+    /// 健康检查注册表，数据库、缓存等组件可在此注册检查项
+    pub health: HealthRegistry,
+    /// 关闭令牌，后台任务和订阅连接通过它感知服务关闭
+    pub shutdown: ShutdownToken,
+}
+
+impl AppState {
+    /// 连接数据库并创建各项服务，按配置自动执行迁移
+    pub async fn new(config: &AppConfig) -> anyhow::Result<Self> {
+        let pool = db::connect(&config.database).await?;
+        if config.database.auto_migrate {
+            db::MIGRATOR.run(&pool).await?;
+        }
+
+        let health = HealthRegistry::new();
+        health.register(db::DatabaseHealthCheck::new(pool.clone()));
+
+        // 邮件通过后台任务发送
+        let mailer = Mailer::new(&config.mailer)?;
+        let jobs = JobQueue::new(pool.clone(), config.jobs.clone());
+        jobs.register(SendEmailHandler::new(mailer.clone()));
+
+        let session_store = sessions::create_store(&config.sessions, &pool);
+        let users = UserService::new(pool.clone(), config.security.bcrypt_cost, session_store.clone());
+        let accounts = AccountService::new(
+            pool.clone(),
+            users.clone(),
+            jobs.clone(),
+            mailer.clone(),
+            TokenSigner::new(config.security.token_secret.as_deref()),
+            config.accounts.clone(),
+        );
+        jobs.register(AccountEmailHandler::new(accounts.clone()));
+        let mfa = MfaService::new(pool.clone(), users.clone(), config.mfa.clone());
+        let oidc = OidcService::new(pool.clone(), users.clone(), &config.oidc)?;
+        let sessions = SessionService::new(
+            pool.clone(),
+            session_store,
+            users.clone(),
+            mfa.clone(),
+            config.sessions.clone(),
+        );
+
+        // 领域事件转为Webhook投递记录、欢迎邮件及邮箱验证邮件
+        let events = EventBus::new();
+        events.subscribe(WebhookDispatcher::new(pool.clone()));
+        if config.mailer.welcome_enabled {
+            events.subscribe(WelcomeEmail::new(jobs.clone()));
+        }
+        if config.accounts.send_verification {
+            events.subscribe(VerificationEmail::new(jobs.clone()));
+        }
+
+        // 内置的维护任务，执行计划见配置 `scheduler.tasks`
+        let scheduler = Scheduler::new(pool.clone(), config.scheduler.clone())?;
+        if let Some(task) = PurgeDeletedUsers::new(users.clone(), &config.retention) {
+            scheduler.register(task);
+        }
+        if config.sessions.enabled {
+            scheduler.register(PurgeExpiredSessions::new(sessions.clone()));
+        }
+
+        Ok(Self {
+            users,
+            accounts,
+            mfa,
+            api_keys: ApiKeyService::new(pool.clone(), config.api_keys.clone()),
+            oidc,
+            sessions,
+            audit: AuditLog::new(pool.clone()),
+            webhooks: WebhookService::new(pool.clone(), config.webhooks.clone()),
+            webhook_sender: WebhookSender::new(pool.clone(), config.webhooks.clone())?,
+            outbox: Outbox::new(pool.clone(), events.clone(), config.events.clone()),
+            jobs,
+            scheduler,
+            mailer,
+            events,
+            pool,
+            health,
+            shutdown: ShutdownToken::new(),
+        })
+    }
+}
+
+/// 构建公共端应用
+///
+/// 未启用独立管理端时，API文档、健康检查等管理路由也挂载在这里
+pub fn build(config: &AppConfig, state: &AppState) -> impl Endpoint<Output = Response> {
+    let tls_config = &config.server.tls;
+
+    // 携带 Idempotency-Key 的POST请求在重试时重放首次的响应
+    let idempotency = middlewares::Idempotency::new(
+        state.pool.clone(),
+        config.idempotency.ttl(),
+        config.idempotency.lease(),
+    );
+
+    // 携带API密钥的请求以密钥身份调用，REST按请求方法、GraphQL按操作类型检查授权范围
+    let api_key_auth = middlewares::ApiKeyAuth::new(state.api_keys.clone());
+
+    // 携带会话Cookie的请求以登录用户身份调用，修改数据的请求需要提交CSRF令牌；已通过API密钥认证时不使用会话
+    let session_auth = middlewares::SessionAuth::new(state.sessions.clone());
+
+    // 公共路由：对外提供的API
+    let routes = Route::new()
+        // API路由（/api/v1、/api/v2，或通过 Accept-Version 请求头选择版本）
+        .nest(
+            "/api",
+            api::create_api_route()
+                .with_if(config.idempotency.enabled, idempotency)
+                .with_if(config.sessions.enabled, session_auth.clone())
+                .with_if(config.api_keys.enabled, api_key_auth.clone()),
+        )
+        // GraphQL路由
+        .nest(
+            "/graphql",
+            graphql::create_graphql_route()
+                .with_if(config.sessions.enabled, session_auth)
+                .with_if(config.api_keys.enabled, api_key_auth.scope_by_method(false)),
+        );
+
+    // 管理路由：配置了独立管理端时不在公共地址上提供
+    let routes = if config.server.admin.is_enabled() {
+        routes
+    } else {
+        admin_routes(routes, config)
+    };
+
+    // HTTP重定向的目标端口与HTTPS监听端口一致
+    let https_port = config
+        .server
+        .listen_addr
+        .parse::<SocketAddr>()
+        .map(|addr| addr.port())
+        .unwrap_or(443);
+
+    with_state(routes, config, state)
+        // 处理函数及中间件返回的错误（例如401、403）在此转换为响应，外层中间件同样为其写入Cookie、请求ID等响应头
+        .catch_all_error(|err| async move { err.into_response() })
+        // 解析请求中的Cookie，处理函数及会话中间件修改的Cookie写入响应头
+        .with(CookieJarManager::new())
+        // 启用TLS时添加HSTS响应头
+        .with_if(
+            tls_config.enabled && tls_config.hsts_max_age_secs > 0,
+            middlewares::hsts(tls_config),
+        )
+        // 启用HTTP重定向时，将明文请求重定向到HTTPS
+        .with_if(
+            tls_config.enabled && tls_config.redirect_http_addr.is_some(),
+            middlewares::HttpsRedirect::new(https_port),
+        )
+        // 添加CORS中间件
+        .with(Cors::new())
+        // 添加日志中间件
+        .with(Tracing)
+        // 为每个请求分配请求ID（沿用客户端提供的 X-Request-Id），并在响应头中返回
+        .with(RequestId::new().reuse_id(ReuseId::Use))
+}
+
+/// 构建管理端应用（API文档、健康检查、邮件模板预览）
+pub fn build_admin(config: &AppConfig, state: &AppState) -> impl Endpoint<Output = Response> {
+    with_state(admin_routes(Route::new(), config), config, state).with(Tracing)
+}
+
+/// 挂载管理路由（API文档、健康检查、邮件模板预览）
+fn admin_routes(route: Route, config: &AppConfig) -> Route {
+    // 各版本的文档UI及OpenAPI规范端点，可在生产环境关闭
+    let route = if config.docs.enabled {
+        route.nest("/api/docs", api::create_docs_route(&config.docs))
+    } else {
+        route
+    };
+
+    // 邮件模板预览，仅用于开发环境
+    let route = if config.mailer.preview_enabled {
+        route.nest("/dev/mail", mailer::create_preview_route())
+    } else {
+        route
+    };
+
+    // 健康检查路由
+    route.nest("/health", health::create_health_route())
+}
+
+/// 注入共享状态
+fn with_state(route: Route, config: &AppConfig, state: &AppState) -> impl Endpoint<Output = Response> {
+    route
+        // 注入业务服务
+        .data(state.users.clone())
+        .data(state.accounts.clone())
+        .data(state.mfa.clone())
+        .data(state.api_keys.clone())
+        .data(state.oidc.clone())
+        .data(state.sessions.clone())
+        .data(state.audit.clone())
+        .data(state.webhooks.clone())
+        .data(state.jobs.clone())
+        .data(state.scheduler.clone())
+        .data(state.mailer.clone())
+        // 注入API行为配置（条件请求等）
+        .data(config.api.clone())
+        // 注入健康检查注册表和关闭令牌
+        .data(state.health.clone())
+        .data(state.shutdown.clone())
+        .map_to_response()
+}
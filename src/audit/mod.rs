@@ -0,0 +1,300 @@
+//! 审计日志
+//!
+//! 记录每次数据变更的操作者、动作、实体、变更前后的字段差异、请求ID、客户端IP及时间。
+//! 审计记录与数据变更在同一个事务中写入，存储只允许追加（数据库触发器拒绝修改和删除）
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use poem::{middleware::ReqId, FromRequest, Request, RequestBody, Result};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::auth::Principal;
+use crate::db::{DbConnection, DbPool};
+use crate::models::audit::{AuditAction, AuditEvent};
+use crate::services::ServiceError;
+
+/// 敏感字段的值在审计记录中的替代文本
+pub const REDACTED: &str = "[REDACTED]";
+
+/// 不计入变更差异的字段，每次修改都会变化，由事件时间体现
+const IGNORED_FIELDS: [&str; 1] = ["updated_at"];
+
+/// 审计上下文：发起变更的操作者及请求信息
+///
+/// 可在处理函数中直接提取；命令行、后台任务等非请求场景使用 `AuditContext::system`
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    /// 操作者
+    pub actor: Principal,
+    /// 请求ID
+    pub request_id: Option<String>,
+    /// 客户端IP
+    pub ip: Option<String>,
+}
+
+impl AuditContext {
+    /// 服务自身发起的操作，`name` 为发起操作的组件，例如 `cli`、`retention`
+    pub fn system(name: &str) -> Self {
+        Self {
+            actor: Principal::System(name.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> FromRequest<'a> for AuditContext {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        Ok(Self {
+            actor: Principal::from_request(req, body).await?,
+            request_id: req.data::<ReqId>().map(ToString::to_string),
+            ip: req.remote_addr().as_socket_addr().map(|addr| addr.ip().to_string()),
+        })
+    }
+}
+
+/// 待写入的审计记录
+#[derive(Debug)]
+pub struct AuditEntry {
+    action: AuditAction,
+    entity_type: &'static str,
+    entity_id: String,
+    changes: Map<String, Value>,
+}
+
+impl AuditEntry {
+    /// 创建审计记录
+    pub fn new(action: AuditAction, entity_type: &'static str, entity_id: impl ToString) -> Self {
+        Self {
+            action,
+            entity_type,
+            entity_id: entity_id.to_string(),
+            changes: Map::new(),
+        }
+    }
+
+    /// 比较实体变更前后的JSON表示，记录取值不同的字段；创建时 `before` 为空，清除时 `after` 为空
+    pub fn diff<T: Serialize>(mut self, before: Option<&T>, after: Option<&T>) -> Self {
+        let before = object(before);
+        let after = object(after);
+
+        let fields = before.keys().chain(after.keys().filter(|key| !before.contains_key(*key)));
+        for field in fields {
+            if IGNORED_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            let old = before.get(field).unwrap_or(&Value::Null);
+            let new = after.get(field).unwrap_or(&Value::Null);
+            if old != new {
+                self.changes
+                    .insert(field.clone(), json!({ "before": old, "after": new }));
+            }
+        }
+        self
+    }
+
+    /// 记录敏感字段发生了变化，不保存具体取值
+    pub fn redacted(mut self, field: &str) -> Self {
+        self.changes
+            .insert(field.to_string(), json!({ "before": REDACTED, "after": REDACTED }));
+        self
+    }
+}
+
+/// 实体的JSON对象表示，为空或不是对象时视为没有字段
+fn object<T: Serialize>(value: Option<&T>) -> Map<String, Value> {
+    match value.map(serde_json::to_value) {
+        Some(Ok(Value::Object(object))) => object,
+        _ => Map::new(),
+    }
+}
+
+/// 在数据变更所在的事务中写入审计记录，事务回滚时审计记录一并撤销
+pub async fn record(conn: &mut DbConnection, ctx: &AuditContext, entry: AuditEntry) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_events \
+         (occurred_at, actor_type, actor_id, actor_name, action, entity_type, entity_id, changes, request_id, ip) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+    .bind(ctx.actor.kind())
+    .bind(ctx.actor.id().map(|id| id.to_string()))
+    .bind(ctx.actor.name())
+    .bind(entry.action.as_str())
+    .bind(entry.entity_type)
+    .bind(entry.entity_id)
+    .bind(Value::Object(entry.changes).to_string())
+    .bind(&ctx.request_id)
+    .bind(&ctx.ip)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 审计事件过滤条件，各条件均为精确匹配
+#[derive(Debug, Default, Clone)]
+pub struct AuditFilter {
+    /// 操作者用户ID
+    pub actor_id: Option<String>,
+    /// 动作
+    pub action: Option<AuditAction>,
+    /// 实体类型
+    pub entity_type: Option<String>,
+    /// 实体ID
+    pub entity_id: Option<String>,
+    /// 请求ID
+    pub request_id: Option<String>,
+    /// 起始时间（RFC 3339，包含）
+    pub since: Option<String>,
+    /// 截止时间（RFC 3339，不包含）
+    pub until: Option<String>,
+}
+
+/// 数据库中的审计记录
+#[derive(sqlx::FromRow)]
+struct AuditRow {
+    id: i64,
+    occurred_at: String,
+    actor_type: String,
+    actor_id: Option<String>,
+    actor_name: Option<String>,
+    action: String,
+    entity_type: String,
+    entity_id: String,
+    changes: String,
+    request_id: Option<String>,
+    ip: Option<String>,
+}
+
+impl TryFrom<AuditRow> for AuditEvent {
+    type Error = ServiceError;
+
+    fn try_from(row: AuditRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id as u64,
+            occurred_at: row.occurred_at,
+            actor_type: row.actor_type,
+            actor_id: row.actor_id,
+            actor_name: row.actor_name,
+            action: AuditAction::parse(&row.action)
+                .ok_or_else(|| ServiceError::internal(anyhow::anyhow!("未知的审计动作: {}", row.action)))?,
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+            changes: serde_json::from_str(&row.changes).map_err(ServiceError::internal)?,
+            request_id: row.request_id,
+            ip: row.ip,
+        })
+    }
+}
+
+/// 审计日志查询
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    pool: DbPool,
+}
+
+impl AuditLog {
+    /// 创建审计日志查询服务
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 分页查询审计事件（按时间倒序），返回当前页数据及总记录数
+    pub async fn list(
+        &self,
+        filter: &AuditFilter,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<AuditEvent>, u64), ServiceError> {
+        if page == 0 || page_size == 0 {
+            return Err(ServiceError::Validation("页码和每页记录数必须大于0".to_string()));
+        }
+
+        let total = self.count(filter).await?;
+        let offset = (page as i64 - 1) * page_size as i64;
+        let events = self.fetch(filter, None, page_size, offset).await?;
+        Ok((events, total))
+    }
+
+    /// 查询ID小于 `before_id` 的审计事件（按时间倒序），用于游标分页
+    pub async fn list_before(
+        &self,
+        filter: &AuditFilter,
+        before_id: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, ServiceError> {
+        self.fetch(filter, before_id, limit, 0).await
+    }
+
+    /// 符合条件的审计事件总数
+    pub async fn count(&self, filter: &AuditFilter) -> Result<u64, ServiceError> {
+        let (since, until) = time_range(filter)?;
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM audit_events WHERE {}", CONDITION))
+            .bind(&filter.actor_id)
+            .bind(filter.action.map(|action| action.as_str()))
+            .bind(&filter.entity_type)
+            .bind(&filter.entity_id)
+            .bind(&filter.request_id)
+            .bind(since)
+            .bind(until)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(total as u64)
+    }
+
+    /// 按条件查询一页审计事件
+    async fn fetch(
+        &self,
+        filter: &AuditFilter,
+        before_id: Option<u64>,
+        limit: u32,
+        offset: i64,
+    ) -> Result<Vec<AuditEvent>, ServiceError> {
+        let (since, until) = time_range(filter)?;
+        sqlx::query_as::<_, AuditRow>(&format!(
+            "SELECT id, occurred_at, actor_type, actor_id, actor_name, action, entity_type, entity_id, \
+             changes, request_id, ip FROM audit_events WHERE {} AND (?8 IS NULL OR id < ?8) \
+             ORDER BY id DESC LIMIT ?9 OFFSET ?10",
+            CONDITION
+        ))
+        .bind(&filter.actor_id)
+        .bind(filter.action.map(|action| action.as_str()))
+        .bind(&filter.entity_type)
+        .bind(&filter.entity_id)
+        .bind(&filter.request_id)
+        .bind(since)
+        .bind(until)
+        .bind(before_id.map(|id| id as i64))
+        .bind(limit as i64)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(AuditEvent::try_from)
+        .collect()
+    }
+}
+
+/// 过滤条件，未提供的条件恒为真
+const CONDITION: &str = "(?1 IS NULL OR actor_id = ?1) AND (?2 IS NULL OR action = ?2) \
+                         AND (?3 IS NULL OR entity_type = ?3) AND (?4 IS NULL OR entity_id = ?4) \
+                         AND (?5 IS NULL OR request_id = ?5) \
+                         AND (?6 IS NULL OR occurred_at >= ?6) AND (?7 IS NULL OR occurred_at < ?7)";
+
+/// 解析时间范围，统一为存储使用的UTC格式以便按字符串比较
+fn time_range(filter: &AuditFilter) -> Result<(Option<String>, Option<String>), ServiceError> {
+    Ok((
+        parse_time(filter.since.as_deref(), "since")?,
+        parse_time(filter.until.as_deref(), "until")?,
+    ))
+}
+
+/// 解析RFC 3339格式的时间，`field` 为出错时提示的参数名
+fn parse_time(value: Option<&str>, field: &str) -> Result<Option<String>, ServiceError> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|time| time.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Secs, true))
+                .map_err(|_| ServiceError::Validation(format!("{} 必须为RFC 3339格式的时间", field)))
+        })
+        .transpose()
+}
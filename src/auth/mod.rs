@@ -0,0 +1,137 @@
+//! 认证
+//!
+//! 认证中间件识别调用方身份后将 `Principal` 写入请求扩展，处理函数通过提取器获取；
+//! 未经认证的请求视为匿名调用方。API密钥由 `middlewares::ApiKeyAuth` 认证，
+//! 浏览器的Cookie会话由 `middlewares::SessionAuth` 认证。
+//!
+//! 管理接口在处理函数中声明 `Admin` 参数，GraphQL解析器及服务中使用 `Principal::require_admin`。
+//!
+//! `totp` 提供两步验证使用的一次性密码算法
+
+pub mod totp;
+
+use poem::{FromRequest, Request, RequestBody, Result};
+
+use crate::models::api_key::ApiKeyScope;
+use crate::models::user::UserRole;
+use crate::services::ServiceError;
+use crate::utils::response::status_error;
+
+/// 调用方身份
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Principal {
+    /// 未认证的调用方
+    #[default]
+    Anonymous,
+    /// 服务自身发起的操作，例如命令行、后台任务
+    System(String),
+    /// 已认证的用户
+    User {
+        id: u64,
+        username: String,
+        role: UserRole,
+    },
+    /// 使用API密钥的机器调用方
+    ApiKey {
+        id: u64,
+        name: String,
+        scopes: Vec<ApiKeyScope>,
+    },
+}
+
+impl Principal {
+    /// 身份类型标识：`anonymous`、`system`、`user`、`api_key`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Anonymous => "anonymous",
+            Self::System(_) => "system",
+            Self::User { .. } => "user",
+            Self::ApiKey { .. } => "api_key",
+        }
+    }
+
+    /// 用户ID或API密钥ID，其他身份时为空
+    pub fn id(&self) -> Option<u64> {
+        match self {
+            Self::User { id, .. } | Self::ApiKey { id, .. } => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// 显示名称：用户名、系统组件名或API密钥名称，匿名时为空
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Anonymous => None,
+            Self::System(name) => Some(name),
+            Self::User { username, .. } => Some(username),
+            Self::ApiKey { name, .. } => Some(name),
+        }
+    }
+
+    /// 是否具有授权范围 `scope`
+    ///
+    /// 只有API密钥受授权范围限制，其他身份的权限由各自的认证方式决定
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        match self {
+            Self::ApiKey { scopes, .. } => scopes.iter().any(|granted| granted.includes(scope)),
+            _ => true,
+        }
+    }
+
+    /// 是否具有管理权限：管理员用户、具有 `admin` 授权范围的API密钥，以及服务自身
+    pub fn is_admin(&self) -> bool {
+        match self {
+            Self::Anonymous => false,
+            Self::System(_) => true,
+            Self::User { role, .. } => *role == UserRole::Admin,
+            Self::ApiKey { .. } => self.has_scope(ApiKeyScope::Admin),
+        }
+    }
+
+    /// 要求管理权限：匿名调用方返回401，其他身份返回403
+    pub fn require_admin(&self) -> Result<(), ServiceError> {
+        match self {
+            _ if self.is_admin() => Ok(()),
+            Self::Anonymous => Err(ServiceError::Unauthorized("未登录或缺少认证信息".to_string())),
+            Self::ApiKey { .. } => Err(ServiceError::Forbidden("API密钥缺少 admin 授权范围".to_string())),
+            _ => Err(ServiceError::Forbidden("需要管理员权限".to_string())),
+        }
+    }
+
+    /// 要求是用户 `user_id` 本人或具有管理权限，用于管理用户自己的账户设置
+    pub fn require_self_or_admin(&self, user_id: u64) -> Result<(), ServiceError> {
+        match self {
+            Self::User { id, .. } if *id == user_id => Ok(()),
+            Self::User { role, .. } if *role != UserRole::Admin => {
+                Err(ServiceError::Forbidden("只能管理自己的账户".to_string()))
+            }
+            _ => self.require_admin(),
+        }
+    }
+}
+
+impl<'a> FromRequest<'a> for Principal {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req.extensions().get::<Principal>().cloned().unwrap_or_default())
+    }
+}
+
+/// 管理员身份
+///
+/// 作为处理函数的参数使用，调用方不具有管理权限时不执行处理函数，直接返回401或403
+pub struct Admin(pub Principal);
+
+impl<'a> FromRequest<'a> for Admin {
+    async fn from_request(req: &'a Request, body: &mut RequestBody) -> Result<Self> {
+        let principal = Principal::from_request(req, body).await?;
+        match principal.require_admin() {
+            Ok(()) => Ok(Self(principal)),
+            Err(err) => Err(status_error(&err)),
+        }
+    }
+}
+
+/// 长度相同时比较耗时与内容无关，用于比较验证码、CSRF令牌等
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,68 @@
+//! 创建管理员子命令
+
+use clap::Args;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+
+use crate::audit::AuditContext;
+use crate::config::AppConfig;
+use crate::db::{self, MIGRATOR};
+use crate::models::user::CreateUserRequest;
+use crate::services::UserService;
+use crate::sessions;
+
+/// `create-admin` 参数
+#[derive(Debug, Args)]
+pub struct CreateAdminArgs {
+    /// 用户名
+    #[arg(long)]
+    pub username: String,
+
+    /// 邮箱
+    #[arg(long)]
+    pub email: String,
+
+    /// 密码，建议通过环境变量传入，避免出现在命令历史中
+    #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+    pub password: String,
+
+    /// 已存在管理员时仍然创建
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl CreateAdminArgs {
+    /// 按 `CreateUserRequest` 的字段规则（与创建用户接口相同）校验参数，转换为创建请求
+    pub fn to_request(&self) -> anyhow::Result<CreateUserRequest> {
+        let req = CreateUserRequest {
+            username: self.username.clone(),
+            email: self.email.clone(),
+            password: self.password.clone(),
+        };
+        CreateUserRequest::parse_from_json(req.to_json())
+            .map_err(|err| anyhow::anyhow!("参数无效: {}", err.into_message()))
+    }
+}
+
+/// 创建管理员账号
+///
+/// 参数在连接数据库前校验，不符合创建用户接口的字段规则时直接退出
+pub async fn run(config: &AppConfig, args: CreateAdminArgs) -> anyhow::Result<()> {
+    let req = args.to_request()?;
+
+    let pool = db::connect(&config.database).await?;
+    if config.database.auto_migrate {
+        MIGRATOR.run(&pool).await?;
+    }
+
+    let session_store = sessions::create_store(&config.sessions, &pool);
+    let service = UserService::new(pool.clone(), config.security.bcrypt_cost, session_store);
+    if !args.force && service.has_admin().await? {
+        anyhow::bail!("已存在管理员账号，如需继续创建请使用 --force");
+    }
+
+    let user = service.create_admin(&AuditContext::system("cli"), req).await?;
+    tracing::info!("已创建管理员 {}（ID: {}）", user.username, user.id.unwrap_or_default());
+
+    pool.close().await;
+    Ok(())
+}
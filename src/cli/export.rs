@@ -0,0 +1,90 @@
+//! 导出API契约，供前端代码生成或契约检查使用
+
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+
+use crate::api::{self, ApiVersion};
+use crate::config::{AppConfig, DocsUi};
+use crate::graphql;
+
+/// OpenAPI规范的输出格式
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SpecFormat {
+    /// JSON格式
+    Json,
+    /// YAML格式
+    Yaml,
+}
+
+/// `export-openapi` 参数
+#[derive(Debug, Args)]
+pub struct ExportOpenApiArgs {
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = SpecFormat::Json)]
+    pub format: SpecFormat,
+
+    /// API版本，例如 v1、v2
+    #[arg(long, default_value_t = ApiVersion::DEFAULT)]
+    pub api_version: ApiVersion,
+
+    /// 输出文件，未指定时写到标准输出
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// `export-docs` 参数
+#[derive(Debug, Args)]
+pub struct ExportDocsArgs {
+    /// 文档UI：swagger-ui、redoc、rapidoc、scalar、openapi-explorer
+    #[arg(long, default_value = "redoc")]
+    pub ui: DocsUi,
+
+    /// API版本，例如 v1、v2
+    #[arg(long, default_value_t = ApiVersion::DEFAULT)]
+    pub api_version: ApiVersion,
+
+    /// 输出文件，未指定时写到标准输出
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// `export-graphql-sdl` 参数
+#[derive(Debug, Args)]
+pub struct ExportSdlArgs {
+    /// 输出文件，未指定时写到标准输出
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// 导出OpenAPI规范
+pub fn export_openapi(config: &AppConfig, args: ExportOpenApiArgs) -> anyhow::Result<()> {
+    let spec = api::export_spec(
+        args.api_version,
+        matches!(args.format, SpecFormat::Yaml),
+        &config.docs.servers,
+    );
+    write_output(args.output, spec)
+}
+
+/// 导出嵌入了规范的静态HTML文档
+pub fn export_docs(config: &AppConfig, args: ExportDocsArgs) -> anyhow::Result<()> {
+    write_output(args.output, api::docs::render_html(args.api_version, args.ui, &config.docs))
+}
+
+/// 导出GraphQL SDL
+pub fn export_graphql_sdl(args: ExportSdlArgs) -> anyhow::Result<()> {
+    write_output(args.output, graphql::build_schema().sdl())
+}
+
+/// 写入文件或标准输出
+fn write_output(output: Option<PathBuf>, content: String) -> anyhow::Result<()> {
+    match output {
+        Some(path) => {
+            std::fs::write(&path, content)?;
+            tracing::info!("已导出到 {}", path.display());
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! 数据库迁移子命令
+
+use std::collections::HashSet;
+
+use clap::Subcommand;
+use sqlx::migrate::Migrate;
+
+use crate::config::AppConfig;
+use crate::db::{self, MIGRATOR};
+
+/// 迁移操作
+#[derive(Debug, Subcommand)]
+pub enum MigrateAction {
+    /// 执行所有未应用的迁移
+    Up,
+
+    /// 回滚迁移，默认只回滚最近一次
+    Down {
+        /// 回滚到指定版本（保留该版本及之前的迁移），为0时回滚全部
+        #[arg(long)]
+        target: Option<i64>,
+    },
+
+    /// 查看各迁移的应用状态
+    Status,
+}
+
+/// 执行迁移操作
+pub async fn run(config: &AppConfig, action: MigrateAction) -> anyhow::Result<()> {
+    let pool = db::connect(&config.database).await?;
+
+    match action {
+        MigrateAction::Up => {
+            MIGRATOR.run(&pool).await?;
+            tracing::info!("数据库迁移已完成");
+        }
+        MigrateAction::Down { target } => {
+            let target = match target {
+                Some(target) => target,
+                None => {
+                    let mut conn = pool.acquire().await?;
+                    conn.ensure_migrations_table().await?;
+                    let mut applied = conn
+                        .list_applied_migrations()
+                        .await?
+                        .into_iter()
+                        .map(|m| m.version)
+                        .collect::<Vec<_>>();
+                    applied.sort_unstable();
+                    applied.pop();
+                    applied.pop().unwrap_or(0)
+                }
+            };
+            MIGRATOR.undo(&pool, target).await?;
+            tracing::info!("已回滚到版本 {}", target);
+        }
+        MigrateAction::Status => {
+            let mut conn = pool.acquire().await?;
+            conn.ensure_migrations_table().await?;
+            let applied = conn
+                .list_applied_migrations()
+                .await?
+                .into_iter()
+                .map(|m| m.version)
+                .collect::<HashSet<_>>();
+
+            for migration in MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration()) {
+                let state = if applied.contains(&migration.version) { "已应用" } else { "未应用" };
+                println!("{:>16}  {}  {}", migration.version, state, migration.description);
+            }
+        }
+    }
+
+    pool.close().await;
+    Ok(())
+}
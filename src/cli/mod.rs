@@ -0,0 +1,90 @@
+//! 命令行模块
+//!
+//! 二进制程序的子命令：
+//! - `serve`：启动HTTP服务（未指定子命令时的默认行为）
+//! - `migrate up|down|status`：管理数据库迁移
+//! - `export-openapi`：导出OpenAPI规范
+//! - `export-docs`：导出静态HTML文档
+//! - `export-graphql-sdl`：导出GraphQL SDL
+//! - `create-admin`：创建管理员账号
+
+mod admin;
+mod export;
+mod migrate;
+mod serve;
+
+pub use admin::CreateAdminArgs;
+pub use export::{ExportDocsArgs, ExportOpenApiArgs, ExportSdlArgs, SpecFormat};
+pub use migrate::MigrateAction;
+pub use serve::ServeArgs;
+
+use clap::{Parser, Subcommand};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::config;
+
+/// 命令行参数
+#[derive(Debug, Parser)]
+#[command(version, about = "{{ project_description }}")]
+pub struct Cli {
+    /// 额外的配置文件，优先级高于 config/ 目录，低于环境变量
+    #[arg(short, long, global = true, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// 子命令，未指定时启动服务
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// 子命令
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 启动HTTP服务
+    Serve(ServeArgs),
+
+    /// 管理数据库迁移
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+
+    /// 导出OpenAPI规范
+    #[command(name = "export-openapi")]
+    ExportOpenApi(ExportOpenApiArgs),
+
+    /// 导出嵌入了规范的静态HTML文档
+    #[command(name = "export-docs")]
+    ExportDocs(ExportDocsArgs),
+
+    /// 导出GraphQL SDL
+    #[command(name = "export-graphql-sdl")]
+    ExportGraphqlSdl(ExportSdlArgs),
+
+    /// 创建管理员账号
+    CreateAdmin(CreateAdminArgs),
+}
+
+/// 解析命令行参数并执行对应的子命令
+pub async fn run() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    // 加载配置（同时加载 .env 文件）
+    let app_config = config::load_config(cli.config.as_deref())?;
+
+    // 初始化日志，输出到标准错误，避免干扰导出命令写到标准输出的内容
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| "info,poem=info".into()),
+        ))
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+
+    match cli.command.unwrap_or_else(|| Command::Serve(ServeArgs::default())) {
+        Command::Serve(args) => serve::run(app_config, args).await,
+        Command::Migrate { action } => migrate::run(&app_config, action).await,
+        Command::ExportOpenApi(args) => export::export_openapi(&app_config, args),
+        Command::ExportDocs(args) => export::export_docs(&app_config, args),
+        Command::ExportGraphqlSdl(args) => export::export_graphql_sdl(args),
+        Command::CreateAdmin(args) => admin::run(&app_config, args).await,
+    }
+}
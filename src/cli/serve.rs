@@ -0,0 +1,105 @@
+//! `serve` 子命令：启动HTTP服务
+
+use std::net::SocketAddr;
+
+use clap::Args;
+use poem::Server;
+
+use crate::app::{self, AppState};
+use crate::config::AppConfig;
+use crate::{api, server, shutdown, tasks};
+
+/// `serve` 参数
+#[derive(Debug, Default, Args)]
+pub struct ServeArgs {
+    /// 监听地址，覆盖配置中的 `server.listen_addr`
+    #[arg(short, long, value_name = "ADDR")]
+    pub listen: Option<String>,
+}
+
+/// 启动服务，收到关闭信号后优雅退出
+pub async fn run(mut app_config: AppConfig, args: ServeArgs) -> anyhow::Result<()> {
+    if let Some(listen) = args.listen {
+        app_config.server.listen_addr = listen;
+    }
+
+    // 连接数据库并创建共享状态
+    let state = AppState::new(&app_config).await?;
+
+    // 获取监听地址
+    let addr = app_config
+        .server
+        .listen_addr
+        .parse::<SocketAddr>()
+        .map_err(|err| anyhow::anyhow!("无效的监听地址 {}: {}", app_config.server.listen_addr, err))?;
+    let tls_config = &app_config.server.tls;
+    let admin_config = &app_config.server.admin;
+
+    let app = app::build(&app_config, &state);
+
+    // 启动后台任务（清除超过保留期的软删除用户、投递发件箱中的领域事件等）
+    tasks::spawn_all(&state, &app_config);
+
+    let scheme = if tls_config.enabled { "https" } else { "http" };
+    tracing::info!("服务启动在 {}://{}", scheme, addr);
+    tracing::info!("GraphQL 接口地址: {}://127.0.0.1:{}/graphql", scheme, addr.port()); // ✅ 新增
+    if admin_config.is_enabled() {
+        if let Some(admin_addr) = &admin_config.listen_addr {
+            tracing::info!("管理端监听在 http://{}（/api/docs、/health）", admin_addr);
+        }
+        if let Some(path) = &admin_config.unix_socket {
+            tracing::info!("管理端监听在 unix:{}（/api/docs、/health）", path);
+        }
+    } else {
+        if app_config.docs.enabled {
+            for version in api::ApiVersion::ALL {
+                tracing::info!("OpenAPI {} 文档 UI:  {}://127.0.0.1:{}/api/docs/{}", version, scheme, addr.port(), version);
+                tracing::info!("OpenAPI {} 文档 JSON: {}://127.0.0.1:{}/api/docs/{}/json", version, scheme, addr.port(), version);
+            }
+        }
+        tracing::info!("健康检查地址: {}://127.0.0.1:{}/health", scheme, addr.port());
+    }
+
+    // 收到关闭信号后：就绪检查立即返回503，并通知各监听器、后台任务和订阅连接停止
+    tokio::spawn({
+        let state = state.clone();
+        async move {
+            shutdown::wait_for_signal().await;
+            tracing::info!("开始优雅关闭，等待进行中的请求完成");
+            state.health.mark_shutting_down();
+            state.shutdown.trigger();
+        }
+    });
+
+    let shutdown_timeout = app_config.server.shutdown_timeout();
+    let shutdown_token = &state.shutdown;
+
+    // 启动服务器，关闭时最多等待 shutdown_timeout 排空连接
+    let public_server = Server::new(server::public_listener(&app_config.server)?)
+        .run_with_graceful_shutdown(app, shutdown_token.cancelled(), Some(shutdown_timeout));
+
+    match server::admin_listener(admin_config)? {
+        Some(admin_listener) => {
+            let admin_server = Server::new(admin_listener).run_with_graceful_shutdown(
+                app::build_admin(&app_config, &state),
+                shutdown_token.cancelled(),
+                Some(shutdown_timeout),
+            );
+
+            // 公共端与管理端并发运行，任一端启动失败时整体退出
+            let result = tokio::try_join!(public_server, admin_server);
+            server::cleanup_admin_socket(admin_config);
+            result?;
+        }
+        None => public_server.await?,
+    }
+
+    // 等待后台任务完成或持久化进行中的工作
+    if !shutdown_token.wait_for_tasks(shutdown_timeout).await {
+        tracing::warn!("部分后台任务未在 {:?} 内结束，将被强制终止", shutdown_timeout);
+    }
+
+    state.pool.close().await;
+    tracing::info!("服务已停止");
+    Ok(())
+}
@@ -1,28 +1,1075 @@
 //! 配置模块
-//! 
+//!
 //! 包含应用程序配置的加载和管理
+//!
+//! 配置按以下顺序加载，后者覆盖前者：
+//! 1. 各字段的默认值
+//! 2. `config/default.toml`
+//! 3. `config/{APP_ENV}.toml`（`APP_ENV` 默认为 `development`）
+//! 4. 命令行 `--config` 指定的配置文件
+//! 5. 以 `APP_` 为前缀的环境变量，层级使用双下划线分隔，例如 `APP_SERVER__LISTEN_ADDR`
 
-// 在实际项目中，这里会包含配置结构体和加载函数
-// 例如：
-// pub struct AppConfig {
-//     pub server: ServerConfig,
-//     pub database: DatabaseConfig,
-// }
-// 
-// pub struct ServerConfig {
-//     pub host: String,
-//     pub port: u16,
-// }
-// 
-// pub struct DatabaseConfig {
-//     pub url: String,
-//     pub max_connections: u32,
-// }
-// 
-// pub fn load_config() -> Result<AppConfig, config::ConfigError> {
-//     // 配置加载逻辑
-// }
+use config::{Config, ConfigError, Environment, File};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
 
+use crate::models::user::UserRole;
 
 // api标识
-pub mod tags;
\ No newline at end of file
+pub mod tags;
+
+/// 应用配置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// 服务器配置
+    pub server: ServerConfig,
+
+    /// 数据库配置
+    pub database: DatabaseConfig,
+
+    /// 安全相关配置
+    pub security: SecurityConfig,
+
+    /// API行为配置
+    pub api: ApiConfig,
+
+    /// 幂等键配置
+    pub idempotency: IdempotencyConfig,
+
+    /// 数据保留配置
+    pub retention: RetentionConfig,
+
+    /// 领域事件配置
+    pub events: EventsConfig,
+
+    /// Webhook投递配置
+    pub webhooks: WebhooksConfig,
+
+    /// 后台任务队列配置
+    pub jobs: JobsConfig,
+
+    /// 定时任务配置
+    pub scheduler: SchedulerConfig,
+
+    /// 邮件发送配置
+    pub mailer: MailerConfig,
+
+    /// 账号相关配置（邮箱验证、密码重置）
+    pub accounts: AccountsConfig,
+
+    /// 两步验证配置
+    pub mfa: MfaConfig,
+
+    /// API密钥配置
+    pub api_keys: ApiKeysConfig,
+
+    /// 外部身份提供方登录（OpenID Connect）配置
+    pub oidc: OidcConfig,
+
+    /// 浏览器会话（Cookie认证）配置
+    pub sessions: SessionsConfig,
+
+    /// API文档配置
+    pub docs: DocsConfig,
+}
+
+/// 服务器配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// 监听地址
+    pub listen_addr: String,
+
+    /// 优雅关闭时等待连接及后台任务结束的最长时间（秒）
+    pub shutdown_timeout_secs: u64,
+
+    /// TLS配置
+    pub tls: TlsConfig,
+
+    /// 管理端监听配置
+    pub admin: AdminConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: "0.0.0.0:3000".to_string(),
+            shutdown_timeout_secs: 30,
+            tls: TlsConfig::default(),
+            admin: AdminConfig::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// 优雅关闭超时时间
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+}
+
+/// TLS配置
+///
+/// 启用后 `listen_addr` 只接受HTTPS连接，并通过ALPN同时支持HTTP/2与HTTP/1.1
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// 是否启用TLS
+    pub enabled: bool,
+
+    /// 证书链文件路径（PEM格式）
+    pub cert_path: String,
+
+    /// 私钥文件路径（PEM格式）
+    pub key_path: String,
+
+    /// 客户端证书CA文件路径（PEM格式），配置后启用双向TLS（mTLS）
+    pub client_ca_path: Option<String>,
+
+    /// 是否强制要求客户端证书，为 `false` 时客户端证书可选
+    pub client_auth_required: bool,
+
+    /// 检查证书文件变更的间隔（秒），为0时不自动重新加载
+    pub reload_interval_secs: u64,
+
+    /// HTTP重定向监听地址，配置后该地址上的所有请求会被重定向到HTTPS
+    pub redirect_http_addr: Option<String>,
+
+    /// HSTS `max-age`（秒），为0时不发送 `Strict-Transport-Security` 响应头
+    pub hsts_max_age_secs: u64,
+
+    /// HSTS 是否包含子域名
+    pub hsts_include_subdomains: bool,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cert_path: "certs/server.crt".to_string(),
+            key_path: "certs/server.key".to_string(),
+            client_ca_path: None,
+            client_auth_required: true,
+            reload_interval_secs: 30,
+            redirect_http_addr: None,
+            hsts_max_age_secs: 31_536_000,
+            hsts_include_subdomains: true,
+        }
+    }
+}
+
+impl TlsConfig {
+    /// 证书文件检查间隔，未启用自动重新加载时返回 `None`
+    pub fn reload_interval(&self) -> Option<Duration> {
+        (self.reload_interval_secs > 0).then(|| Duration::from_secs(self.reload_interval_secs))
+    }
+}
+
+/// 管理端监听配置
+///
+/// 配置了 `listen_addr` 或 `unix_socket` 后，API文档、健康检查等管理路由只在管理端提供，
+/// 公共监听地址只保留 `/api` 与 `/graphql`；两者都未配置时所有路由共用公共监听地址
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// 管理端TCP监听地址，例如 `127.0.0.1:3001`
+    pub listen_addr: Option<String>,
+
+    /// 管理端Unix域套接字路径（仅Unix系统）
+    pub unix_socket: Option<String>,
+
+    /// Unix域套接字文件权限
+    pub unix_socket_mode: u32,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: None,
+            unix_socket: None,
+            unix_socket_mode: 0o660,
+        }
+    }
+}
+
+impl AdminConfig {
+    /// 是否启用独立的管理端监听
+    pub fn is_enabled(&self) -> bool {
+        self.listen_addr.is_some() || self.unix_socket.is_some()
+    }
+}
+
+/// 数据库配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// 数据库连接地址，例如 `sqlite://app.db`，文件不存在时自动创建
+    pub url: String,
+
+    /// 连接池最大连接数
+    pub max_connections: u32,
+
+    /// 启动服务时是否自动执行未应用的迁移
+    pub auto_migrate: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://app.db".to_string(),
+            max_connections: 5,
+            auto_migrate: true,
+        }
+    }
+}
+
+/// 安全相关配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// bcrypt 密码哈希的计算成本，取值 4~31，越大越安全也越慢
+    pub bcrypt_cost: u32,
+
+    /// 邮箱验证、密码重置等令牌的签名密钥，多实例部署时各实例必须一致；
+    /// 未配置时启动时随机生成，重启后已发出的令牌全部失效
+    pub token_secret: Option<String>,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            token_secret: None,
+        }
+    }
+}
+
+/// API行为配置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// 修改资源（PUT/PATCH/DELETE）时是否必须携带 `If-Match` 请求头，
+    /// 未携带时返回428，防止并发修改互相覆盖
+    pub require_if_match: bool,
+}
+
+/// 幂等键配置
+///
+/// 携带 `Idempotency-Key` 请求头的创建用户请求在有效期内重试时直接重放首次的响应
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IdempotencyConfig {
+    /// 是否启用
+    pub enabled: bool,
+
+    /// 响应保留时间（秒），过期后同一个键视为新请求
+    pub ttl_secs: u64,
+
+    /// 处理中的请求占用键的租约（秒），处理实例崩溃时租约到期后可以使用同一个键重试
+    pub lease_secs: u64,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 24 * 60 * 60,
+            lease_secs: 60,
+        }
+    }
+}
+
+impl IdempotencyConfig {
+    /// 响应保留时间
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    /// 处理中的请求占用键的租约
+    pub fn lease(&self) -> Duration {
+        Duration::from_secs(self.lease_secs)
+    }
+}
+
+/// 数据保留配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// 软删除的用户保留天数，超过后由定时任务（`purge_deleted_users`）彻底清除，为0时不自动清除
+    pub deleted_users_days: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self { deleted_users_days: 30 }
+    }
+}
+
+impl RetentionConfig {
+    /// 软删除用户的保留时间，未启用自动清除时返回 `None`
+    pub fn deleted_users(&self) -> Option<Duration> {
+        (self.deleted_users_days > 0).then(|| Duration::from_secs(self.deleted_users_days * 24 * 60 * 60))
+    }
+}
+
+/// 领域事件配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// 是否启动发件箱投递任务，多实例部署时可只在部分实例上启用
+    pub relay_enabled: bool,
+
+    /// 发件箱轮询间隔（毫秒）
+    pub poll_interval_ms: u64,
+
+    /// 每轮最多领取的事件数
+    pub batch_size: u32,
+
+    /// 最大投递次数，仍失败的事件转入死信
+    pub max_attempts: u32,
+
+    /// 首次重试的等待时间（秒），之后每次翻倍
+    pub retry_base_secs: u64,
+
+    /// 重试等待时间上限（秒）
+    pub retry_max_secs: u64,
+
+    /// 领取租约（秒）：事件被领取后在此期间不会被再次领取，投递进程崩溃时租约到期后重新投递
+    pub lease_secs: u64,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            relay_enabled: true,
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            max_attempts: 10,
+            retry_base_secs: 5,
+            retry_max_secs: 60 * 60,
+            lease_secs: 60,
+        }
+    }
+}
+
+impl EventsConfig {
+    /// 发件箱轮询间隔
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms.max(10))
+    }
+
+    /// 第 `attempts` 次投递失败后的等待时间（指数退避）
+    pub fn retry_delay(&self, attempts: u32) -> Duration {
+        exponential_backoff(self.retry_base_secs, self.retry_max_secs, attempts)
+    }
+}
+
+/// Webhook投递配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    /// 是否启动Webhook投递任务，多实例部署时可只在部分实例上启用
+    pub delivery_enabled: bool,
+
+    /// 轮询待投递记录的间隔（毫秒）
+    pub poll_interval_ms: u64,
+
+    /// 每轮最多并发投递的记录数
+    pub batch_size: u32,
+
+    /// 单次请求超时时间（秒）
+    pub timeout_secs: u64,
+
+    /// 单条记录的最大投递次数，仍失败时标记为失败，可手动重新投递
+    pub max_attempts: u32,
+
+    /// 首次重试的等待时间（秒），之后每次翻倍
+    pub retry_base_secs: u64,
+
+    /// 重试等待时间上限（秒）
+    pub retry_max_secs: u64,
+
+    /// 连续失败多少次后自动停用订阅，为0时不自动停用
+    pub disable_after_failures: u32,
+
+    /// 是否允许订阅本机、链路本地及内网地址，默认拒绝以防SSRF，只应在开发及测试环境中开启
+    pub allow_private_targets: bool,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            delivery_enabled: true,
+            poll_interval_ms: 1000,
+            batch_size: 20,
+            timeout_secs: 10,
+            max_attempts: 8,
+            retry_base_secs: 30,
+            retry_max_secs: 6 * 60 * 60,
+            disable_after_failures: 20,
+            allow_private_targets: false,
+        }
+    }
+}
+
+impl WebhooksConfig {
+    /// 轮询间隔
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms.max(10))
+    }
+
+    /// 单次请求超时时间
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+
+    /// 第 `attempts` 次投递失败后的等待时间（指数退避）
+    pub fn retry_delay(&self, attempts: u32) -> Duration {
+        exponential_backoff(self.retry_base_secs, self.retry_max_secs, attempts)
+    }
+}
+
+/// 后台任务队列配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// 是否在本实例上执行任务，多实例部署时可只在部分实例上启用
+    pub workers_enabled: bool,
+
+    /// 同时执行的任务数上限
+    pub concurrency: u32,
+
+    /// 轮询到期任务的间隔（毫秒）
+    pub poll_interval_ms: u64,
+
+    /// 任务的默认最大执行次数，任务类型或入队时可单独指定
+    pub max_attempts: u32,
+
+    /// 首次重试的等待时间（秒），之后每次翻倍
+    pub retry_base_secs: u64,
+
+    /// 重试等待时间上限（秒）
+    pub retry_max_secs: u64,
+
+    /// 执行租约（秒）：任务领取后超过该时间仍未结束（例如实例崩溃）时可被重新领取，应大于任务的最长执行时间
+    pub lease_secs: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            workers_enabled: true,
+            concurrency: 4,
+            poll_interval_ms: 1000,
+            max_attempts: 5,
+            retry_base_secs: 10,
+            retry_max_secs: 3600,
+            lease_secs: 300,
+        }
+    }
+}
+
+impl JobsConfig {
+    /// 轮询间隔
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms.max(10))
+    }
+
+    /// 第 `attempts` 次执行失败后的等待时间（指数退避）
+    pub fn retry_delay(&self, attempts: u32) -> Duration {
+        exponential_backoff(self.retry_base_secs, self.retry_max_secs, attempts)
+    }
+}
+
+/// 定时任务配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// 是否在本实例上执行定时任务，多实例部署时同一任务同时只会在一个实例上执行
+    pub enabled: bool,
+
+    /// 检查到期任务的间隔（毫秒）
+    pub poll_interval_ms: u64,
+
+    /// 执行锁（秒）：超过该时间仍未结束（例如实例崩溃）时其他实例可再次执行，应大于任务的最长执行时间
+    pub lock_secs: u64,
+
+    /// 各任务的执行计划，键为任务名称；未配置的任务只能手动触发
+    pub tasks: BTreeMap<String, ScheduleConfig>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        let mut tasks = BTreeMap::new();
+        tasks.insert(
+            "purge_deleted_users".to_string(),
+            ScheduleConfig {
+                cron: "0 0 * * * *".to_string(),
+                enabled: true,
+            },
+        );
+        tasks.insert(
+            "purge_expired_sessions".to_string(),
+            ScheduleConfig {
+                cron: "0 30 * * * *".to_string(),
+                enabled: true,
+            },
+        );
+
+        Self {
+            enabled: true,
+            poll_interval_ms: 1000,
+            lock_secs: 600,
+            tasks,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// 轮询间隔
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms.max(10))
+    }
+}
+
+/// 单个定时任务的执行计划
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleConfig {
+    /// cron表达式（UTC），支持 `秒 分 时 日 月 周` 及省略秒的五段格式，例如 `0 3 * * *`
+    pub cron: String,
+
+    /// 是否按计划执行，停用后仍可手动触发
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// 邮件发送配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MailerConfig {
+    /// 发送方式
+    pub transport: MailTransport,
+
+    /// 发件人，例如 `示例应用 <noreply@example.com>`
+    pub from: String,
+
+    /// 默认语言，收件人的语言没有对应模板时使用
+    pub default_locale: String,
+
+    /// 产品名称，显示在邮件标题及页脚
+    pub product_name: String,
+
+    /// `file` 方式写入的mbox文件路径
+    pub file_path: String,
+
+    /// SMTP配置，`smtp` 方式时使用
+    pub smtp: SmtpConfig,
+
+    /// 新用户创建后是否发送欢迎邮件
+    pub welcome_enabled: bool,
+
+    /// 是否提供模板预览接口（`/dev/mail`），仅用于开发环境
+    pub preview_enabled: bool,
+}
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        Self {
+            transport: MailTransport::File,
+            from: "noreply@example.com".to_string(),
+            default_locale: "zh-CN".to_string(),
+            product_name: "Poem API".to_string(),
+            file_path: "mail.mbox".to_string(),
+            smtp: SmtpConfig::default(),
+            welcome_enabled: true,
+            preview_enabled: false,
+        }
+    }
+}
+
+/// 邮件发送方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailTransport {
+    /// 通过SMTP服务器发送
+    Smtp,
+    /// 追加写入本地mbox文件，用于开发环境
+    File,
+    /// 保存在内存中，用于测试
+    Memory,
+}
+
+/// SMTP配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    /// 服务器地址
+    pub host: String,
+
+    /// 端口，为空时按加密方式使用默认端口（tls：465，starttls：587，none：25）
+    pub port: Option<u16>,
+
+    /// 用户名，为空时不认证
+    pub username: Option<String>,
+
+    /// 密码，建议通过环境变量 `APP_MAILER__SMTP__PASSWORD` 设置
+    pub password: Option<String>,
+
+    /// 加密方式
+    pub security: SmtpSecurity,
+
+    /// 连接及发送超时（秒）
+    pub timeout_secs: u64,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: None,
+            username: None,
+            password: None,
+            security: SmtpSecurity::Starttls,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl SmtpConfig {
+    /// 连接及发送超时
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+}
+
+/// SMTP加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// 连接即使用TLS（通常为465端口）
+    Tls,
+    /// 明文连接后通过STARTTLS升级，服务器不支持时发送失败
+    Starttls,
+    /// 不加密，仅用于本地开发（例如MailHog）
+    None,
+}
+
+/// 账号相关配置（邮箱验证、密码重置）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AccountsConfig {
+    /// 邮箱验证页面地址，`{token}` 会被替换为验证令牌，由前端页面调用 `/auth/verify-email`
+    pub verify_email_url: String,
+
+    /// 密码重置页面地址，`{token}` 会被替换为重置令牌，由前端页面调用 `/auth/reset-password`
+    pub reset_password_url: String,
+
+    /// 邮箱验证令牌有效期（秒）
+    pub verify_email_ttl_secs: u64,
+
+    /// 密码重置令牌有效期（秒）
+    pub reset_password_ttl_secs: u64,
+
+    /// 新用户创建或修改邮箱后是否自动发送验证邮件
+    pub send_verification: bool,
+}
+
+impl Default for AccountsConfig {
+    fn default() -> Self {
+        Self {
+            verify_email_url: "http://localhost:3000/verify-email?token={token}".to_string(),
+            reset_password_url: "http://localhost:3000/reset-password?token={token}".to_string(),
+            verify_email_ttl_secs: 48 * 60 * 60,
+            reset_password_ttl_secs: 60 * 60,
+            send_verification: true,
+        }
+    }
+}
+
+impl AccountsConfig {
+    /// 邮箱验证令牌有效期
+    pub fn verify_email_ttl(&self) -> Duration {
+        Duration::from_secs(self.verify_email_ttl_secs.max(1))
+    }
+
+    /// 密码重置令牌有效期
+    pub fn reset_password_ttl(&self) -> Duration {
+        Duration::from_secs(self.reset_password_ttl_secs.max(1))
+    }
+}
+
+/// 两步验证（TOTP）配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MfaConfig {
+    /// 签发方名称，显示在验证器应用中
+    pub issuer: String,
+
+    /// 必须启用两步验证的角色：未启用时登录后的会话只能访问绑定两步验证的接口，且不能停用
+    pub required_roles: Vec<UserRole>,
+
+    /// 每次生成的恢复码数量
+    pub recovery_codes: u32,
+
+    /// 允许的时间偏差（步数，每步30秒），用于容忍客户端时钟误差
+    pub skew_steps: u32,
+}
+
+impl Default for MfaConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "Poem API".to_string(),
+            required_roles: vec![UserRole::Admin],
+            recovery_codes: 10,
+            skew_steps: 1,
+        }
+    }
+}
+
+impl MfaConfig {
+    /// 该角色是否必须启用两步验证
+    pub fn is_required(&self, role: UserRole) -> bool {
+        self.required_roles.contains(&role)
+    }
+}
+
+/// API密钥配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ApiKeysConfig {
+    /// 是否接受API密钥认证，关闭后携带密钥的请求按匿名调用方处理
+    pub enabled: bool,
+
+    /// 新密钥的前缀，便于在日志、代码仓库中识别泄露的密钥
+    pub prefix: String,
+
+    /// 最后使用时间的更新间隔（秒），避免每个请求都写数据库
+    pub last_used_interval_secs: u64,
+}
+
+impl Default for ApiKeysConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prefix: "pak".to_string(),
+            last_used_interval_secs: 60,
+        }
+    }
+}
+
+/// 外部身份提供方登录（OpenID Connect）配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OidcConfig {
+    /// 登录状态（`state`）的有效期（秒），即从发起登录到回调的最长时间
+    pub state_ttl_secs: u64,
+
+    /// 将登录状态绑定到发起登录的浏览器的Cookie名称，防止登录CSRF；
+    /// 只在服务端读取（HttpOnly），其他属性与会话Cookie相同
+    pub state_cookie_name: String,
+
+    /// 发现文档及JWKS的缓存时间（秒），签名密钥未找到时会提前刷新
+    pub metadata_ttl_secs: u64,
+
+    /// 请求身份提供方的超时时间（秒）
+    pub timeout_secs: u64,
+
+    /// 身份提供方，键为提供方标识，用于接口路径，例如 `/auth/oidc/{provider}/authorize`
+    pub providers: BTreeMap<String, OidcProviderConfig>,
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self {
+            state_ttl_secs: 600,
+            state_cookie_name: "oidc_state".to_string(),
+            metadata_ttl_secs: 3600,
+            timeout_secs: 10,
+            providers: BTreeMap::new(),
+        }
+    }
+}
+
+impl OidcConfig {
+    /// 请求身份提供方的超时时间
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.max(1))
+    }
+
+    /// 发现文档及JWKS的缓存时间
+    pub fn metadata_ttl(&self) -> Duration {
+        Duration::from_secs(self.metadata_ttl_secs)
+    }
+}
+
+/// 身份提供方配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OidcProviderConfig {
+    /// 显示名称，未配置时使用提供方标识
+    pub name: Option<String>,
+
+    /// 签发方（issuer），发现文档地址为 `{issuer}/.well-known/openid-configuration`
+    pub issuer: String,
+
+    /// 在身份提供方注册的客户端ID
+    pub client_id: String,
+
+    /// 客户端密钥，以 `client_secret_post` 方式发送；公开客户端可不配置，只使用PKCE
+    pub client_secret: Option<String>,
+
+    /// 登录完成后身份提供方重定向的地址，需与注册时一致；通常是前端页面，由前端将 `code`、`state` 转交回调接口
+    pub redirect_url: String,
+
+    /// 请求的授权范围，必须包含 `openid`
+    pub scopes: Vec<String>,
+
+    /// 首次登录且没有可关联的用户时是否自动创建用户
+    pub allow_signup: bool,
+
+    /// 首次登录时是否按邮箱关联已有用户，只关联身份提供方声明已验证（`email_verified`）且本地用户也已验证的邮箱
+    pub link_by_email: bool,
+}
+
+impl Default for OidcProviderConfig {
+    fn default() -> Self {
+        Self {
+            name: None,
+            issuer: String::new(),
+            client_id: String::new(),
+            client_secret: None,
+            redirect_url: String::new(),
+            scopes: vec!["openid".to_string(), "email".to_string(), "profile".to_string()],
+            allow_signup: true,
+            link_by_email: true,
+        }
+    }
+}
+
+/// 浏览器会话（Cookie认证）配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SessionsConfig {
+    /// 是否启用会话登录及Cookie认证，关闭后请求中的会话Cookie被忽略
+    pub enabled: bool,
+
+    /// 会话的存储方式
+    pub store: SessionStoreKind,
+
+    /// 会话Cookie的名称
+    pub cookie_name: String,
+
+    /// CSRF令牌Cookie的名称，前端读取后通过 `csrf_header` 请求头提交
+    pub csrf_cookie_name: String,
+
+    /// 提交CSRF令牌的请求头
+    pub csrf_header: String,
+
+    /// Cookie是否只通过HTTPS发送，只在本地HTTP调试时关闭
+    pub secure: bool,
+
+    /// 会话Cookie是否禁止脚本读取；CSRF令牌Cookie始终允许脚本读取
+    pub http_only: bool,
+
+    /// Cookie的 `SameSite` 属性
+    pub same_site: CookieSameSite,
+
+    /// Cookie的域名，为空时只发送给当前主机
+    pub domain: Option<String>,
+
+    /// Cookie的路径
+    pub path: String,
+
+    /// 空闲超时（秒）：超过该时间没有请求的会话失效
+    pub idle_timeout_secs: u64,
+
+    /// 绝对超时（秒）：从登录开始计算，到期后无论是否活跃都需要重新登录
+    pub absolute_timeout_secs: u64,
+
+    /// 最后活跃时间的更新间隔（秒），避免每个请求都写存储
+    pub touch_interval_secs: u64,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            store: SessionStoreKind::Database,
+            cookie_name: "sid".to_string(),
+            csrf_cookie_name: "csrf_token".to_string(),
+            csrf_header: "X-CSRF-Token".to_string(),
+            secure: true,
+            http_only: true,
+            same_site: CookieSameSite::Lax,
+            domain: None,
+            path: "/".to_string(),
+            idle_timeout_secs: 1800,
+            absolute_timeout_secs: 43200,
+            touch_interval_secs: 60,
+        }
+    }
+}
+
+impl SessionsConfig {
+    /// 空闲超时
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_secs.max(1))
+    }
+
+    /// 绝对超时，不短于空闲超时
+    pub fn absolute_timeout(&self) -> Duration {
+        Duration::from_secs(self.absolute_timeout_secs.max(self.idle_timeout_secs).max(1))
+    }
+
+    /// 最后活跃时间的更新间隔
+    pub fn touch_interval(&self) -> Duration {
+        Duration::from_secs(self.touch_interval_secs)
+    }
+}
+
+/// 会话的存储方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStoreKind {
+    /// 保存在进程内存中，重启后所有会话失效，只适用于单实例部署及测试
+    Memory,
+    /// 保存在数据库中，多实例共享
+    Database,
+}
+
+/// Cookie的 `SameSite` 属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CookieSameSite {
+    /// 只在同站请求中发送
+    Strict,
+    /// 同站请求及跨站的顶层导航（GET）中发送
+    Lax,
+    /// 所有请求中都发送，必须同时启用 `secure`
+    None,
+}
+
+/// 未配置时默认启用
+fn default_true() -> bool {
+    true
+}
+
+/// 指数退避：第一次失败后等待 `base_secs`，之后每次翻倍，不超过 `max_secs`
+fn exponential_backoff(base_secs: u64, max_secs: u64, attempts: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempts.saturating_sub(1));
+    Duration::from_secs(base_secs.saturating_mul(factor).min(max_secs))
+}
+
+/// API文档配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DocsConfig {
+    /// 是否提供API文档及规范端点，生产环境可关闭
+    pub enabled: bool,
+
+    /// 提供的文档UI，挂载在 `/api/docs/<ui>`，第一个同时作为 `/api/docs` 的默认页面
+    pub ui: Vec<DocsUi>,
+
+    /// 规范中列出的服务器地址（例如各环境的域名），为空时使用相对路径
+    pub servers: Vec<DocsServer>,
+}
+
+/// 规范中的服务器地址
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocsServer {
+    /// 服务根地址，例如 `https://api.example.com`，各版本会追加 `/api/{version}`
+    pub url: String,
+
+    /// 说明，例如环境名称
+    pub description: Option<String>,
+}
+
+impl Default for DocsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ui: DocsUi::ALL.to_vec(),
+            servers: Vec::new(),
+        }
+    }
+}
+
+/// API文档UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DocsUi {
+    /// Swagger UI
+    SwaggerUi,
+    /// Redoc
+    Redoc,
+    /// RapiDoc
+    Rapidoc,
+    /// Scalar
+    Scalar,
+    /// OpenAPI Explorer
+    OpenapiExplorer,
+}
+
+impl DocsUi {
+    /// 所有支持的文档UI
+    pub const ALL: [DocsUi; 5] = [
+        DocsUi::SwaggerUi,
+        DocsUi::Redoc,
+        DocsUi::Rapidoc,
+        DocsUi::Scalar,
+        DocsUi::OpenapiExplorer,
+    ];
+
+    /// UI标识，同时也是路径
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SwaggerUi => "swagger-ui",
+            Self::Redoc => "redoc",
+            Self::Rapidoc => "rapidoc",
+            Self::Scalar => "scalar",
+            Self::OpenapiExplorer => "openapi-explorer",
+        }
+    }
+}
+
+impl std::str::FromStr for DocsUi {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|ui| ui.as_str() == value)
+            .ok_or_else(|| format!("不支持的文档UI: {}", value))
+    }
+}
+
+/// 加载应用配置
+///
+/// `path` 为额外的配置文件（例如命令行 `--config` 指定），优先级高于 `config/` 目录下的文件，
+/// 低于环境变量
+pub fn load_config(path: Option<&str>) -> Result<AppConfig, ConfigError> {
+    // 加载 .env 文件中的环境变量（文件不存在时忽略）
+    dotenv::dotenv().ok();
+
+    let env = std::env::var("APP_ENV").unwrap_or_else(|_| "development".into());
+
+    let mut builder = Config::builder()
+        .add_source(File::with_name("config/default").required(false))
+        .add_source(File::with_name(&format!("config/{}", env)).required(false));
+    if let Some(path) = path {
+        builder = builder.add_source(File::with_name(path));
+    }
+
+    builder
+        .add_source(
+            Environment::with_prefix("APP")
+                .prefix_separator("_")
+                .separator("__"),
+        )
+        // 兼容旧的 LISTEN_ADDR 环境变量
+        .set_override_option("server.listen_addr", std::env::var("LISTEN_ADDR").ok())?
+        .build()?
+        .try_deserialize()
+}
@@ -3,6 +3,24 @@ use poem_openapi::Tags;
 #[derive(Tags)]
 // 对api进行分类
 pub enum ApiTags {
-    /// 用户模块
+    /// 用户模块：用户的创建、查询、更新与删除
     User,
-}
\ No newline at end of file
+    /// 审计日志：数据变更记录的查询，记录只允许追加
+    Audit,
+    /// Webhook：事件订阅的管理、投递记录查询与重新投递
+    Webhook,
+    /// 后台任务：任务执行情况的查询、重试与取消
+    Job,
+    /// 定时任务：执行状态查询与手动触发
+    Scheduler,
+    /// 账号：邮箱验证、找回及重置密码
+    Auth,
+    /// 两步验证：TOTP绑定、启用、停用与恢复码
+    Mfa,
+    /// API密钥：供机器调用方使用的密钥的创建、查询与吊销
+    ApiKey,
+    /// 单点登录：通过外部身份提供方（OpenID Connect）登录
+    Oidc,
+    /// 会话：浏览器管理后台的Cookie登录、会话查询与注销
+    Session,
+}
@@ -0,0 +1,71 @@
+//! 数据库模块
+//!
+//! 负责创建连接池、执行迁移，并提供数据库健康检查
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqliteConnection, SqlitePool,
+};
+
+use crate::config::DatabaseConfig;
+use crate::health::HealthCheck;
+
+/// 数据库连接池
+pub type DbPool = SqlitePool;
+
+/// 数据库连接，事务中的操作通过 `&mut *tx` 传入
+pub type DbConnection = SqliteConnection;
+
+/// 内嵌的数据库迁移，对应项目根目录下的 `migrations/`
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 根据配置创建连接池，SQLite数据库文件不存在时自动创建
+///
+/// 内存数据库（`sqlite::memory:`）的每个连接都是独立的数据库，因此只保留一个长期存活的连接
+pub async fn connect(config: &DatabaseConfig) -> Result<DbPool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(&config.url)?.create_if_missing(true);
+
+    let pool_options = if is_in_memory(&config.url) {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        SqlitePoolOptions::new().max_connections(config.max_connections)
+    };
+
+    pool_options.connect_with(options).await
+}
+
+/// 是否为SQLite内存数据库
+fn is_in_memory(url: &str) -> bool {
+    url.contains(":memory:") || url.contains("mode=memory")
+}
+
+/// 数据库健康检查
+pub struct DatabaseHealthCheck {
+    pool: DbPool,
+}
+
+impl DatabaseHealthCheck {
+    /// 使用已有连接池创建检查项
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for DatabaseHealthCheck {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn check(&self) -> anyhow::Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+}
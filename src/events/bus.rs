@@ -0,0 +1,60 @@
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+
+use super::EventEnvelope;
+
+/// 事件订阅者
+///
+/// 同一事件可能被投递多次，实现需要以事件ID去重或保证处理幂等
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    /// 订阅者名称，用于日志及错误信息
+    fn name(&self) -> &str;
+
+    /// 处理事件，返回错误时整个事件稍后重试
+    async fn handle(&self, event: &EventEnvelope) -> anyhow::Result<()>;
+}
+
+/// 进程内事件总线
+///
+/// 克隆开销很小，可在各组件间共享；订阅者在服务启动时注册，事件由发件箱投递任务发布
+#[derive(Clone, Default)]
+pub struct EventBus {
+    handlers: Arc<RwLock<Vec<Arc<dyn EventHandler>>>>,
+}
+
+impl EventBus {
+    /// 创建没有订阅者的事件总线
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册订阅者，接收之后发布的所有事件
+    pub fn subscribe<H: EventHandler + 'static>(&self, handler: H) {
+        self.handlers
+            .write()
+            .expect("事件总线锁已损坏")
+            .push(Arc::new(handler));
+    }
+
+    /// 依次投递给所有订阅者
+    ///
+    /// 某个订阅者失败不影响其余订阅者，全部投递后返回失败的订阅者及原因
+    pub async fn publish(&self, event: &EventEnvelope) -> anyhow::Result<()> {
+        let handlers = self.handlers.read().expect("事件总线锁已损坏").clone();
+
+        let mut errors = Vec::new();
+        for handler in handlers {
+            if let Err(err) = handler.handle(event).await {
+                errors.push(format!("{}: {:#}", handler.name(), err));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(errors.join("; ")))
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! 领域事件
+//!
+//! 用户变更时在同一个事务中将事件写入发件箱（`outbox`），由后台任务投递给进程内事件总线（`EventBus`）
+//! 的订阅者。投递语义为至少一次：订阅者失败时整个事件稍后重试，超过最大次数转入死信，
+//! 因此订阅者需要以 `EventEnvelope::id` 去重，保证重复处理无副作用；重试的事件可能晚于后续事件到达
+
+mod bus;
+pub mod outbox;
+
+pub use bus::{EventBus, EventHandler};
+pub use outbox::{DeadLetter, Outbox, RelayStats};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::user::User;
+
+/// 领域事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    /// 用户已创建
+    #[serde(rename = "user.created")]
+    UserCreated(UserCreated),
+    /// 用户信息已修改（包括恢复已删除的用户）
+    #[serde(rename = "user.updated")]
+    UserUpdated(UserUpdated),
+    /// 用户已删除（软删除或彻底清除）
+    #[serde(rename = "user.deleted")]
+    UserDeleted(UserDeleted),
+}
+
+impl DomainEvent {
+    /// 事件类型标识，例如 `user.created`
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::UserCreated(_) => "user.created",
+            Self::UserUpdated(_) => "user.updated",
+            Self::UserDeleted(_) => "user.deleted",
+        }
+    }
+}
+
+/// 用户已创建
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserCreated {
+    /// 创建后的用户
+    pub user: User,
+}
+
+/// 用户信息已修改
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUpdated {
+    /// 修改前的用户
+    pub before: User,
+    /// 修改后的用户
+    pub after: User,
+    /// 是否修改了密码（密码不在用户模型中）
+    pub password_changed: bool,
+}
+
+/// 用户已删除
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDeleted {
+    /// 删除时的用户
+    pub user: User,
+    /// 是否已彻底清除，为 `false` 时为软删除，仍可恢复
+    pub purged: bool,
+}
+
+/// 投递给订阅者的事件
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    /// 事件ID，重试及从死信恢复时保持不变，可用于去重
+    pub id: u64,
+    /// 事件发生时间（ISO 8601格式）
+    pub occurred_at: String,
+    /// 第几次投递，从1开始
+    pub attempt: u32,
+    /// 事件内容
+    pub event: DomainEvent,
+}
@@ -0,0 +1,241 @@
+//! 事务性发件箱
+//!
+//! 业务变更与事件在同一个事务中写入，事务回滚时事件一并撤销，提交后由投递任务（`Outbox::relay_once`）
+//! 领取并发布到事件总线。领取时推迟事件的下次可领取时间作为租约，投递失败按指数退避重试，
+//! 超过最大次数转入死信，可通过 `Outbox::requeue` 重新放回发件箱
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::Value;
+
+use super::{DomainEvent, EventBus, EventEnvelope};
+use crate::config::EventsConfig;
+use crate::db::{DbConnection, DbPool};
+use crate::services::ServiceError;
+
+/// 在业务变更所在的事务中写入事件
+pub async fn enqueue(conn: &mut DbConnection, event: &DomainEvent) -> Result<(), sqlx::Error> {
+    let payload = serde_json::to_string(event).map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+    let now = Utc::now();
+
+    sqlx::query(
+        "INSERT INTO outbox_events (event_type, payload, occurred_at, next_attempt_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(event.event_type())
+    .bind(payload)
+    .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+    .bind(now.timestamp())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 一轮投递的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RelayStats {
+    /// 投递成功的事件数
+    pub delivered: u32,
+    /// 投递失败、等待重试的事件数
+    pub retried: u32,
+    /// 转入死信的事件数
+    pub dead_lettered: u32,
+}
+
+/// 死信：超过最大投递次数仍失败的事件
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    /// 原事件ID
+    pub id: u64,
+    /// 事件类型
+    pub event_type: String,
+    /// 事件内容
+    pub payload: Value,
+    /// 事件发生时间
+    pub occurred_at: String,
+    /// 已投递次数
+    pub attempts: u32,
+    /// 最后一次失败的原因
+    pub last_error: Option<String>,
+    /// 转入死信的时间
+    pub failed_at: String,
+}
+
+/// 发件箱中待投递的事件
+#[derive(sqlx::FromRow)]
+struct OutboxRow {
+    id: i64,
+    payload: String,
+    occurred_at: String,
+    attempts: i64,
+}
+
+/// 数据库中的死信记录
+#[derive(sqlx::FromRow)]
+struct DeadLetterRow {
+    id: i64,
+    event_type: String,
+    payload: String,
+    occurred_at: String,
+    attempts: i64,
+    last_error: Option<String>,
+    failed_at: String,
+}
+
+impl From<DeadLetterRow> for DeadLetter {
+    fn from(row: DeadLetterRow) -> Self {
+        Self {
+            id: row.id as u64,
+            event_type: row.event_type,
+            // 无法解析的内容按原文保留
+            payload: serde_json::from_str(&row.payload).unwrap_or(Value::String(row.payload)),
+            occurred_at: row.occurred_at,
+            attempts: row.attempts as u32,
+            last_error: row.last_error,
+            failed_at: row.failed_at,
+        }
+    }
+}
+
+/// 发件箱
+///
+/// 克隆开销很小，投递任务与管理操作共用
+#[derive(Clone)]
+pub struct Outbox {
+    pool: DbPool,
+    bus: EventBus,
+    config: EventsConfig,
+}
+
+impl Outbox {
+    /// 创建发件箱，事件投递到 `bus` 的订阅者
+    pub fn new(pool: DbPool, bus: EventBus, config: EventsConfig) -> Self {
+        Self { pool, bus, config }
+    }
+
+    /// 领取一批到期的事件并按写入顺序投递
+    pub async fn relay_once(&self) -> Result<RelayStats, ServiceError> {
+        let now = Utc::now().timestamp();
+
+        // 领取即推迟下次可领取时间，租约内其他投递任务不会重复领取
+        let mut rows = sqlx::query_as::<_, OutboxRow>(
+            "UPDATE outbox_events SET attempts = attempts + 1, next_attempt_at = ?1 \
+             WHERE id IN (SELECT id FROM outbox_events WHERE next_attempt_at <= ?2 ORDER BY id LIMIT ?3) \
+             RETURNING id, payload, occurred_at, attempts",
+        )
+        .bind(now + self.config.lease_secs as i64)
+        .bind(now)
+        .bind(self.config.batch_size as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.sort_by_key(|row| row.id);
+
+        let mut stats = RelayStats::default();
+        for row in rows {
+            let event = match serde_json::from_str::<DomainEvent>(&row.payload) {
+                Ok(event) => event,
+                // 无法解析的事件重试也不会成功，直接转入死信
+                Err(err) => {
+                    self.dead_letter(row.id, &format!("无法解析事件内容: {}", err)).await?;
+                    stats.dead_lettered += 1;
+                    continue;
+                }
+            };
+
+            let envelope = EventEnvelope {
+                id: row.id as u64,
+                occurred_at: row.occurred_at,
+                attempt: row.attempts as u32,
+                event,
+            };
+            match self.bus.publish(&envelope).await {
+                Ok(()) => {
+                    sqlx::query("DELETE FROM outbox_events WHERE id = ?")
+                        .bind(row.id)
+                        .execute(&self.pool)
+                        .await?;
+                    stats.delivered += 1;
+                }
+                Err(err) if envelope.attempt >= self.config.max_attempts => {
+                    tracing::error!("事件 {} 投递 {} 次仍失败，转入死信: {:#}", row.id, envelope.attempt, err);
+                    self.dead_letter(row.id, &format!("{:#}", err)).await?;
+                    stats.dead_lettered += 1;
+                }
+                Err(err) => {
+                    let delay = self.config.retry_delay(envelope.attempt);
+                    tracing::warn!("事件 {} 第 {} 次投递失败，{:?} 后重试: {:#}", row.id, envelope.attempt, delay, err);
+                    sqlx::query("UPDATE outbox_events SET next_attempt_at = ?, last_error = ? WHERE id = ?")
+                        .bind(Utc::now().timestamp() + delay.as_secs() as i64)
+                        .bind(format!("{:#}", err))
+                        .bind(row.id)
+                        .execute(&self.pool)
+                        .await?;
+                    stats.retried += 1;
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 发件箱中尚未投递成功的事件数（含等待重试的事件）
+    pub async fn pending(&self) -> Result<u64, ServiceError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM outbox_events")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as u64)
+    }
+
+    /// 最近转入死信的事件
+    pub async fn dead_letters(&self, limit: u32) -> Result<Vec<DeadLetter>, ServiceError> {
+        Ok(sqlx::query_as::<_, DeadLetterRow>(
+            "SELECT id, event_type, payload, occurred_at, attempts, last_error, failed_at \
+             FROM dead_letter_events ORDER BY failed_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(DeadLetter::from)
+        .collect())
+    }
+
+    /// 将死信重新放回发件箱，保留原事件ID并重新计算投递次数；死信不存在时返回 `false`
+    pub async fn requeue(&self, id: u64) -> Result<bool, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let moved = sqlx::query(
+            "INSERT INTO outbox_events (id, event_type, payload, occurred_at, attempts, next_attempt_at, last_error) \
+             SELECT id, event_type, payload, occurred_at, 0, ?, last_error FROM dead_letter_events WHERE id = ?",
+        )
+        .bind(Utc::now().timestamp())
+        .bind(id as i64)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("DELETE FROM dead_letter_events WHERE id = ?")
+            .bind(id as i64)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(moved > 0)
+    }
+
+    /// 将事件从发件箱移入死信
+    async fn dead_letter(&self, id: i64, error: &str) -> Result<(), ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO dead_letter_events (id, event_type, payload, occurred_at, attempts, last_error, failed_at) \
+             SELECT id, event_type, payload, occurred_at, attempts, ?, ? FROM outbox_events WHERE id = ?",
+        )
+        .bind(error)
+        .bind(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM outbox_events WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
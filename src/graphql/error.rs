@@ -2,8 +2,9 @@
 //! 
 //! 提供统一的GraphQL错误处理机制，确保错误响应格式一致
 
-use async_graphql::{Error, ErrorExtensions};
+use async_graphql::{Error, ErrorExtensions, Value};
 use crate::models::common::ErrorResponse;
+use crate::services::ServiceError;
 
 /// GraphQL错误类型
 #[derive(Debug, Clone)]
@@ -16,6 +17,14 @@ pub enum GraphQLErrorType {
     Unauthorized,
     /// 禁止访问
     Forbidden,
+    /// 资源冲突
+    Conflict,
+    /// 前置条件不满足，例如版本不一致
+    PreconditionFailed,
+    /// 缺少前置条件，例如配置要求提供期望的版本号
+    PreconditionRequired,
+    /// 请求方法不支持该操作，例如通过GET请求执行变更
+    MethodNotAllowed,
     /// 内部服务器错误
     Internal,
 }
@@ -28,11 +37,36 @@ impl GraphQLErrorType {
             Self::Validation => "VALIDATION_ERROR",
             Self::Unauthorized => "UNAUTHORIZED",
             Self::Forbidden => "FORBIDDEN",
+            Self::Conflict => "CONFLICT",
+            Self::PreconditionFailed => "PRECONDITION_FAILED",
+            Self::PreconditionRequired => "PRECONDITION_REQUIRED",
+            Self::MethodNotAllowed => "METHOD_NOT_ALLOWED",
             Self::Internal => "INTERNAL_SERVER_ERROR",
         }
     }
 }
 
+/// 创建带有错误代码扩展的GraphQL错误
+pub fn graphql_error(error_type: GraphQLErrorType, message: impl Into<String>) -> Error {
+    Error::new(message.into()).extend_with(|_, ext| {
+        ext.set("code", error_type.code());
+        ext.set("type", format!("{:?}", error_type));
+    })
+}
+
+/// 将业务错误转换为带错误代码的GraphQL错误
+pub fn service_error(err: ServiceError) -> Error {
+    let error_type = match &err {
+        ServiceError::NotFound(_) => GraphQLErrorType::NotFound,
+        ServiceError::Validation(_) => GraphQLErrorType::Validation,
+        ServiceError::Unauthorized(_) => GraphQLErrorType::Unauthorized,
+        ServiceError::Forbidden(_) => GraphQLErrorType::Forbidden,
+        ServiceError::Conflict(_) => GraphQLErrorType::Conflict,
+        ServiceError::PreconditionFailed(_) => GraphQLErrorType::PreconditionFailed,
+        ServiceError::Internal(_) => GraphQLErrorType::Internal,
+    };
+    graphql_error(error_type, err.to_string())
+}
 
 /// 从 GraphQL Error 中提取 ErrorResponse
 pub fn to_error_response(error: &Error) -> ErrorResponse {
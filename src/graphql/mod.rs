@@ -1,38 +1,123 @@
 // src/graphql/mod.rs
 
-use async_graphql_poem::GraphQL;
-use poem::{handler, Route, web::Html, get};
-use async_graphql::{Schema, EmptySubscription, http::GraphiQLSource};
+use async_graphql_poem::{GraphQLRequest, GraphQLResponse};
+use poem::{handler, Route, web::{Data, Html}, get, http::{header, Method, StatusCode}, EndpointExt, IntoResponse, Response};
+use async_graphql::{Schema, EmptySubscription, Pos, ServerError, http::GraphiQLSource};
+use async_graphql::parser::{parse_query, types::OperationType};
 
 // 确保正确导入 Mutation
 use crate::graphql::{query::Query, mutation::Mutation};
+use crate::audit::{AuditContext, AuditLog};
+use crate::auth::Principal;
+use crate::config::ApiConfig;
+use crate::graphql::error::{graphql_error, GraphQLErrorType};
+use crate::models::api_key::ApiKeyScope;
+use crate::services::{AccountService, MfaService, UserService};
 
 mod query;
 mod mutation;
+mod subscription;
 pub mod modules;
 pub mod error; // 新增错误处理模块
 
-/// 创建GraphQL服务路由
-/// 
-/// 配置并返回包含GraphQL Playground和API端点的路由
-pub fn create_graphql_route() -> Route {
-    let schema = Schema::build(
+/// 应用GraphQL Schema类型
+pub type AppSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// 构建GraphQL Schema
+pub fn build_schema() -> AppSchema {
+    Schema::build(
         Query::default(),    // 默认查询对象
         Mutation::default(), // 默认变更对象
         EmptySubscription    // 空订阅
     )
-    .finish();
+    .finish()
+}
+
+/// 创建GraphQL服务路由
+///
+/// 配置并返回包含GraphQL Playground、API端点和WebSocket订阅端点的路由
+pub fn create_graphql_route() -> Route {
+    let schema = build_schema();
 
     // 创建包含GraphQL Playground和API端点的路由
     Route::new()
         // 添加GraphQL Playground界面
         .at("/", get(graphql_playground))
-        // 添加GraphQL API端点
-        .at("/query", GraphQL::new(schema))
+        // 添加GraphQL API端点（依赖应用注入的 UserService、AccountService、MfaService、AuditLog、ApiConfig）
+        .at("/query", get(graphql_query).post(graphql_query).data(schema.clone()))
+        // 添加WebSocket订阅端点（依赖应用注入的 ShutdownToken）
+        .at("/ws", get(subscription::graphql_ws.data(schema)))
+}
+
+/// GraphQL查询处理函数
+///
+/// 将应用注入的业务服务、API行为配置及本次请求的审计上下文传入GraphQL上下文，
+/// 解析器通过 `ctx.data::<UserService>()`、`ctx.data::<AuditContext>()` 等获取；
+/// 使用API密钥调用时先检查授权范围。
+///
+/// GET请求只能执行查询：会话中间件对GET请求不校验CSRF令牌，通过GET执行变更时返回405
+#[handler]
+#[allow(clippy::too_many_arguments)]
+async fn graphql_query(
+    method: Method,
+    schema: Data<&AppSchema>,
+    users: Data<&UserService>,
+    accounts: Data<&AccountService>,
+    mfa: Data<&MfaService>,
+    audit_log: Data<&AuditLog>,
+    api: Data<&ApiConfig>,
+    audit: AuditContext,
+    req: GraphQLRequest,
+) -> Response {
+    if method == Method::GET && has_mutation(&req.0) {
+        let err = graphql_error(GraphQLErrorType::MethodNotAllowed, "变更操作必须使用POST请求")
+            .into_server_error(Pos::default());
+        return GraphQLResponse::from(async_graphql::Response::from_errors(vec![err]))
+            .with_status(StatusCode::METHOD_NOT_ALLOWED)
+            .with_header(header::ALLOW, "POST")
+            .into_response();
+    }
+    if let Some(err) = check_scope(&audit.actor, &req.0) {
+        return GraphQLResponse::from(async_graphql::Response::from_errors(vec![err])).into_response();
+    }
+
+    let req = req
+        .0
+        .data(users.clone())
+        .data(accounts.clone())
+        .data(mfa.clone())
+        .data(audit_log.clone())
+        .data(api.clone())
+        .data(audit);
+    GraphQLResponse::from(schema.execute(req).await).into_response()
+}
+
+/// 检查API密钥的授权范围：查询需要 `read`，包含变更时需要 `write`，不满足时返回 FORBIDDEN 错误
+fn check_scope(actor: &Principal, req: &async_graphql::Request) -> Option<ServerError> {
+    if actor.has_scope(ApiKeyScope::Write) {
+        return None;
+    }
+
+    let scope = if has_mutation(req) { ApiKeyScope::Write } else { ApiKeyScope::Read };
+
+    (!actor.has_scope(scope)).then(|| {
+        graphql_error(GraphQLErrorType::Forbidden, format!("API密钥缺少 {} 授权范围", scope.as_str()))
+            .into_server_error(Pos::default())
+    })
+}
+
+/// 请求中是否包含变更操作，无法解析的请求交给Schema返回语法错误
+fn has_mutation(req: &async_graphql::Request) -> bool {
+    parse_query(&req.query).is_ok_and(|document| {
+        document
+            .operations
+            .iter()
+            .any(|(_, operation)| operation.node.ty == OperationType::Mutation)
+    })
 }
 
 /// GraphQL Playground界面处理函数
-/// 
+///
 /// 返回交互式GraphQL查询界面
 #[handler]
 async fn graphql_playground() -> Html<String> {
@@ -40,6 +125,7 @@ async fn graphql_playground() -> Html<String> {
     Html(
         GraphiQLSource::build()
             .endpoint("/graphql/query") // 注意：这里的路径与上面的路由匹配
+            .subscription_endpoint("/graphql/ws")
             .finish()
     )
 }
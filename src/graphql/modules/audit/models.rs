@@ -0,0 +1,127 @@
+use async_graphql::{Enum, InputObject, Json, SimpleObject};
+use crate::audit::AuditFilter;
+use crate::models::audit as rest;
+
+/// 审计动作
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    /// 创建
+    Create,
+    /// 修改
+    Update,
+    /// 删除（软删除）
+    Delete,
+    /// 恢复已删除的数据
+    Restore,
+    /// 超过保留期后彻底清除
+    Purge,
+}
+
+impl From<rest::AuditAction> for AuditAction {
+    fn from(action: rest::AuditAction) -> Self {
+        match action {
+            rest::AuditAction::Create => Self::Create,
+            rest::AuditAction::Update => Self::Update,
+            rest::AuditAction::Delete => Self::Delete,
+            rest::AuditAction::Restore => Self::Restore,
+            rest::AuditAction::Purge => Self::Purge,
+        }
+    }
+}
+
+impl From<AuditAction> for rest::AuditAction {
+    fn from(action: AuditAction) -> Self {
+        match action {
+            AuditAction::Create => Self::Create,
+            AuditAction::Update => Self::Update,
+            AuditAction::Delete => Self::Delete,
+            AuditAction::Restore => Self::Restore,
+            AuditAction::Purge => Self::Purge,
+        }
+    }
+}
+
+/// 审计事件
+#[derive(SimpleObject)]
+pub struct AuditEvent {
+    /// 事件ID，按写入顺序递增
+    pub id: u64,
+    /// 发生时间（ISO 8601格式）
+    pub occurred_at: String,
+    /// 操作者类型：anonymous、system、user、api_key
+    pub actor_type: String,
+    /// 操作者用户ID或API密钥ID，其他操作者时为空
+    pub actor_id: Option<String>,
+    /// 操作者名称：用户名、系统组件名或API密钥名称
+    pub actor_name: Option<String>,
+    /// 动作
+    pub action: AuditAction,
+    /// 实体类型，例如 user
+    pub entity_type: String,
+    /// 实体ID
+    pub entity_id: String,
+    /// 变更字段，格式为 {"字段": {"before": 旧值, "after": 新值}}
+    pub changes: Json<serde_json::Value>,
+    /// 请求ID，与响应头 X-Request-Id 对应
+    pub request_id: Option<String>,
+    /// 客户端IP
+    pub ip: Option<String>,
+}
+
+impl From<rest::AuditEvent> for AuditEvent {
+    fn from(event: rest::AuditEvent) -> Self {
+        Self {
+            id: event.id,
+            occurred_at: event.occurred_at,
+            actor_type: event.actor_type,
+            actor_id: event.actor_id,
+            actor_name: event.actor_name,
+            action: event.action.into(),
+            entity_type: event.entity_type,
+            entity_id: event.entity_id,
+            changes: Json(event.changes),
+            request_id: event.request_id,
+            ip: event.ip,
+        }
+    }
+}
+
+/// 审计事件过滤条件，各条件均为精确匹配
+#[derive(InputObject, Default)]
+pub struct AuditEventFilter {
+    /// 操作者用户ID
+    pub actor_id: Option<String>,
+    /// 动作
+    pub action: Option<AuditAction>,
+    /// 实体类型
+    pub entity_type: Option<String>,
+    /// 实体ID
+    pub entity_id: Option<String>,
+    /// 请求ID
+    pub request_id: Option<String>,
+    /// 起始时间（RFC 3339，包含）
+    pub since: Option<String>,
+    /// 截止时间（RFC 3339，不包含）
+    pub until: Option<String>,
+}
+
+impl From<AuditEventFilter> for AuditFilter {
+    fn from(filter: AuditEventFilter) -> Self {
+        Self {
+            actor_id: filter.actor_id,
+            action: filter.action.map(Into::into),
+            entity_type: filter.entity_type,
+            entity_id: filter.entity_id,
+            request_id: filter.request_id,
+            since: filter.since,
+            until: filter.until,
+        }
+    }
+}
+
+/// 审计事件连接的附加字段
+#[derive(SimpleObject)]
+pub struct AuditEventConnectionFields {
+    /// 符合条件的事件总数
+    pub total_count: u64,
+}
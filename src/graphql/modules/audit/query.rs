@@ -0,0 +1,67 @@
+// src/graphql/modules/audit/query.rs
+
+use async_graphql::{
+    connection::{self, Connection, Edge, EmptyFields},
+    Context, Object, Result,
+};
+use super::models::{AuditEvent, AuditEventConnectionFields, AuditEventFilter};
+use crate::audit::{AuditContext, AuditFilter, AuditLog};
+use crate::graphql::error::service_error;
+
+/// 默认每页事件数
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// 每页事件数上限
+const MAX_PAGE_SIZE: usize = 100;
+
+/// 审计事件连接（游标为事件ID）
+pub type AuditEventConnection = Connection<u64, AuditEvent, AuditEventConnectionFields, EmptyFields>;
+
+/// 审计日志查询操作
+#[derive(Default)]
+pub struct AuditQuery;
+
+#[Object]
+impl AuditQuery {
+    /// 查询审计事件
+    ///
+    /// 按时间倒序返回数据变更记录，使用 first/after 游标分页
+    /// first 默认20，最大100；需要管理权限，否则返回 UNAUTHORIZED 或 FORBIDDEN 错误
+    async fn audit_events(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AuditEventFilter>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<AuditEventConnection> {
+        ctx.data::<AuditContext>()?.actor.require_admin().map_err(service_error)?;
+        let audit = ctx.data::<AuditLog>()?;
+        let filter = AuditFilter::from(filter.unwrap_or_default());
+
+        connection::query(after, None, first, None, |after: Option<u64>, _, first, _| async move {
+            let limit = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+
+            // 多取一条判断是否还有下一页
+            let mut events = audit
+                .list_before(&filter, after, limit as u32 + 1)
+                .await
+                .map_err(service_error)?;
+            let has_next_page = events.len() > limit;
+            events.truncate(limit);
+
+            let total_count = audit.count(&filter).await.map_err(service_error)?;
+            let mut connection = Connection::with_additional_fields(
+                after.is_some(),
+                has_next_page,
+                AuditEventConnectionFields { total_count },
+            );
+            connection.edges.extend(
+                events
+                    .into_iter()
+                    .map(|event| Edge::new(event.id, AuditEvent::from(event))),
+            );
+            Ok::<_, async_graphql::Error>(connection)
+        })
+        .await
+    }
+}
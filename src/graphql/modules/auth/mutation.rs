@@ -0,0 +1,54 @@
+// src/graphql/modules/auth/mutation.rs
+
+use async_graphql::{Context, Object, Result};
+use crate::audit::AuditContext;
+use crate::graphql::error::service_error;
+use crate::graphql::modules::user::models::User;
+use crate::models::auth::ResetPasswordRequest;
+use crate::services::AccountService;
+
+/// 账号变更操作
+#[derive(Default)]
+pub struct AuthMutation;
+
+#[Object]
+impl AuthMutation {
+    /// 验证邮箱
+    ///
+    /// 使用验证邮件中的令牌验证邮箱，令牌无效、已过期或已使用时返回 VALIDATION_ERROR 错误
+    /// 返回验证后的用户信息
+    async fn verify_email(&self, ctx: &Context<'_>, token: String) -> Result<User> {
+        let audit = ctx.data::<AuditContext>()?;
+        let user = ctx
+            .data::<AccountService>()?
+            .verify_email(audit, &token)
+            .await
+            .map_err(service_error)?;
+        Ok(user.into())
+    }
+
+    /// 找回密码
+    ///
+    /// 邮箱对应的用户存在时发送密码重置邮件
+    /// 无论邮箱是否存在都返回 true
+    async fn forgot_password(&self, ctx: &Context<'_>, email: String) -> Result<bool> {
+        ctx.data::<AccountService>()?
+            .forgot_password(&email)
+            .await
+            .map_err(service_error)?;
+        Ok(true)
+    }
+
+    /// 重置密码
+    ///
+    /// 使用密码重置邮件中的令牌设置新密码，密码校验规则与REST接口一致
+    /// 返回操作是否成功
+    async fn reset_password(&self, ctx: &Context<'_>, token: String, password: String) -> Result<bool> {
+        let audit = ctx.data::<AuditContext>()?;
+        ctx.data::<AccountService>()?
+            .reset_password(audit, ResetPasswordRequest { token, password })
+            .await
+            .map_err(service_error)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,17 @@
+pub mod query;
+pub mod mutation;
+pub mod models;
+
+use async_graphql::Result;
+use crate::graphql::error::{graphql_error, GraphQLErrorType};
+
+/// 校验用户ID
+fn parse_user_id(id: i32) -> Result<u64> {
+    if id <= 0 {
+        return Err(graphql_error(
+            GraphQLErrorType::Validation,
+            "用户ID必须为正整数"
+        ));
+    }
+    Ok(id as u64)
+}
@@ -0,0 +1,52 @@
+use async_graphql::SimpleObject;
+use crate::models::mfa as rest;
+
+/// 两步验证状态
+#[derive(SimpleObject)]
+pub struct MfaStatus {
+    /// 是否已启用
+    pub enabled: bool,
+    /// 启用时间（ISO 8601格式），未启用时为空
+    pub enabled_at: Option<String>,
+    /// 用户的角色是否必须启用两步验证，必须启用时不能停用
+    pub required: bool,
+    /// 剩余可用的恢复码数量
+    pub recovery_codes_remaining: u32,
+}
+
+impl From<rest::MfaStatus> for MfaStatus {
+    fn from(status: rest::MfaStatus) -> Self {
+        Self {
+            enabled: status.enabled,
+            enabled_at: status.enabled_at,
+            required: status.required,
+            recovery_codes_remaining: status.recovery_codes_remaining,
+        }
+    }
+}
+
+/// 两步验证绑定信息
+///
+/// 将 otpauthUri 生成二维码供验证器应用扫描，或手动输入 secret
+#[derive(SimpleObject)]
+pub struct MfaEnrollment {
+    /// TOTP密钥（Base32）
+    pub secret: String,
+    /// otpauth:// URI，即二维码的内容
+    pub otpauth_uri: String,
+    /// 验证码位数
+    pub digits: u32,
+    /// 验证码有效时长（秒）
+    pub period: u64,
+}
+
+impl From<rest::MfaEnrollment> for MfaEnrollment {
+    fn from(enrollment: rest::MfaEnrollment) -> Self {
+        Self {
+            secret: enrollment.secret,
+            otpauth_uri: enrollment.otpauth_uri,
+            digits: enrollment.digits,
+            period: enrollment.period,
+        }
+    }
+}
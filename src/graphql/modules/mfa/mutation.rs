@@ -0,0 +1,71 @@
+// src/graphql/modules/mfa/mutation.rs
+
+use async_graphql::{Context, Object, Result};
+use super::models::MfaEnrollment;
+use super::parse_user_id;
+use crate::audit::AuditContext;
+use crate::graphql::error::service_error;
+use crate::services::MfaService;
+
+/// 两步验证变更操作
+///
+/// 只允许用户本人或管理员调用，否则返回 UNAUTHORIZED 或 FORBIDDEN 错误
+#[derive(Default)]
+pub struct MfaMutation;
+
+#[Object]
+impl MfaMutation {
+    /// 开始绑定两步验证
+    ///
+    /// 生成新的TOTP密钥及二维码内容，已启用时返回 CONFLICT 错误
+    async fn enroll_mfa(&self, ctx: &Context<'_>, user_id: i32) -> Result<MfaEnrollment> {
+        let audit = ctx.data::<AuditContext>()?;
+        let enrollment = ctx
+            .data::<MfaService>()?
+            .enroll(&audit.actor, parse_user_id(user_id)?)
+            .await
+            .map_err(service_error)?;
+        Ok(enrollment.into())
+    }
+
+    /// 启用两步验证
+    ///
+    /// 提交验证器应用显示的验证码确认绑定
+    /// 返回恢复码，恢复码只返回这一次
+    async fn enable_mfa(&self, ctx: &Context<'_>, user_id: i32, code: String) -> Result<Vec<String>> {
+        let audit = ctx.data::<AuditContext>()?;
+        let codes = ctx
+            .data::<MfaService>()?
+            .enable(audit, parse_user_id(user_id)?, &code)
+            .await
+            .map_err(service_error)?;
+        Ok(codes.codes)
+    }
+
+    /// 停用两步验证
+    ///
+    /// 需要验证码或恢复码，用户的角色必须启用两步验证时返回 CONFLICT 错误
+    /// 返回操作是否成功
+    async fn disable_mfa(&self, ctx: &Context<'_>, user_id: i32, code: String) -> Result<bool> {
+        let audit = ctx.data::<AuditContext>()?;
+        ctx.data::<MfaService>()?
+            .disable(audit, parse_user_id(user_id)?, &code)
+            .await
+            .map_err(service_error)?;
+        Ok(true)
+    }
+
+    /// 重新生成恢复码
+    ///
+    /// 需要验证码或恢复码，原有的恢复码全部失效
+    /// 返回新的恢复码
+    async fn regenerate_recovery_codes(&self, ctx: &Context<'_>, user_id: i32, code: String) -> Result<Vec<String>> {
+        let audit = ctx.data::<AuditContext>()?;
+        let codes = ctx
+            .data::<MfaService>()?
+            .regenerate_recovery_codes(audit, parse_user_id(user_id)?, &code)
+            .await
+            .map_err(service_error)?;
+        Ok(codes.codes)
+    }
+}
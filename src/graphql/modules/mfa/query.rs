@@ -0,0 +1,28 @@
+// src/graphql/modules/mfa/query.rs
+
+use async_graphql::{Context, Object, Result};
+use super::models::MfaStatus;
+use super::parse_user_id;
+use crate::audit::AuditContext;
+use crate::graphql::error::service_error;
+use crate::services::MfaService;
+
+/// 两步验证查询操作
+#[derive(Default)]
+pub struct MfaQuery;
+
+#[Object]
+impl MfaQuery {
+    /// 获取用户的两步验证状态
+    ///
+    /// 只允许用户本人或管理员查询，用户不存在时返回 NOT_FOUND 错误
+    async fn mfa_status(&self, ctx: &Context<'_>, user_id: i32) -> Result<MfaStatus> {
+        let audit = ctx.data::<AuditContext>()?;
+        let status = ctx
+            .data::<MfaService>()?
+            .status(&audit.actor, parse_user_id(user_id)?)
+            .await
+            .map_err(service_error)?;
+        Ok(status.into())
+    }
+}
@@ -1,2 +1,5 @@
+pub mod audit;
+pub mod auth;
+pub mod mfa;
 pub mod user;
-// 添加新模块: pub mod your_module;
\ No newline at end of file
+// 添加新模块: pub mod your_module;
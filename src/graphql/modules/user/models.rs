@@ -1,11 +1,93 @@
-use async_graphql::SimpleObject;
+use async_graphql::{InputObject, MaybeUndefined, SimpleObject};
 use crate::models::common::{UserBase, BaseUser};
+use crate::models::user::{CreateUserRequest, PatchUserRequest, UpdateUserRequest};
 
 /// 用户模型
 #[derive(SimpleObject, Clone)]
 pub struct User {
     pub id: i32,
     pub name: String,
+    /// 用户邮箱
+    pub email: String,
+    /// 邮箱验证时间（ISO 8601格式），未验证时为空
+    pub email_verified_at: Option<String>,
+    /// 版本号，每次修改递增，可作为变更的 `expectedVersion` 参数
+    pub version: i32,
+    /// 删除时间（ISO 8601格式），未删除时为空
+    pub deleted_at: Option<String>,
+}
+
+/// 用户创建输入
+///
+/// 校验规则与REST接口一致
+#[derive(InputObject)]
+pub struct CreateUserInput {
+    /// 用户名，用于登录
+    pub username: String,
+    /// 用户邮箱
+    pub email: String,
+    /// 用户密码
+    pub password: String,
+}
+
+impl From<CreateUserInput> for CreateUserRequest {
+    fn from(input: CreateUserInput) -> Self {
+        Self {
+            username: input.username,
+            email: input.email,
+            password: input.password,
+        }
+    }
+}
+
+/// 用户更新输入
+///
+/// 未提供的字段保持不变，校验规则与REST接口一致
+#[derive(InputObject, Default)]
+pub struct UpdateUserInput {
+    /// 用户邮箱
+    pub email: Option<String>,
+    /// 用户密码
+    pub password: Option<String>,
+}
+
+impl From<UpdateUserInput> for UpdateUserRequest {
+    fn from(input: UpdateUserInput) -> Self {
+        Self {
+            email: input.email,
+            password: input.password,
+        }
+    }
+}
+
+/// 用户部分更新输入
+///
+/// 字段均显式可空：不传表示保持不变，传 `null` 表示清空（邮箱、密码不允许清空），
+/// 校验规则与REST接口一致
+#[derive(InputObject, Default)]
+pub struct PatchUserInput {
+    /// 用户邮箱
+    pub email: MaybeUndefined<String>,
+    /// 用户密码
+    pub password: MaybeUndefined<String>,
+}
+
+impl From<PatchUserInput> for PatchUserRequest {
+    fn from(input: PatchUserInput) -> Self {
+        Self {
+            email: maybe_undefined(input.email),
+            password: maybe_undefined(input.password),
+        }
+    }
+}
+
+/// GraphQL与OpenAPI的 `MaybeUndefined` 语义相同，逐一对应转换
+fn maybe_undefined<T>(value: MaybeUndefined<T>) -> poem_openapi::types::MaybeUndefined<T> {
+    match value {
+        MaybeUndefined::Undefined => poem_openapi::types::MaybeUndefined::Undefined,
+        MaybeUndefined::Null => poem_openapi::types::MaybeUndefined::Null,
+        MaybeUndefined::Value(value) => poem_openapi::types::MaybeUndefined::Value(value),
+    }
 }
 
 // 实现UserBase特性，支持与REST API模型的转换
@@ -25,6 +107,10 @@ impl From<BaseUser> for User {
         Self {
             id: base.id.unwrap_or(0) as i32,
             name: base.name,
+            email: String::new(), // 需要外部设置
+            email_verified_at: None,
+            version: 0,
+            deleted_at: None,
         }
     }
 }
@@ -35,6 +121,10 @@ impl From<crate::models::user::User> for User {
         Self {
             id: rest_user.id.unwrap_or(0) as i32,
             name: rest_user.username,
+            email: rest_user.email,
+            email_verified_at: rest_user.email_verified_at,
+            version: rest_user.version.unwrap_or_default() as i32,
+            deleted_at: rest_user.deleted_at,
         }
     }
 }
@@ -1,10 +1,16 @@
 // src/graphql/modules/user/mutation.rs
 
 use async_graphql::{Context, Object, Result};
-use super::models::User;
-use crate::graphql::error::{graphql_error, GraphQLErrorType};
+use super::models::{CreateUserInput, PatchUserInput, UpdateUserInput, User};
+use crate::graphql::error::{graphql_error, service_error, GraphQLErrorType};
+use crate::audit::AuditContext;
+use crate::config::ApiConfig;
+use crate::services::UserService;
 
 /// 用户变更操作
+///
+/// 配置 `api.require_if_match` 时修改、删除及恢复必须提供 expectedVersion，
+/// 与REST接口必须携带 `If-Match` 请求头一致，未提供时返回 PRECONDITION_REQUIRED 错误
 #[derive(Default)] // 添加 Default 派生
 pub struct UserMutation;
 
@@ -12,26 +18,31 @@ pub struct UserMutation;
 impl UserMutation {
     /// 创建新用户
     /// 
-    /// 根据提供的用户名创建新用户
+    /// 校验规则与REST接口一致，用户名或邮箱已存在时返回 CONFLICT 错误
     /// 返回创建成功的用户信息
-    async fn create_user(&self, _ctx: &Context<'_>, name: String) -> Result<User> {
-        // 验证用户名
-        if name.len() < 3 {
-            return Err(graphql_error(
-                GraphQLErrorType::Validation,
-                "用户名至少需要3个字符"
-            ));
-        }
-        
-        // 实际项目中保存到数据库
-        Ok(User { id: 100, name })
+    async fn create_user(&self, ctx: &Context<'_>, input: CreateUserInput) -> Result<User> {
+        let audit = ctx.data::<AuditContext>()?;
+        let user = ctx
+            .data::<UserService>()?
+            .create(audit, input.into())
+            .await
+            .map_err(service_error)?;
+        Ok(user.into())
     }
     
     /// 更新用户信息
     /// 
-    /// 根据用户ID更新用户名
+    /// 只修改输入中提供的字段，修改邮箱后需要重新验证
+    /// 只能修改自己的账户，管理员可以修改任意用户
+    /// 提供 expectedVersion 时只在版本一致时更新，否则返回 PRECONDITION_FAILED 错误
     /// 返回更新后的用户信息
-    async fn update_user(&self, _ctx: &Context<'_>, id: i32, name: String) -> Result<User> {
+    async fn update_user(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        input: UpdateUserInput,
+        expected_version: Option<i32>,
+    ) -> Result<User> {
         // 验证用户ID
         if id <= 0 {
             return Err(graphql_error(
@@ -39,25 +50,53 @@ impl UserMutation {
                 "用户ID必须为正整数"
             ));
         }
-        
-        // 验证用户名
-        if name.len() < 3 {
+
+        let audit = ctx.data::<AuditContext>()?;
+        let user = ctx
+            .data::<UserService>()?
+            .update(audit, id as u64, input.into(), checked_version(ctx, expected_version)?)
+            .await
+            .map_err(service_error)?;
+        Ok(user.into())
+    }
+    
+    /// 部分更新用户信息
+    /// 
+    /// 只修改输入中出现的字段，显式传入 null 表示清空该字段
+    /// 只能修改自己的账户，管理员可以修改任意用户
+    /// 提供 expectedVersion 时只在版本一致时更新，否则返回 PRECONDITION_FAILED 错误
+    /// 返回更新后的用户信息
+    async fn patch_user(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        input: PatchUserInput,
+        expected_version: Option<i32>,
+    ) -> Result<User> {
+        // 验证用户ID
+        if id <= 0 {
             return Err(graphql_error(
                 GraphQLErrorType::Validation,
-                "用户名至少需要3个字符"
+                "用户ID必须为正整数"
             ));
         }
-        
-        // 模拟更新操作
-        // 实际应用中应先检查用户是否存在
-        Ok(User { id, name })
+
+        let audit = ctx.data::<AuditContext>()?;
+        let user = ctx
+            .data::<UserService>()?
+            .patch(audit, id as u64, input.into(), checked_version(ctx, expected_version)?)
+            .await
+            .map_err(service_error)?;
+        Ok(user.into())
     }
-    
+
     /// 删除用户
     /// 
-    /// 根据用户ID删除用户
+    /// 根据用户ID软删除用户，可通过 restoreUser 恢复，超过保留期后彻底清除
+    /// 需要管理员权限
+    /// 提供 expectedVersion 时只在版本一致时删除
     /// 返回操作是否成功
-    async fn delete_user(&self, _ctx: &Context<'_>, id: i32) -> Result<bool> {
+    async fn delete_user(&self, ctx: &Context<'_>, id: i32, expected_version: Option<i32>) -> Result<bool> {
         // 验证用户ID
         if id <= 0 {
             return Err(graphql_error(
@@ -66,8 +105,47 @@ impl UserMutation {
             ));
         }
         
-        // 模拟删除操作
-        // 实际应用中应先检查用户是否存在
+        let audit = ctx.data::<AuditContext>()?;
+        ctx.data::<UserService>()?
+            .delete(audit, id as u64, checked_version(ctx, expected_version)?)
+            .await
+            .map_err(service_error)?;
         Ok(true)
     }
+
+    /// 恢复已删除的用户
+    /// 
+    /// 恢复被软删除且尚未彻底清除的用户
+    /// 需要管理员权限
+    /// 提供 expectedVersion 时只在版本一致时恢复
+    /// 返回恢复后的用户信息
+    async fn restore_user(&self, ctx: &Context<'_>, id: i32, expected_version: Option<i32>) -> Result<User> {
+        // 验证用户ID
+        if id <= 0 {
+            return Err(graphql_error(
+                GraphQLErrorType::Validation,
+                "用户ID必须为正整数"
+            ));
+        }
+
+        let audit = ctx.data::<AuditContext>()?;
+        let user = ctx
+            .data::<UserService>()?
+            .restore(audit, id as u64, checked_version(ctx, expected_version)?)
+            .await
+            .map_err(service_error)?;
+        Ok(user.into())
+    }
+}
+
+/// 变更期望的版本号，配置要求提供而未提供时返回 PRECONDITION_REQUIRED 错误
+fn checked_version(ctx: &Context<'_>, expected_version: Option<i32>) -> Result<Option<u64>> {
+    match expected_version {
+        Some(version) => Ok(Some(version as u64)),
+        None if ctx.data::<ApiConfig>()?.require_if_match => Err(graphql_error(
+            GraphQLErrorType::PreconditionRequired,
+            "修改资源时必须提供 expectedVersion",
+        )),
+        None => Ok(None),
+    }
 }
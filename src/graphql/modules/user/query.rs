@@ -2,7 +2,9 @@
 
 use async_graphql::{Context, Object, Result};
 use super::models::User;
-use crate::graphql::error::{graphql_error, GraphQLErrorType};
+use crate::audit::AuditContext;
+use crate::graphql::error::{graphql_error, service_error, GraphQLErrorType};
+use crate::services::{ServiceError, UserFilter, UserService};
 
 /// 用户查询操作
 #[derive(Default)] // 添加 Default 派生
@@ -12,23 +14,22 @@ pub struct UserQuery;
 impl UserQuery {
     /// 获取所有用户
     /// 
-    /// 返回系统中所有用户的列表
-    async fn users(&self, _ctx: &Context<'_>) -> Result<Vec<User>> {
-        // 实际项目中从数据库获取
-        // 这里使用模拟数据进行演示
-        Ok(vec![
-            User { id: 1, name: "Alice".to_string() },
-            User { id: 2, name: "Bob".to_string() },
-        ])
+    /// 返回系统中所有用户的列表，默认不包含已删除的用户
+    /// 需要管理员权限
+    async fn users(&self, ctx: &Context<'_>, #[graphql(default)] include_deleted: bool) -> Result<Vec<User>> {
+        let filter = UserFilter {
+            include_deleted,
+            ..Default::default()
+        };
+        search(ctx, filter).await
     }
 
     /// 根据ID获取用户
     /// 
     /// 根据提供的用户ID查询并返回用户信息
+    /// 只能查询自己的账户，管理员可以查询任意用户
     /// 如果用户不存在，返回None
-    async fn user(&self, _ctx: &Context<'_>, id: i32) -> Result<Option<User>> {
-        // 模拟数据库查询
-        // 在实际应用中，应该检查用户是否存在
+    async fn user(&self, ctx: &Context<'_>, id: i32) -> Result<Option<User>> {
         if id <= 0 {
             return Err(graphql_error(
                 GraphQLErrorType::Validation,
@@ -36,14 +37,19 @@ impl UserQuery {
             ));
         }
         
-        // 模拟查询结果
-        Ok(Some(User { id, name: "Test User".to_string() }))
+        let actor = &ctx.data::<AuditContext>()?.actor;
+        match ctx.data::<UserService>()?.get_as(actor, id as u64).await {
+            Ok(user) => Ok(Some(user.into())),
+            Err(ServiceError::NotFound(_)) => Ok(None),
+            Err(err) => Err(service_error(err)),
+        }
     }
     
     /// 根据用户名搜索用户
     /// 
     /// 根据提供的用户名模糊匹配用户
-    async fn search_users(&self, _ctx: &Context<'_>, name_contains: String) -> Result<Vec<User>> {
+    /// 需要管理员权限
+    async fn search_users(&self, ctx: &Context<'_>, name_contains: String) -> Result<Vec<User>> {
         // 验证搜索参数
         if name_contains.len() < 2 {
             return Err(graphql_error(
@@ -52,9 +58,21 @@ impl UserQuery {
             ));
         }
         
-        // 模拟搜索结果
-        Ok(vec![
-            User { id: 1, name: format!("User with {}", name_contains) },
-        ])
+        let filter = UserFilter {
+            username: Some(name_contains),
+            ..Default::default()
+        };
+        search(ctx, filter).await
     }
 }
+
+/// 查询符合条件的全部用户
+async fn search(ctx: &Context<'_>, filter: UserFilter) -> Result<Vec<User>> {
+    let actor = &ctx.data::<AuditContext>()?.actor;
+    let (users, _) = ctx
+        .data::<UserService>()?
+        .list(actor, &filter, 1, u32::MAX)
+        .await
+        .map_err(service_error)?;
+    Ok(users.into_iter().map(User::from).collect())
+}
@@ -7,5 +7,7 @@ use crate::graphql::modules;
 #[derive(MergedObject, Default)]
 pub struct Mutation(
     modules::user::mutation::UserMutation,
+    modules::auth::mutation::AuthMutation,
+    modules::mfa::mutation::MfaMutation,
     // 添加新模块的变更类型
 );
\ No newline at end of file
@@ -5,5 +5,7 @@ use crate::graphql::modules;
 #[derive(MergedObject, Default)]
 pub struct Query(
     modules::user::query::UserQuery,
+    modules::audit::query::AuditQuery,
+    modules::mfa::query::MfaQuery,
     // 添加新模块的查询类型
 );
\ No newline at end of file
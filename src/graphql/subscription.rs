@@ -0,0 +1,146 @@
+//! GraphQL WebSocket订阅端点
+//!
+//! 支持 `graphql-transport-ws` 与 `graphql-ws` 协议，服务关闭时向客户端发送
+//! 1001（Going Away）关闭帧，而不是直接断开TCP连接。
+//!
+//! 连接上的操作与 `graphql_query` 使用相同的上下文数据（业务服务、API行为配置及建立连接时的审计上下文），
+//! 并在执行前检查API密钥的授权范围。WebSocket连接通过GET请求建立，会话中间件不校验CSRF令牌，
+//! 因此通过会话Cookie认证的连接只能执行查询
+
+use std::sync::Arc;
+
+use async_graphql::http::{WebSocket as GraphQLWebSocket, WsMessage, ALL_WEBSOCKET_PROTOCOLS};
+use async_graphql::{Executor, Pos, ServerError};
+use async_graphql_poem::GraphQLProtocol;
+use futures::{future, stream::BoxStream, SinkExt, StreamExt};
+use poem::{
+    handler,
+    web::{
+        websocket::{CloseCode, Message, WebSocket},
+        Data,
+    },
+    IntoResponse,
+};
+
+use super::{check_scope, has_mutation, AppSchema};
+use crate::audit::{AuditContext, AuditLog};
+use crate::auth::Principal;
+use crate::config::ApiConfig;
+use crate::graphql::error::{graphql_error, GraphQLErrorType};
+use crate::services::{AccountService, MfaService, UserService};
+use crate::sessions::CurrentSession;
+use crate::shutdown::ShutdownToken;
+
+/// GraphQL订阅处理函数
+#[handler]
+#[allow(clippy::too_many_arguments)]
+pub async fn graphql_ws(
+    schema: Data<&AppSchema>,
+    shutdown: Data<&ShutdownToken>,
+    users: Data<&UserService>,
+    accounts: Data<&AccountService>,
+    mfa: Data<&MfaService>,
+    audit_log: Data<&AuditLog>,
+    api: Data<&ApiConfig>,
+    audit: AuditContext,
+    session: Option<Data<&CurrentSession>>,
+    protocol: GraphQLProtocol,
+    websocket: WebSocket,
+) -> impl IntoResponse {
+    let executor = GuardedExecutor {
+        schema: schema.clone(),
+        actor: audit.actor.clone(),
+        session: session.is_some(),
+    };
+    let shutdown = shutdown.clone();
+
+    let mut data = async_graphql::Data::default();
+    data.insert(users.clone());
+    data.insert(accounts.clone());
+    data.insert(mfa.clone());
+    data.insert(audit_log.clone());
+    data.insert(api.clone());
+    data.insert(audit);
+
+    websocket
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| async move {
+            let (mut sink, stream) = socket.split();
+
+            // 只处理文本和二进制消息，连接出错时结束输入流
+            let stream = stream
+                .take_while(|msg| future::ready(msg.is_ok()))
+                .filter_map(|msg| {
+                    future::ready(match msg {
+                        Ok(msg) if msg.is_text() || msg.is_binary() => Some(msg.into_bytes()),
+                        _ => None,
+                    })
+                });
+
+            let mut connection = GraphQLWebSocket::new(executor, stream, protocol.0).connection_data(data);
+
+            loop {
+                tokio::select! {
+                    msg = connection.next() => match msg {
+                        Some(WsMessage::Text(text)) => {
+                            if sink.send(Message::Text(text)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Some(WsMessage::Close(code, reason)) => {
+                            let _ = sink.send(Message::close_with(code, reason)).await;
+                            break;
+                        }
+                        None => break,
+                    },
+                    _ = shutdown.cancelled() => {
+                        let _ = sink
+                            .send(Message::close_with(CloseCode::Away, "server shutting down"))
+                            .await;
+                        break;
+                    }
+                }
+            }
+        })
+}
+
+/// 执行前检查操作的执行器：会话Cookie认证的连接不能执行变更，API密钥需要满足授权范围
+#[derive(Clone)]
+struct GuardedExecutor {
+    schema: AppSchema,
+    actor: Principal,
+    /// 连接是否通过会话Cookie认证
+    session: bool,
+}
+
+impl GuardedExecutor {
+    fn check(&self, req: &async_graphql::Request) -> Option<ServerError> {
+        if self.session && has_mutation(req) {
+            return Some(
+                graphql_error(GraphQLErrorType::MethodNotAllowed, "变更操作必须使用POST请求")
+                    .into_server_error(Pos::default()),
+            );
+        }
+        check_scope(&self.actor, req)
+    }
+}
+
+impl Executor for GuardedExecutor {
+    async fn execute(&self, request: async_graphql::Request) -> async_graphql::Response {
+        match self.check(&request) {
+            Some(err) => async_graphql::Response::from_errors(vec![err]),
+            None => self.schema.execute(request).await,
+        }
+    }
+
+    fn execute_stream(
+        &self,
+        request: async_graphql::Request,
+        session_data: Option<Arc<async_graphql::Data>>,
+    ) -> BoxStream<'static, async_graphql::Response> {
+        match self.check(&request) {
+            Some(err) => futures::stream::once(future::ready(async_graphql::Response::from_errors(vec![err]))).boxed(),
+            None => Executor::execute_stream(&self.schema, request, session_data),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::Serialize;
+
+/// 默认的单项检查超时时间
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 健康检查特性
+///
+/// 数据库连接池、缓存、后台任务等组件实现该特性后注册到 `HealthRegistry`，
+/// 由 `/health` 和 `/health/ready` 统一调用
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// 检查项名称，会作为JSON输出中的键
+    fn name(&self) -> &str;
+
+    /// 单项检查超时时间，超时视为检查失败
+    fn timeout(&self) -> Duration {
+        DEFAULT_CHECK_TIMEOUT
+    }
+
+    /// 是否为关键依赖
+    ///
+    /// 关键依赖失败时服务不可用（就绪检查失败），非关键依赖失败只会标记为降级
+    fn critical(&self) -> bool {
+        true
+    }
+
+    /// 执行检查，返回错误表示依赖不可用
+    async fn check(&self) -> anyhow::Result<()>;
+}
+
+/// 健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// 正常
+    Up,
+    /// 非关键依赖异常，服务仍可用
+    Degraded,
+    /// 不可用
+    Down,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// 检查状态
+    pub status: HealthStatus,
+    /// 是否为关键依赖
+    pub critical: bool,
+    /// 检查耗时（毫秒）
+    pub duration_ms: u64,
+    /// 失败原因（可选）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 汇总后的健康报告
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// 整体状态
+    pub status: HealthStatus,
+    /// 服务版本
+    pub version: &'static str,
+    /// 各检查项结果
+    pub checks: std::collections::BTreeMap<String, CheckResult>,
+}
+
+/// 健康检查注册表
+///
+/// 克隆开销很小，可在各组件间共享
+#[derive(Clone, Default)]
+pub struct HealthRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+#[derive(Default)]
+struct RegistryInner {
+    checks: RwLock<Vec<Arc<dyn HealthCheck>>>,
+    shutting_down: AtomicBool,
+}
+
+impl HealthRegistry {
+    /// 创建空的注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个健康检查
+    pub fn register<C: HealthCheck + 'static>(&self, check: C) {
+        self.inner
+            .checks
+            .write()
+            .expect("健康检查注册表锁已损坏")
+            .push(Arc::new(check));
+    }
+
+    /// 标记服务正在关闭，之后就绪检查始终返回不可用
+    pub fn mark_shutting_down(&self) {
+        self.inner.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// 服务是否正在关闭
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// 并发执行所有检查并汇总结果
+    pub async fn run(&self) -> HealthReport {
+        let checks = self
+            .inner
+            .checks
+            .read()
+            .expect("健康检查注册表锁已损坏")
+            .clone();
+
+        let results = join_all(checks.iter().map(|check| run_check(check.as_ref()))).await;
+
+        let failed = |critical: bool| {
+            results
+                .iter()
+                .any(|(_, r)| r.status == HealthStatus::Down && (r.critical || !critical))
+        };
+
+        // 关闭过程中整体状态视为不可用，让负载均衡尽快摘除流量
+        let status = if self.is_shutting_down() || failed(true) {
+            HealthStatus::Down
+        } else if failed(false) {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Up
+        };
+
+        HealthReport {
+            status,
+            version: env!("CARGO_PKG_VERSION"),
+            checks: results.into_iter().collect(),
+        }
+    }
+}
+
+/// 执行单项检查，超时或失败时记录错误信息
+async fn run_check(check: &dyn HealthCheck) -> (String, CheckResult) {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(check.timeout(), check.check()).await;
+
+    let error = match outcome {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("检查超时（{}ms）", check.timeout().as_millis())),
+    };
+
+    if let Some(ref error) = error {
+        tracing::warn!(check = check.name(), %error, "健康检查失败");
+    }
+
+    let result = CheckResult {
+        status: if error.is_none() {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        },
+        critical: check.critical(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+    };
+
+    (check.name().to_string(), result)
+}
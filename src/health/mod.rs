@@ -0,0 +1,65 @@
+//! 健康检查模块
+//!
+//! 提供存活（liveness）、就绪（readiness）及详细健康状态接口，供Kubernetes等编排系统探测
+
+mod check;
+
+pub use check::{CheckResult, HealthCheck, HealthRegistry, HealthReport, HealthStatus};
+
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json},
+    IntoResponse, Response, Route,
+};
+use serde_json::json;
+
+/// 创建健康检查路由
+///
+/// - `/live`：进程存活即返回200，不检查依赖
+/// - `/ready`：关键依赖全部正常且未处于关闭流程时返回200，否则返回503
+/// - `/`：返回每个检查项的详细结果
+pub fn create_health_route() -> Route {
+    Route::new()
+        .at("/", get(health))
+        .at("/live", get(live))
+        .at("/ready", get(ready))
+}
+
+/// 存活检查
+#[handler]
+async fn live() -> Json<serde_json::Value> {
+    Json(json!({ "status": HealthStatus::Up }))
+}
+
+/// 就绪检查
+#[handler]
+async fn ready(registry: Data<&HealthRegistry>) -> Response {
+    // 关闭流程中无需再执行检查，直接返回不可用
+    if registry.is_shutting_down() {
+        return Json(json!({ "status": HealthStatus::Down, "reason": "shutting down" }))
+            .with_status(StatusCode::SERVICE_UNAVAILABLE)
+            .into_response();
+    }
+
+    let report = registry.run().await;
+    Json(json!({ "status": report.status }))
+        .with_status(status_code(report.status))
+        .into_response()
+}
+
+/// 详细健康状态
+#[handler]
+async fn health(registry: Data<&HealthRegistry>) -> Response {
+    let report = registry.run().await;
+    let code = status_code(report.status);
+    Json(report).with_status(code).into_response()
+}
+
+/// 健康状态对应的HTTP状态码，降级仍视为可用
+fn status_code(status: HealthStatus) -> StatusCode {
+    match status {
+        HealthStatus::Up | HealthStatus::Degraded => StatusCode::OK,
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
@@ -0,0 +1,119 @@
+//! 后台任务队列
+//!
+//! 将发送邮件、导出数据等耗时操作移出请求处理流程：任务以JSON参数持久化到数据库，
+//! 由工作池（`tasks::spawn_job_workers`）按并发上限领取执行，失败按指数退避重试，
+//! 超过最大次数标记为失败，可通过管理接口重试或取消。
+//!
+//! 每种任务定义一个参数类型（实现 `Job`）和一个处理器（实现 `JobHandler`），处理器在启动时注册到 `JobQueue`。
+//!
+//! 执行语义为至少一次：实例在执行中途崩溃时，任务在租约到期后会被重新领取，处理器需要保证重复执行无副作用
+
+mod queue;
+
+pub use queue::{ClaimedJob, JobFilter, JobOutcome, JobQueue, RunStats};
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::SqliteExecutor;
+
+/// 任务参数
+///
+/// 以JSON持久化，修改字段时需要兼容已入队的旧参数
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// 任务类型标识，全局唯一，入队后不可修改
+    const KIND: &'static str;
+
+    /// 该类型任务的最大执行次数，为空时使用配置的默认值
+    const MAX_ATTEMPTS: Option<u32> = None;
+}
+
+/// 任务处理器
+#[async_trait]
+pub trait JobHandler: Send + Sync + 'static {
+    /// 处理的任务类型
+    type Job: Job;
+
+    /// 执行任务，返回错误时按指数退避重试
+    async fn handle(&self, job: Self::Job, ctx: &JobContext) -> anyhow::Result<()>;
+}
+
+/// 执行中的任务信息
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    /// 任务ID
+    pub id: u64,
+    /// 第几次执行，从1开始
+    pub attempt: u32,
+    /// 最大执行次数
+    pub max_attempts: u32,
+}
+
+/// 入队选项
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    run_at: Option<DateTime<Utc>>,
+    max_attempts: Option<u32>,
+    unique_key: Option<String>,
+}
+
+impl JobOptions {
+    /// 立即执行，使用默认最大执行次数
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在指定时间之后执行
+    pub fn run_at(mut self, run_at: DateTime<Utc>) -> Self {
+        self.run_at = Some(run_at);
+        self
+    }
+
+    /// 延迟指定时间后执行
+    pub fn delay(self, delay: Duration) -> Self {
+        let delay = chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX);
+        self.run_at(Utc::now().checked_add_signed(delay).unwrap_or(DateTime::<Utc>::MAX_UTC))
+    }
+
+    /// 最大执行次数，优先于任务类型及配置的默认值
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    /// 唯一键：已存在同一键的等待或执行中的任务时不再入队
+    pub fn unique(mut self, key: impl Into<String>) -> Self {
+        self.unique_key = Some(key.into());
+        self
+    }
+}
+
+/// 任务入队
+///
+/// 可传入事务连接，与业务变更一同提交；返回任务ID，因唯一键重复未入队时返回 `None`
+pub async fn enqueue<'e, J: Job>(
+    executor: impl SqliteExecutor<'e>,
+    job: &J,
+    options: JobOptions,
+) -> Result<Option<u64>, sqlx::Error> {
+    let payload = serde_json::to_string(job).map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+    let now = Utc::now();
+    let created_at = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let id: Option<i64> = sqlx::query_scalar(
+        "INSERT OR IGNORE INTO jobs (kind, payload, max_attempts, run_at, unique_key, created_at, updated_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING id",
+    )
+    .bind(J::KIND)
+    .bind(payload)
+    .bind(options.max_attempts.or(J::MAX_ATTEMPTS))
+    .bind(options.run_at.unwrap_or(now).timestamp())
+    .bind(options.unique_key)
+    .bind(&created_at)
+    .bind(&created_at)
+    .fetch_optional(executor)
+    .await?;
+    Ok(id.map(|id| id as u64))
+}
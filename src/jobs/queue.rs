@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{future::join_all, FutureExt};
+
+use super::{enqueue, Job, JobContext, JobHandler, JobOptions};
+use crate::config::JobsConfig;
+use crate::db::DbPool;
+use crate::models::job::{JobRecord, JobStatus};
+use crate::services::ServiceError;
+
+/// 查询任务时返回的列
+const JOB_COLUMNS: &str = "id, kind, payload, status, attempts, max_attempts, run_at, unique_key, last_error, \
+     created_at, updated_at, finished_at";
+
+/// 一轮执行的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RunStats {
+    /// 执行成功的任务数
+    pub succeeded: u32,
+    /// 执行失败、等待重试的任务数
+    pub retried: u32,
+    /// 标记为失败的任务数
+    pub failed: u32,
+}
+
+impl RunStats {
+    /// 累计一个任务的执行结果
+    fn add(&mut self, outcome: JobOutcome) {
+        match outcome {
+            JobOutcome::Succeeded => self.succeeded += 1,
+            JobOutcome::Retried => self.retried += 1,
+            JobOutcome::Failed => self.failed += 1,
+        }
+    }
+}
+
+/// 单个任务的执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    /// 执行成功
+    Succeeded,
+    /// 执行失败，等待重试
+    Retried,
+    /// 超过最大执行次数或无法执行，标记为失败
+    Failed,
+}
+
+/// 任务列表过滤条件
+#[derive(Debug, Default, Clone)]
+pub struct JobFilter {
+    /// 任务状态
+    pub status: Option<JobStatus>,
+    /// 任务类型
+    pub kind: Option<String>,
+}
+
+/// 已领取、等待执行的任务
+#[derive(sqlx::FromRow)]
+pub struct ClaimedJob {
+    id: i64,
+    kind: String,
+    payload: String,
+    attempts: i64,
+    max_attempts: Option<i64>,
+}
+
+/// 数据库中的任务记录
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: i64,
+    kind: String,
+    payload: String,
+    status: String,
+    attempts: i64,
+    max_attempts: Option<i64>,
+    run_at: i64,
+    unique_key: Option<String>,
+    last_error: Option<String>,
+    created_at: String,
+    updated_at: String,
+    finished_at: Option<String>,
+}
+
+/// 执行失败的原因
+enum JobFailure {
+    /// 可重试的错误
+    Retryable(String),
+    /// 重试也不会成功的错误（未注册的任务类型、参数无法解析等），直接标记为失败
+    Permanent(String),
+}
+
+/// 类型擦除后的处理器，按任务类型查找
+#[async_trait]
+trait ErasedHandler: Send + Sync {
+    async fn handle(&self, payload: &str, ctx: &JobContext) -> Result<(), JobFailure>;
+}
+
+/// 将类型化的处理器包装为 `ErasedHandler`
+struct TypedHandler<H>(H);
+
+#[async_trait]
+impl<H: JobHandler> ErasedHandler for TypedHandler<H> {
+    async fn handle(&self, payload: &str, ctx: &JobContext) -> Result<(), JobFailure> {
+        let job = serde_json::from_str::<H::Job>(payload)
+            .map_err(|err| JobFailure::Permanent(format!("无法解析任务参数: {}", err)))?;
+        self.0
+            .handle(job, ctx)
+            .await
+            .map_err(|err| JobFailure::Retryable(format!("{:#}", err)))
+    }
+}
+
+/// 后台任务队列
+///
+/// 克隆开销很小，入队、执行与管理操作共用；处理器在启动时通过 `register` 注册
+#[derive(Clone)]
+pub struct JobQueue {
+    pool: DbPool,
+    config: JobsConfig,
+    handlers: Arc<RwLock<HashMap<&'static str, Arc<dyn ErasedHandler>>>>,
+}
+
+impl JobQueue {
+    /// 创建没有处理器的任务队列
+    pub fn new(pool: DbPool, config: JobsConfig) -> Self {
+        Self {
+            pool,
+            config,
+            handlers: Arc::default(),
+        }
+    }
+
+    /// 注册处理器，同一任务类型重复注册时以后者为准
+    pub fn register<H: JobHandler>(&self, handler: H) {
+        self.handlers
+            .write()
+            .expect("任务处理器锁已损坏")
+            .insert(<H::Job as Job>::KIND, Arc::new(TypedHandler(handler)));
+    }
+
+    /// 任务入队，返回任务ID；因唯一键重复未入队时返回 `None`
+    pub async fn enqueue<J: Job>(&self, job: &J, options: JobOptions) -> Result<Option<u64>, ServiceError> {
+        Ok(enqueue(&self.pool, job, options).await?)
+    }
+
+    /// 领取一批到期任务（不超过并发上限）并发执行，全部结束后返回
+    pub async fn run_once(&self) -> Result<RunStats, ServiceError> {
+        let jobs = self.claim(self.config.concurrency).await?;
+        let outcomes = join_all(jobs.into_iter().map(|job| self.execute(job))).await;
+
+        let mut stats = RunStats::default();
+        for outcome in outcomes {
+            stats.add(outcome?);
+        }
+        Ok(stats)
+    }
+
+    /// 领取最多 `limit` 个到期任务，包括租约已到期（执行中途崩溃）的任务
+    pub async fn claim(&self, limit: u32) -> Result<Vec<ClaimedJob>, ServiceError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let now = Utc::now();
+
+        let mut jobs = sqlx::query_as::<_, ClaimedJob>(
+            "UPDATE jobs SET status = 'running', attempts = attempts + 1, locked_until = ?1, updated_at = ?2 \
+             WHERE id IN (SELECT id FROM jobs \
+                          WHERE (status = 'scheduled' AND run_at <= ?3) OR (status = 'running' AND locked_until <= ?3) \
+                          ORDER BY run_at, id LIMIT ?4) \
+             RETURNING id, kind, payload, attempts, max_attempts",
+        )
+        .bind(now.timestamp() + self.config.lease_secs as i64)
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(now.timestamp())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        jobs.sort_by_key(|job| job.id);
+        Ok(jobs)
+    }
+
+    /// 执行已领取的任务并记录结果，处理器panic视为执行失败
+    pub async fn execute(&self, job: ClaimedJob) -> Result<JobOutcome, ServiceError> {
+        let ctx = JobContext {
+            id: job.id as u64,
+            attempt: job.attempts as u32,
+            max_attempts: self.max_attempts(job.max_attempts),
+        };
+        let handler = self.handlers.read().expect("任务处理器锁已损坏").get(job.kind.as_str()).cloned();
+
+        let result = match handler {
+            // 上次执行中途崩溃且已用完次数
+            _ if ctx.attempt > ctx.max_attempts => {
+                Err(JobFailure::Permanent("执行中断且已达到最大执行次数".to_string()))
+            }
+            None => Err(JobFailure::Permanent(format!("未注册的任务类型: {}", job.kind))),
+            Some(handler) => AssertUnwindSafe(handler.handle(&job.payload, &ctx))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err(JobFailure::Retryable("处理器panic".to_string()))),
+        };
+
+        let now = Utc::now();
+        let (status, run_at, error, outcome) = match result {
+            Ok(()) => (JobStatus::Succeeded, None, None, JobOutcome::Succeeded),
+            Err(JobFailure::Retryable(error)) if ctx.attempt < ctx.max_attempts => {
+                let delay = self.config.retry_delay(ctx.attempt);
+                tracing::warn!("任务 {}（{}）第 {} 次执行失败，{:?} 后重试: {}", job.id, job.kind, ctx.attempt, delay, error);
+                let run_at = now.timestamp() + delay.as_secs() as i64;
+                (JobStatus::Scheduled, Some(run_at), Some(error), JobOutcome::Retried)
+            }
+            Err(JobFailure::Retryable(error) | JobFailure::Permanent(error)) => {
+                tracing::error!("任务 {}（{}）第 {} 次执行失败，不再重试: {}", job.id, job.kind, ctx.attempt, error);
+                (JobStatus::Failed, None, Some(error), JobOutcome::Failed)
+            }
+        };
+
+        let finished_at = (status != JobStatus::Scheduled).then(|| now.to_rfc3339_opts(SecondsFormat::Secs, true));
+        // 只更新本次领取的任务，租约到期后被其他工作者重新领取的不受影响
+        sqlx::query(
+            "UPDATE jobs SET status = ?, run_at = COALESCE(?, run_at), locked_until = NULL, last_error = ?, \
+             finished_at = ?, updated_at = ? WHERE id = ? AND status = 'running' AND attempts = ?",
+        )
+        .bind(status.as_str())
+        .bind(run_at)
+        .bind(error)
+        .bind(finished_at)
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(job.id)
+        .bind(job.attempts)
+        .execute(&self.pool)
+        .await?;
+        Ok(outcome)
+    }
+
+    /// 分页查询任务，按创建时间倒序，返回当前页数据及总记录数
+    pub async fn list(&self, filter: &JobFilter, page: u32, page_size: u32) -> Result<(Vec<JobRecord>, u64), ServiceError> {
+        if page == 0 || page_size == 0 {
+            return Err(ServiceError::Validation("页码和每页记录数必须大于0".to_string()));
+        }
+
+        let status = filter.status.map(|status| status.as_str());
+        let condition = "(?1 IS NULL OR status = ?1) AND (?2 IS NULL OR kind = ?2)";
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM jobs WHERE {}", condition))
+            .bind(status)
+            .bind(&filter.kind)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let jobs = sqlx::query_as::<_, JobRow>(&format!(
+            "SELECT {} FROM jobs WHERE {} ORDER BY id DESC LIMIT ?3 OFFSET ?4",
+            JOB_COLUMNS, condition
+        ))
+        .bind(status)
+        .bind(&filter.kind)
+        .bind(page_size as i64)
+        .bind((page as i64 - 1) * page_size as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| self.record(row))
+        .collect();
+
+        Ok((jobs, total as u64))
+    }
+
+    /// 根据ID获取任务
+    pub async fn get(&self, id: u64) -> Result<JobRecord, ServiceError> {
+        sqlx::query_as::<_, JobRow>(&format!("SELECT {} FROM jobs WHERE id = ?", JOB_COLUMNS))
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|row| self.record(row))
+            .ok_or_else(|| not_found(id))
+    }
+
+    /// 重新执行失败或已取消的任务，执行次数从零开始
+    pub async fn retry(&self, id: u64) -> Result<JobRecord, ServiceError> {
+        let now = Utc::now();
+        let row = sqlx::query_as::<_, JobRow>(&format!(
+            "UPDATE jobs SET status = 'scheduled', attempts = 0, run_at = ?, finished_at = NULL, updated_at = ? \
+             WHERE id = ? AND status IN ('failed', 'cancelled') RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(now.timestamp())
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|err| match ServiceError::from(err) {
+            ServiceError::Conflict(_) => ServiceError::Conflict("已存在相同唯一键的等待或执行中的任务".to_string()),
+            err => err,
+        })?;
+
+        match row {
+            Some(row) => Ok(self.record(row)),
+            None => Err(self.unexpected_status(id, "只能重试失败或已取消的任务").await),
+        }
+    }
+
+    /// 取消等待执行的任务，执行中的任务无法取消
+    pub async fn cancel(&self, id: u64) -> Result<JobRecord, ServiceError> {
+        let now = now();
+        let row = sqlx::query_as::<_, JobRow>(&format!(
+            "UPDATE jobs SET status = 'cancelled', finished_at = ?, updated_at = ? \
+             WHERE id = ? AND status = 'scheduled' RETURNING {}",
+            JOB_COLUMNS
+        ))
+        .bind(&now)
+        .bind(&now)
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(self.record(row)),
+            None => Err(self.unexpected_status(id, "只能取消等待执行的任务").await),
+        }
+    }
+
+    /// 状态变更未命中时区分任务不存在与状态不符
+    async fn unexpected_status(&self, id: u64, msg: &str) -> ServiceError {
+        match self.get(id).await {
+            Ok(job) => ServiceError::Conflict(format!("{}，任务当前状态为 {}", msg, job.status.as_str())),
+            Err(err) => err,
+        }
+    }
+
+    /// 实际生效的最大执行次数
+    fn max_attempts(&self, max_attempts: Option<i64>) -> u32 {
+        max_attempts.map(|value| value as u32).unwrap_or(self.config.max_attempts).max(1)
+    }
+
+    /// 数据库记录转为任务模型
+    fn record(&self, row: JobRow) -> JobRecord {
+        JobRecord {
+            id: row.id as u64,
+            kind: row.kind,
+            // 无法解析的参数按原文返回
+            payload: serde_json::from_str(&row.payload).unwrap_or(serde_json::Value::String(row.payload)),
+            status: JobStatus::parse(&row.status),
+            attempts: row.attempts as u32,
+            max_attempts: self.max_attempts(row.max_attempts),
+            run_at: DateTime::<Utc>::from_timestamp(row.run_at, 0)
+                .unwrap_or_default()
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            unique_key: row.unique_key,
+            last_error: row.last_error,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            finished_at: row.finished_at,
+        }
+    }
+}
+
+/// 任务不存在
+fn not_found(id: u64) -> ServiceError {
+    ServiceError::NotFound(format!("Job with id {} not found", id))
+}
+
+/// 当前时间（ISO 8601格式）
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
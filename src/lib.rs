@@ -2,13 +2,32 @@
 //! 
 //! 这个文件是整个应用程序的入口点，负责初始化日志、创建API服务、配置路由和启动HTTP服务器。
 
+pub mod accounts;
 pub mod api;
+pub mod app;
+pub mod audit;
+pub mod auth;
+pub mod cli;
+pub mod db;
+pub mod events;
+pub mod graphql;
+pub mod jobs;
+pub mod mailer;
 pub mod models;
+pub mod oidc;
+pub mod scheduler;
 pub mod services;
+pub mod sessions;
 pub mod utils;
 pub mod config;
 pub mod middlewares;
+pub mod health;
+pub mod server;
+pub mod shutdown;
+pub mod tasks;
+pub mod tls;
+pub mod webhooks;
 
 // 重新导出一些常用模块，方便其他模块引用
-pub use api::create_api_service;
+pub use api::{create_api_route, create_docs_route};
 pub use utils::response::{ApiResponse, success_json, error_json};
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{MailTemplate, Mailer};
+use crate::jobs::{Job, JobContext, JobHandler};
+
+/// 邮件发送任务
+///
+/// 保存模板名称及参数，执行时才渲染，模板修改后尚未发送的邮件也使用新模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendEmail {
+    /// 收件人
+    pub to: String,
+    /// 收件人的语言，为空时使用默认语言
+    pub locale: Option<String>,
+    /// 模板名称
+    pub template: String,
+    /// 模板参数
+    pub context: Value,
+}
+
+impl SendEmail {
+    /// 使用指定模板发送给 `to`
+    pub fn new<T: MailTemplate>(to: impl Into<String>, locale: Option<String>, template: &T) -> serde_json::Result<Self> {
+        Ok(Self {
+            to: to.into(),
+            locale,
+            template: T::NAME.to_string(),
+            context: serde_json::to_value(template)?,
+        })
+    }
+}
+
+impl Job for SendEmail {
+    const KIND: &'static str = "send_email";
+}
+
+/// 邮件发送任务的处理器，发送失败时按任务队列的退避策略重试
+pub struct SendEmailHandler {
+    mailer: Mailer,
+}
+
+impl SendEmailHandler {
+    /// 使用指定的邮件发送器
+    pub fn new(mailer: Mailer) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl JobHandler for SendEmailHandler {
+    type Job = SendEmail;
+
+    async fn handle(&self, job: SendEmail, _ctx: &JobContext) -> anyhow::Result<()> {
+        let rendered = self
+            .mailer
+            .templates()
+            .render_value(&job.template, job.locale.as_deref(), &job.context)?;
+        self.mailer.send_rendered(&job.to, rendered).await
+    }
+}
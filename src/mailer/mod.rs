@@ -0,0 +1,112 @@
+//! 邮件发送
+//!
+//! 邮件由模板（`templates/mail/{语言}/{名称}.subject|html|txt`，编译时内嵌）渲染出标题、HTML及纯文本正文，
+//! 通过配置的发送方式（SMTP、本地mbox文件或内存）发出。业务流程中不直接发送，而是将 `SendEmail`
+//! 任务放入后台任务队列，由 `SendEmailHandler` 渲染并发送，失败时按任务队列的策略重试。
+//!
+//! 开发环境可通过 `/dev/mail` 以示例参数预览各模板的渲染结果
+
+mod job;
+mod preview;
+mod templates;
+mod transport;
+mod welcome;
+
+pub use job::{SendEmail, SendEmailHandler};
+pub use preview::create_preview_route;
+pub use templates::{MailTemplate, RenderedMail, ResetPassword, Templates, VerifyEmail, Welcome, LOCALES};
+pub use transport::{FileTransport, MemoryTransport, SmtpTransport, Transport};
+pub use welcome::WelcomeEmail;
+
+use std::sync::Arc;
+
+use lettre::{message::MultiPart, Message};
+
+use crate::config::{MailTransport, MailerConfig};
+
+/// 一封待发送的邮件
+#[derive(Debug, Clone)]
+pub struct Email {
+    /// 发件人
+    pub from: String,
+    /// 收件人
+    pub to: String,
+    /// 标题
+    pub subject: String,
+    /// HTML正文
+    pub html: String,
+    /// 纯文本正文，不支持HTML的客户端显示
+    pub text: String,
+}
+
+impl Email {
+    /// 转为包含纯文本及HTML两种正文的邮件，地址无效时返回错误
+    pub fn to_message(&self) -> anyhow::Result<Message> {
+        Ok(Message::builder()
+            .from(self.from.parse()?)
+            .to(self.to.parse()?)
+            .subject(&self.subject)
+            .multipart(MultiPart::alternative_plain_html(self.text.clone(), self.html.clone()))?)
+    }
+}
+
+/// 邮件发送器
+///
+/// 克隆开销很小，可在各组件间共享
+#[derive(Clone)]
+pub struct Mailer {
+    transport: Arc<dyn Transport>,
+    templates: Templates,
+    from: String,
+    /// 使用内存发送方式时保留一份，便于测试中检查已发送的邮件
+    memory: Option<MemoryTransport>,
+}
+
+impl Mailer {
+    /// 按配置创建发送方式并加载模板
+    pub fn new(config: &MailerConfig) -> anyhow::Result<Self> {
+        let (transport, memory): (Arc<dyn Transport>, _) = match config.transport {
+            MailTransport::Smtp => (Arc::new(SmtpTransport::new(&config.smtp)?), None),
+            MailTransport::File => (Arc::new(FileTransport::new(&config.file_path)), None),
+            MailTransport::Memory => {
+                let memory = MemoryTransport::new();
+                (Arc::new(memory.clone()), Some(memory))
+            }
+        };
+
+        Ok(Self {
+            transport,
+            templates: Templates::new(config)?,
+            from: config.from.clone(),
+            memory,
+        })
+    }
+
+    /// 邮件模板
+    pub fn templates(&self) -> &Templates {
+        &self.templates
+    }
+
+    /// 使用内存发送方式时返回它，可读取已发送的邮件
+    pub fn memory(&self) -> Option<&MemoryTransport> {
+        self.memory.as_ref()
+    }
+
+    /// 渲染模板并立即发送，业务流程中应改为入队 `SendEmail` 任务
+    pub async fn send<T: MailTemplate>(&self, to: &str, locale: Option<&str>, template: &T) -> anyhow::Result<()> {
+        let rendered = self.templates.render(locale, template)?;
+        self.send_rendered(to, rendered).await
+    }
+
+    /// 发送已渲染的邮件
+    pub async fn send_rendered(&self, to: &str, rendered: RenderedMail) -> anyhow::Result<()> {
+        let email = Email {
+            from: self.from.clone(),
+            to: to.to_string(),
+            subject: rendered.subject,
+            html: rendered.html,
+            text: rendered.text,
+        };
+        self.transport.send(&email).await
+    }
+}
@@ -0,0 +1,60 @@
+use poem::{
+    get, handler,
+    http::StatusCode,
+    web::{Data, Html, Json, Path, Query},
+    IntoResponse, Response, Route,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::templates::{previews, LOCALES};
+use super::Mailer;
+
+/// 创建邮件模板预览路由，仅用于开发环境
+///
+/// - `/`：列出所有模板及支持的语言
+/// - `/:template?locale=en&format=html`：以示例参数渲染模板，`format` 可选 `html`（默认）、`text`、`json`
+pub fn create_preview_route() -> Route {
+    Route::new().at("/", get(list)).at("/:template", get(preview))
+}
+
+/// 预览参数
+#[derive(Deserialize)]
+struct PreviewParams {
+    /// 语言，为空时使用默认语言
+    locale: Option<String>,
+    /// 输出格式
+    format: Option<String>,
+}
+
+/// 模板列表
+#[handler]
+async fn list() -> Json<serde_json::Value> {
+    let templates: Vec<_> = previews().into_iter().map(|(name, _)| name).collect();
+    Json(json!({ "templates": templates, "locales": LOCALES }))
+}
+
+/// 以示例参数渲染模板
+#[handler]
+async fn preview(mailer: Data<&Mailer>, Path(name): Path<String>, Query(params): Query<PreviewParams>) -> Response {
+    let Some((_, example)) = previews().into_iter().find(|(template, _)| *template == name) else {
+        return format!("邮件模板 {} 不存在", name)
+            .with_status(StatusCode::NOT_FOUND)
+            .into_response();
+    };
+
+    let rendered = match mailer.templates().render_value(&name, params.locale.as_deref(), &example) {
+        Ok(rendered) => rendered,
+        Err(err) => {
+            return format!("{:#}", err)
+                .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+                .into_response();
+        }
+    };
+
+    match params.format.as_deref() {
+        Some("text") => format!("Subject: {}\n\n{}", rendered.subject, rendered.text).into_response(),
+        Some("json") => Json(rendered).into_response(),
+        _ => Html(rendered.html).into_response(),
+    }
+}
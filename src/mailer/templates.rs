@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use minijinja::Environment;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::config::MailerConfig;
+
+/// 内嵌的模板文件（模板名，内容），模板名为相对 `templates/mail/` 的路径
+macro_rules! embed {
+    ($($path:literal),* $(,)?) => {
+        &[$(($path, include_str!(concat!("../../templates/mail/", $path)))),*]
+    };
+}
+
+const FILES: &[(&str, &str)] = embed![
+    "base.html",
+    "zh-CN/welcome.subject",
+    "zh-CN/welcome.html",
+    "zh-CN/welcome.txt",
+    "en/welcome.subject",
+    "en/welcome.html",
+    "en/welcome.txt",
+    "zh-CN/verify_email.subject",
+    "zh-CN/verify_email.html",
+    "zh-CN/verify_email.txt",
+    "en/verify_email.subject",
+    "en/verify_email.html",
+    "en/verify_email.txt",
+    "zh-CN/reset_password.subject",
+    "zh-CN/reset_password.html",
+    "zh-CN/reset_password.txt",
+    "en/reset_password.subject",
+    "en/reset_password.html",
+    "en/reset_password.txt",
+];
+
+/// 支持的语言，每个模板在各语言下都有标题（`.subject`）、HTML（`.html`）及纯文本（`.txt`）三个文件
+pub const LOCALES: &[&str] = &["zh-CN", "en"];
+
+/// 邮件模板参数
+///
+/// 以JSON保存在发送任务中，修改字段时需要兼容已入队的旧参数
+pub trait MailTemplate: Serialize {
+    /// 模板名称，对应 `templates/mail/{语言}/{名称}.*`
+    const NAME: &'static str;
+
+    /// 示例参数，用于模板预览
+    fn example() -> Self;
+}
+
+/// 欢迎邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Welcome {
+    /// 用户名
+    pub username: String,
+}
+
+impl MailTemplate for Welcome {
+    const NAME: &'static str = "welcome";
+
+    fn example() -> Self {
+        Self {
+            username: "alice".to_string(),
+        }
+    }
+}
+
+/// 邮箱验证邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyEmail {
+    /// 用户名
+    pub username: String,
+    /// 验证链接
+    pub url: String,
+    /// 链接有效期（小时）
+    pub expires_hours: u64,
+}
+
+impl MailTemplate for VerifyEmail {
+    const NAME: &'static str = "verify_email";
+
+    fn example() -> Self {
+        Self {
+            username: "alice".to_string(),
+            url: "http://localhost:3000/verify-email?token=42.1735804800.9c1f0e4b.5d2f".to_string(),
+            expires_hours: 48,
+        }
+    }
+}
+
+/// 密码重置邮件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResetPassword {
+    /// 用户名
+    pub username: String,
+    /// 重置链接
+    pub url: String,
+    /// 链接有效期（分钟）
+    pub expires_minutes: u64,
+}
+
+impl MailTemplate for ResetPassword {
+    const NAME: &'static str = "reset_password";
+
+    fn example() -> Self {
+        Self {
+            username: "alice".to_string(),
+            url: "http://localhost:3000/reset-password?token=42.1735736400.3b8e1d6a.a7c4".to_string(),
+            expires_minutes: 60,
+        }
+    }
+}
+
+/// 所有模板的名称及示例参数，用于模板预览
+pub fn previews() -> Vec<(&'static str, Value)> {
+    vec![preview::<Welcome>(), preview::<VerifyEmail>(), preview::<ResetPassword>()]
+}
+
+fn preview<T: MailTemplate>() -> (&'static str, Value) {
+    let example = serde_json::to_value(T::example()).expect("邮件模板示例参数无法序列化");
+    (T::NAME, example)
+}
+
+/// 渲染后的邮件内容
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedMail {
+    /// 实际使用的语言
+    pub locale: &'static str,
+    /// 邮件标题
+    pub subject: String,
+    /// HTML正文
+    pub html: String,
+    /// 纯文本正文
+    pub text: String,
+}
+
+/// 邮件模板
+///
+/// HTML模板自动转义参数，标题及纯文本模板不转义；所有模板可使用 `product_name`、`locale`，
+/// HTML及纯文本模板还可使用渲染后的 `subject`
+#[derive(Clone)]
+pub struct Templates {
+    env: Arc<Environment<'static>>,
+    default_locale: &'static str,
+}
+
+impl Templates {
+    /// 加载内嵌的模板，模板语法错误或默认语言不受支持时返回错误
+    pub fn new(config: &MailerConfig) -> anyhow::Result<Self> {
+        let default_locale = LOCALES
+            .iter()
+            .copied()
+            .find(|locale| *locale == config.default_locale)
+            .with_context(|| format!("不支持的邮件默认语言 {}，可选 {:?}", config.default_locale, LOCALES))?;
+
+        let mut env = Environment::new();
+        for (name, source) in FILES {
+            env.add_template(name, source)
+                .with_context(|| format!("邮件模板 {} 有误", name))?;
+        }
+        env.add_global("product_name", config.product_name.clone());
+
+        Ok(Self {
+            env: Arc::new(env),
+            default_locale,
+        })
+    }
+
+    /// 选择语言：完全匹配，其次匹配语言部分（例如 `en-US` 使用 `en`），都没有时使用默认语言
+    pub fn resolve_locale(&self, locale: Option<&str>) -> &'static str {
+        let Some(locale) = locale else {
+            return self.default_locale;
+        };
+        let language = |value: &str| value.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+
+        LOCALES
+            .iter()
+            .copied()
+            .find(|supported| supported.eq_ignore_ascii_case(locale))
+            .or_else(|| LOCALES.iter().copied().find(|supported| language(supported) == language(locale)))
+            .unwrap_or(self.default_locale)
+    }
+
+    /// 渲染模板
+    pub fn render<T: MailTemplate>(&self, locale: Option<&str>, template: &T) -> anyhow::Result<RenderedMail> {
+        self.render_value(T::NAME, locale, &serde_json::to_value(template)?)
+    }
+
+    /// 按名称渲染模板，参数必须是JSON对象
+    pub fn render_value(&self, name: &str, locale: Option<&str>, context: &Value) -> anyhow::Result<RenderedMail> {
+        let locale = self.resolve_locale(locale);
+        let Value::Object(context) = context else {
+            anyhow::bail!("邮件模板 {} 的参数必须是对象", name);
+        };
+        let mut context: Map<String, Value> = context.clone();
+        context.insert("locale".to_string(), Value::from(locale));
+
+        let subject = self.render_file(name, locale, "subject", &context)?.trim().to_string();
+        context.insert("subject".to_string(), Value::from(subject.as_str()));
+
+        Ok(RenderedMail {
+            locale,
+            html: self.render_file(name, locale, "html", &context)?,
+            text: self.render_file(name, locale, "txt", &context)?,
+            subject,
+        })
+    }
+
+    fn render_file(&self, name: &str, locale: &str, ext: &str, context: &Map<String, Value>) -> anyhow::Result<String> {
+        let file = format!("{}/{}.{}", locale, name, ext);
+        let template = self
+            .env
+            .get_template(&file)
+            .with_context(|| format!("邮件模板 {} 不存在", file))?;
+        template
+            .render(context)
+            .with_context(|| format!("渲染邮件模板 {} 失败", file))
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::{
+    transport::smtp::authentication::Credentials, AsyncSmtpTransport, AsyncTransport as _, Tokio1Executor,
+};
+use tokio::io::AsyncWriteExt;
+
+use super::Email;
+use crate::config::{SmtpConfig, SmtpSecurity};
+
+/// 邮件发送方式
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// 发送邮件，返回错误时由调用方决定是否重试
+    async fn send(&self, email: &Email) -> anyhow::Result<()>;
+}
+
+/// 通过SMTP服务器发送
+pub struct SmtpTransport {
+    inner: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpTransport {
+    /// 按配置创建，连接在首次发送时建立
+    pub fn new(config: &SmtpConfig) -> anyhow::Result<Self> {
+        let builder = match config.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+        let builder = match config.port {
+            Some(port) => builder.port(port),
+            None => builder,
+        };
+        let builder = match (&config.username, &config.password) {
+            (Some(username), password) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone().unwrap_or_default()))
+            }
+            (None, _) => builder,
+        };
+
+        Ok(Self {
+            inner: builder.timeout(Some(config.timeout())).build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for SmtpTransport {
+    async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        self.inner.send(email.to_message()?).await?;
+        Ok(())
+    }
+}
+
+/// 追加写入本地mbox文件，可用邮件客户端打开查看，用于开发环境
+pub struct FileTransport {
+    path: PathBuf,
+    /// 串行写入，避免并发发送时内容交错
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl FileTransport {
+    /// 写入指定文件，文件不存在时自动创建
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for FileTransport {
+    async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        let message = String::from_utf8_lossy(&email.to_message()?.formatted()).replace("\r\n", "\n");
+
+        // mbox格式：每封邮件以 "From " 行开始，正文中以 "From " 开头的行需要转义
+        let mut entry = format!("From MAILER-DAEMON {}\n", Utc::now().format("%a %b %e %H:%M:%S %Y"));
+        for line in message.lines() {
+            if line.starts_with("From ") {
+                entry.push('>');
+            }
+            entry.push_str(line);
+            entry.push('\n');
+        }
+        entry.push('\n');
+
+        let _guard = self.lock.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(entry.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// 保存在内存中，用于测试中检查发送的邮件
+#[derive(Clone, Default)]
+pub struct MemoryTransport {
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+impl MemoryTransport {
+    /// 创建空的内存发送方式
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已发送的邮件，按发送顺序
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().expect("邮件记录锁已损坏").clone()
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, email: &Email) -> anyhow::Result<()> {
+        // 与其他发送方式一致，地址无效时返回错误
+        email.to_message()?;
+        self.sent.lock().expect("邮件记录锁已损坏").push(email.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+
+use super::{SendEmail, Welcome};
+use crate::events::{DomainEvent, EventEnvelope, EventHandler};
+use crate::jobs::{JobOptions, JobQueue};
+
+/// 新用户创建后发送欢迎邮件
+///
+/// 只负责将发送任务入队，事件重复投递时以用户ID作为唯一键，避免同时存在多个发送任务
+pub struct WelcomeEmail {
+    jobs: JobQueue,
+}
+
+impl WelcomeEmail {
+    /// 发送任务放入指定的任务队列
+    pub fn new(jobs: JobQueue) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl EventHandler for WelcomeEmail {
+    fn name(&self) -> &str {
+        "welcome-email"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> anyhow::Result<()> {
+        let DomainEvent::UserCreated(created) = &envelope.event else {
+            return Ok(());
+        };
+        let user = &created.user;
+
+        let job = SendEmail::new(
+            &user.email,
+            None,
+            &Welcome {
+                username: user.username.clone(),
+            },
+        )?;
+        let options = match user.id {
+            Some(id) => JobOptions::new().unique(format!("welcome:{}", id)),
+            None => JobOptions::new(),
+        };
+        self.jobs.enqueue(&job, options).await?;
+        Ok(())
+    }
+}
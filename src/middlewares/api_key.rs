@@ -0,0 +1,130 @@
+//! API密钥认证中间件
+//!
+//! 从 `X-API-Key` 或 `Authorization: ApiKey <密钥>` 请求头读取密钥：
+//!
+//! - 未携带密钥：按匿名调用方处理
+//! - 密钥不存在、已过期或已吊销：返回401
+//! - 密钥有效：将 `Principal::ApiKey` 写入请求扩展；按请求方法检查授权范围时，
+//!   GET、HEAD、OPTIONS请求需要 `read`，其他请求需要 `write`，不满足时返回403
+
+use poem::{
+    http::{header, HeaderMap, Method, StatusCode},
+    web::Json,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+use crate::models::api_key::ApiKeyScope;
+use crate::services::ApiKeyService;
+use crate::utils::response::{ApiResponse, EmptyResponse};
+
+/// API密钥请求头
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// `Authorization` 请求头中API密钥的认证方案
+pub const API_KEY_SCHEME: &str = "ApiKey";
+
+/// API密钥认证中间件
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    service: ApiKeyService,
+    scope_by_method: bool,
+}
+
+impl ApiKeyAuth {
+    /// 创建认证中间件，默认按请求方法检查授权范围
+    pub fn new(service: ApiKeyService) -> Self {
+        Self {
+            service,
+            scope_by_method: true,
+        }
+    }
+
+    /// 是否按请求方法检查授权范围；GraphQL的查询和变更都可以使用POST，由GraphQL端点自行检查
+    pub fn scope_by_method(mut self, enabled: bool) -> Self {
+        self.scope_by_method = enabled;
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ApiKeyAuth {
+    type Output = ApiKeyAuthEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ApiKeyAuthEndpoint {
+            inner: ep,
+            service: self.service.clone(),
+            scope_by_method: self.scope_by_method,
+        }
+    }
+}
+
+/// `ApiKeyAuth` 中间件生成的端点
+pub struct ApiKeyAuthEndpoint<E> {
+    inner: E,
+    service: ApiKeyService,
+    scope_by_method: bool,
+}
+
+impl<E: Endpoint> Endpoint for ApiKeyAuthEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let Some(key) = extract_key(req.headers()) else {
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        };
+
+        let principal = match self.service.authenticate(&key).await {
+            Ok(Some(principal)) => principal,
+            Ok(None) => {
+                return Ok(error_response(StatusCode::UNAUTHORIZED, "API密钥无效、已过期或已吊销".to_string())
+                    .with_header(header::WWW_AUTHENTICATE, API_KEY_SCHEME)
+                    .into_response());
+            }
+            Err(err) => {
+                let status = StatusCode::from_u16(err.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                return Ok(error_response(status, err.to_string()));
+            }
+        };
+
+        if self.scope_by_method {
+            let scope = required_scope(req.method());
+            if !principal.has_scope(scope) {
+                return Ok(error_response(
+                    StatusCode::FORBIDDEN,
+                    format!("API密钥缺少 {} 授权范围", scope.as_str()),
+                ));
+            }
+        }
+
+        req.extensions_mut().insert(principal);
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+/// 读取请求中的密钥，两个请求头都没有时返回 `None`；`Authorization` 使用其他认证方案时忽略
+pub fn extract_key(headers: &HeaderMap) -> Option<String> {
+    if let Some(value) = headers.get(API_KEY_HEADER) {
+        return Some(value.to_str().unwrap_or_default().trim().to_string());
+    }
+
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, key) = value.trim().split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case(API_KEY_SCHEME)
+        .then(|| key.trim().to_string())
+}
+
+/// 按请求方法需要的授权范围
+fn required_scope(method: &Method) -> ApiKeyScope {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        ApiKeyScope::Read
+    } else {
+        ApiKeyScope::Write
+    }
+}
+
+fn error_response(status: StatusCode, msg: String) -> Response {
+    Json(ApiResponse::<EmptyResponse>::error(status.as_u16(), msg))
+        .with_status(status)
+        .into_response()
+}
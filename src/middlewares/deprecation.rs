@@ -0,0 +1,99 @@
+//! 接口弃用响应头
+//!
+//! 按 RFC 9745（`Deprecation`）与 RFC 8594（`Sunset`）为已弃用的接口添加响应头，
+//! 提醒客户端尽快迁移到新版本
+
+use chrono::{DateTime, Utc};
+use poem::{
+    http::{header, HeaderValue},
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+/// 接口弃用中间件
+///
+/// 通常通过 `#[oai(deprecated, transform = "...")]` 应用到单个接口：
+///
+/// ```ignore
+/// fn deprecate_list_users(ep: impl Endpoint) -> impl Endpoint {
+///     ep.with(Deprecation::new().sunset(sunset_at).successor("/api/v2/users"))
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Deprecation {
+    deprecated_at: Option<DateTime<Utc>>,
+    sunset: Option<DateTime<Utc>>,
+    successor: Option<String>,
+}
+
+impl Deprecation {
+    /// 创建弃用中间件
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 弃用生效时间，未设置时 `Deprecation` 头的值为 `true`
+    pub fn deprecated_at(mut self, at: DateTime<Utc>) -> Self {
+        self.deprecated_at = Some(at);
+        self
+    }
+
+    /// 接口下线时间
+    pub fn sunset(mut self, at: DateTime<Utc>) -> Self {
+        self.sunset = Some(at);
+        self
+    }
+
+    /// 替代接口的地址，以 `Link: <...>; rel="successor-version"` 返回
+    pub fn successor(mut self, uri: impl Into<String>) -> Self {
+        self.successor = Some(uri.into());
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Deprecation {
+    type Output = DeprecationEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let deprecation = match self.deprecated_at {
+            Some(at) => format!("@{}", at.timestamp()),
+            None => "true".to_string(),
+        };
+
+        let mut headers = vec![(header::HeaderName::from_static("deprecation"), deprecation)];
+        if let Some(sunset) = self.sunset {
+            headers.push((
+                header::HeaderName::from_static("sunset"),
+                sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            ));
+        }
+        if let Some(successor) = &self.successor {
+            headers.push((header::LINK, format!("<{}>; rel=\"successor-version\"", successor)));
+        }
+
+        DeprecationEndpoint {
+            inner: ep,
+            headers: headers
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, HeaderValue::from_str(&value).ok()?)))
+                .collect(),
+        }
+    }
+}
+
+/// `Deprecation` 中间件生成的端点
+pub struct DeprecationEndpoint<E> {
+    inner: E,
+    headers: Vec<(header::HeaderName, HeaderValue)>,
+}
+
+impl<E: Endpoint> Endpoint for DeprecationEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let mut resp = self.inner.call(req).await?.into_response();
+        for (name, value) in &self.headers {
+            resp.headers_mut().append(name.clone(), value.clone());
+        }
+        Ok(resp)
+    }
+}
@@ -0,0 +1,15 @@
+//! HSTS（HTTP Strict Transport Security）响应头
+
+use poem::{http::header, middleware::SetHeader};
+
+use crate::config::TlsConfig;
+
+/// 根据TLS配置创建设置 `Strict-Transport-Security` 响应头的中间件
+pub fn hsts(config: &TlsConfig) -> SetHeader {
+    let mut value = format!("max-age={}", config.hsts_max_age_secs);
+    if config.hsts_include_subdomains {
+        value.push_str("; includeSubDomains");
+    }
+
+    SetHeader::new().overriding(header::STRICT_TRANSPORT_SECURITY, value)
+}
@@ -0,0 +1,71 @@
+//! HTTP到HTTPS重定向中间件
+
+use poem::{
+    http::{
+        header,
+        uri::{Authority, Scheme},
+    },
+    web::Redirect,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+/// 将通过明文HTTP到达的请求永久重定向（308）到HTTPS
+///
+/// 适用于与TLS监听器组合的HTTP监听器，HTTPS请求会原样交给内部端点处理
+pub struct HttpsRedirect {
+    https_port: u16,
+}
+
+impl HttpsRedirect {
+    /// 创建重定向中间件，`https_port` 为HTTPS监听端口
+    pub fn new(https_port: u16) -> Self {
+        Self { https_port }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for HttpsRedirect {
+    type Output = HttpsRedirectEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        HttpsRedirectEndpoint {
+            inner: ep,
+            https_port: self.https_port,
+        }
+    }
+}
+
+/// `HttpsRedirect` 中间件生成的端点
+pub struct HttpsRedirectEndpoint<E> {
+    inner: E,
+    https_port: u16,
+}
+
+impl<E: Endpoint> Endpoint for HttpsRedirectEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if *req.scheme() != Scheme::HTTP {
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        }
+
+        // 优先使用Host请求头，去掉其中的端口后拼接HTTPS端口；IPv6地址保留方括号，例如 `[::1]`
+        let authority = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Authority>().ok())
+            .or_else(|| req.uri().authority().cloned());
+        let host = authority.as_ref().map_or("localhost", Authority::host);
+
+        let authority = match self.https_port {
+            443 => host.to_string(),
+            port => format!("{}:{}", host, port),
+        };
+        let path = req
+            .uri()
+            .path_and_query()
+            .map_or("/", |path| path.as_str());
+
+        Ok(Redirect::permanent(format!("https://{}{}", authority, path)).into_response())
+    }
+}
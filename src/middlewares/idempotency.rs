@@ -0,0 +1,345 @@
+//! 幂等键中间件
+//!
+//! 客户端在创建用户（`POST /users`）的请求中携带 `Idempotency-Key` 请求头，超时后使用相同的键重试时：
+//!
+//! - 首次请求已完成：直接重放保存的完整响应（状态码、响应头、响应体），并附带 `Idempotent-Replayed: true`
+//! - 首次请求仍在处理中：返回409，客户端稍后重试
+//! - 同一个键用于不同的请求（方法、路径或请求体不同）：返回422
+//!
+//! 幂等键按调用方身份（`auth::Principal`）隔离，不同用户或API密钥使用相同的键互不影响。
+//! 记录保存在数据库中，多个实例共享；服务端错误（HTTP状态码或响应体中的 `code` 为5xx）的响应不保存，
+//! 客户端可以使用同一个键重试；处理中的请求持有租约，处理实例崩溃时租约到期后视为未使用。
+//!
+//! 响应会原样保存在数据库中，因此只对 `IDEMPOTENT_OPERATIONS` 中的请求生效，其他请求忽略该请求头，
+//! 避免保存创建API密钥、启用两步验证等响应中只返回一次的密钥及恢复码
+
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use poem::{
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    web::Json,
+    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+use sha2::{Digest, Sha256};
+
+use super::api_path_segments;
+use crate::auth::Principal;
+use crate::db::DbPool;
+use crate::utils::response::{ApiResponse, EmptyResponse};
+
+/// 幂等键请求头
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// 标识响应为重放结果的响应头
+pub const IDEMPOTENT_REPLAYED: &str = "idempotent-replayed";
+
+/// 幂等键最大长度
+const MAX_KEY_LEN: usize = 255;
+
+/// 支持幂等键的请求（方法及去掉 `/api`、版本前缀后的路径）
+const IDEMPOTENT_OPERATIONS: &[(Method, &[&str])] = &[(Method::POST, &["users"])];
+
+/// 幂等键中间件
+#[derive(Debug, Clone)]
+pub struct Idempotency {
+    pool: DbPool,
+    ttl: Duration,
+    lease: Duration,
+}
+
+impl Idempotency {
+    /// 创建幂等键中间件，`ttl` 为响应保留时间，`lease` 为处理中的请求占用键的租约
+    pub fn new(pool: DbPool, ttl: Duration, lease: Duration) -> Self {
+        Self { pool, ttl, lease }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Idempotency {
+    type Output = IdempotencyEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        IdempotencyEndpoint {
+            inner: ep,
+            store: IdempotencyStore {
+                pool: self.pool.clone(),
+                ttl: self.ttl,
+                lease: self.lease,
+            },
+        }
+    }
+}
+
+/// `Idempotency` 中间件生成的端点
+pub struct IdempotencyEndpoint<E> {
+    inner: E,
+    store: IdempotencyStore,
+}
+
+impl<E: Endpoint> Endpoint for IdempotencyEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let key = match req.headers().get(IDEMPOTENCY_KEY) {
+            Some(key) if is_idempotent(&req) => key.to_str().unwrap_or_default().trim().to_string(),
+            _ => return self.inner.call(req).await.map(IntoResponse::into_response),
+        };
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            return Ok(error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Idempotency-Key 长度必须为1~{}个字符", MAX_KEY_LEN),
+            ));
+        }
+
+        let principal = req.extensions().get::<Principal>().cloned().unwrap_or_default();
+        let key = scoped_key(&principal, &key);
+
+        // 读取请求体计算指纹，再放回请求中交给内部端点
+        let body = req.take_body().into_bytes().await?;
+        let fingerprint = fingerprint(&req, &body);
+        req.set_body(body);
+
+        match self.store.claim(&key, &fingerprint).await {
+            Ok(Claim::Acquired) => {}
+            Ok(Claim::InFlight) => {
+                return Ok(error_response(
+                    StatusCode::CONFLICT,
+                    "使用相同 Idempotency-Key 的请求正在处理中，请稍后重试".to_string(),
+                ))
+            }
+            Ok(Claim::Mismatch) => {
+                return Ok(error_response(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "Idempotency-Key 已用于其他请求".to_string(),
+                ))
+            }
+            Ok(Claim::Completed(stored)) => return Ok(stored.into_response()),
+            Err(err) => {
+                tracing::error!("读取幂等键失败: {}", err);
+                return Ok(error_response(StatusCode::INTERNAL_SERVER_ERROR, "服务器内部错误".to_string()));
+            }
+        }
+
+        let resp = match self.inner.call(req).await {
+            Ok(resp) => resp.into_response(),
+            Err(err) => err.into_response(),
+        };
+
+        // 服务端错误不保存，释放键以便客户端重试
+        if resp.status().is_server_error() {
+            self.store.release(&key).await;
+            return Ok(resp);
+        }
+
+        let (parts, body) = resp.into_parts();
+        let body = match body.into_bytes().await {
+            Ok(body) => body,
+            Err(err) => {
+                self.store.release(&key).await;
+                return Err(err.into());
+            }
+        };
+        // 业务错误以HTTP 200返回，服务端错误的状态码在响应体的 `code` 中
+        if is_server_error_body(&body) {
+            self.store.release(&key).await;
+        } else {
+            self.store.complete(&key, parts.status, &parts.headers, &body).await;
+        }
+
+        Ok(Response::from_parts(parts, Body::from(body)))
+    }
+}
+
+/// 占用幂等键的结果
+enum Claim {
+    /// 首次使用，由当前请求处理
+    Acquired,
+    /// 相同的请求正在处理中
+    InFlight,
+    /// 键已用于其他请求
+    Mismatch,
+    /// 相同的请求已完成，重放保存的响应
+    Completed(StoredResponse),
+}
+
+/// 保存的响应
+struct StoredResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl IntoResponse for StoredResponse {
+    fn into_response(self) -> Response {
+        let mut resp = Response::builder()
+            .status(StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK))
+            .body(self.body);
+
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+                resp.headers_mut().append(name, value);
+            }
+        }
+        resp.headers_mut()
+            .insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+        resp
+    }
+}
+
+/// 数据库中的幂等键记录
+#[derive(sqlx::FromRow)]
+struct IdempotencyRow {
+    fingerprint: String,
+    status: Option<i64>,
+    headers: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+/// 幂等键存储
+struct IdempotencyStore {
+    pool: DbPool,
+    ttl: Duration,
+    lease: Duration,
+}
+
+impl IdempotencyStore {
+    /// 尝试占用幂等键，已被占用时返回已有记录的状态
+    ///
+    /// 未完成且租约已到期的记录视为未使用，由当前请求重新占用
+    async fn claim(&self, key: &str, fingerprint: &str) -> Result<Claim, sqlx::Error> {
+        let now = Utc::now();
+
+        // 顺带清理过期记录，过期的键视为未使用
+        sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= ?")
+            .bind(now.timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO idempotency_keys (idempotency_key, fingerprint, created_at, expires_at, locked_until) \
+             VALUES (?1, ?2, ?3, ?4, ?5) \
+             ON CONFLICT (idempotency_key) DO UPDATE SET fingerprint = excluded.fingerprint, \
+             created_at = excluded.created_at, expires_at = excluded.expires_at, locked_until = excluded.locked_until \
+             WHERE idempotency_keys.status IS NULL \
+             AND (idempotency_keys.locked_until IS NULL OR idempotency_keys.locked_until <= ?6)",
+        )
+        .bind(key)
+        .bind(fingerprint)
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(now.timestamp() + self.ttl.as_secs() as i64)
+        .bind(now.timestamp() + self.lease.as_secs() as i64)
+        .bind(now.timestamp())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if inserted > 0 {
+            return Ok(Claim::Acquired);
+        }
+
+        let row = sqlx::query_as::<_, IdempotencyRow>(
+            "SELECT fingerprint, status, headers, body FROM idempotency_keys WHERE idempotency_key = ?",
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match row {
+            // 插入与查询之间记录被释放，按处理中对待，由客户端重试
+            None => Claim::InFlight,
+            Some(IdempotencyRow { status: None, .. }) => Claim::InFlight,
+            Some(row) if row.fingerprint != fingerprint => Claim::Mismatch,
+            Some(IdempotencyRow {
+                status: Some(status),
+                headers,
+                body,
+                ..
+            }) => Claim::Completed(StoredResponse {
+                status: status as u16,
+                headers: headers
+                    .and_then(|headers| serde_json::from_str(&headers).ok())
+                    .unwrap_or_default(),
+                body: body.unwrap_or_default(),
+            }),
+        })
+    }
+
+    /// 保存完整响应
+    async fn complete(&self, key: &str, status: StatusCode, headers: &HeaderMap, body: &[u8]) {
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+            .collect::<Vec<_>>();
+
+        let result = sqlx::query(
+            "UPDATE idempotency_keys SET status = ?, headers = ?, body = ?, locked_until = NULL WHERE idempotency_key = ?",
+        )
+        .bind(status.as_u16() as i64)
+        .bind(serde_json::to_string(&headers).unwrap_or_default())
+        .bind(body)
+        .bind(key)
+        .execute(&self.pool)
+        .await;
+
+        if let Err(err) = result {
+            tracing::error!("保存幂等键响应失败: {}", err);
+            self.release(key).await;
+        }
+    }
+
+    /// 释放幂等键，允许客户端使用同一个键重试
+    async fn release(&self, key: &str) {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE idempotency_key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await;
+
+        if let Err(err) = result {
+            tracing::error!("释放幂等键失败: {}", err);
+        }
+    }
+}
+
+/// 请求是否支持幂等键
+fn is_idempotent(req: &Request) -> bool {
+    let segments = api_path_segments(req.uri().path());
+    IDEMPOTENT_OPERATIONS
+        .iter()
+        .any(|(method, path)| req.method() == method && segments == *path)
+}
+
+/// 统一格式的响应体中 `code` 是否为服务端错误（5xx）
+fn is_server_error_body(body: &[u8]) -> bool {
+    serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|body| body.get("code")?.as_u64())
+        .is_some_and(|code| (500..600).contains(&code))
+}
+
+/// 按调用方身份区分的幂等键，例如 `user:42:<键>`，匿名调用方共用 `anonymous:<键>`
+fn scoped_key(principal: &Principal, key: &str) -> String {
+    match (principal.id(), principal.name()) {
+        (Some(id), _) => format!("{}:{}:{}", principal.kind(), id, key),
+        (None, Some(name)) => format!("{}:{}:{}", principal.kind(), name, key),
+        (None, None) => format!("{}:{}", principal.kind(), key),
+    }
+}
+
+/// 请求指纹：方法、路径（含查询参数）及请求体的SHA-256
+fn fingerprint(req: &Request, body: &[u8]) -> String {
+    let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+
+    let mut hasher = Sha256::new();
+    hasher.update(req.method().as_str());
+    hasher.update(b"\n");
+    hasher.update(path);
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// 统一格式的错误响应
+fn error_response(status: StatusCode, msg: String) -> Response {
+    Json(ApiResponse::<EmptyResponse>::error(status.as_u16(), msg))
+        .with_status(status)
+        .into_response()
+}
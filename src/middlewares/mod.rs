@@ -2,6 +2,30 @@
 //! 
 //! 包含所有自定义中间件的实现
 
+pub mod api_key;
+mod deprecation;
+mod hsts;
+mod https_redirect;
+pub mod idempotency;
+pub mod session;
+
+pub use api_key::ApiKeyAuth;
+pub use deprecation::Deprecation;
+pub use hsts::hsts;
+pub use https_redirect::HttpsRedirect;
+pub use idempotency::Idempotency;
+pub use session::SessionAuth;
+
+/// 去掉 `/api` 及版本前缀（例如 `/v1`）后的路径分段，`/api/v1/users/1` 返回 `["users", "1"]`
+fn api_path_segments(path: &str) -> Vec<&str> {
+    let mut segments = path.trim_matches('/').split('/').peekable();
+    segments.next_if_eq(&"api");
+    segments.next_if(|segment| {
+        segment.strip_prefix('v').is_some_and(|version| version.parse::<u32>().is_ok())
+    });
+    segments.collect()
+}
+
 // 在实际项目中，这里会包含各种中间件实现
 // 例如：
 // use poem::{Endpoint, Middleware, Request, Response, Result};
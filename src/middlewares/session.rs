@@ -0,0 +1,138 @@
+//! 会话认证中间件
+//!
+//! 从会话Cookie读取令牌：
+//!
+//! - 已由其他方式认证（例如API密钥）或未携带会话Cookie：不做处理
+//! - 会话不存在或已过期：删除浏览器中的会话Cookie，按匿名调用方处理
+//! - 会话有效：将 `Principal::User` 及 `CurrentSession` 写入请求扩展；GET、HEAD、OPTIONS以外的请求
+//!   还需要通过CSRF令牌请求头提交与CSRF令牌Cookie、会话中保存的令牌都一致的令牌，否则返回403
+//! - 用户的角色要求启用两步验证但尚未启用：完成绑定前只能访问本人的两步验证接口、当前会话及退出登录，
+//!   其他请求（包括GraphQL）返回403
+//!
+//! 需要在外层使用 `CookieJarManager`
+
+use poem::{
+    http::{Method, StatusCode},
+    web::Json,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+
+use super::api_path_segments;
+use crate::auth::{constant_time_eq, Principal};
+use crate::sessions;
+use crate::services::SessionService;
+use crate::utils::response::{ApiResponse, EmptyResponse};
+
+/// 会话认证中间件
+#[derive(Clone)]
+pub struct SessionAuth {
+    service: SessionService,
+}
+
+impl SessionAuth {
+    /// 创建认证中间件，Cookie名称、CSRF请求头等取自会话服务的配置
+    pub fn new(service: SessionService) -> Self {
+        Self { service }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for SessionAuth {
+    type Output = SessionAuthEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        SessionAuthEndpoint {
+            inner: ep,
+            service: self.service.clone(),
+        }
+    }
+}
+
+/// `SessionAuth` 中间件生成的端点
+pub struct SessionAuthEndpoint<E> {
+    inner: E,
+    service: SessionService,
+}
+
+impl<E: Endpoint> Endpoint for SessionAuthEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let config = self.service.config();
+        let token = match req.extensions().get::<Principal>() {
+            Some(principal) if *principal != Principal::Anonymous => None,
+            _ => req.cookie().get(&config.cookie_name).map(|cookie| cookie.value_str().to_string()),
+        };
+        let Some(token) = token else {
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        };
+
+        let session = match self.service.authenticate(&token).await {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                sessions::clear_cookies(req.cookie(), config);
+                return self.inner.call(req).await.map(IntoResponse::into_response);
+            }
+            Err(err) => {
+                let status = StatusCode::from_u16(err.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                return Ok(error_response(status, err.to_string()));
+            }
+        };
+
+        if !is_safe(req.method()) {
+            let header = req
+                .headers()
+                .get(config.csrf_header.as_str())
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            let cookie = req
+                .cookie()
+                .get(&config.csrf_cookie_name)
+                .map(|cookie| cookie.value_str().to_string())
+                .unwrap_or_default();
+            // 双重提交：请求头需要与Cookie一致，且是该会话签发的令牌
+            let valid = !header.is_empty()
+                && constant_time_eq(header.as_bytes(), cookie.as_bytes())
+                && constant_time_eq(header.as_bytes(), session.csrf_token.as_bytes());
+            if !valid {
+                return Ok(error_response(
+                    StatusCode::FORBIDDEN,
+                    format!("CSRF令牌无效，请通过 {} 请求头提交", config.csrf_header),
+                ));
+            }
+        }
+
+        if session.mfa_enrollment_required && !allowed_before_enrollment(req.uri().path(), session.session.user_id) {
+            return Ok(error_response(
+                StatusCode::FORBIDDEN,
+                "该账户的角色要求启用两步验证，请先完成绑定".to_string(),
+            ));
+        }
+
+        req.extensions_mut().insert(session.principal);
+        req.extensions_mut().insert(session.session);
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+/// 不修改数据的请求方法，不需要CSRF令牌
+fn is_safe(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// 尚未完成两步验证绑定的会话可以访问的接口：本人的两步验证接口、当前会话及退出登录
+///
+/// 路径可以带有 `/api` 及版本前缀，例如 `/api/v1/users/1/mfa/enroll`
+fn allowed_before_enrollment(path: &str, user_id: u64) -> bool {
+    let own = user_id.to_string();
+    match api_path_segments(path).as_slice() {
+        ["auth", "logout"] | ["sessions", "current"] => true,
+        ["users", id, "mfa", ..] => *id == own,
+        _ => false,
+    }
+}
+
+fn error_response(status: StatusCode, msg: String) -> Response {
+    Json(ApiResponse::<EmptyResponse>::error(status.as_u16(), msg))
+        .with_status(status)
+        .into_response()
+}
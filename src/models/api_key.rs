@@ -0,0 +1,153 @@
+use poem_openapi::{types::Example, Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// API密钥的授权范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyScope {
+    /// 只读：GET请求及GraphQL查询
+    Read,
+    /// 读写：修改数据的请求及GraphQL变更
+    Write,
+    /// 管理：包含读写，并可管理API密钥
+    Admin,
+}
+
+impl ApiKeyScope {
+    /// 授权范围标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// 解析授权范围标识
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "read" => Some(Self::Read),
+            "write" => Some(Self::Write),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    /// 是否包含 `scope`：`admin` 包含所有范围，`write` 包含 `read`
+    pub fn includes(&self, scope: ApiKeyScope) -> bool {
+        match self {
+            Self::Admin => true,
+            Self::Write => scope != Self::Admin,
+            Self::Read => scope == Self::Read,
+        }
+    }
+}
+
+/// API密钥
+///
+/// 供没有用户会话的机器调用方使用，密钥原文只在创建时返回一次
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ApiKey {
+    /// 密钥ID
+    pub id: u64,
+
+    /// 名称，例如使用密钥的服务
+    pub name: String,
+
+    /// 密钥的前缀部分，用于识别密钥
+    pub prefix: String,
+
+    /// 授权范围
+    pub scopes: Vec<ApiKeyScope>,
+
+    /// 过期时间（ISO 8601格式），为空时不过期
+    pub expires_at: Option<String>,
+
+    /// 最后使用时间（ISO 8601格式），按分钟级精度更新
+    pub last_used_at: Option<String>,
+
+    /// 吊销时间（ISO 8601格式），吊销后不能再使用
+    pub revoked_at: Option<String>,
+
+    /// 创建时间（ISO 8601格式）
+    pub created_at: String,
+}
+
+/// API密钥创建请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct CreateApiKeyRequest {
+    /// 名称，例如使用密钥的服务
+    #[oai(validator(min_length = 1, max_length = 100))]
+    pub name: String,
+
+    /// 授权范围，至少一个
+    #[oai(validator(min_items = 1))]
+    pub scopes: Vec<ApiKeyScope>,
+
+    /// 过期时间（ISO 8601格式），必须晚于当前时间；为空时不过期
+    pub expires_at: Option<String>,
+}
+
+/// 新创建的API密钥
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct CreatedApiKey {
+    /// 密钥信息
+    pub api_key: ApiKey,
+
+    /// 密钥原文，只返回这一次，请求时通过 `X-API-Key` 或 `Authorization: ApiKey <密钥>` 提交
+    pub key: String,
+}
+
+/// API密钥列表响应
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ApiKeyListResponse {
+    /// 所有密钥（包括已吊销的），按创建顺序
+    pub items: Vec<ApiKey>,
+}
+
+impl Example for ApiKey {
+    fn example() -> Self {
+        Self {
+            id: 1,
+            name: "billing-service".to_string(),
+            prefix: "pak_4f7k2m9x".to_string(),
+            scopes: vec![ApiKeyScope::Read],
+            expires_at: Some("2026-01-01T00:00:00Z".to_string()),
+            last_used_at: Some("2025-01-05T09:30:00Z".to_string()),
+            revoked_at: None,
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+        }
+    }
+}
+
+impl Example for CreateApiKeyRequest {
+    fn example() -> Self {
+        Self {
+            name: "billing-service".to_string(),
+            scopes: vec![ApiKeyScope::Read],
+            expires_at: Some("2026-01-01T00:00:00Z".to_string()),
+        }
+    }
+}
+
+impl Example for CreatedApiKey {
+    fn example() -> Self {
+        Self {
+            api_key: ApiKey::example(),
+            key: "pak_4f7k2m9x_Qm3v8ZpL2cT9wX6nR4yB7dF1hK5sJ0gA".to_string(),
+        }
+    }
+}
+
+impl Example for ApiKeyListResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![ApiKey::example()],
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use poem_openapi::{types::Example, Enum, Object};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// 审计动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    /// 创建
+    Create,
+    /// 修改
+    Update,
+    /// 删除（软删除）
+    Delete,
+    /// 恢复已删除的数据
+    Restore,
+    /// 超过保留期后彻底清除
+    Purge,
+}
+
+impl AuditAction {
+    /// 数据库中存储的动作标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::Purge => "purge",
+        }
+    }
+
+    /// 解析数据库中的动作标识
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "create" => Some(Self::Create),
+            "update" => Some(Self::Update),
+            "delete" => Some(Self::Delete),
+            "restore" => Some(Self::Restore),
+            "purge" => Some(Self::Purge),
+            _ => None,
+        }
+    }
+}
+
+/// 审计事件
+///
+/// 一次数据变更的记录，写入后不可修改或删除
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct AuditEvent {
+    /// 事件ID，按写入顺序递增
+    pub id: u64,
+
+    /// 发生时间（ISO 8601格式）
+    pub occurred_at: String,
+
+    /// 操作者类型：`anonymous`（未认证）、`system`（命令行、后台任务）、`user`、`api_key`（API密钥）
+    pub actor_type: String,
+
+    /// 操作者用户ID或API密钥ID，其他操作者时为空
+    pub actor_id: Option<String>,
+
+    /// 操作者名称：用户名、系统组件名或API密钥名称
+    pub actor_name: Option<String>,
+
+    /// 动作
+    pub action: AuditAction,
+
+    /// 实体类型，例如 `user`
+    pub entity_type: String,
+
+    /// 实体ID
+    pub entity_id: String,
+
+    /// 变更字段，格式为 `{"字段": {"before": 旧值, "after": 新值}}`，敏感字段的值以 `[REDACTED]` 代替
+    pub changes: serde_json::Value,
+
+    /// 请求ID，与响应头 `X-Request-Id` 对应
+    pub request_id: Option<String>,
+
+    /// 客户端IP
+    pub ip: Option<String>,
+}
+
+/// 审计事件分页响应
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct AuditPageResponse {
+    /// 当前页数据，按时间倒序
+    pub items: Vec<AuditEvent>,
+
+    /// 总记录数
+    pub total: u64,
+
+    /// 当前页码
+    pub page: u32,
+
+    /// 每页记录数
+    pub page_size: u32,
+
+    /// 总页数
+    pub total_pages: u32,
+}
+
+impl Example for AuditEvent {
+    fn example() -> Self {
+        Self {
+            id: 128,
+            occurred_at: "2025-01-02T09:30:00Z".to_string(),
+            actor_type: "user".to_string(),
+            actor_id: Some("1".to_string()),
+            actor_name: Some("admin".to_string()),
+            action: AuditAction::Update,
+            entity_type: "user".to_string(),
+            entity_id: "42".to_string(),
+            changes: json!({
+                "email": { "before": "alice@example.com", "after": "alice@example.org" },
+                "version": { "before": 2, "after": 3 },
+            }),
+            request_id: Some("2f1c8e4a-6b3d-4f0e-9a7c-5d2b1e8f3c6a".to_string()),
+            ip: Some("203.0.113.7".to_string()),
+        }
+    }
+}
+
+impl Example for AuditPageResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![AuditEvent::example()],
+            total: 1,
+            page: 1,
+            page_size: 20,
+            total_pages: 1,
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use poem_openapi::{types::Example, Object};
+use serde::{Deserialize, Serialize};
+
+/// 邮箱验证请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct VerifyEmailRequest {
+    /// 验证邮件中的令牌
+    #[oai(validator(max_length = 256))]
+    pub token: String,
+}
+
+/// 找回密码请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ForgotPasswordRequest {
+    /// 注册时使用的邮箱
+    #[oai(validator(pattern = r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"))]
+    pub email: String,
+}
+
+/// 重置密码请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ResetPasswordRequest {
+    /// 重置邮件中的令牌
+    #[oai(validator(max_length = 256))]
+    pub token: String,
+
+    /// 新密码
+    #[oai(validator(min_length = 6, max_length = 100))]
+    pub password: String,
+}
+
+impl Example for VerifyEmailRequest {
+    fn example() -> Self {
+        Self {
+            token: "42.1735804800.9c1f0e4b7a2d5c8e3f6a1b4d7e0c2f5a.5d2f...".to_string(),
+        }
+    }
+}
+
+impl Example for ForgotPasswordRequest {
+    fn example() -> Self {
+        Self {
+            email: "alice@example.com".to_string(),
+        }
+    }
+}
+
+impl Example for ResetPasswordRequest {
+    fn example() -> Self {
+        Self {
+            token: "42.1735736400.3b8e1d6a9c2f5e0b7d4a1c8f3e6b9d2a.a7c4...".to_string(),
+            password: "new-password".to_string(),
+        }
+    }
+}
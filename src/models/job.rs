@@ -0,0 +1,136 @@
+use poem_openapi::{types::Example, Enum, Object};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// 后台任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// 等待执行（包括延迟执行及等待重试）
+    Scheduled,
+    /// 执行中
+    Running,
+    /// 执行成功
+    Succeeded,
+    /// 超过最大执行次数仍失败
+    Failed,
+    /// 已取消
+    Cancelled,
+}
+
+impl JobStatus {
+    /// 数据库中存储的状态标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// 解析数据库中的状态标识，未知值视为等待执行
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "running" => Self::Running,
+            "succeeded" => Self::Succeeded,
+            "failed" => Self::Failed,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Scheduled,
+        }
+    }
+}
+
+/// 后台任务
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct JobRecord {
+    /// 任务ID
+    pub id: u64,
+
+    /// 任务类型
+    pub kind: String,
+
+    /// 任务参数
+    pub payload: serde_json::Value,
+
+    /// 任务状态
+    pub status: JobStatus,
+
+    /// 已执行次数
+    pub attempts: u32,
+
+    /// 最大执行次数
+    pub max_attempts: u32,
+
+    /// 最早可执行的时间（ISO 8601格式），等待重试时为下次执行时间
+    pub run_at: String,
+
+    /// 唯一键，同一键同时只能存在一个等待或执行中的任务
+    pub unique_key: Option<String>,
+
+    /// 最后一次失败的原因
+    pub last_error: Option<String>,
+
+    /// 创建时间（ISO 8601格式）
+    pub created_at: String,
+
+    /// 最后更新时间（ISO 8601格式）
+    pub updated_at: String,
+
+    /// 结束时间（ISO 8601格式），成功、失败或取消时记录
+    pub finished_at: Option<String>,
+}
+
+/// 后台任务分页响应
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct JobPageResponse {
+    /// 当前页数据，按创建时间倒序
+    pub items: Vec<JobRecord>,
+
+    /// 总记录数
+    pub total: u64,
+
+    /// 当前页码
+    pub page: u32,
+
+    /// 每页记录数
+    pub page_size: u32,
+
+    /// 总页数
+    pub total_pages: u32,
+}
+
+impl Example for JobRecord {
+    fn example() -> Self {
+        Self {
+            id: 57,
+            kind: "export_users".to_string(),
+            payload: json!({ "format": "csv", "requested_by": 1 }),
+            status: JobStatus::Scheduled,
+            attempts: 1,
+            max_attempts: 5,
+            run_at: "2025-01-02T09:31:00Z".to_string(),
+            unique_key: Some("export_users:1".to_string()),
+            last_error: Some("连接超时".to_string()),
+            created_at: "2025-01-02T09:30:00Z".to_string(),
+            updated_at: "2025-01-02T09:30:50Z".to_string(),
+            finished_at: None,
+        }
+    }
+}
+
+impl Example for JobPageResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![JobRecord::example()],
+            total: 1,
+            page: 1,
+            page_size: 20,
+            total_pages: 1,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use poem_openapi::{types::Example, Object};
+use serde::{Deserialize, Serialize};
+
+/// 两步验证状态
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct MfaStatus {
+    /// 是否已启用
+    pub enabled: bool,
+
+    /// 启用时间（ISO 8601格式），未启用时为空
+    pub enabled_at: Option<String>,
+
+    /// 用户的角色是否必须启用两步验证，必须启用时不能停用
+    pub required: bool,
+
+    /// 剩余可用的恢复码数量
+    pub recovery_codes_remaining: u32,
+}
+
+/// 两步验证绑定信息
+///
+/// 将 `otpauth_uri` 生成二维码供验证器应用扫描，或手动输入 `secret`，
+/// 再提交验证器应用显示的验证码完成启用
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct MfaEnrollment {
+    /// TOTP密钥（Base32）
+    pub secret: String,
+
+    /// `otpauth://` URI，即二维码的内容
+    pub otpauth_uri: String,
+
+    /// 验证码位数
+    pub digits: u32,
+
+    /// 验证码有效时长（秒）
+    pub period: u64,
+}
+
+/// 两步验证码请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct MfaCodeRequest {
+    /// 验证器应用显示的6位验证码；停用及重新生成恢复码时也可使用未使用过的恢复码
+    #[oai(validator(min_length = 1, max_length = 32))]
+    pub code: String,
+}
+
+/// 恢复码
+///
+/// 只在生成时返回一次，每个只能使用一次，应提示用户妥善保存
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct RecoveryCodesResponse {
+    /// 恢复码列表
+    pub codes: Vec<String>,
+}
+
+impl Example for MfaStatus {
+    fn example() -> Self {
+        Self {
+            enabled: true,
+            enabled_at: Some("2025-01-03T10:00:00Z".to_string()),
+            required: true,
+            recovery_codes_remaining: 9,
+        }
+    }
+}
+
+impl Example for MfaEnrollment {
+    fn example() -> Self {
+        Self {
+            secret: "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP".to_string(),
+            otpauth_uri: "otpauth://totp/Poem%20API:alice?secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=Poem%20API&algorithm=SHA1&digits=6&period=30".to_string(),
+            digits: 6,
+            period: 30,
+        }
+    }
+}
+
+impl Example for MfaCodeRequest {
+    fn example() -> Self {
+        Self {
+            code: "492039".to_string(),
+        }
+    }
+}
+
+impl Example for RecoveryCodesResponse {
+    fn example() -> Self {
+        Self {
+            codes: vec!["k7m2p-x9q4t".to_string(), "3hv8n-wd5ra".to_string()],
+        }
+    }
+}
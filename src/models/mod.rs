@@ -2,7 +2,16 @@
 //! 
 //! 本模块包含应用程序中使用的所有数据模型定义。
 
+pub mod api_key;
+pub mod audit;
+pub mod auth;
+pub mod job;
+pub mod mfa;
+pub mod oidc;
+pub mod scheduler;
+pub mod session;
 pub mod user;
+pub mod webhook;
 pub mod common; // 新增通用模型模块
 
 // 重新导出常用模型，方便其他模块引用
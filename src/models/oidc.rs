@@ -0,0 +1,139 @@
+use poem_openapi::{types::Example, Object};
+use serde::{Deserialize, Serialize};
+
+use super::session::Session;
+use super::user::User;
+
+/// 外部身份提供方
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct OidcProvider {
+    /// 提供方标识，用于登录接口的路径
+    pub id: String,
+
+    /// 显示名称
+    pub name: String,
+}
+
+/// 外部身份提供方列表
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct OidcProviderListResponse {
+    /// 已配置的身份提供方
+    pub items: Vec<OidcProvider>,
+}
+
+/// 外部登录的授权请求
+///
+/// 将浏览器重定向到 `authorization_url`，用户在身份提供方登录后会带着 `code`、`state` 重定向回配置的地址
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct OidcAuthorization {
+    /// 身份提供方的授权地址
+    pub authorization_url: String,
+
+    /// 本次登录的 `state`，回调时原样返回，可用于前端关联登录请求
+    pub state: String,
+
+    /// 需要在多少秒内完成登录
+    pub expires_in: u64,
+}
+
+/// 用户在外部身份提供方中的身份
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct UserIdentity {
+    /// 身份ID
+    pub id: u64,
+
+    /// 关联的用户ID
+    pub user_id: u64,
+
+    /// 提供方标识
+    pub provider: String,
+
+    /// 用户在身份提供方中的唯一标识（ID令牌中的 `sub`）
+    pub subject: String,
+
+    /// 最后一次登录时身份提供方返回的邮箱
+    pub email: Option<String>,
+
+    /// 关联时间（ISO 8601格式）
+    pub created_at: String,
+
+    /// 最后登录时间（ISO 8601格式）
+    pub last_login_at: String,
+}
+
+/// 外部登录结果
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct OidcLoginResponse {
+    /// 登录的用户
+    pub user: User,
+
+    /// 本次登录使用的身份
+    pub identity: UserIdentity,
+
+    /// 是否在本次登录时创建了用户
+    pub created: bool,
+
+    /// 启用会话登录时新建的会话，会话令牌及CSRF令牌通过 `Set-Cookie` 响应头返回
+    pub session: Option<Session>,
+
+    /// 用户的角色要求启用两步验证但尚未启用，前端应引导用户完成绑定
+    pub mfa_enrollment_required: bool,
+}
+
+impl Example for OidcProvider {
+    fn example() -> Self {
+        Self {
+            id: "company".to_string(),
+            name: "公司SSO".to_string(),
+        }
+    }
+}
+
+impl Example for OidcProviderListResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![OidcProvider::example()],
+        }
+    }
+}
+
+impl Example for OidcAuthorization {
+    fn example() -> Self {
+        Self {
+            authorization_url: "https://sso.example.com/authorize?response_type=code&client_id=poem-api&state=Xk3vQ9mT2pLw8RzN".to_string(),
+            state: "Xk3vQ9mT2pLw8RzN".to_string(),
+            expires_in: 600,
+        }
+    }
+}
+
+impl Example for UserIdentity {
+    fn example() -> Self {
+        Self {
+            id: 7,
+            user_id: 42,
+            provider: "company".to_string(),
+            subject: "248289761001".to_string(),
+            email: Some("alice@example.com".to_string()),
+            created_at: "2025-01-03T10:00:00Z".to_string(),
+            last_login_at: "2025-01-05T08:30:00Z".to_string(),
+        }
+    }
+}
+
+impl Example for OidcLoginResponse {
+    fn example() -> Self {
+        Self {
+            user: User::example(),
+            identity: UserIdentity::example(),
+            created: true,
+            session: Some(Session::example()),
+            mfa_enrollment_required: false,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use poem_openapi::{types::Example, Enum, Object};
+use serde::{Deserialize, Serialize};
+
+/// 定时任务最后一次执行的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TaskRunStatus {
+    /// 执行成功
+    Succeeded,
+    /// 执行失败（返回错误或panic）
+    Failed,
+}
+
+impl TaskRunStatus {
+    /// 数据库中存储的结果标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// 解析数据库中的结果标识
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "succeeded" => Some(Self::Succeeded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// 定时任务的执行状态
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ScheduledTaskStatus {
+    /// 任务名称，与配置 `scheduler.tasks` 中的键对应
+    pub name: String,
+
+    /// cron表达式（UTC），未按计划执行（只能手动触发）时为空
+    pub cron: Option<String>,
+
+    /// 下次执行时间（ISO 8601格式），为空时不会自动执行
+    pub next_run_at: Option<String>,
+
+    /// 是否正在执行
+    pub running: bool,
+
+    /// 正在执行的实例标识
+    pub locked_by: Option<String>,
+
+    /// 最后一次开始执行的时间（ISO 8601格式）
+    pub last_started_at: Option<String>,
+
+    /// 最后一次执行结束的时间（ISO 8601格式）
+    pub last_finished_at: Option<String>,
+
+    /// 最后一次执行的结果
+    pub last_status: Option<TaskRunStatus>,
+
+    /// 最后一次执行失败的原因
+    pub last_error: Option<String>,
+
+    /// 最后一次执行的耗时（毫秒）
+    pub last_duration_ms: Option<u64>,
+}
+
+/// 定时任务列表响应
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ScheduledTaskListResponse {
+    /// 所有定时任务，按名称排序
+    pub items: Vec<ScheduledTaskStatus>,
+}
+
+impl Example for ScheduledTaskStatus {
+    fn example() -> Self {
+        Self {
+            name: "purge_deleted_users".to_string(),
+            cron: Some("0 0 * * * *".to_string()),
+            next_run_at: Some("2025-01-02T10:00:00Z".to_string()),
+            running: false,
+            locked_by: None,
+            last_started_at: Some("2025-01-02T09:00:00Z".to_string()),
+            last_finished_at: Some("2025-01-02T09:00:01Z".to_string()),
+            last_status: Some(TaskRunStatus::Succeeded),
+            last_error: None,
+            last_duration_ms: Some(842),
+        }
+    }
+}
+
+impl Example for ScheduledTaskListResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![ScheduledTaskStatus::example()],
+        }
+    }
+}
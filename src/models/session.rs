@@ -0,0 +1,135 @@
+use poem_openapi::{types::Example, Object};
+use serde::{Deserialize, Serialize};
+
+use super::user::User;
+
+/// 登录请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct LoginRequest {
+    /// 用户名或邮箱
+    #[oai(validator(min_length = 1, max_length = 100))]
+    pub username: String,
+
+    /// 密码
+    #[oai(validator(min_length = 1, max_length = 100))]
+    pub password: String,
+
+    /// 两步验证码或恢复码，已启用两步验证的用户需要提供
+    #[oai(validator(max_length = 32))]
+    pub mfa_code: Option<String>,
+}
+
+/// 浏览器会话
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct Session {
+    /// 会话ID
+    pub id: String,
+
+    /// 登录的用户ID
+    pub user_id: u64,
+
+    /// 登录时的客户端IP
+    pub ip: Option<String>,
+
+    /// 登录时的客户端 `User-Agent`
+    pub user_agent: Option<String>,
+
+    /// 登录时间（ISO 8601格式）
+    pub created_at: String,
+
+    /// 最后活跃时间（ISO 8601格式），按分钟级精度更新
+    pub last_seen_at: String,
+
+    /// 没有新请求时的失效时间（ISO 8601格式）
+    pub idle_expires_at: String,
+
+    /// 绝对过期时间（ISO 8601格式），到期后需要重新登录
+    pub expires_at: String,
+
+    /// 是否为本次请求使用的会话
+    pub current: bool,
+}
+
+/// 登录结果
+///
+/// 会话令牌及CSRF令牌通过 `Set-Cookie` 响应头返回
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct LoginResponse {
+    /// 登录的用户
+    pub user: User,
+
+    /// 新建的会话
+    pub session: Session,
+
+    /// 用户的角色要求启用两步验证但尚未启用，前端应引导用户完成绑定
+    pub mfa_enrollment_required: bool,
+}
+
+/// 会话列表
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct SessionListResponse {
+    /// 当前用户未过期的会话，按登录时间排序
+    pub items: Vec<Session>,
+}
+
+/// 批量注销结果
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct RevokedSessions {
+    /// 注销的会话数量
+    pub count: u64,
+}
+
+impl Example for LoginRequest {
+    fn example() -> Self {
+        Self {
+            username: "alice".to_string(),
+            password: "password123".to_string(),
+            mfa_code: Some("492039".to_string()),
+        }
+    }
+}
+
+impl Example for Session {
+    fn example() -> Self {
+        Self {
+            id: "V7kQ2mX9pLw4RzN8".to_string(),
+            user_id: 42,
+            ip: Some("203.0.113.7".to_string()),
+            user_agent: Some("Mozilla/5.0 (Macintosh; Intel Mac OS X 14_2) AppleWebKit/605.1.15".to_string()),
+            created_at: "2025-01-05T08:30:00Z".to_string(),
+            last_seen_at: "2025-01-05T09:12:00Z".to_string(),
+            idle_expires_at: "2025-01-05T09:42:00Z".to_string(),
+            expires_at: "2025-01-05T20:30:00Z".to_string(),
+            current: true,
+        }
+    }
+}
+
+impl Example for LoginResponse {
+    fn example() -> Self {
+        Self {
+            user: User::example(),
+            session: Session::example(),
+            mfa_enrollment_required: false,
+        }
+    }
+}
+
+impl Example for SessionListResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![Session::example()],
+        }
+    }
+}
+
+impl Example for RevokedSessions {
+    fn example() -> Self {
+        Self { count: 2 }
+    }
+}
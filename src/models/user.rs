@@ -1,4 +1,8 @@
-use poem_openapi::Object;
+use poem_openapi::{
+    payload::Json,
+    types::{Example, MaybeUndefined},
+    ApiRequest, Enum, Object,
+};
 use serde::{Deserialize, Serialize};
 use super::common::{UserBase, BaseUser};
 
@@ -6,6 +10,7 @@ use super::common::{UserBase, BaseUser};
 /// 
 /// 用于表示系统中的用户实体
 #[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
 pub struct User {
     /// 用户唯一标识符
     #[oai(read_only)]
@@ -18,7 +23,21 @@ pub struct User {
     /// 用户邮箱
     #[oai(validator(pattern = r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"))]
     pub email: String,
+
+    /// 邮箱验证时间（ISO 8601格式），未验证时为空，修改邮箱后需重新验证
+    #[oai(read_only)]
+    #[serde(default)]
+    pub email_verified_at: Option<String>,
     
+    /// 用户角色
+    #[oai(read_only)]
+    #[serde(default)]
+    pub role: UserRole,
+
+    /// 版本号，每次修改递增，与响应头 `ETag` 对应
+    #[oai(read_only)]
+    pub version: Option<u64>,
+
     /// 用户创建时间（ISO 8601格式）
     #[oai(read_only)]
     pub created_at: Option<String>,
@@ -26,6 +45,10 @@ pub struct User {
     /// 用户最后更新时间（ISO 8601格式）
     #[oai(read_only)]
     pub updated_at: Option<String>,
+
+    /// 删除时间（ISO 8601格式），未删除时为空
+    #[oai(read_only)]
+    pub deleted_at: Option<String>,
 }
 
 // 实现UserBase特性，支持与GraphQL模型的转换
@@ -46,8 +69,42 @@ impl From<BaseUser> for User {
             id: base.id,
             username: base.name,
             email: String::new(), // 需要外部设置
+            email_verified_at: None,
+            role: UserRole::default(),
+            version: None,
             created_at: None,
             updated_at: None,
+            deleted_at: None,
+        }
+    }
+}
+
+/// 用户角色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// 普通用户
+    #[default]
+    User,
+    /// 管理员
+    Admin,
+}
+
+impl UserRole {
+    /// 数据库中存储的角色标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// 解析数据库中的角色标识，未知值视为普通用户
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "admin" => Self::Admin,
+            _ => Self::User,
         }
     }
 }
@@ -56,6 +113,7 @@ impl From<BaseUser> for User {
 /// 
 /// 用于创建新用户时的请求体
 #[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
 pub struct CreateUserRequest {
     /// 用户名，用于登录
     #[oai(validator(min_length = 3, max_length = 50))]
@@ -74,6 +132,7 @@ pub struct CreateUserRequest {
 /// 
 /// 用于更新现有用户信息的请求体
 #[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
 pub struct UpdateUserRequest {
     /// 用户邮箱（可选）
     #[oai(validator(pattern = r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"))]
@@ -84,6 +143,88 @@ pub struct UpdateUserRequest {
     pub password: Option<String>,
 }
 
+/// 用户部分更新请求（JSON Merge Patch，RFC 7396）
+///
+/// 未出现的字段保持不变，显式设置为 `null` 表示清空该字段；
+/// 邮箱和密码均为必填信息，清空时返回校验错误
+#[derive(Debug, Default, Object)]
+#[oai(example)]
+pub struct PatchUserRequest {
+    /// 用户邮箱
+    #[oai(validator(pattern = r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$"))]
+    pub email: MaybeUndefined<String>,
+
+    /// 用户密码
+    #[oai(validator(min_length = 6, max_length = 100))]
+    pub password: MaybeUndefined<String>,
+}
+
+/// JSON Patch操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+pub enum JsonPatchOp {
+    /// 添加字段
+    Add,
+    /// 删除字段
+    Remove,
+    /// 替换字段值
+    Replace,
+    /// 移动字段
+    Move,
+    /// 复制字段
+    Copy,
+    /// 校验字段值，不一致时整个补丁失败
+    Test,
+}
+
+impl JsonPatchOp {
+    /// RFC 6902中的操作名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Remove => "remove",
+            Self::Replace => "replace",
+            Self::Move => "move",
+            Self::Copy => "copy",
+            Self::Test => "test",
+        }
+    }
+}
+
+/// JSON Patch操作（RFC 6902）
+///
+/// 操作作用于用户的JSON表示，可修改 `/email`，并可通过 `add`/`replace` 写入 `/password`，
+/// 其余字段只读
+#[derive(Debug, Object)]
+#[oai(example)]
+pub struct JsonPatchOperation {
+    /// 操作类型
+    pub op: JsonPatchOp,
+
+    /// 目标位置（JSON Pointer），例如 `/email`
+    pub path: String,
+
+    /// 来源位置，`move`/`copy` 操作必填
+    pub from: Option<String>,
+
+    /// 操作值，`add`/`replace`/`test` 操作必填
+    pub value: MaybeUndefined<serde_json::Value>,
+}
+
+/// 用户部分更新请求体
+///
+/// 根据 `Content-Type` 区分JSON Merge Patch与JSON Patch
+#[derive(Debug, ApiRequest)]
+pub enum PatchUserPayload {
+    /// JSON Merge Patch（RFC 7396）
+    #[oai(content_type = "application/merge-patch+json")]
+    MergePatch(Json<PatchUserRequest>),
+
+    /// JSON Patch（RFC 6902）
+    #[oai(content_type = "application/json-patch+json")]
+    JsonPatch(Json<Vec<JsonPatchOperation>>),
+}
+
 /// 用户查询参数
 /// 
 /// 用于查询用户列表时的过滤条件
@@ -118,6 +259,7 @@ fn default_page_size() -> u32 {
 /// 
 /// 用于返回用户列表查询结果
 #[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
 pub struct UserListResponse {
     /// 用户列表
     pub users: Vec<User>,
@@ -131,3 +273,103 @@ pub struct UserListResponse {
     /// 每页记录数
     pub page_size: u32,
 }
+
+/// 用户分页响应（v2）
+///
+/// 相比 `UserListResponse` 增加了总页数，列表字段统一命名为 `items`
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct UserPageResponse {
+    /// 当前页数据
+    pub items: Vec<User>,
+
+    /// 总记录数
+    pub total: u64,
+
+    /// 当前页码
+    pub page: u32,
+
+    /// 每页记录数
+    pub page_size: u32,
+
+    /// 总页数
+    pub total_pages: u32,
+}
+
+impl Example for User {
+    fn example() -> Self {
+        Self {
+            id: Some(42),
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            email_verified_at: Some("2025-01-01T08:05:00Z".to_string()),
+            role: UserRole::User,
+            version: Some(3),
+            created_at: Some("2025-01-01T08:00:00Z".to_string()),
+            updated_at: Some("2025-01-02T09:30:00Z".to_string()),
+            deleted_at: None,
+        }
+    }
+}
+
+impl Example for CreateUserRequest {
+    fn example() -> Self {
+        Self {
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            password: "s3cret-passw0rd".to_string(),
+        }
+    }
+}
+
+impl Example for UpdateUserRequest {
+    fn example() -> Self {
+        Self {
+            email: Some("alice@example.org".to_string()),
+            password: None,
+        }
+    }
+}
+
+impl Example for PatchUserRequest {
+    fn example() -> Self {
+        Self {
+            email: MaybeUndefined::Value("alice@example.org".to_string()),
+            password: MaybeUndefined::Undefined,
+        }
+    }
+}
+
+impl Example for JsonPatchOperation {
+    fn example() -> Self {
+        Self {
+            op: JsonPatchOp::Replace,
+            path: "/email".to_string(),
+            from: None,
+            value: MaybeUndefined::Value(serde_json::Value::String("alice@example.org".to_string())),
+        }
+    }
+}
+
+impl Example for UserListResponse {
+    fn example() -> Self {
+        Self {
+            users: vec![User::example()],
+            total: 1,
+            page: 1,
+            page_size: 10,
+        }
+    }
+}
+
+impl Example for UserPageResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![User::example()],
+            total: 1,
+            page: 1,
+            page_size: 10,
+            total_pages: 1,
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use poem_openapi::{types::Example, Enum, Object};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// 可订阅的事件类型，与领域事件类型一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+pub enum WebhookEventType {
+    /// 用户已创建
+    #[oai(rename = "user.created")]
+    #[serde(rename = "user.created")]
+    UserCreated,
+    /// 用户信息已修改（包括恢复已删除的用户）
+    #[oai(rename = "user.updated")]
+    #[serde(rename = "user.updated")]
+    UserUpdated,
+    /// 用户已删除（软删除或彻底清除）
+    #[oai(rename = "user.deleted")]
+    #[serde(rename = "user.deleted")]
+    UserDeleted,
+}
+
+impl WebhookEventType {
+    /// 事件类型标识，例如 `user.created`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserCreated => "user.created",
+            Self::UserUpdated => "user.updated",
+            Self::UserDeleted => "user.deleted",
+        }
+    }
+
+    /// 解析事件类型标识
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user.created" => Some(Self::UserCreated),
+            "user.updated" => Some(Self::UserUpdated),
+            "user.deleted" => Some(Self::UserDeleted),
+            _ => None,
+        }
+    }
+}
+
+/// Webhook订阅
+///
+/// 订阅的事件发生后，以POST请求将事件推送到 `url`，请求带有签名，签名密钥创建后不再返回
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct Webhook {
+    /// 订阅ID
+    pub id: u64,
+
+    /// 接收事件的地址（http或https）
+    pub url: String,
+
+    /// 订阅的事件类型
+    pub event_types: Vec<WebhookEventType>,
+
+    /// 备注
+    pub description: Option<String>,
+
+    /// 是否启用，停用期间产生的事件仍会记录，重新启用后继续投递
+    pub enabled: bool,
+
+    /// 连续投递失败次数，投递成功后清零
+    pub consecutive_failures: u32,
+
+    /// 自动停用的原因，启用中或手动停用时为空
+    pub disabled_reason: Option<String>,
+
+    /// 创建时间（ISO 8601格式）
+    pub created_at: String,
+
+    /// 最后更新时间（ISO 8601格式）
+    pub updated_at: String,
+}
+
+/// Webhook订阅创建请求
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct CreateWebhookRequest {
+    /// 接收事件的地址（http或https）
+    #[oai(validator(max_length = 2048))]
+    pub url: String,
+
+    /// 订阅的事件类型，至少一个
+    #[oai(validator(min_items = 1))]
+    pub event_types: Vec<WebhookEventType>,
+
+    /// 签名密钥，用于计算请求签名，只在创建和修改时提交，不会返回
+    #[oai(validator(min_length = 16, max_length = 256), write_only)]
+    pub secret: String,
+
+    /// 备注
+    #[oai(validator(max_length = 500))]
+    pub description: Option<String>,
+}
+
+/// Webhook订阅更新请求
+///
+/// 只修改提交的字段；`enabled` 设为 `true` 时同时清零连续失败次数
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct UpdateWebhookRequest {
+    /// 接收事件的地址（可选）
+    #[oai(validator(max_length = 2048))]
+    pub url: Option<String>,
+
+    /// 订阅的事件类型（可选），至少一个
+    #[oai(validator(min_items = 1))]
+    pub event_types: Option<Vec<WebhookEventType>>,
+
+    /// 新的签名密钥（可选），用于轮换密钥
+    #[oai(validator(min_length = 16, max_length = 256), write_only)]
+    pub secret: Option<String>,
+
+    /// 备注（可选）
+    #[oai(validator(max_length = 500))]
+    pub description: Option<String>,
+
+    /// 是否启用（可选）
+    pub enabled: Option<bool>,
+}
+
+/// Webhook订阅列表响应
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct WebhookListResponse {
+    /// 所有订阅，按创建顺序
+    pub items: Vec<Webhook>,
+}
+
+/// 投递状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[oai(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// 等待投递或等待重试
+    Pending,
+    /// 投递成功（接收方返回2xx）
+    Succeeded,
+    /// 超过最大投递次数仍失败
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// 数据库中存储的状态标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// 解析数据库中的状态标识，未知值视为等待投递
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "succeeded" => Self::Succeeded,
+            "failed" => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// Webhook投递记录
+#[derive(Debug, Clone, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct WebhookDelivery {
+    /// 投递ID，与请求头 `X-Webhook-Delivery-Id` 对应
+    pub id: u64,
+
+    /// 订阅ID
+    pub webhook_id: u64,
+
+    /// 事件ID，与请求头 `X-Webhook-Event-Id` 对应，重试和重新投递时不变，可用于去重
+    pub event_id: u64,
+
+    /// 事件类型
+    pub event_type: String,
+
+    /// 请求体
+    pub payload: serde_json::Value,
+
+    /// 投递状态
+    pub status: DeliveryStatus,
+
+    /// 已投递次数
+    pub attempts: u32,
+
+    /// 下次投递时间（ISO 8601格式），投递结束后为空
+    pub next_attempt_at: Option<String>,
+
+    /// 最后一次响应的状态码，请求未完成时为空
+    pub response_status: Option<u16>,
+
+    /// 最后一次响应的内容（最多保留1KB）
+    pub response_body: Option<String>,
+
+    /// 最后一次失败的原因
+    pub error: Option<String>,
+
+    /// 手动重新投递时为原投递ID
+    pub redelivery_of: Option<u64>,
+
+    /// 创建时间（ISO 8601格式）
+    pub created_at: String,
+
+    /// 投递成功的时间（ISO 8601格式）
+    pub delivered_at: Option<String>,
+}
+
+/// Webhook投递记录分页响应
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct WebhookDeliveryPageResponse {
+    /// 当前页数据，按创建时间倒序
+    pub items: Vec<WebhookDelivery>,
+
+    /// 总记录数
+    pub total: u64,
+
+    /// 当前页码
+    pub page: u32,
+
+    /// 每页记录数
+    pub page_size: u32,
+
+    /// 总页数
+    pub total_pages: u32,
+}
+
+impl Example for Webhook {
+    fn example() -> Self {
+        Self {
+            id: 1,
+            url: "https://partner.example.com/hooks/users".to_string(),
+            event_types: vec![WebhookEventType::UserCreated, WebhookEventType::UserDeleted],
+            description: Some("CRM同步".to_string()),
+            enabled: true,
+            consecutive_failures: 0,
+            disabled_reason: None,
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            updated_at: "2025-01-01T00:00:00Z".to_string(),
+        }
+    }
+}
+
+impl Example for WebhookListResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![Webhook::example()],
+        }
+    }
+}
+
+impl Example for CreateWebhookRequest {
+    fn example() -> Self {
+        Self {
+            url: "https://partner.example.com/hooks/users".to_string(),
+            event_types: vec![WebhookEventType::UserCreated, WebhookEventType::UserDeleted],
+            secret: "whsec_3f9a1c7e5b2d8f4a".to_string(),
+            description: Some("CRM同步".to_string()),
+        }
+    }
+}
+
+impl Example for UpdateWebhookRequest {
+    fn example() -> Self {
+        Self {
+            url: None,
+            event_types: Some(vec![
+                WebhookEventType::UserCreated,
+                WebhookEventType::UserUpdated,
+                WebhookEventType::UserDeleted,
+            ]),
+            secret: None,
+            description: None,
+            enabled: Some(true),
+        }
+    }
+}
+
+impl Example for WebhookDelivery {
+    fn example() -> Self {
+        Self {
+            id: 310,
+            webhook_id: 1,
+            event_id: 128,
+            event_type: "user.deleted".to_string(),
+            payload: json!({
+                "id": 128,
+                "type": "user.deleted",
+                "occurred_at": "2025-01-02T09:30:00Z",
+                "data": { "user": { "id": 42, "username": "alice" }, "purged": false },
+            }),
+            status: DeliveryStatus::Pending,
+            attempts: 2,
+            next_attempt_at: Some("2025-01-02T09:31:00Z".to_string()),
+            response_status: Some(503),
+            response_body: Some("Service Unavailable".to_string()),
+            error: Some("接收方返回状态码 503".to_string()),
+            redelivery_of: None,
+            created_at: "2025-01-02T09:30:00Z".to_string(),
+            delivered_at: None,
+        }
+    }
+}
+
+impl Example for WebhookDeliveryPageResponse {
+    fn example() -> Self {
+        Self {
+            items: vec![WebhookDelivery::example()],
+            total: 1,
+            page: 1,
+            page_size: 20,
+            total_pages: 1,
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::{Jwk, JwkSet, PublicKeyUse};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::config::OidcProviderConfig;
+
+/// 接受的ID令牌签名算法，不接受 `none` 及需要共享密钥的HMAC算法
+const SUPPORTED_ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+/// 校验ID令牌时间时允许的时钟偏差（秒）
+const LEEWAY_SECS: u64 = 60;
+
+/// OIDC流程中的错误
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    /// 无法访问身份提供方，或其响应（发现文档、JWKS等）格式无效
+    #[error("身份提供方请求失败: {0:#}")]
+    Provider(anyhow::Error),
+
+    /// 身份提供方拒绝兑换授权码，例如授权码已使用或已过期
+    #[error("身份提供方拒绝了授权码: {0}")]
+    Rejected(String),
+
+    /// ID令牌校验失败
+    #[error("ID令牌无效: {0}")]
+    InvalidToken(String),
+}
+
+impl From<reqwest::Error> for OidcError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Provider(err.into())
+    }
+}
+
+/// 校验通过的ID令牌中的声明
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    /// 用户在身份提供方中的唯一标识
+    pub sub: String,
+
+    /// 邮箱
+    pub email: Option<String>,
+
+    /// 身份提供方是否已验证邮箱
+    pub email_verified: Option<bool>,
+
+    /// 用户偏好的用户名
+    pub preferred_username: Option<String>,
+
+    /// 显示名称
+    pub name: Option<String>,
+
+    /// 发起登录时生成的 `nonce`
+    pub nonce: Option<String>,
+
+    /// 授权方，ID令牌有多个受众时应为本客户端
+    pub azp: Option<String>,
+}
+
+/// 发现文档（`/.well-known/openid-configuration`）中用到的字段
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// 令牌端点的响应
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// 带获取时间的缓存项
+struct Cached<T> {
+    value: Arc<T>,
+    fetched_at: Instant,
+}
+
+/// 单个身份提供方的OIDC客户端
+pub struct OidcClient {
+    config: OidcProviderConfig,
+    http: reqwest::Client,
+    metadata_ttl: Duration,
+    metadata: Mutex<Option<Cached<ProviderMetadata>>>,
+    jwks: Mutex<Option<Cached<JwkSet>>>,
+}
+
+impl OidcClient {
+    /// 创建客户端，发现文档在第一次使用时获取
+    pub fn new(config: OidcProviderConfig, http: reqwest::Client, metadata_ttl: Duration) -> Self {
+        Self {
+            config,
+            http,
+            metadata_ttl,
+            metadata: Mutex::new(None),
+            jwks: Mutex::new(None),
+        }
+    }
+
+    /// 提供方配置
+    pub fn config(&self) -> &OidcProviderConfig {
+        &self.config
+    }
+
+    /// 授权地址，浏览器重定向到该地址开始登录
+    pub async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<String, OidcError> {
+        let metadata = self.metadata().await?;
+        let scope = self.config.scopes.join(" ");
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            [
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("scope", scope.as_str()),
+                ("state", state),
+                ("nonce", nonce),
+                ("code_challenge", code_challenge),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|err| OidcError::Provider(anyhow::anyhow!("授权地址无效: {}", err)))?;
+        Ok(url.into())
+    }
+
+    /// 使用授权码及PKCE校验码兑换ID令牌，返回未经校验的ID令牌
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<String, OidcError> {
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let resp = self.http.post(&metadata.token_endpoint).form(&form).send().await?;
+        let status = resp.status();
+        let body = resp.bytes().await?;
+        let token = serde_json::from_slice::<TokenResponse>(&body)
+            .map_err(|err| OidcError::Provider(anyhow::anyhow!("令牌响应格式无效（HTTP {}）: {}", status, err)))?;
+
+        if let Some(error) = token.error {
+            let reason = match token.error_description {
+                Some(description) => format!("{}（{}）", error, description),
+                None => error,
+            };
+            return Err(OidcError::Rejected(reason));
+        }
+        if !status.is_success() {
+            return Err(OidcError::Provider(anyhow::anyhow!("令牌端点返回 HTTP {}", status)));
+        }
+        token
+            .id_token
+            .ok_or_else(|| OidcError::InvalidToken("令牌响应中没有ID令牌".to_string()))
+    }
+
+    /// 校验ID令牌的签名、签发方、受众、过期时间及 `nonce`，返回其中的声明
+    pub async fn validate_id_token(&self, id_token: &str, nonce: &str) -> Result<IdTokenClaims, OidcError> {
+        let header = decode_header(id_token).map_err(invalid_token)?;
+        if !SUPPORTED_ALGORITHMS.contains(&header.alg) {
+            return Err(OidcError::InvalidToken(format!("不支持的签名算法 {:?}", header.alg)));
+        }
+
+        // 找不到密钥时身份提供方可能已轮换密钥，重新获取一次JWKS
+        let kid = header.kid.as_deref();
+        let jwk = match find_key(&*self.jwks(false).await?, kid) {
+            Some(jwk) => jwk,
+            None => find_key(&*self.jwks(true).await?, kid)
+                .ok_or_else(|| OidcError::InvalidToken("未找到签名密钥".to_string()))?,
+        };
+        let key = DecodingKey::from_jwk(&jwk).map_err(invalid_token)?;
+
+        let metadata = self.metadata().await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = LEEWAY_SECS;
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(invalid_token)?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidToken("nonce不匹配".to_string()));
+        }
+        if claims.azp.as_ref().is_some_and(|azp| *azp != self.config.client_id) {
+            return Err(OidcError::InvalidToken("授权方不匹配".to_string()));
+        }
+        Ok(claims)
+    }
+
+    /// 发现文档，缓存过期后重新获取；文档中的签发方必须与配置一致
+    async fn metadata(&self) -> Result<Arc<ProviderMetadata>, OidcError> {
+        if let Some(metadata) = fresh(&self.metadata, self.metadata_ttl) {
+            return Ok(metadata);
+        }
+
+        let issuer = self.config.issuer.trim_end_matches('/');
+        let url = format!("{}/.well-known/openid-configuration", issuer);
+        let metadata: ProviderMetadata = self.fetch_json(&url).await?;
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(OidcError::Provider(anyhow::anyhow!(
+                "发现文档中的签发方 {} 与配置的 {} 不一致",
+                metadata.issuer,
+                self.config.issuer
+            )));
+        }
+        Ok(store(&self.metadata, metadata))
+    }
+
+    /// JWKS，缓存过期或 `refresh` 时重新获取
+    async fn jwks(&self, refresh: bool) -> Result<Arc<JwkSet>, OidcError> {
+        if !refresh {
+            if let Some(jwks) = fresh(&self.jwks, self.metadata_ttl) {
+                return Ok(jwks);
+            }
+        }
+
+        let metadata = self.metadata().await?;
+        let jwks: JwkSet = self.fetch_json(&metadata.jwks_uri).await?;
+        Ok(store(&self.jwks, jwks))
+    }
+
+    async fn fetch_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, OidcError> {
+        let resp = self.http.get(url).send().await?.error_for_status()?;
+        let body = resp.bytes().await?;
+        serde_json::from_slice(&body).map_err(|err| OidcError::Provider(anyhow::anyhow!("{} 的响应格式无效: {}", url, err)))
+    }
+}
+
+/// 未过期的缓存值
+fn fresh<T>(cache: &Mutex<Option<Cached<T>>>, ttl: Duration) -> Option<Arc<T>> {
+    let cache = cache.lock().expect("OIDC缓存锁已损坏");
+    cache
+        .as_ref()
+        .filter(|cached| cached.fetched_at.elapsed() < ttl)
+        .map(|cached| cached.value.clone())
+}
+
+fn store<T>(cache: &Mutex<Option<Cached<T>>>, value: T) -> Arc<T> {
+    let value = Arc::new(value);
+    *cache.lock().expect("OIDC缓存锁已损坏") = Some(Cached {
+        value: value.clone(),
+        fetched_at: Instant::now(),
+    });
+    value
+}
+
+/// 按 `kid` 查找签名密钥；令牌未指定 `kid` 时只在JWKS中恰好有一个签名密钥时使用该密钥
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+    let mut keys = jwks
+        .keys
+        .iter()
+        .filter(|jwk| !matches!(jwk.common.public_key_use, Some(PublicKeyUse::Encryption)));
+    match kid {
+        Some(kid) => keys.find(|jwk| jwk.common.key_id.as_deref() == Some(kid)).cloned(),
+        None => match (keys.next(), keys.next()) {
+            (Some(jwk), None) => Some(jwk.clone()),
+            _ => None,
+        },
+    }
+}
+
+fn invalid_token(err: jsonwebtoken::errors::Error) -> OidcError {
+    let reason = match err.kind() {
+        ErrorKind::ExpiredSignature => "已过期",
+        ErrorKind::ImmatureSignature => "尚未生效",
+        ErrorKind::InvalidIssuer => "签发方不匹配",
+        ErrorKind::InvalidAudience => "受众不匹配",
+        ErrorKind::InvalidSignature => "签名无效",
+        ErrorKind::MissingRequiredClaim(_) => "缺少必需的声明",
+        ErrorKind::InvalidAlgorithm => "签名算法与密钥不匹配",
+        _ => "格式无效",
+    };
+    OidcError::InvalidToken(reason.to_string())
+}
@@ -0,0 +1,36 @@
+//! OpenID Connect 客户端
+//!
+//! 使用授权码流程加PKCE（RFC 7636，`S256`）：发起登录时生成 `state`、`nonce` 及PKCE校验码，
+//! 回调时用授权码和校验码向身份提供方兑换ID令牌，并按 OpenID Connect Core 3.1.3.7 校验ID令牌的签名、
+//! 签发方、受众、过期时间及 `nonce`。发现文档及JWKS按提供方缓存，遇到未知的签名密钥时重新获取JWKS，
+//! 身份提供方轮换密钥后无需重启。
+//!
+//! 登录状态的保存及本地用户的创建、关联见 `services::OidcService`
+
+mod client;
+
+pub use client::{IdTokenClaims, OidcClient, OidcError};
+
+use data_encoding::BASE64URL_NOPAD;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+/// PKCE校验码的长度，RFC 7636 要求43至128个字符
+pub const CODE_VERIFIER_LEN: usize = 64;
+
+/// `state`、`nonce` 的长度
+pub const STATE_LEN: usize = 32;
+
+/// 由字母和数字组成的随机字符串，也是合法的PKCE校验码字符
+pub fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// PKCE校验码对应的 `S256` 挑战码：SHA-256摘要的Base64URL编码（无填充）
+pub fn code_challenge(code_verifier: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()))
+}
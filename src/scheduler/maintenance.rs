@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use chrono::Utc;
+
+use super::ScheduledTask;
+use crate::audit::AuditContext;
+use crate::config::RetentionConfig;
+use crate::services::{SessionService, UserService};
+
+/// 彻底清除超过保留期的软删除用户
+pub struct PurgeDeletedUsers {
+    users: UserService,
+    keep: chrono::Duration,
+    ctx: AuditContext,
+}
+
+impl PurgeDeletedUsers {
+    /// 按保留期配置创建任务，未启用自动清除时返回 `None`
+    pub fn new(users: UserService, retention: &RetentionConfig) -> Option<Self> {
+        let Some(keep) = retention.deleted_users() else {
+            tracing::info!("未启用软删除用户的自动清除");
+            return None;
+        };
+        let Ok(keep) = chrono::Duration::from_std(keep) else {
+            tracing::warn!("软删除用户的保留时间过长，不启用自动清除");
+            return None;
+        };
+
+        Some(Self {
+            users,
+            keep,
+            ctx: AuditContext::system("retention"),
+        })
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for PurgeDeletedUsers {
+    fn name(&self) -> &str {
+        "purge_deleted_users"
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let count = self.users.purge_deleted(&self.ctx, Utc::now() - self.keep).await?;
+        if count > 0 {
+            tracing::info!("已清除 {} 个超过保留期的软删除用户", count);
+        }
+        Ok(())
+    }
+}
+
+/// 删除已过期的浏览器会话
+///
+/// 过期会话在下次使用时也会被删除，这里清理不再使用的会话，避免存储持续增长
+pub struct PurgeExpiredSessions {
+    sessions: SessionService,
+}
+
+impl PurgeExpiredSessions {
+    /// 创建任务
+    pub fn new(sessions: SessionService) -> Self {
+        Self { sessions }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for PurgeExpiredSessions {
+    fn name(&self) -> &str {
+        "purge_expired_sessions"
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let count = self.sessions.purge_expired().await?;
+        if count > 0 {
+            tracing::info!("已删除 {} 个过期会话", count);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+//! 定时任务
+//!
+//! 按配置中的cron表达式（`scheduler.tasks`）定期执行清理、过期等维护任务。任务实现 `ScheduledTask`，
+//! 在启动时注册到 `Scheduler`，由后台任务（`tasks::spawn_scheduler`）检查并执行到期任务。
+//!
+//! 执行状态保存在 `scheduled_tasks` 表中，同时作为多实例间的执行锁：到期后只有抢到锁的实例执行，
+//! 实例中途崩溃时锁在 `lock_secs` 后到期。错过的多次执行（例如服务停机期间）只补执行一次
+
+mod maintenance;
+
+pub use maintenance::{PurgeDeletedUsers, PurgeExpiredSessions};
+
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use cron::Schedule;
+use futures::{future::join_all, FutureExt};
+
+use crate::config::SchedulerConfig;
+use crate::db::DbPool;
+use crate::models::scheduler::{ScheduledTaskStatus, TaskRunStatus};
+use crate::services::ServiceError;
+
+/// 定时任务
+#[async_trait]
+pub trait ScheduledTask: Send + Sync + 'static {
+    /// 任务名称，与配置 `scheduler.tasks` 中的键对应，全局唯一
+    fn name(&self) -> &str;
+
+    /// 执行一次，返回错误时记录为失败，等到下次计划时间再执行
+    async fn run(&self) -> anyhow::Result<()>;
+}
+
+/// 已抢到执行锁、等待执行的任务
+pub struct ClaimedTask {
+    name: String,
+    task: Arc<dyn ScheduledTask>,
+    started_at: String,
+}
+
+/// 数据库中的任务状态
+#[derive(sqlx::FromRow)]
+struct TaskRow {
+    name: String,
+    cron: Option<String>,
+    next_run_at: Option<i64>,
+    locked_by: Option<String>,
+    locked_until: Option<i64>,
+    last_started_at: Option<String>,
+    last_finished_at: Option<String>,
+    last_status: Option<String>,
+    last_error: Option<String>,
+    last_duration_ms: Option<i64>,
+}
+
+/// 定时任务调度器
+///
+/// 克隆开销很小，执行与管理接口共用；任务在启动时通过 `register` 注册
+#[derive(Clone)]
+pub struct Scheduler {
+    pool: DbPool,
+    config: SchedulerConfig,
+    /// 已启用的执行计划，键为任务名称
+    schedules: Arc<BTreeMap<String, Schedule>>,
+    tasks: Arc<RwLock<BTreeMap<String, Arc<dyn ScheduledTask>>>>,
+    /// 已注册的任务是否已写入 `scheduled_tasks`
+    synced: Arc<AtomicBool>,
+    /// 本实例的标识，记录在执行锁中
+    instance: String,
+}
+
+impl Scheduler {
+    /// 创建没有任务的调度器，cron表达式无效时返回错误
+    pub fn new(pool: DbPool, config: SchedulerConfig) -> anyhow::Result<Self> {
+        let mut schedules = BTreeMap::new();
+        for (name, schedule) in config.tasks.iter().filter(|(_, schedule)| schedule.enabled) {
+            let parsed = parse_cron(&schedule.cron).with_context(|| format!("定时任务 {} 的cron表达式无效", name))?;
+            schedules.insert(name.clone(), parsed);
+        }
+
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+        Ok(Self {
+            pool,
+            config,
+            schedules: Arc::new(schedules),
+            tasks: Arc::default(),
+            synced: Arc::default(),
+            instance: format!("{}:{}", host, std::process::id()),
+        })
+    }
+
+    /// 注册任务，同名任务重复注册时以后者为准
+    pub fn register<T: ScheduledTask>(&self, task: T) {
+        let name = task.name().to_string();
+        self.tasks.write().expect("定时任务锁已损坏").insert(name, Arc::new(task));
+        self.synced.store(false, Ordering::SeqCst);
+    }
+
+    /// 检查所有到期任务并执行，全部结束后返回各任务的执行结果
+    pub async fn run_due(&self) -> Result<Vec<(String, TaskRunStatus)>, ServiceError> {
+        let claimed = self.claim_due().await?;
+        let names: Vec<_> = claimed.iter().map(|task| task.name.clone()).collect();
+        let outcomes = join_all(claimed.into_iter().map(|task| self.execute(task))).await;
+
+        names
+            .into_iter()
+            .zip(outcomes)
+            .map(|(name, outcome)| Ok((name, outcome?)))
+            .collect()
+    }
+
+    /// 为到期的任务抢占执行锁，返回本实例抢到的任务
+    ///
+    /// 抢到锁时即推进下次执行时间，正在执行（锁未到期）的任务不会重复执行
+    pub async fn claim_due(&self) -> Result<Vec<ClaimedTask>, ServiceError> {
+        self.sync().await?;
+
+        let now = Utc::now();
+        let started_at = now.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let tasks = self.tasks.read().expect("定时任务锁已损坏").clone();
+
+        let mut claimed = Vec::new();
+        for (name, task) in tasks {
+            let result = sqlx::query(
+                "UPDATE scheduled_tasks SET next_run_at = ?1, locked_by = ?2, locked_until = ?3, \
+                 last_started_at = ?4, updated_at = ?4 \
+                 WHERE name = ?5 AND next_run_at <= ?6 AND (locked_until IS NULL OR locked_until <= ?6)",
+            )
+            .bind(self.next_run(&name, now))
+            .bind(&self.instance)
+            .bind(now.timestamp() + self.config.lock_secs as i64)
+            .bind(&started_at)
+            .bind(&name)
+            .bind(now.timestamp())
+            .execute(&self.pool)
+            .await?;
+
+            if result.rows_affected() == 1 {
+                claimed.push(ClaimedTask {
+                    name,
+                    task,
+                    started_at: started_at.clone(),
+                });
+            }
+        }
+        Ok(claimed)
+    }
+
+    /// 执行已抢到锁的任务并记录结果，任务panic视为执行失败
+    pub async fn execute(&self, claimed: ClaimedTask) -> Result<TaskRunStatus, ServiceError> {
+        let started = Instant::now();
+        let result = AssertUnwindSafe(claimed.task.run())
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("任务panic")));
+        let duration_ms = started.elapsed().as_millis() as i64;
+
+        let (status, error) = match result {
+            Ok(()) => {
+                tracing::info!("定时任务 {} 执行成功，耗时 {} 毫秒", claimed.name, duration_ms);
+                (TaskRunStatus::Succeeded, None)
+            }
+            Err(err) => {
+                tracing::error!("定时任务 {} 执行失败: {:#}", claimed.name, err);
+                (TaskRunStatus::Failed, Some(format!("{:#}", err)))
+            }
+        };
+
+        // 只释放本次执行持有的锁，锁到期后被其他实例抢到的不受影响
+        sqlx::query(
+            "UPDATE scheduled_tasks SET locked_by = NULL, locked_until = NULL, last_finished_at = ?1, \
+             last_status = ?2, last_error = ?3, last_duration_ms = ?4, updated_at = ?1 \
+             WHERE name = ?5 AND locked_by = ?6 AND last_started_at = ?7",
+        )
+        .bind(now())
+        .bind(status.as_str())
+        .bind(error)
+        .bind(duration_ms)
+        .bind(&claimed.name)
+        .bind(&self.instance)
+        .bind(&claimed.started_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(status)
+    }
+
+    /// 所有定时任务的执行状态，按名称排序
+    pub async fn list(&self) -> Result<Vec<ScheduledTaskStatus>, ServiceError> {
+        self.sync().await?;
+
+        let rows = sqlx::query_as::<_, TaskRow>(
+            "SELECT name, cron, next_run_at, locked_by, locked_until, last_started_at, last_finished_at, \
+             last_status, last_error, last_duration_ms FROM scheduled_tasks ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(status).collect())
+    }
+
+    /// 手动触发：将下次执行时间设为现在，由执行中的实例尽快执行
+    pub async fn trigger(&self, name: &str) -> Result<ScheduledTaskStatus, ServiceError> {
+        if !self.tasks.read().expect("定时任务锁已损坏").contains_key(name) {
+            return Err(ServiceError::NotFound(format!("Scheduled task {} not found", name)));
+        }
+        self.sync().await?;
+
+        let now = Utc::now();
+        let row = sqlx::query_as::<_, TaskRow>(
+            "UPDATE scheduled_tasks SET next_run_at = ?1, updated_at = ?2 \
+             WHERE name = ?3 AND (locked_until IS NULL OR locked_until <= ?1) \
+             RETURNING name, cron, next_run_at, locked_by, locked_until, last_started_at, last_finished_at, \
+                       last_status, last_error, last_duration_ms",
+        )
+        .bind(now.timestamp())
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(status)
+            .ok_or_else(|| ServiceError::Conflict(format!("定时任务 {} 正在执行", name)))
+    }
+
+    /// 将已注册的任务写入 `scheduled_tasks`
+    ///
+    /// cron表达式变化（包括启用、停用）时重新计算下次执行时间，否则保留原有状态
+    async fn sync(&self) -> Result<(), ServiceError> {
+        if self.synced.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let names: Vec<String> = self.tasks.read().expect("定时任务锁已损坏").keys().cloned().collect();
+        for name in self.config.tasks.keys().filter(|name| !names.contains(*name)) {
+            tracing::warn!("配置了未注册的定时任务 {}，将被忽略", name);
+        }
+
+        let now = Utc::now();
+        for name in &names {
+            let cron = self.schedules.contains_key(name).then(|| self.config.tasks[name].cron.clone());
+            sqlx::query(
+                "INSERT INTO scheduled_tasks (name, cron, next_run_at, updated_at) VALUES (?, ?, ?, ?) \
+                 ON CONFLICT (name) DO UPDATE SET cron = excluded.cron, next_run_at = excluded.next_run_at, \
+                 updated_at = excluded.updated_at WHERE cron IS NOT excluded.cron",
+            )
+            .bind(name)
+            .bind(cron)
+            .bind(self.next_run(name, now))
+            .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .execute(&self.pool)
+            .await?;
+        }
+
+        self.synced.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 任务在 `after` 之后的下次执行时间（Unix时间戳，秒），未按计划执行时返回 `None`
+    fn next_run(&self, name: &str, after: DateTime<Utc>) -> Option<i64> {
+        self.schedules
+            .get(name)
+            .and_then(|schedule| schedule.after(&after).next())
+            .map(|next| next.timestamp())
+    }
+}
+
+/// 解析cron表达式，省略秒的五段格式按第0秒执行
+pub fn parse_cron(expr: &str) -> anyhow::Result<Schedule> {
+    let expr = expr.trim();
+    let schedule = if expr.split_whitespace().count() == 5 {
+        Schedule::from_str(&format!("0 {}", expr))
+    } else {
+        Schedule::from_str(expr)
+    };
+    Ok(schedule?)
+}
+
+/// 数据库记录转为执行状态
+fn status(row: TaskRow) -> ScheduledTaskStatus {
+    let now = Utc::now().timestamp();
+    let running = row.locked_until.is_some_and(|until| until > now);
+
+    ScheduledTaskStatus {
+        name: row.name,
+        cron: row.cron,
+        next_run_at: row
+            .next_run_at
+            .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+            .map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        running,
+        // 锁已到期（执行中途崩溃）时不再显示持有者
+        locked_by: row.locked_by.filter(|_| running),
+        last_started_at: row.last_started_at,
+        last_finished_at: row.last_finished_at,
+        last_status: row.last_status.as_deref().and_then(TaskRunStatus::parse),
+        last_error: row.last_error,
+        last_duration_ms: row.last_duration_ms.map(|ms| ms as u64),
+    }
+}
+
+/// 当前时间（ISO 8601格式）
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
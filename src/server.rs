@@ -0,0 +1,83 @@
+//! 服务监听模块
+//!
+//! 根据配置创建公共监听器（可选TLS）和管理端监听器（TCP和/或Unix域套接字）
+
+use std::io;
+
+use poem::listener::{BoxListener, Listener, TcpListener};
+
+use crate::config::{AdminConfig, ServerConfig};
+use crate::tls;
+
+/// 创建公共监听器
+///
+/// 启用TLS时使用Rustls（支持HTTP/2及证书热加载），并可组合一个HTTP重定向监听器
+pub fn public_listener(config: &ServerConfig) -> io::Result<BoxListener> {
+    let tls_config = &config.tls;
+    if !tls_config.enabled {
+        return Ok(TcpListener::bind(config.listen_addr.clone()).boxed());
+    }
+
+    let https = TcpListener::bind(config.listen_addr.clone())
+        .rustls(tls::rustls_config_stream(tls_config.clone())?);
+
+    Ok(match &tls_config.redirect_http_addr {
+        Some(redirect_addr) => {
+            tracing::info!("HTTP重定向监听在 http://{}", redirect_addr);
+            https.combine(TcpListener::bind(redirect_addr.clone())).boxed()
+        }
+        None => https.boxed(),
+    })
+}
+
+/// 创建管理端监听器，未配置独立管理端时返回 `None`
+///
+/// 同时配置TCP地址和Unix域套接字时，两者通过 `combine` 组合为同一个监听器
+pub fn admin_listener(config: &AdminConfig) -> io::Result<Option<BoxListener>> {
+    let tcp = config
+        .listen_addr
+        .as_ref()
+        .map(|addr| TcpListener::bind(addr.clone()).boxed());
+
+    let unix = config
+        .unix_socket
+        .as_ref()
+        .map(|path| unix_listener(path, config.unix_socket_mode))
+        .transpose()?;
+
+    Ok(match (tcp, unix) {
+        (Some(tcp), Some(unix)) => Some(tcp.combine(unix).boxed()),
+        (tcp, unix) => tcp.or(unix),
+    })
+}
+
+/// 删除管理端Unix域套接字文件
+pub fn cleanup_admin_socket(config: &AdminConfig) {
+    if let Some(path) = &config.unix_socket {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(unix)]
+fn unix_listener(path: &str, mode: u32) -> io::Result<BoxListener> {
+    use poem::listener::UnixListener;
+    use std::os::unix::fs::PermissionsExt;
+
+    // 清理上次异常退出遗留的套接字文件，否则绑定会失败
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+
+    Ok(UnixListener::bind(path.to_string())
+        .with_permissions(std::fs::Permissions::from_mode(mode))
+        .boxed())
+}
+
+#[cfg(not(unix))]
+fn unix_listener(_path: &str, _mode: u32) -> io::Result<BoxListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "当前平台不支持Unix域套接字",
+    ))
+}
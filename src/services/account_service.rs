@@ -0,0 +1,255 @@
+//! 账号服务
+//!
+//! 邮箱验证、找回及重置密码，供各版本REST API和GraphQL共用
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use serde_json::Value;
+
+use super::user_service::{find_user, now, record_change, UserRow, AUDIT_ENTITY, USER_COLUMNS};
+use super::{ServiceError, UserService};
+use crate::accounts::{token_digest, AccountEmail, TokenPurpose, TokenSigner};
+use crate::audit::{AuditContext, AuditEntry};
+use crate::config::AccountsConfig;
+use crate::db::{DbConnection, DbPool};
+use crate::events::{DomainEvent, UserUpdated};
+use crate::jobs::JobQueue;
+use crate::mailer::{Mailer, ResetPassword, VerifyEmail};
+use crate::models::audit::AuditAction;
+use crate::models::auth::ResetPasswordRequest;
+use crate::models::user::User;
+
+/// 账号服务
+///
+/// 令牌的签发与校验见 `accounts` 模块；验证邮箱、重置密码与对应的审计记录、领域事件在同一个事务中写入
+#[derive(Clone)]
+pub struct AccountService {
+    pool: DbPool,
+    users: UserService,
+    jobs: JobQueue,
+    mailer: Mailer,
+    signer: TokenSigner,
+    config: Arc<AccountsConfig>,
+}
+
+impl AccountService {
+    /// 创建账号服务，邮件通过 `jobs` 中注册的 `AccountEmailHandler` 发送
+    pub fn new(
+        pool: DbPool,
+        users: UserService,
+        jobs: JobQueue,
+        mailer: Mailer,
+        signer: TokenSigner,
+        config: AccountsConfig,
+    ) -> Self {
+        Self {
+            pool,
+            users,
+            jobs,
+            mailer,
+            signer,
+            config: Arc::new(config),
+        }
+    }
+
+    /// 使用验证令牌验证邮箱，返回验证后的用户
+    ///
+    /// 令牌签发后邮箱被修改时令牌失效；邮箱已验证时直接返回用户
+    pub async fn verify_email(&self, ctx: &AuditContext, token: &str) -> Result<User, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let (user_id, email) = self.consume(&mut tx, TokenPurpose::VerifyEmail, token).await?;
+        let before = find_user(&mut *tx, user_id)
+            .await?
+            .filter(|user| user.deleted_at.is_none() && user.email == email)
+            .ok_or_else(invalid_token)?;
+        if before.email_verified_at.is_some() {
+            tx.commit().await?;
+            return Ok(before);
+        }
+
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "UPDATE users SET email_verified_at = ?1, updated_at = ?1, version = version + 1 \
+             WHERE id = ?2 RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(now())
+        .bind(user_id as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        let user = User::from(row);
+
+        let entry = AuditEntry::new(AuditAction::Update, AUDIT_ENTITY, user_id).diff(Some(&before), Some(&user));
+        let event = DomainEvent::UserUpdated(UserUpdated {
+            before,
+            after: user.clone(),
+            password_changed: false,
+        });
+        record_change(&mut tx, ctx, entry, event).await?;
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    /// 找回密码：邮箱对应的用户存在时发送密码重置邮件
+    ///
+    /// 无论邮箱是否存在都返回成功，避免通过该接口探测已注册的邮箱
+    pub async fn forgot_password(&self, email: &str) -> Result<(), ServiceError> {
+        let id: Option<i64> = sqlx::query_scalar("SELECT id FROM users WHERE email = ? AND deleted_at IS NULL")
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?;
+        match id {
+            Some(id) => self.enqueue(id as u64, TokenPurpose::ResetPassword).await,
+            None => Ok(()),
+        }
+    }
+
+    /// 使用重置令牌设置新密码
+    ///
+    /// 请求会按 `ResetPasswordRequest` 的字段规则重新校验，供未经OpenAPI解析的调用方（如GraphQL）共用；
+    /// 成功后该用户其余未使用的重置令牌一并失效，已登录的会话全部注销
+    pub async fn reset_password(&self, ctx: &AuditContext, req: ResetPasswordRequest) -> Result<(), ServiceError> {
+        let req = ResetPasswordRequest::parse_from_json(Some(req.to_json().unwrap_or(Value::Null)))
+            .map_err(|err| ServiceError::Validation(err.into_message()))?;
+        // 签名无效时不必计算密码哈希
+        self.signer
+            .verify(TokenPurpose::ResetPassword, &req.token)
+            .ok_or_else(invalid_token)?;
+        let password_hash = self.users.hash_password(req.password).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let (user_id, _) = self.consume(&mut tx, TokenPurpose::ResetPassword, &req.token).await?;
+        let before = find_user(&mut *tx, user_id)
+            .await?
+            .filter(|user| user.deleted_at.is_none())
+            .ok_or_else(invalid_token)?;
+
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "UPDATE users SET password_hash = ?1, updated_at = ?2, version = version + 1 \
+             WHERE id = ?3 RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(password_hash)
+        .bind(now())
+        .bind(user_id as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        let user = User::from(row);
+
+        sqlx::query("UPDATE user_tokens SET used_at = ? WHERE user_id = ? AND purpose = ? AND used_at IS NULL")
+            .bind(now())
+            .bind(user_id as i64)
+            .bind(TokenPurpose::ResetPassword.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        let entry = AuditEntry::new(AuditAction::Update, AUDIT_ENTITY, user_id)
+            .diff(Some(&before), Some(&user))
+            .redacted("password");
+        let event = DomainEvent::UserUpdated(UserUpdated {
+            before,
+            after: user,
+            password_changed: true,
+        });
+        record_change(&mut tx, ctx, entry, event).await?;
+        tx.commit().await?;
+        self.users.revoke_sessions(user_id).await?;
+        Ok(())
+    }
+
+    /// 签发令牌并立即发送账号邮件，由 `AccountEmailHandler` 调用
+    ///
+    /// 用户已删除或邮箱已验证（验证邮件）时不再发送
+    pub async fn send_email(&self, user_id: u64, purpose: TokenPurpose) -> anyhow::Result<()> {
+        let Some(user) = find_user(&self.pool, user_id).await?.filter(|user| user.deleted_at.is_none()) else {
+            return Ok(());
+        };
+        if purpose == TokenPurpose::VerifyEmail && user.email_verified_at.is_some() {
+            return Ok(());
+        }
+
+        let ttl = match purpose {
+            TokenPurpose::VerifyEmail => self.config.verify_email_ttl(),
+            TokenPurpose::ResetPassword => self.config.reset_password_ttl(),
+        };
+        let expires_at = Utc::now().timestamp() + ttl.as_secs() as i64;
+        let token = self.signer.issue(purpose, user_id, expires_at);
+
+        sqlx::query(
+            "INSERT INTO user_tokens (user_id, purpose, token_hash, email, expires_at, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(user_id as i64)
+        .bind(purpose.as_str())
+        .bind(token_digest(&token))
+        .bind(&user.email)
+        .bind(expires_at)
+        .bind(now())
+        .execute(&self.pool)
+        .await?;
+
+        match purpose {
+            TokenPurpose::VerifyEmail => {
+                let template = VerifyEmail {
+                    username: user.username,
+                    url: self.config.verify_email_url.replace("{token}", &token),
+                    expires_hours: ttl.as_secs().div_ceil(3600),
+                };
+                self.mailer.send(&user.email, None, &template).await
+            }
+            TokenPurpose::ResetPassword => {
+                let template = ResetPassword {
+                    username: user.username,
+                    url: self.config.reset_password_url.replace("{token}", &token),
+                    expires_minutes: ttl.as_secs().div_ceil(60),
+                };
+                self.mailer.send(&user.email, None, &template).await
+            }
+        }
+    }
+
+    /// 将账号邮件发送任务入队
+    async fn enqueue(&self, user_id: u64, purpose: TokenPurpose) -> Result<(), ServiceError> {
+        let job = AccountEmail { user_id, purpose };
+        self.jobs.enqueue(&job, job.options()).await?;
+        Ok(())
+    }
+
+    /// 校验并使用令牌，返回令牌对应的用户ID及签发时的邮箱
+    ///
+    /// 签名无效、已过期或已使用时返回校验错误；令牌在调用方的事务中标记为已使用，事务回滚时可再次使用
+    async fn consume(
+        &self,
+        conn: &mut DbConnection,
+        purpose: TokenPurpose,
+        token: &str,
+    ) -> Result<(u64, String), ServiceError> {
+        let now_ts = Utc::now().timestamp();
+        let signed = self.signer.verify(purpose, token).ok_or_else(invalid_token)?;
+        if signed.expires_at <= now_ts {
+            return Err(invalid_token());
+        }
+
+        let row: Option<(i64, String)> = sqlx::query_as(
+            "UPDATE user_tokens SET used_at = ?1 \
+             WHERE token_hash = ?2 AND purpose = ?3 AND user_id = ?4 AND used_at IS NULL AND expires_at > ?5 \
+             RETURNING user_id, email",
+        )
+        .bind(now())
+        .bind(token_digest(token))
+        .bind(purpose.as_str())
+        .bind(signed.user_id as i64)
+        .bind(now_ts)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let (user_id, email) = row.ok_or_else(invalid_token)?;
+        Ok((user_id as u64, email))
+    }
+}
+
+/// 令牌无效、已过期或已使用，不区分具体原因
+fn invalid_token() -> ServiceError {
+    ServiceError::Validation("令牌无效或已过期".to_string())
+}
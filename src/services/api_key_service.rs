@@ -0,0 +1,265 @@
+//! API密钥服务
+//!
+//! 密钥的创建、查询、吊销及请求认证；认证中间件见 `middlewares::ApiKeyAuth`
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+use super::ServiceError;
+use crate::audit::{self, AuditContext, AuditEntry};
+use crate::auth::Principal;
+use crate::config::ApiKeysConfig;
+use crate::db::DbPool;
+use crate::models::api_key::{ApiKey, ApiKeyScope, CreateApiKeyRequest, CreatedApiKey};
+use crate::models::audit::AuditAction;
+
+/// 审计记录中的实体类型
+const AUDIT_ENTITY: &str = "api_key";
+
+/// 查询密钥时返回的列（不含摘要）
+const API_KEY_COLUMNS: &str = "id, name, prefix, scopes, expires_at, last_used_at, revoked_at, created_at";
+
+/// 前缀中随机部分的长度
+const LOOKUP_LEN: usize = 8;
+
+/// 密钥中保密部分的长度
+const SECRET_LEN: usize = 32;
+
+/// API密钥服务
+#[derive(Debug, Clone)]
+pub struct ApiKeyService {
+    pool: DbPool,
+    config: Arc<ApiKeysConfig>,
+}
+
+/// 数据库中的密钥记录
+#[derive(sqlx::FromRow)]
+struct ApiKeyRow {
+    id: i64,
+    name: String,
+    prefix: String,
+    scopes: String,
+    expires_at: Option<String>,
+    last_used_at: Option<String>,
+    revoked_at: Option<String>,
+    created_at: String,
+}
+
+impl From<ApiKeyRow> for ApiKey {
+    fn from(row: ApiKeyRow) -> Self {
+        // 未知的授权范围（例如已下线的范围）不再授予
+        let scopes = serde_json::from_str::<Vec<String>>(&row.scopes)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|value| ApiKeyScope::parse(value))
+            .collect();
+
+        Self {
+            id: row.id as u64,
+            name: row.name,
+            prefix: row.prefix,
+            scopes,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+impl ApiKeyService {
+    /// 创建API密钥服务
+    pub fn new(pool: DbPool, config: ApiKeysConfig) -> Self {
+        Self {
+            pool,
+            config: Arc::new(config),
+        }
+    }
+
+    /// 创建密钥，返回的密钥原文不会保存
+    pub async fn create(&self, ctx: &AuditContext, req: CreateApiKeyRequest) -> Result<CreatedApiKey, ServiceError> {
+        authorize(&ctx.actor)?;
+        let name = req.name.trim().to_string();
+        if name.is_empty() {
+            return Err(ServiceError::Validation("名称不能为空".to_string()));
+        }
+        let expires_at = req.expires_at.as_deref().map(parse_expires_at).transpose()?;
+
+        let mut scopes = Vec::new();
+        for scope in req.scopes {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        let scopes_json = serde_json::to_string(&scopes.iter().map(ApiKeyScope::as_str).collect::<Vec<_>>())
+            .expect("字符串数组序列化不会失败");
+
+        let (prefix, key) = self.generate_key();
+        let now = now();
+        let mut tx = self.pool.begin().await?;
+        let api_key = ApiKey::from(
+            sqlx::query_as::<_, ApiKeyRow>(&format!(
+                "INSERT INTO api_keys (name, prefix, key_hash, scopes, expires_at, created_at, updated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6) RETURNING {}",
+                API_KEY_COLUMNS
+            ))
+            .bind(name)
+            .bind(prefix)
+            .bind(key_digest(&key))
+            .bind(scopes_json)
+            .bind(expires_at)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?,
+        );
+
+        let entry = AuditEntry::new(AuditAction::Create, AUDIT_ENTITY, api_key.id).diff(None, Some(&api_key));
+        audit::record(&mut tx, ctx, entry).await?;
+        tx.commit().await?;
+        Ok(CreatedApiKey { api_key, key })
+    }
+
+    /// 查询所有密钥（包括已吊销的）
+    pub async fn list(&self, actor: &Principal) -> Result<Vec<ApiKey>, ServiceError> {
+        authorize(actor)?;
+        let rows = sqlx::query_as::<_, ApiKeyRow>(&format!("SELECT {} FROM api_keys ORDER BY id", API_KEY_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(ApiKey::from).collect())
+    }
+
+    /// 根据ID查询密钥，管理其他密钥需要 `admin` 范围，查询调用方自己的密钥不需要
+    pub async fn get(&self, actor: &Principal, id: u64) -> Result<ApiKey, ServiceError> {
+        if !matches!(actor, Principal::ApiKey { id: own, .. } if *own == id) {
+            authorize(actor)?;
+        }
+        self.find(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("API密钥 {} 不存在", id)))
+    }
+
+    /// 吊销密钥，吊销后立即失效且不能恢复
+    pub async fn revoke(&self, ctx: &AuditContext, id: u64) -> Result<ApiKey, ServiceError> {
+        authorize(&ctx.actor)?;
+        let before = self.get(&ctx.actor, id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query_as::<_, ApiKeyRow>(&format!(
+            "UPDATE api_keys SET revoked_at = ?1, updated_at = ?1 WHERE id = ?2 AND revoked_at IS NULL RETURNING {}",
+            API_KEY_COLUMNS
+        ))
+        .bind(now())
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Err(ServiceError::Conflict(format!("API密钥 {} 已吊销", id)));
+        };
+        let api_key = ApiKey::from(row);
+
+        let entry = AuditEntry::new(AuditAction::Update, AUDIT_ENTITY, id).diff(Some(&before), Some(&api_key));
+        audit::record(&mut tx, ctx, entry).await?;
+        tx.commit().await?;
+        Ok(api_key)
+    }
+
+    /// 认证请求中的密钥，返回对应的调用方身份；密钥不存在、已过期或已吊销时返回 `None`
+    ///
+    /// 按完整密钥的摘要查找，修改配置的前缀不影响已创建的密钥；最后使用时间按 `last_used_interval_secs` 的间隔更新
+    pub async fn authenticate(&self, key: &str) -> Result<Option<Principal>, ServiceError> {
+        let row = sqlx::query_as::<_, ApiKeyRow>(&format!(
+            "SELECT {} FROM api_keys WHERE key_hash = ? AND revoked_at IS NULL",
+            API_KEY_COLUMNS
+        ))
+        .bind(key_digest(key))
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(api_key) = row.map(ApiKey::from) else {
+            return Ok(None);
+        };
+        if api_key.expires_at.as_deref().is_some_and(is_expired) {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let threshold = now - Duration::seconds(self.config.last_used_interval_secs as i64);
+        sqlx::query("UPDATE api_keys SET last_used_at = ?1 WHERE id = ?2 AND (last_used_at IS NULL OR last_used_at < ?3)")
+            .bind(format_time(now))
+            .bind(api_key.id as i64)
+            .bind(format_time(threshold))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(Some(Principal::ApiKey {
+            id: api_key.id,
+            name: api_key.name,
+            scopes: api_key.scopes,
+        }))
+    }
+
+    async fn find(&self, id: u64) -> Result<Option<ApiKey>, ServiceError> {
+        Ok(sqlx::query_as::<_, ApiKeyRow>(&format!("SELECT {} FROM api_keys WHERE id = ?", API_KEY_COLUMNS))
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(ApiKey::from))
+    }
+
+    /// 生成密钥，返回前缀及完整密钥，格式为 `{配置的前缀}_{8位随机字符}_{32位随机字符}`
+    fn generate_key(&self) -> (String, String) {
+        let prefix = format!("{}_{}", self.config.prefix, random_string(LOOKUP_LEN).to_lowercase());
+        let key = format!("{}_{}", prefix, random_string(SECRET_LEN));
+        (prefix, key)
+    }
+}
+
+/// 管理API密钥需要管理权限：用户须为管理员，API密钥须具有 `admin` 范围，避免低权限的密钥创建高权限的密钥
+fn authorize(actor: &Principal) -> Result<(), ServiceError> {
+    match actor {
+        Principal::ApiKey { .. } if !actor.is_admin() => {
+            Err(ServiceError::Forbidden("管理API密钥需要 admin 授权范围".to_string()))
+        }
+        _ => actor.require_admin(),
+    }
+}
+
+/// 校验过期时间并统一格式
+fn parse_expires_at(value: &str) -> Result<String, ServiceError> {
+    let time = DateTime::parse_from_rfc3339(value)
+        .map_err(|_| ServiceError::Validation(format!("过期时间格式无效: {}", value)))?
+        .with_timezone(&Utc);
+    if time <= Utc::now() {
+        return Err(ServiceError::Validation("过期时间必须晚于当前时间".to_string()));
+    }
+    Ok(format_time(time))
+}
+
+/// 过期时间是否已到，无法解析时视为已过期
+fn is_expired(expires_at: &str) -> bool {
+    DateTime::parse_from_rfc3339(expires_at).map_or(true, |time| time <= Utc::now())
+}
+
+/// 由字母和数字组成的随机字符串
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// 密钥的SHA-256摘要
+fn key_digest(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn now() -> String {
+    format_time(Utc::now())
+}
@@ -0,0 +1,352 @@
+//! 两步验证服务
+//!
+//! TOTP的绑定、启用、停用与恢复码管理，供各版本REST API和GraphQL共用；
+//! 登录流程在密码校验通过后调用 `requirement` 决定下一步，需要时再调用 `verify` 校验验证码。
+//! 查询及管理接口只允许用户本人或具有管理权限的调用方操作
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use rand::Rng;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use super::user_service::{now, AUDIT_ENTITY};
+use super::{ServiceError, UserService};
+use crate::audit::{self, AuditContext, AuditEntry};
+use crate::auth::totp::{self, Totp, DIGITS, PERIOD};
+use crate::auth::Principal;
+use crate::config::MfaConfig;
+use crate::db::{DbConnection, DbPool};
+use crate::models::audit::AuditAction;
+use crate::models::mfa::{MfaEnrollment, MfaStatus, RecoveryCodesResponse};
+use crate::models::user::User;
+
+/// 恢复码使用的字符，去掉了容易混淆的 `0`、`1`、`i`、`l`、`o`
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// 恢复码每段的长度，恢复码由两段组成，例如 `k7m2p-x9q4t`
+const RECOVERY_CODE_GROUP_LEN: usize = 5;
+
+/// 登录时对两步验证的要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MfaRequirement {
+    /// 未启用且角色不要求，密码校验通过即可登录
+    NotRequired,
+    /// 已启用，需要再提交验证码或恢复码
+    Verify,
+    /// 角色要求启用但尚未启用，登录后的会话只能访问绑定两步验证的接口
+    Enroll,
+}
+
+/// 通过校验的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MfaMethod {
+    /// 验证器应用的验证码
+    Totp,
+    /// 恢复码，使用后失效
+    RecoveryCode,
+}
+
+/// 两步验证服务
+#[derive(Clone)]
+pub struct MfaService {
+    pool: DbPool,
+    users: UserService,
+    config: Arc<MfaConfig>,
+}
+
+/// 用户的两步验证记录
+#[derive(sqlx::FromRow)]
+struct MfaRow {
+    secret: String,
+    enabled_at: Option<String>,
+}
+
+impl MfaService {
+    /// 创建两步验证服务
+    pub fn new(pool: DbPool, users: UserService, config: MfaConfig) -> Self {
+        Self {
+            pool,
+            users,
+            config: Arc::new(config),
+        }
+    }
+
+    /// 查询用户的两步验证状态
+    pub async fn status(&self, actor: &Principal, user_id: u64) -> Result<MfaStatus, ServiceError> {
+        actor.require_self_or_admin(user_id)?;
+        let user = self.users.get(user_id).await?;
+        let row = self.find(&self.pool, user_id).await?;
+        let remaining: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mfa_recovery_codes WHERE user_id = ? AND used_at IS NULL",
+        )
+        .bind(user_id as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let enabled_at = row.and_then(|row| row.enabled_at);
+        Ok(MfaStatus {
+            enabled: enabled_at.is_some(),
+            enabled_at,
+            required: self.config.is_required(user.role),
+            recovery_codes_remaining: remaining as u32,
+        })
+    }
+
+    /// 开始绑定：生成新的密钥，提交验证码确认后才启用；重复调用时替换尚未确认的密钥
+    pub async fn enroll(&self, actor: &Principal, user_id: u64) -> Result<MfaEnrollment, ServiceError> {
+        actor.require_self_or_admin(user_id)?;
+        let user = self.users.get(user_id).await?;
+        let secret = Totp::generate_secret();
+        let now = now();
+
+        let result = sqlx::query(
+            "INSERT INTO user_mfa (user_id, secret, created_at, updated_at) VALUES (?1, ?2, ?3, ?3) \
+             ON CONFLICT (user_id) DO UPDATE SET secret = ?2, last_used_step = NULL, created_at = ?3, updated_at = ?3 \
+             WHERE user_mfa.enabled_at IS NULL",
+        )
+        .bind(user_id as i64)
+        .bind(&secret)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(ServiceError::Conflict("已启用两步验证，如需更换设备请先停用".to_string()));
+        }
+
+        Ok(MfaEnrollment {
+            otpauth_uri: totp::otpauth_uri(&self.config.issuer, &user.username, &secret),
+            secret,
+            digits: DIGITS,
+            period: PERIOD,
+        })
+    }
+
+    /// 提交验证器应用显示的验证码确认绑定并启用，返回新生成的恢复码
+    pub async fn enable(&self, ctx: &AuditContext, user_id: u64, code: &str) -> Result<RecoveryCodesResponse, ServiceError> {
+        ctx.actor.require_self_or_admin(user_id)?;
+        self.users.get(user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let row = match self.find(&mut *tx, user_id).await? {
+            Some(row) if row.enabled_at.is_none() => row,
+            Some(_) => return Err(ServiceError::Conflict("已启用两步验证".to_string())),
+            None => return Err(ServiceError::Conflict("请先开始绑定两步验证".to_string())),
+        };
+        self.check_code(&mut tx, user_id, &row, code, false).await?;
+
+        sqlx::query("UPDATE user_mfa SET enabled_at = ?1, updated_at = ?1 WHERE user_id = ?2")
+            .bind(now())
+            .bind(user_id as i64)
+            .execute(&mut *tx)
+            .await?;
+        let codes = self.replace_recovery_codes(&mut tx, user_id).await?;
+
+        let entry = mfa_entry(user_id, false, true).redacted("mfa_recovery_codes");
+        audit::record(&mut tx, ctx, entry).await?;
+        tx.commit().await?;
+        Ok(RecoveryCodesResponse { codes })
+    }
+
+    /// 停用两步验证，需要验证码或恢复码；角色要求启用时不能停用
+    pub async fn disable(&self, ctx: &AuditContext, user_id: u64, code: &str) -> Result<(), ServiceError> {
+        ctx.actor.require_self_or_admin(user_id)?;
+        let user = self.users.get(user_id).await?;
+        if self.config.is_required(user.role) {
+            return Err(ServiceError::Conflict(format!(
+                "角色 {} 必须启用两步验证，不能停用",
+                user.role.as_str()
+            )));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let row = self.enabled(&mut tx, user_id).await?;
+        self.check_code(&mut tx, user_id, &row, code, true).await?;
+
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = ?")
+            .bind(user_id as i64)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM user_mfa WHERE user_id = ?")
+            .bind(user_id as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        audit::record(&mut tx, ctx, mfa_entry(user_id, true, false)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 重新生成恢复码，需要验证码或恢复码，原有的恢复码全部失效
+    pub async fn regenerate_recovery_codes(
+        &self,
+        ctx: &AuditContext,
+        user_id: u64,
+        code: &str,
+    ) -> Result<RecoveryCodesResponse, ServiceError> {
+        ctx.actor.require_self_or_admin(user_id)?;
+        self.users.get(user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let row = self.enabled(&mut tx, user_id).await?;
+        self.check_code(&mut tx, user_id, &row, code, true).await?;
+        let codes = self.replace_recovery_codes(&mut tx, user_id).await?;
+
+        let entry = AuditEntry::new(AuditAction::Update, AUDIT_ENTITY, user_id).redacted("mfa_recovery_codes");
+        audit::record(&mut tx, ctx, entry).await?;
+        tx.commit().await?;
+        Ok(RecoveryCodesResponse { codes })
+    }
+
+    /// 登录时对两步验证的要求
+    pub async fn requirement(&self, user: &User) -> Result<MfaRequirement, ServiceError> {
+        let enabled = match user.id {
+            Some(id) => self.find(&self.pool, id).await?.is_some_and(|row| row.enabled_at.is_some()),
+            None => false,
+        };
+        Ok(if enabled {
+            MfaRequirement::Verify
+        } else if self.config.is_required(user.role) {
+            MfaRequirement::Enroll
+        } else {
+            MfaRequirement::NotRequired
+        })
+    }
+
+    /// 角色要求启用两步验证但尚未启用，此时用户的会话只能用于完成绑定
+    pub async fn enrollment_required(&self, user: &User) -> Result<bool, ServiceError> {
+        if !self.config.is_required(user.role) {
+            return Ok(false);
+        }
+        Ok(self.requirement(user).await? == MfaRequirement::Enroll)
+    }
+
+    /// 登录时校验验证码或恢复码，返回通过校验的方式；同一验证码及恢复码只能使用一次
+    pub async fn verify(&self, user_id: u64, code: &str) -> Result<MfaMethod, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let row = self.enabled(&mut tx, user_id).await?;
+        let method = self.check_code(&mut tx, user_id, &row, code, true).await?;
+        tx.commit().await?;
+        Ok(method)
+    }
+
+    /// 查找用户的两步验证记录，包括尚未确认的绑定
+    async fn find<'e>(
+        &self,
+        executor: impl sqlx::SqliteExecutor<'e>,
+        user_id: u64,
+    ) -> Result<Option<MfaRow>, ServiceError> {
+        Ok(sqlx::query_as::<_, MfaRow>("SELECT secret, enabled_at FROM user_mfa WHERE user_id = ?")
+            .bind(user_id as i64)
+            .fetch_optional(executor)
+            .await?)
+    }
+
+    /// 已启用的两步验证记录，未启用时返回冲突错误
+    async fn enabled(&self, conn: &mut DbConnection, user_id: u64) -> Result<MfaRow, ServiceError> {
+        self.find(&mut *conn, user_id)
+            .await?
+            .filter(|row| row.enabled_at.is_some())
+            .ok_or_else(|| ServiceError::Conflict("未启用两步验证".to_string()))
+    }
+
+    /// 校验验证码，`allow_recovery` 时也接受未使用过的恢复码
+    ///
+    /// 通过校验的验证码所在的步数会被记录，同一步及更早的验证码不能再次使用
+    async fn check_code(
+        &self,
+        conn: &mut DbConnection,
+        user_id: u64,
+        row: &MfaRow,
+        code: &str,
+        allow_recovery: bool,
+    ) -> Result<MfaMethod, ServiceError> {
+        let totp = Totp::from_base32(&row.secret)
+            .ok_or_else(|| ServiceError::internal(anyhow::anyhow!("用户 {} 的TOTP密钥无效", user_id)))?;
+
+        if let Some(step) = totp.verify(code, Utc::now().timestamp(), self.config.skew_steps) {
+            let updated = sqlx::query(
+                "UPDATE user_mfa SET last_used_step = ?1, updated_at = ?2 \
+                 WHERE user_id = ?3 AND (last_used_step IS NULL OR last_used_step < ?1)",
+            )
+            .bind(step as i64)
+            .bind(now())
+            .bind(user_id as i64)
+            .execute(&mut *conn)
+            .await?;
+            if updated.rows_affected() == 1 {
+                return Ok(MfaMethod::Totp);
+            }
+        } else if allow_recovery {
+            let used = sqlx::query(
+                "UPDATE mfa_recovery_codes SET used_at = ? WHERE user_id = ? AND code_hash = ? AND used_at IS NULL",
+            )
+            .bind(now())
+            .bind(user_id as i64)
+            .bind(recovery_code_digest(code))
+            .execute(&mut *conn)
+            .await?;
+            if used.rows_affected() == 1 {
+                return Ok(MfaMethod::RecoveryCode);
+            }
+        }
+
+        Err(ServiceError::Validation("验证码无效".to_string()))
+    }
+
+    /// 生成新的恢复码并替换原有的恢复码，返回恢复码原文
+    async fn replace_recovery_codes(&self, conn: &mut DbConnection, user_id: u64) -> Result<Vec<String>, ServiceError> {
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = ?")
+            .bind(user_id as i64)
+            .execute(&mut *conn)
+            .await?;
+
+        let now = now();
+        let mut codes = Vec::with_capacity(self.config.recovery_codes as usize);
+        while codes.len() < self.config.recovery_codes as usize {
+            let code = generate_recovery_code();
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO mfa_recovery_codes (user_id, code_hash, created_at) VALUES (?, ?, ?)",
+            )
+            .bind(user_id as i64)
+            .bind(recovery_code_digest(&code))
+            .bind(&now)
+            .execute(&mut *conn)
+            .await?;
+            // 极少数情况下随机生成了重复的恢复码，跳过重新生成
+            if inserted.rows_affected() == 1 {
+                codes.push(code);
+            }
+        }
+        Ok(codes)
+    }
+}
+
+/// 启用或停用两步验证的审计记录
+fn mfa_entry(user_id: u64, before: bool, after: bool) -> AuditEntry {
+    AuditEntry::new(AuditAction::Update, AUDIT_ENTITY, user_id)
+        .diff(Some(&json!({ "mfa_enabled": before })), Some(&json!({ "mfa_enabled": after })))
+}
+
+/// 生成恢复码，例如 `k7m2p-x9q4t`
+fn generate_recovery_code() -> String {
+    let mut rng = rand::thread_rng();
+    let mut group = || -> String {
+        (0..RECOVERY_CODE_GROUP_LEN)
+            .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+            .collect()
+    };
+    let first = group();
+    format!("{}-{}", first, group())
+}
+
+/// 恢复码的SHA-256摘要，忽略大小写、空格及分隔符
+fn recovery_code_digest(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
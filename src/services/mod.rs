@@ -1,6 +1,87 @@
 //! 服务模块
-//! 
+//!
 //! 包含所有业务逻辑的实现
 
-// 在实际项目中，这里会包含各种服务实现
-// 例如：pub mod user_service;
+pub mod account_service;
+pub mod api_key_service;
+pub mod mfa_service;
+pub mod oidc_service;
+pub mod session_service;
+pub mod user_service;
+pub mod webhook_service;
+
+pub use account_service::AccountService;
+pub use api_key_service::ApiKeyService;
+pub use mfa_service::{MfaMethod, MfaRequirement, MfaService};
+pub use oidc_service::OidcService;
+pub use session_service::{AuthenticatedSession, IssuedSession, SessionService};
+pub use user_service::{UserFilter, UserService};
+pub use webhook_service::WebhookService;
+
+/// 业务错误
+///
+/// 由各API层转换为对应的响应格式（REST的 `ApiResponse`、GraphQL的错误扩展）
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceError {
+    /// 资源不存在
+    #[error("{0}")]
+    NotFound(String),
+
+    /// 参数校验失败
+    #[error("{0}")]
+    Validation(String),
+
+    /// 调用方未认证或认证失败，例如未登录、用户名或密码错误
+    #[error("{0}")]
+    Unauthorized(String),
+
+    /// 调用方没有执行该操作的权限，例如API密钥缺少授权范围
+    #[error("{0}")]
+    Forbidden(String),
+
+    /// 资源冲突，例如唯一字段重复
+    #[error("{0}")]
+    Conflict(String),
+
+    /// 前置条件不满足，例如资源版本与调用方期望的不一致
+    #[error("{0}")]
+    PreconditionFailed(String),
+
+    /// 内部错误，详细原因只记录日志，不返回给调用方
+    #[error("服务器内部错误")]
+    Internal(#[source] anyhow::Error),
+}
+
+impl ServiceError {
+    /// 对应的HTTP状态码
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::NotFound(_) => 404,
+            Self::Validation(_) => 400,
+            Self::Unauthorized(_) => 401,
+            Self::Forbidden(_) => 403,
+            Self::Conflict(_) => 409,
+            Self::PreconditionFailed(_) => 412,
+            Self::Internal(_) => 500,
+        }
+    }
+
+    /// 包装内部错误并记录日志
+    pub fn internal(err: impl Into<anyhow::Error>) -> Self {
+        let err = err.into();
+        tracing::error!("内部错误: {:#}", err);
+        Self::Internal(err)
+    }
+}
+
+impl From<sqlx::Error> for ServiceError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => Self::NotFound("资源不存在".to_string()),
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                Self::Conflict("资源已存在".to_string())
+            }
+            err => Self::internal(err),
+        }
+    }
+}
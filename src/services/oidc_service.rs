@@ -0,0 +1,375 @@
+//! 外部身份提供方登录服务
+//!
+//! OpenID Connect 登录：`authorize` 生成授权地址并保存本次登录的状态，`complete` 在回调时兑换授权码、
+//! 校验ID令牌，再按身份查找本地用户；首次登录时按已验证的邮箱关联已有用户，或按配置创建新用户。
+//! 协议细节见 `oidc` 模块
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use serde_json::Value;
+
+use super::user_service::{find_user, insert_user, now, NewUser, UserRow, USER_COLUMNS};
+use super::{ServiceError, UserService};
+use crate::audit::{self, AuditContext, AuditEntry};
+use crate::auth::constant_time_eq;
+use crate::config::OidcConfig;
+use crate::db::{DbConnection, DbPool};
+use crate::models::audit::AuditAction;
+use crate::models::oidc::{OidcAuthorization, OidcLoginResponse, OidcProvider, UserIdentity};
+use crate::models::user::{CreateUserRequest, User, UserRole};
+use crate::oidc::{code_challenge, random_token, IdTokenClaims, OidcClient, OidcError, CODE_VERIFIER_LEN, STATE_LEN};
+
+/// 审计记录中的实体类型
+const AUDIT_ENTITY: &str = "user_identity";
+
+/// 查询身份时返回的列
+const IDENTITY_COLUMNS: &str = "id, user_id, provider, subject, email, created_at, last_login_at";
+
+/// 从ID令牌生成的用户名的最大长度，留出重名时追加后缀的空间
+const USERNAME_MAX_LEN: usize = 40;
+
+/// 用户名重名时最多尝试的次数
+const USERNAME_ATTEMPTS: usize = 5;
+
+/// 自动创建的用户的随机密码长度，用户需要通过找回密码设置密码后才能用密码登录
+const PASSWORD_LEN: usize = 32;
+
+/// 外部身份提供方登录服务
+#[derive(Clone)]
+pub struct OidcService {
+    pool: DbPool,
+    users: UserService,
+    clients: Arc<BTreeMap<String, OidcClient>>,
+    state_ttl_secs: u64,
+    state_cookie_name: String,
+}
+
+/// 数据库中的身份记录
+#[derive(sqlx::FromRow)]
+struct IdentityRow {
+    id: i64,
+    user_id: i64,
+    provider: String,
+    subject: String,
+    email: Option<String>,
+    created_at: String,
+    last_login_at: String,
+}
+
+impl From<IdentityRow> for UserIdentity {
+    fn from(row: IdentityRow) -> Self {
+        Self {
+            id: row.id as u64,
+            user_id: row.user_id as u64,
+            provider: row.provider,
+            subject: row.subject,
+            email: row.email,
+            created_at: row.created_at,
+            last_login_at: row.last_login_at,
+        }
+    }
+}
+
+impl From<OidcError> for ServiceError {
+    fn from(err: OidcError) -> Self {
+        match err {
+            OidcError::Provider(_) => ServiceError::internal(err),
+            OidcError::Rejected(_) | OidcError::InvalidToken(_) => ServiceError::Validation(err.to_string()),
+        }
+    }
+}
+
+impl OidcService {
+    /// 创建登录服务，为每个配置的身份提供方创建客户端；提供方缺少必需的配置时返回错误
+    pub fn new(pool: DbPool, users: UserService, config: &OidcConfig) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout())
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "-oidc/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        let mut clients = BTreeMap::new();
+        for (id, provider) in &config.providers {
+            if provider.issuer.is_empty() || provider.client_id.is_empty() || provider.redirect_url.is_empty() {
+                anyhow::bail!("身份提供方 {} 缺少 issuer、client_id 或 redirect_url", id);
+            }
+            if !provider.scopes.iter().any(|scope| scope == "openid") {
+                anyhow::bail!("身份提供方 {} 的 scopes 必须包含 openid", id);
+            }
+            let client = OidcClient::new(provider.clone(), http.clone(), config.metadata_ttl());
+            clients.insert(id.clone(), client);
+        }
+
+        Ok(Self {
+            pool,
+            users,
+            clients: Arc::new(clients),
+            state_ttl_secs: config.state_ttl_secs,
+            state_cookie_name: config.state_cookie_name.clone(),
+        })
+    }
+
+    /// 绑定登录状态与浏览器的Cookie名称
+    pub fn state_cookie_name(&self) -> &str {
+        &self.state_cookie_name
+    }
+
+    /// 已配置的身份提供方
+    pub fn providers(&self) -> Vec<OidcProvider> {
+        self.clients
+            .iter()
+            .map(|(id, client)| OidcProvider {
+                id: id.clone(),
+                name: client.config().name.clone().unwrap_or_else(|| id.clone()),
+            })
+            .collect()
+    }
+
+    /// 发起登录，返回身份提供方的授权地址
+    ///
+    /// `state`、`nonce` 及PKCE校验码保存在服务端，在 `state_ttl_secs` 内回调有效；
+    /// 调用方需要将 `state` 写入发起登录的浏览器的Cookie，回调时传给 `complete` 校验
+    pub async fn authorize(&self, provider: &str) -> Result<OidcAuthorization, ServiceError> {
+        let client = self.client(provider)?;
+        let state = random_token(STATE_LEN);
+        let nonce = random_token(STATE_LEN);
+        let code_verifier = random_token(CODE_VERIFIER_LEN);
+        let authorization_url = client
+            .authorization_url(&state, &nonce, &code_challenge(&code_verifier))
+            .await?;
+
+        // 顺带清理过期的登录状态
+        let now_ts = Utc::now().timestamp();
+        sqlx::query("DELETE FROM oidc_login_states WHERE expires_at <= ?")
+            .bind(now_ts)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "INSERT INTO oidc_login_states (state, provider, nonce, code_verifier, expires_at, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&state)
+        .bind(provider)
+        .bind(nonce)
+        .bind(code_verifier)
+        .bind(now_ts + self.state_ttl_secs as i64)
+        .bind(now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(OidcAuthorization {
+            authorization_url,
+            state,
+            expires_in: self.state_ttl_secs,
+        })
+    }
+
+    /// 完成登录：兑换授权码并校验ID令牌，返回对应的本地用户
+    ///
+    /// 身份首次登录时，身份提供方声明邮箱已验证且配置了 `link_by_email` 时关联邮箱相同且在本地也已验证邮箱的用户，
+    /// 否则在配置了 `allow_signup` 时创建用户；关联及创建都记录审计事件。返回结果中不包含会话，
+    /// 会话由调用方通过 `SessionService::start` 创建。
+    ///
+    /// `browser_state` 为回调请求中浏览器保存的登录状态，与 `state` 不一致时说明登录不是由该浏览器发起的
+    /// （登录CSRF），返回校验错误
+    pub async fn complete(
+        &self,
+        ctx: &AuditContext,
+        provider: &str,
+        code: &str,
+        state: &str,
+        browser_state: Option<&str>,
+    ) -> Result<OidcLoginResponse, ServiceError> {
+        let client = self.client(provider)?;
+        if !browser_state.is_some_and(|bound| constant_time_eq(bound.as_bytes(), state.as_bytes())) {
+            return Err(ServiceError::Validation(
+                "登录状态与当前浏览器不匹配，请在同一浏览器中重新登录".to_string(),
+            ));
+        }
+
+        // 登录状态只能使用一次，兑换授权码失败时需要重新发起登录
+        let login: Option<(String, String)> = sqlx::query_as(
+            "DELETE FROM oidc_login_states WHERE state = ? AND provider = ? AND expires_at > ? \
+             RETURNING nonce, code_verifier",
+        )
+        .bind(state)
+        .bind(provider)
+        .bind(Utc::now().timestamp())
+        .fetch_optional(&self.pool)
+        .await?;
+        let (nonce, code_verifier) =
+            login.ok_or_else(|| ServiceError::Validation("登录状态无效或已过期，请重新登录".to_string()))?;
+
+        let id_token = client.exchange_code(code, &code_verifier).await?;
+        let claims = client.validate_id_token(&id_token, &nonce).await?;
+
+        if let Some(identity) = self.touch(provider, &claims).await? {
+            let user = find_user(&self.pool, identity.user_id)
+                .await?
+                .filter(|user| user.deleted_at.is_none())
+                .ok_or_else(|| ServiceError::Forbidden("该身份关联的用户已被删除".to_string()))?;
+            return Ok(OidcLoginResponse {
+                user,
+                identity,
+                created: false,
+                session: None,
+                mfa_enrollment_required: false,
+            });
+        }
+
+        let config = client.config();
+        let verified_email = claims.email.as_deref().filter(|_| claims.email_verified == Some(true));
+        if let Some(email) = verified_email.filter(|_| config.link_by_email) {
+            let user = sqlx::query_as::<_, UserRow>(&format!(
+                "SELECT {} FROM users WHERE email = ? AND email_verified_at IS NOT NULL AND deleted_at IS NULL",
+                USER_COLUMNS
+            ))
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(User::from);
+            if let Some(user) = user {
+                let mut tx = self.pool.begin().await?;
+                let identity = insert_identity(&mut tx, ctx, provider, &claims, user.id.unwrap_or_default()).await?;
+                tx.commit().await?;
+                return Ok(OidcLoginResponse {
+                    user,
+                    identity,
+                    created: false,
+                    session: None,
+                    mfa_enrollment_required: false,
+                });
+            }
+        }
+
+        if !config.allow_signup {
+            return Err(ServiceError::Forbidden(format!("身份提供方 {} 未开放注册，请联系管理员", provider)));
+        }
+        let email = claims
+            .email
+            .clone()
+            .ok_or_else(|| ServiceError::Validation("身份提供方未返回邮箱，无法创建用户".to_string()))?;
+        let taken: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE email = ?)")
+            .bind(&email)
+            .fetch_one(&self.pool)
+            .await?;
+        if taken {
+            return Err(ServiceError::Conflict(format!("邮箱 {} 已被其他用户使用", email)));
+        }
+
+        // 按 `CreateUserRequest` 的字段规则校验身份提供方返回的邮箱
+        let req = CreateUserRequest {
+            username: self.available_username(&claims).await?,
+            email,
+            password: random_token(PASSWORD_LEN),
+        };
+        let req = CreateUserRequest::parse_from_json(Some(req.to_json().unwrap_or(Value::Null)))
+            .map_err(|err| ServiceError::Validation(err.into_message()))?;
+        let new_user = NewUser {
+            username: req.username,
+            email: req.email,
+            password_hash: self.users.hash_password(req.password).await?,
+            role: UserRole::User,
+            email_verified_at: verified_email.map(|_| now()),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let user = insert_user(&mut tx, ctx, new_user).await?;
+        let identity = insert_identity(&mut tx, ctx, provider, &claims, user.id.unwrap_or_default()).await?;
+        tx.commit().await?;
+        Ok(OidcLoginResponse {
+            user,
+            identity,
+            created: true,
+            session: None,
+            mfa_enrollment_required: false,
+        })
+    }
+
+    fn client(&self, provider: &str) -> Result<&OidcClient, ServiceError> {
+        self.clients
+            .get(provider)
+            .ok_or_else(|| ServiceError::NotFound(format!("身份提供方 {} 不存在", provider)))
+    }
+
+    /// 已关联的身份更新最后登录时间及邮箱，未关联时返回 `None`
+    async fn touch(&self, provider: &str, claims: &IdTokenClaims) -> Result<Option<UserIdentity>, ServiceError> {
+        Ok(sqlx::query_as::<_, IdentityRow>(&format!(
+            "UPDATE user_identities SET email = ?1, last_login_at = ?2 WHERE provider = ?3 AND subject = ?4 RETURNING {}",
+            IDENTITY_COLUMNS
+        ))
+        .bind(&claims.email)
+        .bind(now())
+        .bind(provider)
+        .bind(&claims.sub)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(UserIdentity::from))
+    }
+
+    /// 按ID令牌中的用户名、邮箱或显示名称生成未被使用的用户名，重名时追加随机后缀
+    async fn available_username(&self, claims: &IdTokenClaims) -> Result<String, ServiceError> {
+        let base = [
+            claims.preferred_username.as_deref(),
+            claims.email.as_deref().and_then(|email| email.split('@').next()),
+            claims.name.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(sanitize_username)
+        .find(|username| username.len() >= 3)
+        .unwrap_or_else(|| "user".to_string());
+
+        let mut candidate = base.clone();
+        for _ in 0..USERNAME_ATTEMPTS {
+            let taken: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE username = ?)")
+                .bind(&candidate)
+                .fetch_one(&self.pool)
+                .await?;
+            if !taken {
+                return Ok(candidate);
+            }
+            candidate = format!("{}-{}", base, random_token(6).to_lowercase());
+        }
+        Err(ServiceError::Conflict("无法生成可用的用户名".to_string()))
+    }
+}
+
+/// 在调用方的事务中关联身份并记录审计事件
+async fn insert_identity(
+    conn: &mut DbConnection,
+    ctx: &AuditContext,
+    provider: &str,
+    claims: &IdTokenClaims,
+    user_id: u64,
+) -> Result<UserIdentity, ServiceError> {
+    let identity = UserIdentity::from(
+        sqlx::query_as::<_, IdentityRow>(&format!(
+            "INSERT INTO user_identities (user_id, provider, subject, email, created_at, last_login_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5) RETURNING {}",
+            IDENTITY_COLUMNS
+        ))
+        .bind(user_id as i64)
+        .bind(provider)
+        .bind(&claims.sub)
+        .bind(&claims.email)
+        .bind(now())
+        .fetch_one(&mut *conn)
+        .await?,
+    );
+
+    let entry = AuditEntry::new(AuditAction::Create, AUDIT_ENTITY, identity.id).diff(None, Some(&identity));
+    audit::record(conn, ctx, entry).await?;
+    Ok(identity)
+}
+
+/// 只保留字母、数字及 `.`、`_`、`-`，并截断到 `USERNAME_MAX_LEN`
+fn sanitize_username(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .take(USERNAME_MAX_LEN)
+        .collect()
+}
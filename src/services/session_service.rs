@@ -0,0 +1,346 @@
+//! 会话服务
+//!
+//! 浏览器会话的登录、认证、列出及注销；Cookie及CSRF防护的说明见 `sessions` 模块，
+//! 认证中间件见 `middlewares::SessionAuth`
+
+use std::sync::Arc;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use super::{MfaRequirement, MfaService, ServiceError, UserService};
+use crate::audit::{self, AuditContext, AuditEntry};
+use crate::auth::Principal;
+use crate::config::SessionsConfig;
+use crate::db::DbPool;
+use crate::models::audit::AuditAction;
+use crate::models::session::{LoginRequest, LoginResponse, Session};
+use crate::models::user::User;
+use crate::sessions::{
+    random_token, token_digest, CurrentSession, SessionRecord, SessionStore, SESSION_ID_LEN, TOKEN_LEN,
+};
+
+/// 审计记录中的实体类型
+const AUDIT_ENTITY: &str = "session";
+
+/// 会话服务
+///
+/// 克隆开销很小，各克隆共享同一个存储
+#[derive(Clone)]
+pub struct SessionService {
+    pool: DbPool,
+    store: Arc<dyn SessionStore>,
+    users: UserService,
+    mfa: MfaService,
+    config: Arc<SessionsConfig>,
+}
+
+/// 新建的会话，令牌原文只在这里返回，由调用方写入Cookie
+pub struct IssuedSession {
+    /// 会话令牌
+    pub token: String,
+    /// CSRF令牌
+    pub csrf_token: String,
+    /// 会话信息
+    pub session: Session,
+}
+
+/// 认证通过的会话
+pub struct AuthenticatedSession {
+    /// 登录的用户
+    pub principal: Principal,
+    /// 会话
+    pub session: CurrentSession,
+    /// 会话的CSRF令牌
+    pub csrf_token: String,
+    /// 角色要求启用两步验证但尚未启用，会话只能访问绑定两步验证的接口
+    pub mfa_enrollment_required: bool,
+}
+
+impl SessionService {
+    /// 创建会话服务，`store` 由 `sessions::create_store` 按配置创建，与用户服务共用
+    pub fn new(
+        pool: DbPool,
+        store: Arc<dyn SessionStore>,
+        users: UserService,
+        mfa: MfaService,
+        config: SessionsConfig,
+    ) -> Self {
+        Self {
+            pool,
+            store,
+            users,
+            mfa,
+            config: Arc::new(config),
+        }
+    }
+
+    /// 会话配置
+    pub fn config(&self) -> &SessionsConfig {
+        &self.config
+    }
+
+    /// 使用用户名（或邮箱）及密码登录，返回新建的会话（由调用方写入Cookie）及登录结果
+    ///
+    /// 已启用两步验证的用户还需要提供验证码或恢复码；角色要求启用但尚未启用时可以登录，
+    /// 但完成绑定前会话只能访问绑定两步验证的接口，响应中的 `mfa_enrollment_required` 提示前端引导用户完成绑定
+    pub async fn login(
+        &self,
+        ctx: &AuditContext,
+        req: LoginRequest,
+        user_agent: Option<String>,
+    ) -> Result<(IssuedSession, LoginResponse), ServiceError> {
+        self.ensure_enabled()?;
+        let user = self
+            .users
+            .verify_password(req.username.trim(), &req.password)
+            .await?
+            .ok_or_else(|| ServiceError::Unauthorized("用户名或密码错误".to_string()))?;
+
+        let requirement = self.verify_mfa(&user, req.mfa_code.as_deref()).await?;
+        let issued = self.start(ctx, &user, user_agent).await?;
+        let response = LoginResponse {
+            user,
+            session: issued.session.clone(),
+            mfa_enrollment_required: requirement == MfaRequirement::Enroll,
+        };
+        Ok((issued, response))
+    }
+
+    /// 按两步验证的要求校验登录：已启用时必须提供验证码或恢复码，缺少时返回401，错误时返回400
+    ///
+    /// 返回用户的两步验证要求，`Enroll` 表示登录后需要引导用户完成绑定
+    pub async fn verify_mfa(&self, user: &User, mfa_code: Option<&str>) -> Result<MfaRequirement, ServiceError> {
+        let requirement = self.mfa.requirement(user).await?;
+        if requirement == MfaRequirement::Verify {
+            let code = mfa_code
+                .map(str::trim)
+                .filter(|code| !code.is_empty())
+                .ok_or_else(|| ServiceError::Unauthorized("需要两步验证码".to_string()))?;
+            self.mfa.verify(user.id.unwrap_or_default(), code).await?;
+        }
+        Ok(requirement)
+    }
+
+    /// 为已通过认证的用户新建会话，例如密码登录或外部身份提供方登录之后；调用前应先通过 `verify_mfa`
+    pub async fn start(
+        &self,
+        ctx: &AuditContext,
+        user: &User,
+        user_agent: Option<String>,
+    ) -> Result<IssuedSession, ServiceError> {
+        self.ensure_enabled()?;
+        let token = random_token(TOKEN_LEN);
+        let csrf_token = random_token(TOKEN_LEN);
+        let now = Utc::now().timestamp();
+        let record = SessionRecord {
+            id: random_token(SESSION_ID_LEN),
+            token_hash: token_digest(&token),
+            user_id: user.id.unwrap_or_default(),
+            csrf_token: csrf_token.clone(),
+            ip: ctx.ip.clone(),
+            user_agent: user_agent.map(|value| value.chars().take(256).collect()),
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now.saturating_add(self.config.absolute_timeout().as_secs() as i64),
+        };
+        self.store.insert(&record).await.map_err(ServiceError::internal)?;
+
+        let session = self.to_session(&record, &record.id);
+        let entry = AuditEntry::new(AuditAction::Create, AUDIT_ENTITY, &record.id).diff(None, Some(&session));
+        self.audit(ctx, entry).await?;
+        Ok(IssuedSession {
+            token,
+            csrf_token,
+            session,
+        })
+    }
+
+    /// 认证会话令牌，会话不存在、已过期或用户已删除时返回 `None`
+    ///
+    /// 最后活跃时间按 `touch_interval_secs` 的间隔更新，空闲超时的精度与该间隔相同
+    pub async fn authenticate(&self, token: &str) -> Result<Option<AuthenticatedSession>, ServiceError> {
+        let Some(record) = self.store.find(&token_digest(token)).await.map_err(ServiceError::internal)? else {
+            return Ok(None);
+        };
+        let now = Utc::now().timestamp();
+        if !record.is_active(now, self.idle_timeout_secs()) {
+            self.store.remove(&record.id).await.map_err(ServiceError::internal)?;
+            return Ok(None);
+        }
+
+        let user = match self.users.get(record.user_id).await {
+            Ok(user) => user,
+            Err(ServiceError::NotFound(_)) => {
+                self.store.remove(&record.id).await.map_err(ServiceError::internal)?;
+                return Ok(None);
+            }
+            Err(err) => return Err(err),
+        };
+
+        if now - record.last_seen_at >= self.config.touch_interval().as_secs() as i64 {
+            self.store.touch(&record.id, now).await.map_err(ServiceError::internal)?;
+        }
+
+        let mfa_enrollment_required = self.mfa.enrollment_required(&user).await?;
+        Ok(Some(AuthenticatedSession {
+            principal: Principal::User {
+                id: record.user_id,
+                username: user.username,
+                role: user.role,
+            },
+            session: CurrentSession {
+                id: record.id,
+                user_id: record.user_id,
+            },
+            csrf_token: record.csrf_token,
+            mfa_enrollment_required,
+        }))
+    }
+
+    /// 当前会话
+    pub async fn current(&self, current: Option<&CurrentSession>) -> Result<Session, ServiceError> {
+        let current = require(current)?;
+        self.sessions(current)
+            .await?
+            .into_iter()
+            .find(|session| session.current)
+            .ok_or_else(|| ServiceError::Unauthorized("会话已失效，请重新登录".to_string()))
+    }
+
+    /// 当前用户未过期的会话，按登录时间排序
+    pub async fn list(&self, current: Option<&CurrentSession>) -> Result<Vec<Session>, ServiceError> {
+        self.sessions(require(current)?).await
+    }
+
+    /// 注销当前用户的某个会话；注销当前会话等同于退出登录
+    pub async fn revoke(
+        &self,
+        ctx: &AuditContext,
+        current: Option<&CurrentSession>,
+        id: &str,
+    ) -> Result<Session, ServiceError> {
+        let current = require(current)?;
+        let session = self
+            .sessions(current)
+            .await?
+            .into_iter()
+            .find(|session| session.id == id)
+            .ok_or_else(|| ServiceError::NotFound(format!("会话 {} 不存在", id)))?;
+        self.remove(ctx, &session).await?;
+        Ok(session)
+    }
+
+    /// 注销当前用户除当前会话以外的所有会话（包括已过期但尚未清理的），返回注销的未过期会话数量
+    pub async fn revoke_others(&self, ctx: &AuditContext, current: Option<&CurrentSession>) -> Result<u64, ServiceError> {
+        let current = require(current)?;
+        let others: Vec<_> = self
+            .sessions(current)
+            .await?
+            .into_iter()
+            .filter(|session| !session.current)
+            .collect();
+        self.store
+            .remove_user(current.user_id, Some(&current.id))
+            .await
+            .map_err(ServiceError::internal)?;
+        for session in &others {
+            let entry = AuditEntry::new(AuditAction::Delete, AUDIT_ENTITY, &session.id).diff(Some(session), None);
+            self.audit(ctx, entry).await?;
+        }
+        Ok(others.len() as u64)
+    }
+
+    /// 退出登录：注销当前会话；请求没有使用会话时不做任何处理
+    pub async fn logout(&self, ctx: &AuditContext, current: Option<&CurrentSession>) -> Result<(), ServiceError> {
+        let Some(current) = current else {
+            return Ok(());
+        };
+        if let Some(session) = self.sessions(current).await?.into_iter().find(|session| session.current) {
+            self.remove(ctx, &session).await?;
+        }
+        Ok(())
+    }
+
+    /// 删除已过期的会话，返回删除的数量；过期会话在认证时也会被删除，这里清理不再访问的会话
+    pub async fn purge_expired(&self) -> Result<u64, ServiceError> {
+        let now = Utc::now().timestamp();
+        self.store
+            .purge_expired(now, now - self.idle_timeout_secs())
+            .await
+            .map_err(ServiceError::internal)
+    }
+
+    /// 会话登录已关闭时拒绝登录
+    fn ensure_enabled(&self) -> Result<(), ServiceError> {
+        if self.config.enabled {
+            Ok(())
+        } else {
+            Err(ServiceError::Forbidden("未启用会话登录".to_string()))
+        }
+    }
+
+    /// 当前用户未过期的会话
+    async fn sessions(&self, current: &CurrentSession) -> Result<Vec<Session>, ServiceError> {
+        let now = Utc::now().timestamp();
+        let idle_timeout_secs = self.idle_timeout_secs();
+        Ok(self
+            .store
+            .list(current.user_id)
+            .await
+            .map_err(ServiceError::internal)?
+            .iter()
+            .filter(|record| record.is_active(now, idle_timeout_secs))
+            .map(|record| self.to_session(record, &current.id))
+            .collect())
+    }
+
+    /// 删除会话并记录审计事件；会话已被并发删除时不重复记录
+    async fn remove(&self, ctx: &AuditContext, session: &Session) -> Result<(), ServiceError> {
+        if self.store.remove(&session.id).await.map_err(ServiceError::internal)? {
+            let entry = AuditEntry::new(AuditAction::Delete, AUDIT_ENTITY, &session.id).diff(Some(session), None);
+            self.audit(ctx, entry).await?;
+        }
+        Ok(())
+    }
+
+    /// 会话保存在内存中时没有事务，审计记录单独写入
+    async fn audit(&self, ctx: &AuditContext, entry: AuditEntry) -> Result<(), ServiceError> {
+        let mut conn = self.pool.acquire().await?;
+        audit::record(&mut conn, ctx, entry).await.map_err(ServiceError::from)
+    }
+
+    fn idle_timeout_secs(&self) -> i64 {
+        self.config.idle_timeout().as_secs() as i64
+    }
+
+    /// 转为接口返回的会话信息，`current_id` 为本次请求使用的会话
+    fn to_session(&self, record: &SessionRecord, current_id: &str) -> Session {
+        let idle_expires_at = record
+            .last_seen_at
+            .saturating_add(self.idle_timeout_secs())
+            .min(record.expires_at);
+        Session {
+            id: record.id.clone(),
+            user_id: record.user_id,
+            ip: record.ip.clone(),
+            user_agent: record.user_agent.clone(),
+            created_at: format_timestamp(record.created_at),
+            last_seen_at: format_timestamp(record.last_seen_at),
+            idle_expires_at: format_timestamp(idle_expires_at),
+            expires_at: format_timestamp(record.expires_at),
+            current: record.id == current_id,
+        }
+    }
+}
+
+/// 需要通过会话认证的操作，请求没有使用会话时返回401
+fn require(current: Option<&CurrentSession>) -> Result<&CurrentSession, ServiceError> {
+    current.ok_or_else(|| ServiceError::Unauthorized("未登录或会话已失效".to_string()))
+}
+
+/// Unix时间戳（秒）转为ISO 8601格式
+fn format_timestamp(secs: i64) -> String {
+    DateTime::<Utc>::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,602 @@
+//! 用户服务
+//!
+//! 用户相关的业务逻辑，供各版本REST API和GraphQL共用
+
+use std::sync::Arc;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use poem_openapi::types::{MaybeUndefined, ParseFromJSON, ToJSON};
+use serde_json::{Map, Value};
+use sqlx::SqliteExecutor;
+
+use crate::audit::{self, AuditContext, AuditEntry};
+use crate::auth::Principal;
+use crate::db::{DbConnection, DbPool};
+use crate::events::{outbox, DomainEvent, UserCreated, UserDeleted, UserUpdated};
+use crate::models::audit::AuditAction;
+use crate::models::user::{
+    CreateUserRequest, JsonPatchOperation, PatchUserRequest, UpdateUserRequest, User, UserRole,
+};
+use crate::sessions::SessionStore;
+
+use super::ServiceError;
+
+/// 查询用户时返回的列
+pub(super) const USER_COLUMNS: &str =
+    "id, username, email, email_verified_at, role, version, created_at, updated_at, deleted_at";
+
+/// 审计记录中的实体类型
+pub(super) const AUDIT_ENTITY: &str = "user";
+
+/// JSON Patch可修改的字段，其余字段只读
+const PATCHABLE_FIELDS: [&str; 2] = ["email", "password"];
+
+/// 用户列表过滤条件
+#[derive(Debug, Default, Clone)]
+pub struct UserFilter {
+    /// 用户名模糊匹配
+    pub username: Option<String>,
+    /// 邮箱模糊匹配
+    pub email: Option<String>,
+    /// 是否包含已软删除的用户
+    pub include_deleted: bool,
+}
+
+/// 用户服务
+///
+/// 基于数据库的实现，密码使用bcrypt哈希后存储；
+/// 每次变更与对应的审计记录、领域事件在同一个事务中写入，`ctx` 标识发起变更的操作者；
+/// 修改密码后注销该用户的所有浏览器会话。
+///
+/// REST各版本及GraphQL共用这里的权限检查：用户只能查看、修改自己的账户，
+/// 查询用户列表、删除及恢复用户需要管理权限
+#[derive(Clone)]
+pub struct UserService {
+    pool: DbPool,
+    bcrypt_cost: u32,
+    sessions: Arc<dyn SessionStore>,
+}
+
+/// 数据库中的用户记录
+#[derive(sqlx::FromRow)]
+pub(super) struct UserRow {
+    id: i64,
+    username: String,
+    email: String,
+    email_verified_at: Option<String>,
+    role: String,
+    version: i64,
+    created_at: String,
+    updated_at: String,
+    deleted_at: Option<String>,
+}
+
+impl From<UserRow> for User {
+    fn from(row: UserRow) -> Self {
+        Self {
+            id: Some(row.id as u64),
+            username: row.username,
+            email: row.email,
+            email_verified_at: row.email_verified_at,
+            role: UserRole::parse(&row.role),
+            version: Some(row.version as u64),
+            created_at: Some(row.created_at),
+            updated_at: Some(row.updated_at),
+            deleted_at: row.deleted_at,
+        }
+    }
+}
+
+impl UserService {
+    /// 创建用户服务，`sessions` 与会话服务共用，见 `sessions::create_store`
+    pub fn new(pool: DbPool, bcrypt_cost: u32, sessions: Arc<dyn SessionStore>) -> Self {
+        Self {
+            pool,
+            bcrypt_cost,
+            sessions,
+        }
+    }
+
+    /// 创建新用户
+    ///
+    /// 请求会按 `CreateUserRequest` 的字段规则重新校验
+    pub async fn create(&self, ctx: &AuditContext, req: CreateUserRequest) -> Result<User, ServiceError> {
+        self.insert(ctx, req, UserRole::User).await
+    }
+
+    /// 创建管理员
+    pub async fn create_admin(&self, ctx: &AuditContext, req: CreateUserRequest) -> Result<User, ServiceError> {
+        self.insert(ctx, req, UserRole::Admin).await
+    }
+
+    /// 是否已存在管理员
+    pub async fn has_admin(&self) -> Result<bool, ServiceError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = ? AND deleted_at IS NULL")
+            .bind(UserRole::Admin.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// 根据ID获取用户，已软删除的用户视为不存在
+    pub async fn get(&self, id: u64) -> Result<User, ServiceError> {
+        find_user(&self.pool, id)
+            .await?
+            .filter(|user| user.deleted_at.is_none())
+            .ok_or_else(|| not_found(id))
+    }
+
+    /// 以 `actor` 的身份获取用户，只能获取自己的账户，管理员可以获取任意用户
+    pub async fn get_as(&self, actor: &Principal, id: u64) -> Result<User, ServiceError> {
+        actor.require_self_or_admin(id)?;
+        self.get(id).await
+    }
+
+    /// 按用户名或邮箱校验密码，用户不存在、已软删除或密码错误时返回 `None`
+    pub async fn verify_password(&self, login: &str, password: &str) -> Result<Option<User>, ServiceError> {
+        let row: Option<(i64, String)> =
+            sqlx::query_as("SELECT id, password_hash FROM users WHERE (username = ?1 OR email = ?1) AND deleted_at IS NULL")
+                .bind(login)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some((id, password_hash)) = row else {
+            return Ok(None);
+        };
+
+        // bcrypt校验与计算哈希一样耗时，放到阻塞线程池中执行；无法解析的哈希视为密码错误
+        let password = password.to_string();
+        let valid = tokio::task::spawn_blocking(move || bcrypt::verify(password, &password_hash).unwrap_or(false))
+            .await
+            .map_err(ServiceError::internal)?;
+        if !valid {
+            return Ok(None);
+        }
+        self.get(id as u64).await.map(Some)
+    }
+
+    /// 更新用户信息
+    ///
+    /// 提供 `expected_version` 时只在版本一致时更新，否则返回前置条件失败；每次更新版本号加一。
+    /// 请求会按 `UpdateUserRequest` 的字段规则重新校验；邮箱变化时清空邮箱验证时间，需要重新验证；
+    /// 修改密码后注销该用户的所有会话。只能修改自己的账户，管理员可以修改任意用户
+    pub async fn update(
+        &self,
+        ctx: &AuditContext,
+        id: u64,
+        req: UpdateUserRequest,
+        expected_version: Option<u64>,
+    ) -> Result<User, ServiceError> {
+        ctx.actor.require_self_or_admin(id)?;
+        let req = revalidate(req)?;
+        let password_hash = match req.password {
+            Some(password) => Some(self.hash_password(password).await?),
+            None => None,
+        };
+        let password_changed = password_hash.is_some();
+
+        let mut tx = self.pool.begin().await?;
+        let before = find_user(&mut *tx, id).await?;
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "UPDATE users SET email = COALESCE(?1, email), password_hash = COALESCE(?2, password_hash), \
+             email_verified_at = CASE WHEN ?1 IS NULL OR ?1 = email THEN email_verified_at END, \
+             updated_at = ?3, version = version + 1 \
+             WHERE id = ?4 AND deleted_at IS NULL AND (?5 IS NULL OR version = ?5) RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(req.email)
+        .bind(password_hash)
+        .bind(now())
+        .bind(id as i64)
+        .bind(expected_version.map(|version| version as i64))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (Some(before), Some(row)) = (before, row) else {
+            tx.rollback().await?;
+            return Err(self.missing_or_modified(id).await);
+        };
+        let user = User::from(row);
+
+        let mut entry = AuditEntry::new(AuditAction::Update, AUDIT_ENTITY, id).diff(Some(&before), Some(&user));
+        if password_changed {
+            entry = entry.redacted("password");
+        }
+        let event = DomainEvent::UserUpdated(UserUpdated {
+            before,
+            after: user.clone(),
+            password_changed,
+        });
+        record_change(&mut tx, ctx, entry, event).await?;
+        tx.commit().await?;
+        if password_changed {
+            self.revoke_sessions(id).await?;
+        }
+        Ok(user)
+    }
+
+    /// 部分更新用户信息（JSON Merge Patch语义）
+    ///
+    /// 未提供的字段保持不变，显式置空必填字段时返回校验错误；
+    /// 请求会按 `PatchUserRequest` 的字段规则重新校验，供未经OpenAPI解析的调用方（如GraphQL）共用
+    pub async fn patch(
+        &self,
+        ctx: &AuditContext,
+        id: u64,
+        req: PatchUserRequest,
+        expected_version: Option<u64>,
+    ) -> Result<User, ServiceError> {
+        ctx.actor.require_self_or_admin(id)?;
+        let req = parse_patch(req.to_json().unwrap_or(Value::Null))?;
+
+        let req = UpdateUserRequest {
+            email: required(req.email, "邮箱")?,
+            password: required(req.password, "密码")?,
+        };
+        self.update(ctx, id, req, expected_version).await
+    }
+
+    /// 按JSON Patch操作部分更新用户信息
+    ///
+    /// 操作依次作用于用户当前的JSON表示，任一操作失败时不做任何修改；
+    /// 修改只读字段或未知字段时返回校验错误。未提供 `expected_version` 时以读取到的版本为准，
+    /// 避免补丁基于的数据在应用前被其他请求修改
+    pub async fn apply_json_patch(
+        &self,
+        ctx: &AuditContext,
+        id: u64,
+        ops: Vec<JsonPatchOperation>,
+        expected_version: Option<u64>,
+    ) -> Result<User, ServiceError> {
+        ctx.actor.require_self_or_admin(id)?;
+        let patch: json_patch::Patch = serde_json::from_value(Value::Array(
+            ops.into_iter().map(patch_operation_json).collect(),
+        ))
+        .map_err(|err| ServiceError::Validation(format!("无效的JSON Patch操作: {}", err)))?;
+
+        let current = self.get(id).await?;
+        let expected_version = expected_version.or(current.version);
+
+        // 密码不在用户的JSON表示中，以null占位以便 replace 操作写入
+        let mut original = serde_json::to_value(current).map_err(ServiceError::internal)?;
+        original["password"] = Value::Null;
+
+        let mut document = original.clone();
+        json_patch::patch(&mut document, &patch)
+            .map_err(|err| ServiceError::Validation(format!("JSON Patch应用失败: {}", err)))?;
+
+        self.patch(ctx, id, merge_patch_from(&original, &document)?, expected_version).await
+    }
+
+    /// 软删除用户
+    ///
+    /// 只记录删除时间，删除后的用户不再出现在查询结果中，可以恢复，超过保留期后由定时任务彻底清除；
+    /// 提供 `expected_version` 时只在版本一致时删除；需要管理权限
+    pub async fn delete(&self, ctx: &AuditContext, id: u64, expected_version: Option<u64>) -> Result<(), ServiceError> {
+        ctx.actor.require_admin()?;
+        let mut tx = self.pool.begin().await?;
+        let before = find_user(&mut *tx, id).await?;
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "UPDATE users SET deleted_at = ?1, updated_at = ?1, version = version + 1 \
+             WHERE id = ?2 AND deleted_at IS NULL AND (?3 IS NULL OR version = ?3) RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(now())
+        .bind(id as i64)
+        .bind(expected_version.map(|version| version as i64))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (Some(before), Some(row)) = (before, row) else {
+            tx.rollback().await?;
+            return Err(self.missing_or_modified(id).await);
+        };
+
+        let user = User::from(row);
+        let entry = AuditEntry::new(AuditAction::Delete, AUDIT_ENTITY, id).diff(Some(&before), Some(&user));
+        let event = DomainEvent::UserDeleted(UserDeleted { user, purged: false });
+        record_change(&mut tx, ctx, entry, event).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 恢复已软删除的用户
+    ///
+    /// 提供 `expected_version` 时只在版本一致时恢复；需要管理权限
+    pub async fn restore(&self, ctx: &AuditContext, id: u64, expected_version: Option<u64>) -> Result<User, ServiceError> {
+        ctx.actor.require_admin()?;
+        let mut tx = self.pool.begin().await?;
+        let before = find_user(&mut *tx, id).await?;
+        let row = sqlx::query_as::<_, UserRow>(&format!(
+            "UPDATE users SET deleted_at = NULL, updated_at = ?1, version = version + 1 \
+             WHERE id = ?2 AND deleted_at IS NOT NULL AND (?3 IS NULL OR version = ?3) RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(now())
+        .bind(id as i64)
+        .bind(expected_version.map(|version| version as i64))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let (Some(before), Some(row)) = (before, row) {
+            let user = User::from(row);
+            let entry = AuditEntry::new(AuditAction::Restore, AUDIT_ENTITY, id).diff(Some(&before), Some(&user));
+            let event = DomainEvent::UserUpdated(UserUpdated {
+                before,
+                after: user.clone(),
+                password_changed: false,
+            });
+            record_change(&mut tx, ctx, entry, event).await?;
+            tx.commit().await?;
+            return Ok(user);
+        }
+
+        tx.rollback().await?;
+        match find_user(&self.pool, id).await? {
+            None => Err(not_found(id)),
+            Some(user) if user.deleted_at.is_none() => {
+                Err(ServiceError::Conflict(format!("用户 {} 未被删除", id)))
+            }
+            Some(user) => Err(modified(&user)),
+        }
+    }
+
+    /// 彻底清除删除时间早于 `deleted_before` 的用户，返回清除的数量
+    ///
+    /// 每个被清除的用户各记录一条审计事件
+    pub async fn purge_deleted(
+        &self,
+        ctx: &AuditContext,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<u64, ServiceError> {
+        let mut tx = self.pool.begin().await?;
+        let users = sqlx::query_as::<_, UserRow>(&format!(
+            "DELETE FROM users WHERE deleted_at IS NOT NULL AND deleted_at <= ? RETURNING {}",
+            USER_COLUMNS
+        ))
+        .bind(deleted_before.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let count = users.len() as u64;
+        for user in users.into_iter().map(User::from) {
+            let entry = AuditEntry::new(AuditAction::Purge, AUDIT_ENTITY, user.id.unwrap_or_default())
+                .diff(Some(&user), None);
+            let event = DomainEvent::UserDeleted(UserDeleted { user, purged: true });
+            record_change(&mut tx, ctx, entry, event).await?;
+        }
+        tx.commit().await?;
+        Ok(count)
+    }
+
+    /// 分页查询用户列表，返回当前页数据及总记录数；列表包含邮箱等信息，需要管理权限
+    pub async fn list(
+        &self,
+        actor: &Principal,
+        filter: &UserFilter,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<User>, u64), ServiceError> {
+        actor.require_admin()?;
+        if page == 0 || page_size == 0 {
+            return Err(ServiceError::Validation("页码和每页记录数必须大于0".to_string()));
+        }
+
+        // 未提供过滤条件时对应的条件恒为真，默认排除已软删除的用户
+        let condition = "(?1 IS NULL OR username LIKE '%' || ?1 || '%') AND (?2 IS NULL OR email LIKE '%' || ?2 || '%') \
+                         AND (?3 OR deleted_at IS NULL)";
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM users WHERE {}", condition))
+            .bind(&filter.username)
+            .bind(&filter.email)
+            .bind(filter.include_deleted)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let users = sqlx::query_as::<_, UserRow>(&format!(
+            "SELECT {} FROM users WHERE {} ORDER BY id LIMIT ?4 OFFSET ?5",
+            USER_COLUMNS, condition
+        ))
+        .bind(&filter.username)
+        .bind(&filter.email)
+        .bind(filter.include_deleted)
+        .bind(page_size as i64)
+        .bind((page as i64 - 1) * page_size as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(User::from)
+        .collect();
+
+        Ok((users, total as u64))
+    }
+
+    /// 插入用户记录，用户名或邮箱重复时返回冲突错误
+    async fn insert(&self, ctx: &AuditContext, req: CreateUserRequest, role: UserRole) -> Result<User, ServiceError> {
+        let req = revalidate(req)?;
+        let password_hash = self.hash_password(req.password).await?;
+        let new_user = NewUser {
+            username: req.username,
+            email: req.email,
+            password_hash,
+            role,
+            email_verified_at: None,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let user = insert_user(&mut tx, ctx, new_user).await?;
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    /// 注销用户的所有会话，修改或重置密码后调用，已登录的设备需要使用新密码重新登录
+    pub(super) async fn revoke_sessions(&self, id: u64) -> Result<(), ServiceError> {
+        self.sessions.remove_user(id, None).await.map_err(ServiceError::internal)?;
+        Ok(())
+    }
+
+    /// 条件修改未命中时区分用户不存在与版本不一致
+    async fn missing_or_modified(&self, id: u64) -> ServiceError {
+        match self.get(id).await {
+            Ok(user) => modified(&user),
+            Err(err) => err,
+        }
+    }
+
+    /// 计算密码哈希，bcrypt计算耗时较长，放到阻塞线程池中执行
+    pub(super) async fn hash_password(&self, password: String) -> Result<String, ServiceError> {
+        let cost = self.bcrypt_cost;
+        tokio::task::spawn_blocking(move || bcrypt::hash(password, cost))
+            .await
+            .map_err(ServiceError::internal)?
+            .map_err(ServiceError::internal)
+    }
+}
+
+/// 在变更所在的事务中记录审计事件并写入领域事件
+pub(super) async fn record_change(
+    conn: &mut DbConnection,
+    ctx: &AuditContext,
+    entry: AuditEntry,
+    event: DomainEvent,
+) -> Result<(), ServiceError> {
+    audit::record(conn, ctx, entry).await?;
+    outbox::enqueue(conn, &event).await?;
+    Ok(())
+}
+
+/// 待写入的用户
+pub(super) struct NewUser {
+    pub username: String,
+    pub email: String,
+    pub password_hash: String,
+    pub role: UserRole,
+    /// 邮箱验证时间，外部身份提供方已验证的邮箱可直接标记为已验证
+    pub email_verified_at: Option<String>,
+}
+
+/// 在调用方的事务中写入用户及对应的审计记录、领域事件
+pub(super) async fn insert_user(
+    conn: &mut DbConnection,
+    ctx: &AuditContext,
+    new_user: NewUser,
+) -> Result<User, ServiceError> {
+    let now = now();
+    let user = sqlx::query_as::<_, UserRow>(&format!(
+        "INSERT INTO users (username, email, email_verified_at, password_hash, role, created_at, updated_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING {}",
+        USER_COLUMNS
+    ))
+    .bind(new_user.username)
+    .bind(new_user.email)
+    .bind(new_user.email_verified_at)
+    .bind(new_user.password_hash)
+    .bind(new_user.role.as_str())
+    .bind(&now)
+    .bind(&now)
+    .fetch_one(&mut *conn)
+    .await
+    .map(User::from)
+    .map_err(|err| match ServiceError::from(err) {
+        ServiceError::Conflict(_) => ServiceError::Conflict("用户名或邮箱已存在".to_string()),
+        err => err,
+    })?;
+
+    let entry = AuditEntry::new(AuditAction::Create, AUDIT_ENTITY, user.id.unwrap_or_default())
+        .diff(None, Some(&user));
+    let event = DomainEvent::UserCreated(UserCreated { user: user.clone() });
+    record_change(conn, ctx, entry, event).await?;
+    Ok(user)
+}
+
+/// 根据ID查找用户，包含已软删除的用户
+pub(super) async fn find_user<'e>(executor: impl SqliteExecutor<'e>, id: u64) -> Result<Option<User>, ServiceError> {
+    Ok(sqlx::query_as::<_, UserRow>(&format!("SELECT {} FROM users WHERE id = ?", USER_COLUMNS))
+        .bind(id as i64)
+        .fetch_optional(executor)
+        .await?
+        .map(User::from))
+}
+
+/// 按请求类型的字段规则重新校验，供未经OpenAPI解析的调用方（如GraphQL）共用
+fn revalidate<T: ParseFromJSON + ToJSON>(req: T) -> Result<T, ServiceError> {
+    T::parse_from_json(Some(req.to_json().unwrap_or(Value::Null)))
+        .map_err(|err| ServiceError::Validation(err.into_message()))
+}
+
+/// 按 `PatchUserRequest` 的字段规则解析合并补丁
+fn parse_patch(value: Value) -> Result<PatchUserRequest, ServiceError> {
+    PatchUserRequest::parse_from_json(Some(value))
+        .map_err(|err| ServiceError::Validation(err.into_message()))
+}
+
+/// 必填字段不允许置空，未提供时保持不变
+fn required(value: MaybeUndefined<String>, field: &str) -> Result<Option<String>, ServiceError> {
+    match value {
+        MaybeUndefined::Undefined => Ok(None),
+        MaybeUndefined::Null => Err(ServiceError::Validation(format!("{}不能为空", field))),
+        MaybeUndefined::Value(value) => Ok(Some(value)),
+    }
+}
+
+/// 转换为RFC 6902格式的操作对象
+fn patch_operation_json(op: JsonPatchOperation) -> Value {
+    let mut object = Map::new();
+    object.insert("op".to_string(), Value::from(op.op.as_str()));
+    object.insert("path".to_string(), Value::from(op.path));
+    if let Some(from) = op.from {
+        object.insert("from".to_string(), Value::from(from));
+    }
+    match op.value {
+        MaybeUndefined::Undefined => {}
+        MaybeUndefined::Null => {
+            object.insert("value".to_string(), Value::Null);
+        }
+        MaybeUndefined::Value(value) => {
+            object.insert("value".to_string(), value);
+        }
+    }
+    Value::Object(object)
+}
+
+/// 比较补丁前后的JSON表示，生成等价的合并补丁
+fn merge_patch_from(original: &Value, patched: &Value) -> Result<PatchUserRequest, ServiceError> {
+    let (Some(original), Some(patched)) = (original.as_object(), patched.as_object()) else {
+        return Err(ServiceError::Validation("不能替换整个用户对象".to_string()));
+    };
+
+    if let Some(field) = patched.keys().find(|key| !original.contains_key(*key)) {
+        return Err(ServiceError::Validation(format!("不支持的字段: {}", field)));
+    }
+
+    let mut merge = Map::new();
+    for (field, value) in original {
+        let patched = patched.get(field).unwrap_or(&Value::Null);
+        if patched == value {
+            continue;
+        }
+        if !PATCHABLE_FIELDS.contains(&field.as_str()) {
+            return Err(ServiceError::Validation(format!("字段 {} 为只读", field)));
+        }
+        merge.insert(field.clone(), patched.clone());
+    }
+
+    parse_patch(Value::Object(merge))
+}
+
+/// 用户版本与期望的不一致
+fn modified(user: &User) -> ServiceError {
+    ServiceError::PreconditionFailed(format!(
+        "用户已被修改，当前版本为 {}，请获取最新数据后重试",
+        user.version.unwrap_or_default()
+    ))
+}
+
+/// 用户不存在
+fn not_found(id: u64) -> ServiceError {
+    ServiceError::NotFound(format!("User with id {} not found", id))
+}
+
+/// 当前时间（ISO 8601格式）
+pub(super) fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,345 @@
+//! Webhook订阅服务
+//!
+//! 订阅的增删改查、投递记录查询及手动重新投递；投递本身由 `webhooks` 模块完成
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::Url;
+
+use crate::config::WebhooksConfig;
+use crate::db::DbPool;
+use crate::models::webhook::{
+    CreateWebhookRequest, DeliveryStatus, UpdateWebhookRequest, Webhook, WebhookDelivery, WebhookEventType,
+};
+
+use super::ServiceError;
+
+/// 查询订阅时返回的列（不含密钥）
+const WEBHOOK_COLUMNS: &str =
+    "id, url, event_types, description, enabled, consecutive_failures, disabled_reason, created_at, updated_at";
+
+/// 查询投递记录时返回的列
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event_id, event_type, payload, status, attempts, next_attempt_at, \
+     response_status, response_body, error, redelivery_of, created_at, delivered_at";
+
+/// Webhook订阅服务
+#[derive(Debug, Clone)]
+pub struct WebhookService {
+    pool: DbPool,
+    config: Arc<WebhooksConfig>,
+}
+
+/// 数据库中的订阅记录
+#[derive(sqlx::FromRow)]
+struct WebhookRow {
+    id: i64,
+    url: String,
+    event_types: String,
+    description: Option<String>,
+    enabled: bool,
+    consecutive_failures: i64,
+    disabled_reason: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<WebhookRow> for Webhook {
+    fn from(row: WebhookRow) -> Self {
+        // 未知的事件类型（例如已下线的事件）不再返回
+        let event_types = serde_json::from_str::<Vec<String>>(&row.event_types)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|value| WebhookEventType::parse(value))
+            .collect();
+
+        Self {
+            id: row.id as u64,
+            url: row.url,
+            event_types,
+            description: row.description,
+            enabled: row.enabled,
+            consecutive_failures: row.consecutive_failures as u32,
+            disabled_reason: row.disabled_reason,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// 数据库中的投递记录
+#[derive(sqlx::FromRow)]
+struct DeliveryRow {
+    id: i64,
+    webhook_id: i64,
+    event_id: i64,
+    event_type: String,
+    payload: String,
+    status: String,
+    attempts: i64,
+    next_attempt_at: Option<i64>,
+    response_status: Option<i64>,
+    response_body: Option<String>,
+    error: Option<String>,
+    redelivery_of: Option<i64>,
+    created_at: String,
+    delivered_at: Option<String>,
+}
+
+impl From<DeliveryRow> for WebhookDelivery {
+    fn from(row: DeliveryRow) -> Self {
+        let status = DeliveryStatus::parse(&row.status);
+        // 投递结束后下次投递时间没有意义
+        let next_attempt_at = row
+            .next_attempt_at
+            .filter(|_| status == DeliveryStatus::Pending)
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true));
+
+        Self {
+            id: row.id as u64,
+            webhook_id: row.webhook_id as u64,
+            event_id: row.event_id as u64,
+            event_type: row.event_type,
+            // 无法解析的内容按原文返回
+            payload: serde_json::from_str(&row.payload).unwrap_or(serde_json::Value::String(row.payload)),
+            status,
+            attempts: row.attempts as u32,
+            next_attempt_at,
+            response_status: row.response_status.map(|status| status as u16),
+            response_body: row.response_body,
+            error: row.error,
+            redelivery_of: row.redelivery_of.map(|id| id as u64),
+            created_at: row.created_at,
+            delivered_at: row.delivered_at,
+        }
+    }
+}
+
+impl WebhookService {
+    /// 创建订阅服务
+    pub fn new(pool: DbPool, config: WebhooksConfig) -> Self {
+        Self {
+            pool,
+            config: Arc::new(config),
+        }
+    }
+
+    /// 创建订阅
+    pub async fn create(&self, req: CreateWebhookRequest) -> Result<Webhook, ServiceError> {
+        validate_url(&req.url, self.config.allow_private_targets)?;
+        let now = now();
+
+        Ok(sqlx::query_as::<_, WebhookRow>(&format!(
+            "INSERT INTO webhooks (url, event_types, secret, description, created_at, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?) RETURNING {}",
+            WEBHOOK_COLUMNS
+        ))
+        .bind(req.url)
+        .bind(event_types_json(&req.event_types))
+        .bind(req.secret)
+        .bind(req.description)
+        .bind(&now)
+        .bind(&now)
+        .fetch_one(&self.pool)
+        .await?
+        .into())
+    }
+
+    /// 所有订阅，按创建顺序
+    pub async fn list(&self) -> Result<Vec<Webhook>, ServiceError> {
+        Ok(sqlx::query_as::<_, WebhookRow>(&format!("SELECT {} FROM webhooks ORDER BY id", WEBHOOK_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(Webhook::from)
+            .collect())
+    }
+
+    /// 根据ID获取订阅
+    pub async fn get(&self, id: u64) -> Result<Webhook, ServiceError> {
+        sqlx::query_as::<_, WebhookRow>(&format!("SELECT {} FROM webhooks WHERE id = ?", WEBHOOK_COLUMNS))
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(Webhook::from)
+            .ok_or_else(|| not_found(id))
+    }
+
+    /// 修改订阅，只修改提交的字段
+    ///
+    /// 启用订阅时清零连续失败次数及停用原因，停用期间积累的投递记录随后继续投递
+    pub async fn update(&self, id: u64, req: UpdateWebhookRequest) -> Result<Webhook, ServiceError> {
+        if let Some(url) = &req.url {
+            validate_url(url, self.config.allow_private_targets)?;
+        }
+
+        sqlx::query_as::<_, WebhookRow>(&format!(
+            "UPDATE webhooks SET url = COALESCE(?1, url), event_types = COALESCE(?2, event_types), \
+             secret = COALESCE(?3, secret), description = COALESCE(?4, description), \
+             enabled = COALESCE(?5, enabled), \
+             consecutive_failures = CASE WHEN ?5 THEN 0 ELSE consecutive_failures END, \
+             disabled_reason = CASE WHEN ?5 IS NULL THEN disabled_reason ELSE NULL END, \
+             updated_at = ?6 WHERE id = ?7 RETURNING {}",
+            WEBHOOK_COLUMNS
+        ))
+        .bind(req.url)
+        .bind(req.event_types.as_deref().map(event_types_json))
+        .bind(req.secret)
+        .bind(req.description)
+        .bind(req.enabled)
+        .bind(now())
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Webhook::from)
+        .ok_or_else(|| not_found(id))
+    }
+
+    /// 删除订阅及其投递记录
+    pub async fn delete(&self, id: u64) -> Result<(), ServiceError> {
+        let deleted = sqlx::query("DELETE FROM webhooks WHERE id = ?")
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(not_found(id));
+        }
+        Ok(())
+    }
+
+    /// 分页查询订阅的投递记录，按创建时间倒序，返回当前页数据及总记录数
+    pub async fn deliveries(
+        &self,
+        webhook_id: u64,
+        status: Option<DeliveryStatus>,
+        page: u32,
+        page_size: u32,
+    ) -> Result<(Vec<WebhookDelivery>, u64), ServiceError> {
+        if page == 0 || page_size == 0 {
+            return Err(ServiceError::Validation("页码和每页记录数必须大于0".to_string()));
+        }
+        self.get(webhook_id).await?;
+
+        let status = status.map(|status| status.as_str());
+        let condition = "webhook_id = ?1 AND (?2 IS NULL OR status = ?2)";
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM webhook_deliveries WHERE {}", condition))
+            .bind(webhook_id as i64)
+            .bind(status)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let deliveries = sqlx::query_as::<_, DeliveryRow>(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE {} ORDER BY id DESC LIMIT ?3 OFFSET ?4",
+            DELIVERY_COLUMNS, condition
+        ))
+        .bind(webhook_id as i64)
+        .bind(status)
+        .bind(page_size as i64)
+        .bind((page as i64 - 1) * page_size as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(WebhookDelivery::from)
+        .collect();
+
+        Ok((deliveries, total as u64))
+    }
+
+    /// 重新投递
+    ///
+    /// 以原请求体创建一条新的投递记录并尽快投递，事件ID不变；订阅已停用时返回冲突错误
+    pub async fn redeliver(&self, webhook_id: u64, delivery_id: u64) -> Result<WebhookDelivery, ServiceError> {
+        let webhook = self.get(webhook_id).await?;
+        if !webhook.enabled {
+            return Err(ServiceError::Conflict(format!("Webhook {} 已停用，请先启用", webhook_id)));
+        }
+        let now = Utc::now();
+
+        sqlx::query_as::<_, DeliveryRow>(&format!(
+            "INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, payload, next_attempt_at, redelivery_of, created_at) \
+             SELECT webhook_id, event_id, event_type, payload, ?1, id, ?2 FROM webhook_deliveries \
+             WHERE id = ?3 AND webhook_id = ?4 RETURNING {}",
+            DELIVERY_COLUMNS
+        ))
+        .bind(now.timestamp())
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .bind(delivery_id as i64)
+        .bind(webhook_id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(WebhookDelivery::from)
+        .ok_or_else(|| ServiceError::NotFound(format!("Delivery with id {} not found", delivery_id)))
+    }
+}
+
+/// 只允许投递到http或https地址；未开启 `allow_private_targets` 时拒绝本机、链路本地及内网地址
+///
+/// 只检查URL中的IP地址及 `localhost`，不解析域名，域名指向内网地址的情况需要在网络出口处限制
+fn validate_url(url: &str, allow_private_targets: bool) -> Result<(), ServiceError> {
+    let parsed = Url::parse(url).map_err(|err| ServiceError::Validation(format!("无效的URL: {}", err)))?;
+    let host = match parsed.host_str() {
+        Some(host) if matches!(parsed.scheme(), "http" | "https") => host,
+        _ => return Err(ServiceError::Validation("URL必须是http或https地址".to_string())),
+    };
+
+    // IPv6地址带有方括号，例如 `[::1]`
+    let private = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_private_ip(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+    };
+    if private && !allow_private_targets {
+        return Err(ServiceError::Validation("不允许投递到本机、链路本地或内网地址".to_string()));
+    }
+    Ok(())
+}
+
+/// 是否为本机、链路本地、内网或未指定地址
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_private_ipv4(ip),
+            None => is_private_ipv6(ip),
+        },
+    }
+}
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // 运营商级NAT地址 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+}
+
+fn is_private_ipv6(ip: Ipv6Addr) -> bool {
+    ip.is_loopback() || ip.is_unspecified() || ip.is_unique_local() || ip.is_unicast_link_local()
+}
+
+/// 去重后的事件类型JSON数组
+fn event_types_json(event_types: &[WebhookEventType]) -> String {
+    let mut values: Vec<&str> = event_types.iter().map(|event_type| event_type.as_str()).collect();
+    values.sort_unstable();
+    values.dedup();
+    serde_json::Value::from(values).to_string()
+}
+
+/// 订阅不存在
+fn not_found(id: u64) -> ServiceError {
+    ServiceError::NotFound(format!("Webhook with id {} not found", id))
+}
+
+/// 当前时间（ISO 8601格式）
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,126 @@
+//! 浏览器会话
+//!
+//! 供浏览器管理后台使用的Cookie认证：登录后签发随机会话令牌，通过 `HttpOnly` 的会话Cookie发送，
+//! 服务端只保存令牌的SHA-256摘要。会话在空闲超时或绝对超时后失效，两者都按服务端时间判断。
+//!
+//! Cookie由浏览器自动携带，因此修改数据的请求还需要CSRF防护（双重提交）：登录时同时签发一个
+//! 允许脚本读取的CSRF令牌Cookie，前端读取后通过请求头提交，服务端比较请求头、Cookie及会话中保存的令牌。
+//!
+//! 会话可以保存在内存或数据库中，见 `SessionStore`；认证中间件见 `middlewares::SessionAuth`，
+//! 登录、注销及会话管理见 `services::SessionService`
+
+mod store;
+
+pub use store::{DatabaseSessionStore, MemorySessionStore, SessionRecord, SessionStore};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use poem::http::header;
+use poem::web::cookie::{Cookie, CookieJar, SameSite};
+use poem::Request;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+use crate::config::{CookieSameSite, SessionStoreKind, SessionsConfig};
+use crate::db::DbPool;
+
+/// 会话ID的长度
+pub const SESSION_ID_LEN: usize = 16;
+
+/// 会话令牌及CSRF令牌的长度
+pub const TOKEN_LEN: usize = 43;
+
+/// 当前请求使用的会话，由认证中间件写入请求扩展，处理函数中用 `Data<&CurrentSession>` 获取
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentSession {
+    /// 会话ID
+    pub id: String,
+    /// 登录的用户
+    pub user_id: u64,
+}
+
+/// 按配置创建会话存储，会话服务与用户服务（修改密码后注销会话）共用同一个存储
+pub fn create_store(config: &SessionsConfig, pool: &DbPool) -> Arc<dyn SessionStore> {
+    match config.store {
+        SessionStoreKind::Memory => Arc::new(MemorySessionStore::new()),
+        SessionStoreKind::Database => Arc::new(DatabaseSessionStore::new(pool.clone())),
+    }
+}
+
+/// 由字母和数字组成的随机字符串
+pub fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// 会话令牌的SHA-256摘要，存储中只保存摘要
+pub fn token_digest(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// 写入会话Cookie及CSRF令牌Cookie，有效期与会话的绝对超时一致
+pub fn set_cookies(jar: &CookieJar, config: &SessionsConfig, token: &str, csrf_token: &str) {
+    let max_age = config.absolute_timeout();
+
+    let mut session = cookie(config, &config.cookie_name, token);
+    session.set_http_only(config.http_only);
+    session.set_max_age(max_age);
+    jar.add(session);
+
+    // 前端需要读取CSRF令牌，不能设置 HttpOnly
+    let mut csrf = cookie(config, &config.csrf_cookie_name, csrf_token);
+    csrf.set_http_only(false);
+    csrf.set_max_age(max_age);
+    jar.add(csrf);
+}
+
+/// 让浏览器删除会话Cookie及CSRF令牌Cookie
+pub fn clear_cookies(jar: &CookieJar, config: &SessionsConfig) {
+    for name in [&config.cookie_name, &config.csrf_cookie_name] {
+        remove_cookie(jar, config, name);
+    }
+}
+
+/// 写入只在服务端读取的短期Cookie，`Secure`、`SameSite` 等属性与会话Cookie相同，
+/// 例如单点登录时绑定登录状态与浏览器
+pub fn set_private_cookie(jar: &CookieJar, config: &SessionsConfig, name: &str, value: &str, max_age: Duration) {
+    let mut cookie = cookie(config, name, value);
+    cookie.set_http_only(true);
+    cookie.set_max_age(max_age);
+    jar.add(cookie);
+}
+
+/// 让浏览器删除Cookie
+pub fn remove_cookie(jar: &CookieJar, config: &SessionsConfig, name: &str) {
+    let mut removal = cookie(config, name, "");
+    removal.make_removal();
+    jar.add(removal);
+}
+
+/// 请求的 `User-Agent`，记录在会话中便于用户识别设备
+pub fn user_agent(req: &Request) -> Option<String> {
+    req.headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+/// 按配置设置 `Secure`、`SameSite`、`Domain`、`Path` 属性的Cookie
+fn cookie(config: &SessionsConfig, name: &str, value: &str) -> Cookie {
+    let mut cookie = Cookie::new_with_str(name, value);
+    cookie.set_secure(config.secure);
+    cookie.set_same_site(match config.same_site {
+        CookieSameSite::Strict => SameSite::Strict,
+        CookieSameSite::Lax => SameSite::Lax,
+        CookieSameSite::None => SameSite::None,
+    });
+    if let Some(domain) = &config.domain {
+        cookie.set_domain(domain);
+    }
+    cookie.set_path(&config.path);
+    cookie
+}
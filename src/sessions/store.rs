@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::db::DbPool;
+
+/// 保存的会话
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    /// 会话ID，用于列出及注销会话
+    pub id: String,
+    /// 会话Cookie中令牌的SHA-256摘要
+    pub token_hash: String,
+    /// 登录的用户
+    pub user_id: u64,
+    /// 双重提交校验使用的CSRF令牌
+    pub csrf_token: String,
+    /// 登录时的客户端IP
+    pub ip: Option<String>,
+    /// 登录时的客户端 `User-Agent`
+    pub user_agent: Option<String>,
+    /// 登录时间（Unix时间戳，秒）
+    pub created_at: i64,
+    /// 最后活跃时间（Unix时间戳，秒），按 `touch_interval_secs` 的间隔更新
+    pub last_seen_at: i64,
+    /// 绝对过期时间（Unix时间戳，秒）
+    pub expires_at: i64,
+}
+
+impl SessionRecord {
+    /// 按绝对过期时间及空闲超时判断会话在 `now` 时是否有效
+    pub fn is_active(&self, now: i64, idle_timeout_secs: i64) -> bool {
+        now < self.expires_at && now < self.last_seen_at.saturating_add(idle_timeout_secs)
+    }
+}
+
+/// 会话的存储方式
+///
+/// 存储只负责保存，不检查会话是否过期，过期判断见 `SessionRecord::is_active`
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// 保存新会话
+    async fn insert(&self, session: &SessionRecord) -> anyhow::Result<()>;
+
+    /// 按令牌摘要查找会话
+    async fn find(&self, token_hash: &str) -> anyhow::Result<Option<SessionRecord>>;
+
+    /// 更新最后活跃时间
+    async fn touch(&self, id: &str, last_seen_at: i64) -> anyhow::Result<()>;
+
+    /// 用户的所有会话，按登录时间排序
+    async fn list(&self, user_id: u64) -> anyhow::Result<Vec<SessionRecord>>;
+
+    /// 删除会话，返回会话是否存在
+    async fn remove(&self, id: &str) -> anyhow::Result<bool>;
+
+    /// 删除用户的所有会话，`except` 指定的会话除外，返回删除的数量
+    async fn remove_user(&self, user_id: u64, except: Option<&str>) -> anyhow::Result<u64>;
+
+    /// 删除已过期的会话：绝对过期时间不晚于 `now`，或最后活跃时间不晚于 `idle_since`，返回删除的数量
+    async fn purge_expired(&self, now: i64, idle_since: i64) -> anyhow::Result<u64>;
+}
+
+/// 保存在进程内存中，重启后所有会话失效，只适用于单实例部署及测试
+#[derive(Default)]
+pub struct MemorySessionStore {
+    /// 按令牌摘要索引，认证时直接查找
+    sessions: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl MemorySessionStore {
+    /// 创建空的内存存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只保留满足条件的会话，返回删除的数量
+    fn retain(&self, keep: impl Fn(&SessionRecord) -> bool) -> u64 {
+        let mut sessions = self.sessions.lock().expect("会话存储锁已损坏");
+        let before = sessions.len();
+        sessions.retain(|_, session| keep(session));
+        (before - sessions.len()) as u64
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn insert(&self, session: &SessionRecord) -> anyhow::Result<()> {
+        let mut sessions = self.sessions.lock().expect("会话存储锁已损坏");
+        sessions.insert(session.token_hash.clone(), session.clone());
+        Ok(())
+    }
+
+    async fn find(&self, token_hash: &str) -> anyhow::Result<Option<SessionRecord>> {
+        Ok(self.sessions.lock().expect("会话存储锁已损坏").get(token_hash).cloned())
+    }
+
+    async fn touch(&self, id: &str, last_seen_at: i64) -> anyhow::Result<()> {
+        let mut sessions = self.sessions.lock().expect("会话存储锁已损坏");
+        if let Some(session) = sessions.values_mut().find(|session| session.id == id) {
+            session.last_seen_at = last_seen_at;
+        }
+        Ok(())
+    }
+
+    async fn list(&self, user_id: u64) -> anyhow::Result<Vec<SessionRecord>> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .lock()
+            .expect("会话存储锁已损坏")
+            .values()
+            .filter(|session| session.user_id == user_id)
+            .cloned()
+            .collect();
+        sessions.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        Ok(sessions)
+    }
+
+    async fn remove(&self, id: &str) -> anyhow::Result<bool> {
+        Ok(self.retain(|session| session.id != id) > 0)
+    }
+
+    async fn remove_user(&self, user_id: u64, except: Option<&str>) -> anyhow::Result<u64> {
+        Ok(self.retain(|session| session.user_id != user_id || Some(session.id.as_str()) == except))
+    }
+
+    async fn purge_expired(&self, now: i64, idle_since: i64) -> anyhow::Result<u64> {
+        Ok(self.retain(|session| session.expires_at > now && session.last_seen_at > idle_since))
+    }
+}
+
+/// 保存在数据库的 `sessions` 表中，多实例共享
+pub struct DatabaseSessionStore {
+    pool: DbPool,
+}
+
+/// 查询会话时返回的列
+const SESSION_COLUMNS: &str = "id, token_hash, user_id, csrf_token, ip, user_agent, created_at, last_seen_at, expires_at";
+
+/// 数据库中的会话记录
+#[derive(sqlx::FromRow)]
+struct SessionRow {
+    id: String,
+    token_hash: String,
+    user_id: i64,
+    csrf_token: String,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    created_at: i64,
+    last_seen_at: i64,
+    expires_at: i64,
+}
+
+impl From<SessionRow> for SessionRecord {
+    fn from(row: SessionRow) -> Self {
+        Self {
+            id: row.id,
+            token_hash: row.token_hash,
+            user_id: row.user_id as u64,
+            csrf_token: row.csrf_token,
+            ip: row.ip,
+            user_agent: row.user_agent,
+            created_at: row.created_at,
+            last_seen_at: row.last_seen_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+impl DatabaseSessionStore {
+    /// 使用应用的数据库连接池
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionStore for DatabaseSessionStore {
+    async fn insert(&self, session: &SessionRecord) -> anyhow::Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO sessions ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            SESSION_COLUMNS
+        ))
+        .bind(&session.id)
+        .bind(&session.token_hash)
+        .bind(session.user_id as i64)
+        .bind(&session.csrf_token)
+        .bind(&session.ip)
+        .bind(&session.user_agent)
+        .bind(session.created_at)
+        .bind(session.last_seen_at)
+        .bind(session.expires_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn find(&self, token_hash: &str) -> anyhow::Result<Option<SessionRecord>> {
+        Ok(
+            sqlx::query_as::<_, SessionRow>(&format!("SELECT {} FROM sessions WHERE token_hash = ?", SESSION_COLUMNS))
+                .bind(token_hash)
+                .fetch_optional(&self.pool)
+                .await?
+                .map(SessionRecord::from),
+        )
+    }
+
+    async fn touch(&self, id: &str, last_seen_at: i64) -> anyhow::Result<()> {
+        sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE id = ?")
+            .bind(last_seen_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn list(&self, user_id: u64) -> anyhow::Result<Vec<SessionRecord>> {
+        let rows = sqlx::query_as::<_, SessionRow>(&format!(
+            "SELECT {} FROM sessions WHERE user_id = ? ORDER BY created_at, id",
+            SESSION_COLUMNS
+        ))
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(SessionRecord::from).collect())
+    }
+
+    async fn remove(&self, id: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM sessions WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn remove_user(&self, user_id: u64, except: Option<&str>) -> anyhow::Result<u64> {
+        let result = sqlx::query("DELETE FROM sessions WHERE user_id = ?1 AND (?2 IS NULL OR id <> ?2)")
+            .bind(user_id as i64)
+            .bind(except)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn purge_expired(&self, now: i64, idle_since: i64) -> anyhow::Result<u64> {
+        let result = sqlx::query("DELETE FROM sessions WHERE expires_at <= ? OR last_seen_at <= ?")
+            .bind(now)
+            .bind(idle_since)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,100 @@
+//! 优雅关闭模块
+//!
+//! 监听 SIGINT/SIGTERM 信号，并通过 `ShutdownToken` 通知后台任务、事件总线、
+//! GraphQL订阅连接等组件停止接收新工作，在超时时间内完成或持久化进行中的工作
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFuture};
+use tokio_util::task::TaskTracker;
+
+/// 关闭令牌
+///
+/// 克隆开销很小，所有克隆共享同一关闭状态。通过 `spawn` 启动的后台任务会被跟踪，
+/// 服务停止后会等待它们结束
+#[derive(Clone, Default)]
+pub struct ShutdownToken {
+    token: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl ShutdownToken {
+    /// 创建新的关闭令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 触发关闭
+    pub fn trigger(&self) {
+        self.token.cancel();
+    }
+
+    /// 是否已触发关闭
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// 等待关闭被触发
+    ///
+    /// 常用于 `tokio::select!` 中，例如：
+    ///
+    /// ```ignore
+    /// loop {
+    ///     tokio::select! {
+    ///         _ = shutdown.cancelled() => break,
+    ///         job = queue.next() => handle(job).await,
+    ///     }
+    /// }
+    /// ```
+    pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+        self.token.cancelled()
+    }
+
+    /// 启动一个被跟踪的后台任务
+    ///
+    /// 任务应自行观察 `cancelled()`，在关闭时尽快完成或持久化当前工作
+    pub fn spawn<F>(&self, task: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tracker.spawn(task)
+    }
+
+    /// 等待所有被跟踪的后台任务结束
+    ///
+    /// 超时返回 `false`，此时仍未结束的任务会随进程退出被丢弃
+    pub async fn wait_for_tasks(&self, timeout: Duration) -> bool {
+        self.tracker.close();
+        tokio::time::timeout(timeout, self.tracker.wait())
+            .await
+            .is_ok()
+    }
+}
+
+/// 等待 SIGINT（Ctrl+C）或 SIGTERM 信号
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("无法监听 Ctrl+C 信号");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("无法监听 SIGTERM 信号")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("收到 SIGINT 信号"),
+        _ = terminate => tracing::info!("收到 SIGTERM 信号"),
+    }
+}
@@ -0,0 +1,190 @@
+//! 后台任务
+//!
+//! 任务通过 `ShutdownToken::spawn` 启动，服务关闭时结束当前轮次后退出
+
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::app::AppState;
+use crate::config::{AppConfig, EventsConfig, JobsConfig, SchedulerConfig, WebhooksConfig};
+
+/// 启动所有后台任务
+pub fn spawn_all(state: &AppState, config: &AppConfig) {
+    spawn_outbox_relay(state, &config.events);
+    spawn_webhook_delivery(state, &config.webhooks);
+    spawn_job_workers(state, &config.jobs);
+    spawn_scheduler(state, &config.scheduler);
+}
+
+/// 持续投递发件箱中的领域事件
+///
+/// 一轮领取满一批时立即继续下一轮，否则等待轮询间隔
+pub fn spawn_outbox_relay(state: &AppState, events: &EventsConfig) {
+    if !events.relay_enabled {
+        tracing::info!("未启用发件箱投递任务");
+        return;
+    }
+
+    let outbox = state.outbox.clone();
+    let shutdown = state.shutdown.clone();
+    let batch_size = events.batch_size;
+    let mut interval = tokio::time::interval(events.poll_interval());
+
+    state.shutdown.spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            loop {
+                match outbox.relay_once().await {
+                    Ok(stats) if stats.delivered + stats.retried + stats.dead_lettered >= batch_size => {}
+                    Ok(_) => break,
+                    Err(err) => {
+                        tracing::warn!("投递领域事件失败: {}", err);
+                        break;
+                    }
+                }
+                if shutdown.is_shutting_down() {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// 持续投递到期的Webhook请求
+///
+/// 一轮领取满一批时立即继续下一轮，否则等待轮询间隔
+pub fn spawn_webhook_delivery(state: &AppState, webhooks: &WebhooksConfig) {
+    if !webhooks.delivery_enabled {
+        tracing::info!("未启用Webhook投递任务");
+        return;
+    }
+
+    let sender = state.webhook_sender.clone();
+    let shutdown = state.shutdown.clone();
+    let batch_size = webhooks.batch_size;
+    let mut interval = tokio::time::interval(webhooks.poll_interval());
+
+    state.shutdown.spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            loop {
+                match sender.deliver_due().await {
+                    Ok(stats) if stats.succeeded + stats.retried + stats.failed >= batch_size => {}
+                    Ok(_) => break,
+                    Err(err) => {
+                        tracing::warn!("投递Webhook失败: {}", err);
+                        break;
+                    }
+                }
+                if shutdown.is_shutting_down() {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// 后台任务工作池
+///
+/// 同时执行的任务数不超过 `concurrency`，有空闲名额时按轮询间隔领取到期任务；
+/// 每个任务单独运行，服务关闭时等待执行中的任务结束
+pub fn spawn_job_workers(state: &AppState, jobs: &JobsConfig) {
+    if !jobs.workers_enabled || jobs.concurrency == 0 {
+        tracing::info!("未启用后台任务执行");
+        return;
+    }
+
+    let queue = state.jobs.clone();
+    let shutdown = state.shutdown.clone();
+    let slots = Arc::new(Semaphore::new(jobs.concurrency as usize));
+    let mut interval = tokio::time::interval(jobs.poll_interval());
+
+    state.shutdown.spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            // 持续领取直到名额用完或没有到期任务
+            while !shutdown.is_shutting_down() {
+                let available = slots.available_permits() as u32;
+                if available == 0 {
+                    break;
+                }
+                let claimed = match queue.claim(available).await {
+                    Ok(claimed) => claimed,
+                    Err(err) => {
+                        tracing::warn!("领取后台任务失败: {}", err);
+                        break;
+                    }
+                };
+                let drained = (claimed.len() as u32) < available;
+
+                for job in claimed {
+                    let Ok(permit) = slots.clone().acquire_owned().await else {
+                        break;
+                    };
+                    let queue = queue.clone();
+                    shutdown.spawn(async move {
+                        if let Err(err) = queue.execute(job).await {
+                            tracing::warn!("记录后台任务结果失败: {}", err);
+                        }
+                        drop(permit);
+                    });
+                }
+                if drained {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// 定时任务调度
+///
+/// 按轮询间隔检查到期任务，抢到执行锁的任务各自单独运行，执行时间较长的任务不影响其他任务按时执行
+pub fn spawn_scheduler(state: &AppState, config: &SchedulerConfig) {
+    if !config.enabled {
+        tracing::info!("未启用定时任务");
+        return;
+    }
+
+    let scheduler = state.scheduler.clone();
+    let shutdown = state.shutdown.clone();
+    let mut interval = tokio::time::interval(config.poll_interval());
+
+    state.shutdown.spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            let claimed = match scheduler.claim_due().await {
+                Ok(claimed) => claimed,
+                Err(err) => {
+                    tracing::warn!("检查定时任务失败: {}", err);
+                    continue;
+                }
+            };
+            for task in claimed {
+                let scheduler = scheduler.clone();
+                shutdown.spawn(async move {
+                    if let Err(err) = scheduler.execute(task).await {
+                        tracing::warn!("记录定时任务结果失败: {}", err);
+                    }
+                });
+            }
+        }
+    });
+}
@@ -0,0 +1,93 @@
+//! TLS模块
+//!
+//! 根据配置加载证书并生成 `RustlsConfig` 流，证书文件变更时自动重新加载，
+//! 无需重启服务即可完成证书轮换
+
+use std::io;
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream, StreamExt};
+use poem::listener::{RustlsCertificate, RustlsConfig};
+
+use crate::config::TlsConfig;
+
+/// 证书相关文件内容，用于判断文件是否发生变更
+#[derive(PartialEq, Eq)]
+struct TlsFiles {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+    client_ca: Option<Vec<u8>>,
+}
+
+impl TlsFiles {
+    /// 读取配置中的证书、私钥及客户端CA文件
+    fn read(config: &TlsConfig) -> io::Result<Self> {
+        let read = |path: &str| {
+            std::fs::read(path)
+                .map_err(|err| io::Error::new(err.kind(), format!("读取 {} 失败: {}", path, err)))
+        };
+
+        Ok(Self {
+            cert: read(&config.cert_path)?,
+            key: read(&config.key_path)?,
+            client_ca: config.client_ca_path.as_deref().map(read).transpose()?,
+        })
+    }
+
+    /// 转换为 poem 的 `RustlsConfig`
+    fn to_rustls_config(&self, config: &TlsConfig) -> RustlsConfig {
+        let rustls_config = RustlsConfig::new().fallback(
+            RustlsCertificate::new()
+                .cert(self.cert.clone())
+                .key(self.key.clone()),
+        );
+
+        match &self.client_ca {
+            Some(ca) if config.client_auth_required => rustls_config.client_auth_required(ca.clone()),
+            Some(ca) => rustls_config.client_auth_optional(ca.clone()),
+            None => rustls_config,
+        }
+    }
+}
+
+/// 加载一次TLS配置
+pub fn load_rustls_config(config: &TlsConfig) -> io::Result<RustlsConfig> {
+    Ok(TlsFiles::read(config)?.to_rustls_config(config))
+}
+
+/// 创建TLS配置流
+///
+/// 首先产出当前证书对应的配置，之后按 `reload_interval_secs` 检查文件内容，
+/// 发生变化时产出新的配置。读取失败时保留旧证书继续服务，只记录警告日志
+pub fn rustls_config_stream(config: TlsConfig) -> io::Result<BoxStream<'static, RustlsConfig>> {
+    let current = TlsFiles::read(&config)?;
+    let initial = stream::once(futures::future::ready(current.to_rustls_config(&config)));
+
+    let Some(interval) = config.reload_interval() else {
+        return Ok(initial.boxed());
+    };
+
+    let reloads = stream::unfold((config, current), move |(config, current)| async move {
+        let files = wait_for_change(&config, &current, interval).await;
+        let rustls_config = files.to_rustls_config(&config);
+        Some((rustls_config, (config, files)))
+    });
+
+    Ok(initial.chain(reloads).boxed())
+}
+
+/// 轮询证书文件，直到内容与当前使用的证书不同
+async fn wait_for_change(config: &TlsConfig, current: &TlsFiles, interval: Duration) -> TlsFiles {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        match TlsFiles::read(config) {
+            Ok(files) if files != *current => {
+                tracing::info!(cert = %config.cert_path, "检测到证书文件变更，重新加载TLS配置");
+                return files;
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!(error = %err, "读取证书文件失败，继续使用当前证书"),
+        }
+    }
+}
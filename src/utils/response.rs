@@ -1,10 +1,20 @@
+// 这里的辅助函数作为Poem处理函数的返回值，错误类型必须是 `poem::Error`，无法改为装箱的错误
+#![allow(clippy::result_large_err)]
+
+use poem::{http::StatusCode, IntoResponse};
 use poem_openapi::payload::Json;
-use poem_openapi::{Object, types::Type, types::ToJSON, types::ParseFromJSON};
+use poem_openapi::{Object, types::Example, types::Type, types::ToJSON, types::ParseFromJSON};
 use serde::{Deserialize, Serialize};
 
+use crate::services::ServiceError;
+
 /// 统一API响应结构体
 /// 用于封装所有接口的返回数据
+///
+/// 出错时 `code` 为对应的错误码、`data` 为空，例如：
+/// `{"code": 404, "msg": "User with id 42 not found", "data": null}`
 #[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
 pub struct ApiResponse<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON> {
     /// 状态码，例如 200 表示成功，400 表示客户端错误，500 表示服务器错误
     #[oai(validator(minimum(value = "100"), maximum(value = "599")))]
@@ -75,6 +85,41 @@ impl<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON> ApiResponse<T>
     }
 }
 
+impl<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON + Example> Example for ApiResponse<T> {
+    fn example() -> Self {
+        ApiResponse::success(T::example())
+    }
+}
+
+/// 错误响应
+///
+/// 与 `ApiResponse` 的结构相同，`data` 始终为空；用于以非200状态码返回的错误（例如401、403、412、428），
+/// 以200返回的业务错误同样是这个结构，例如 `{"code": 404, "msg": "User with id 42 not found", "data": null}`
+#[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
+pub struct ErrorResponse {
+    /// 错误码，与HTTP状态码含义相同
+    #[oai(validator(minimum(value = "100"), maximum(value = "599")))]
+    pub code: u16,
+    /// 错误消息
+    pub msg: String,
+    /// 始终为空
+    pub data: Option<EmptyResponse>,
+}
+
+impl ErrorResponse {
+    /// 创建一个错误响应
+    pub fn new(code: u16, msg: String) -> Self {
+        ErrorResponse { code, msg, data: None }
+    }
+}
+
+impl Example for ErrorResponse {
+    fn example() -> Self {
+        ErrorResponse::new(412, "用户已被修改，当前版本为 3，请获取最新数据后重试".to_string())
+    }
+}
+
 /// 创建一个表示成功的 `poem::Result<Json<ApiResponse<T>>>`
 ///
 /// # Arguments
@@ -111,10 +156,48 @@ pub fn error_json<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON>(co
     ApiResponse::<T>::error(code, msg).to_json_result()
 }
 
+/// 将业务层结果转换为 `poem::Result<Json<ApiResponse<T>>>`
+///
+/// 成功时返回数据，失败时使用业务错误对应的状态码和消息；
+/// 认证、授权错误（401、403）以对应的HTTP状态码返回，其余业务错误以200返回
+///
+/// # Arguments
+///
+/// * `result` - 业务层返回的结果
+///
+/// # Returns
+///
+/// 包含成功或失败响应的 `poem::Result`
+pub fn result_json<T: Send + Sync + Serialize + Type + ToJSON + ParseFromJSON>(result: Result<T, ServiceError>) -> poem::Result<Json<ApiResponse<T>>> {
+    match result {
+        Ok(data) => success_json(data),
+        Err(err @ (ServiceError::Unauthorized(_) | ServiceError::Forbidden(_))) => Err(status_error(&err)),
+        Err(err) => error_json(err.status_code(), err.to_string()),
+    }
+}
+
+/// 将业务错误转换为以对应HTTP状态码返回的 `poem::Error`，响应体仍为统一响应格式
+///
+/// 用于认证、授权错误，例如 `Admin` 提取器及 `result_json`
+pub fn status_error(err: &ServiceError) -> poem::Error {
+    let status = StatusCode::from_u16(err.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let resp = Json(ApiResponse::<EmptyResponse>::error(status.as_u16(), err.to_string()))
+        .with_status(status)
+        .into_response();
+    poem::Error::from_response(resp)
+}
+
 /// 空响应类型，用于不需要返回数据的API
 #[derive(Debug, Serialize, Deserialize, Object)]
+#[oai(example)]
 pub struct EmptyResponse {}
 
+impl Example for EmptyResponse {
+    fn example() -> Self {
+        EmptyResponse {}
+    }
+}
+
 /// 创建一个空响应实例
 pub fn empty() -> EmptyResponse {
     EmptyResponse {}
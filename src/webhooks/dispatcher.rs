@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::db::DbPool;
+use crate::events::{EventEnvelope, EventHandler};
+
+/// 将领域事件转为Webhook投递记录
+///
+/// 只写入投递记录，实际请求由 `WebhookSender` 发送，接收方变慢不会拖慢事件投递；
+/// 同一事件重复投递时不会重复创建记录
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    pool: DbPool,
+}
+
+impl WebhookDispatcher {
+    /// 创建分发器
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventHandler for WebhookDispatcher {
+    fn name(&self) -> &str {
+        "webhooks"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> anyhow::Result<()> {
+        let event_type = envelope.event.event_type();
+        let payload = json!({
+            "id": envelope.id,
+            "type": event_type,
+            "occurred_at": envelope.occurred_at,
+            "data": serde_json::to_value(&envelope.event)?.get("data").cloned().unwrap_or(Value::Null),
+        });
+        let now = Utc::now();
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO webhook_deliveries (webhook_id, event_id, event_type, payload, next_attempt_at, created_at) \
+             SELECT id, ?1, ?2, ?3, ?4, ?5 FROM webhooks \
+             WHERE enabled = 1 AND EXISTS (SELECT 1 FROM json_each(webhooks.event_types) WHERE value = ?2)",
+        )
+        .bind(envelope.id as i64)
+        .bind(event_type)
+        .bind(payload.to_string())
+        .bind(now.timestamp())
+        .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
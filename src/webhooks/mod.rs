@@ -0,0 +1,57 @@
+//! Webhook
+//!
+//! 领域事件由 `WebhookDispatcher` 为订阅了该事件类型的每个启用中的订阅生成一条投递记录，
+//! 再由后台任务（`WebhookSender::deliver_due`）以POST请求推送给接收方。投递失败按指数退避重试，
+//! 连续失败达到阈值时自动停用订阅。
+//!
+//! 每个请求携带以下请求头，接收方用订阅的密钥验证签名：
+//!
+//! - `X-Webhook-Signature`：`sha256=<hex>`，对 `"{timestamp}.{body}"` 计算的HMAC-SHA256
+//! - `X-Webhook-Timestamp`：签名时间（Unix时间戳，秒），接收方应拒绝过旧的请求以防重放
+//! - `X-Webhook-Event`、`X-Webhook-Event-Id`：事件类型及ID，同一事件重试时ID不变
+//! - `X-Webhook-Delivery-Id`：投递ID，与投递记录对应
+
+mod dispatcher;
+mod sender;
+
+pub use dispatcher::WebhookDispatcher;
+pub use sender::{DeliveryStats, WebhookSender};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// 签名请求头
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// 签名时间请求头
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// 事件类型请求头
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// 事件ID请求头
+pub const EVENT_ID_HEADER: &str = "X-Webhook-Event-Id";
+/// 投递ID请求头
+pub const DELIVERY_ID_HEADER: &str = "X-Webhook-Delivery-Id";
+
+/// 签名前缀，标识签名算法
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// 计算请求签名，格式为 `sha256=<hex>`
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    format!("{}{}", SIGNATURE_PREFIX, hex::encode(mac(secret, timestamp, body).finalize().into_bytes()))
+}
+
+/// 验证请求签名，比较耗时与签名内容无关
+pub fn verify(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let Some(Ok(expected)) = signature.strip_prefix(SIGNATURE_PREFIX).map(hex::decode) else {
+        return false;
+    };
+    mac(secret, timestamp, body).verify_slice(&expected).is_ok()
+}
+
+/// 以 `"{timestamp}.{body}"` 为内容的HMAC
+fn mac(secret: &str, timestamp: i64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC接受任意长度的密钥");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
@@ -0,0 +1,233 @@
+use chrono::{SecondsFormat, Utc};
+use futures::future::join_all;
+
+use super::{sign, DELIVERY_ID_HEADER, EVENT_HEADER, EVENT_ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::config::WebhooksConfig;
+use crate::db::DbPool;
+use crate::services::ServiceError;
+
+/// 投递记录中保留的响应内容长度上限（字节）
+const MAX_RESPONSE_BODY: usize = 1024;
+
+/// 请求超时之外额外保留的租约时间（秒）
+const LEASE_MARGIN_SECS: i64 = 30;
+
+/// 一轮投递的结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryStats {
+    /// 投递成功的记录数
+    pub succeeded: u32,
+    /// 投递失败、等待重试的记录数
+    pub retried: u32,
+    /// 超过最大投递次数、标记为失败的记录数
+    pub failed: u32,
+    /// 因连续失败被自动停用的订阅数
+    pub disabled: u32,
+}
+
+/// 已领取的投递记录及对应订阅
+#[derive(sqlx::FromRow)]
+struct ClaimedDelivery {
+    id: i64,
+    webhook_id: i64,
+    event_id: i64,
+    event_type: String,
+    payload: String,
+    attempts: i64,
+    url: String,
+    secret: String,
+}
+
+/// 一次请求的结果
+struct Attempt {
+    /// 响应状态码，请求未完成时为空
+    status: Option<u16>,
+    /// 响应内容（已截断）
+    body: Option<String>,
+    /// 失败原因，成功时为空
+    error: Option<String>,
+}
+
+/// Webhook投递
+///
+/// 克隆开销很小，后台任务与测试共用
+#[derive(Clone)]
+pub struct WebhookSender {
+    pool: DbPool,
+    client: reqwest::Client,
+    config: WebhooksConfig,
+}
+
+impl WebhookSender {
+    /// 创建投递器，请求不跟随重定向
+    pub fn new(pool: DbPool, config: WebhooksConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout())
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "-webhooks/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self { pool, client, config })
+    }
+
+    /// 领取一批到期的投递记录并发投递
+    pub async fn deliver_due(&self) -> Result<DeliveryStats, ServiceError> {
+        let now = Utc::now().timestamp();
+        let lease = self.config.timeout().as_secs() as i64 + LEASE_MARGIN_SECS;
+
+        // 领取即推迟下次投递时间，租约内其他投递任务不会重复领取；停用订阅的记录保留到重新启用
+        let claimed = sqlx::query_as::<_, ClaimedDelivery>(
+            "UPDATE webhook_deliveries SET attempts = attempts + 1, next_attempt_at = ?1 \
+             WHERE id IN (SELECT d.id FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id \
+                          WHERE d.status = 'pending' AND d.next_attempt_at <= ?2 AND w.enabled = 1 \
+                          ORDER BY d.next_attempt_at, d.id LIMIT ?3) \
+             RETURNING id, webhook_id, event_id, event_type, payload, attempts, \
+                       (SELECT url FROM webhooks WHERE webhooks.id = webhook_deliveries.webhook_id) AS url, \
+                       (SELECT secret FROM webhooks WHERE webhooks.id = webhook_deliveries.webhook_id) AS secret",
+        )
+        .bind(now + lease)
+        .bind(now)
+        .bind(self.config.batch_size as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let attempts = join_all(claimed.iter().map(|delivery| self.send(delivery))).await;
+
+        let mut stats = DeliveryStats::default();
+        for (delivery, attempt) in claimed.iter().zip(attempts) {
+            self.record(delivery, attempt, &mut stats).await?;
+        }
+        Ok(stats)
+    }
+
+    /// 发送一次请求
+    async fn send(&self, delivery: &ClaimedDelivery) -> Attempt {
+        let timestamp = Utc::now().timestamp();
+        let signature = sign(&delivery.secret, timestamp, delivery.payload.as_bytes());
+
+        let result = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(EVENT_ID_HEADER, delivery.event_id)
+            .header(DELIVERY_ID_HEADER, delivery.id)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+
+        let resp = match result {
+            Ok(resp) => resp,
+            Err(err) => {
+                return Attempt {
+                    status: None,
+                    body: None,
+                    error: Some(format!("请求失败: {}", err)),
+                }
+            }
+        };
+
+        let status = resp.status();
+        let body = resp.text().await.ok().map(truncate);
+        Attempt {
+            status: Some(status.as_u16()),
+            body,
+            error: (!status.is_success()).then(|| format!("接收方返回状态码 {}", status.as_u16())),
+        }
+    }
+
+    /// 记录请求结果：成功时清零连续失败次数，失败时安排重试，连续失败达到阈值时停用订阅
+    async fn record(
+        &self,
+        delivery: &ClaimedDelivery,
+        attempt: Attempt,
+        stats: &mut DeliveryStats,
+    ) -> Result<(), ServiceError> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let Some(error) = attempt.error else {
+            sqlx::query(
+                "UPDATE webhook_deliveries SET status = 'succeeded', next_attempt_at = NULL, response_status = ?, \
+                 response_body = ?, error = NULL, delivered_at = ? WHERE id = ?",
+            )
+            .bind(attempt.status)
+            .bind(attempt.body)
+            .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .bind(delivery.id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE webhooks SET consecutive_failures = 0 WHERE id = ?")
+                .bind(delivery.webhook_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            stats.succeeded += 1;
+            return Ok(());
+        };
+
+        let attempts = delivery.attempts as u32;
+        let (status, next_attempt_at) = if attempts >= self.config.max_attempts {
+            tracing::warn!("Webhook投递 {} 第 {} 次失败，不再重试: {}", delivery.id, attempts, error);
+            stats.failed += 1;
+            ("failed", None)
+        } else {
+            let delay = self.config.retry_delay(attempts);
+            tracing::info!("Webhook投递 {} 第 {} 次失败，{:?} 后重试: {}", delivery.id, attempts, delay, error);
+            stats.retried += 1;
+            ("pending", Some(now.timestamp() + delay.as_secs() as i64))
+        };
+        sqlx::query(
+            "UPDATE webhook_deliveries SET status = ?, next_attempt_at = ?, response_status = ?, \
+             response_body = ?, error = ? WHERE id = ?",
+        )
+        .bind(status)
+        .bind(next_attempt_at)
+        .bind(attempt.status)
+        .bind(attempt.body)
+        .bind(&error)
+        .bind(delivery.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let failures: i64 = sqlx::query_scalar(
+            "UPDATE webhooks SET consecutive_failures = consecutive_failures + 1 WHERE id = ? RETURNING consecutive_failures",
+        )
+        .bind(delivery.webhook_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let threshold = self.config.disable_after_failures;
+        if threshold > 0 && failures >= threshold as i64 {
+            let disabled = sqlx::query(
+                "UPDATE webhooks SET enabled = 0, disabled_reason = ?, updated_at = ? WHERE id = ? AND enabled = 1",
+            )
+            .bind(format!("连续 {} 次投递失败，已自动停用，最后一次失败原因: {}", failures, error))
+            .bind(now.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .bind(delivery.webhook_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if disabled > 0 {
+                tracing::warn!("Webhook订阅 {} 连续 {} 次投递失败，已自动停用", delivery.webhook_id, failures);
+                stats.disabled += 1;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// 截断响应内容，保证不切断多字节字符
+fn truncate(mut body: String) -> String {
+    if body.len() > MAX_RESPONSE_BODY {
+        let mut end = MAX_RESPONSE_BODY;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+    }
+    body
+}
@@ -0,0 +1,231 @@
+//! API密钥测试
+
+mod common;
+
+use common::{body, test_actor, RequestExt, TestApp, UserFixture};
+use poem::http::{header, StatusCode};
+use serde_json::{json, Value};
+use {{crate_name}}::models::api_key::{ApiKeyScope, CreateApiKeyRequest};
+
+/// 创建密钥，返回密钥ID及原文
+async fn create_key(app: &TestApp, scopes: &[ApiKeyScope]) -> (u64, String) {
+    let req = CreateApiKeyRequest {
+        name: "billing-service".to_string(),
+        scopes: scopes.to_vec(),
+        expires_at: None,
+    };
+    let created = app.state.api_keys.create(&test_actor(), req).await.unwrap();
+    (created.api_key.id, created.key)
+}
+
+#[tokio::test]
+async fn created_key_authenticates_requests() {
+    let app = TestApp::new().await;
+    let admin = app.admin_key().await;
+    let resp = app
+        .client
+        .post("/api/v1/api-keys")
+        .api_key(&admin)
+        .body_json(&json!({ "name": "billing-service", "scopes": ["read", "read"] }))
+        .send()
+        .await;
+    let created = body(resp).await;
+    let key = created["data"]["key"].as_str().unwrap().to_string();
+    let prefix = created["data"]["api_key"]["prefix"].as_str().unwrap();
+    assert!(key.starts_with(&format!("{}_", prefix)));
+    assert!(prefix.starts_with("pak_"));
+    assert_eq!(created["data"]["api_key"]["scopes"], json!(["read"]));
+
+    let resp = app.client.get("/api/v2/api-keys/current").api_key(&key).send().await;
+    resp.assert_status_is_ok();
+    let current = body(resp).await;
+    assert_eq!(current["data"]["id"], created["data"]["api_key"]["id"]);
+    assert!(current["data"]["last_used_at"].is_string());
+
+    let resp = app
+        .client
+        .get("/api/v2/api-keys/current")
+        .header(header::AUTHORIZATION, format!("ApiKey {}", key))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+
+    // 当前密钥接口必须携带密钥
+    let resp = app.client.get("/api/v2/api-keys/current").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+
+    // 列表不包含密钥原文及摘要
+    let list = body(app.client.get("/api/v1/api-keys").api_key(&admin).send().await).await;
+    let item = &list["data"]["items"][0];
+    assert!(item.get("key").is_none() && item.get("key_hash").is_none());
+}
+
+#[tokio::test]
+async fn invalid_revoked_and_expired_keys_are_rejected() {
+    let app = TestApp::new().await;
+    let (id, key) = create_key(&app, &[ApiKeyScope::Admin]).await;
+
+    let resp = app.client.get("/api/v1/users").api_key("pak_unknown_key").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    resp.assert_header(header::WWW_AUTHENTICATE, "ApiKey");
+
+    app.client.get("/api/v1/users").api_key(&key).send().await.assert_status_is_ok();
+    let revoked = app.state.api_keys.revoke(&test_actor(), id).await.unwrap();
+    assert!(revoked.revoked_at.is_some());
+    app.client.get("/api/v1/users").api_key(&key).send().await.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(app.state.api_keys.revoke(&test_actor(), id).await.is_err());
+
+    // 过期时间必须晚于当前时间
+    let admin = app.admin_key().await;
+    let resp = app
+        .client
+        .post("/api/v1/api-keys")
+        .api_key(&admin)
+        .body_json(&json!({ "name": "expired", "scopes": ["read"], "expires_at": "2020-01-01T00:00:00Z" }))
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 400);
+
+    let (id, key) = create_key(&app, &[ApiKeyScope::Admin]).await;
+    sqlx::query("UPDATE api_keys SET expires_at = '2020-01-01T00:00:00Z' WHERE id = ?")
+        .bind(id as i64)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    app.client.get("/api/v1/users").api_key(&key).send().await.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn scopes_limit_what_keys_can_do() {
+    let app = TestApp::new().await;
+    let (_, read) = create_key(&app, &[ApiKeyScope::Read]).await;
+    let (_, write) = create_key(&app, &[ApiKeyScope::Write]).await;
+    let (_, admin) = create_key(&app, &[ApiKeyScope::Admin]).await;
+
+    // 只读密钥可以发起GET请求，但查询用户列表另需要 admin 授权范围
+    let resp = app.client.get("/api/v2/users").api_key(&read).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(body(resp).await["msg"], "API密钥缺少 admin 授权范围");
+    app.client.get("/api/v2/users").api_key(&admin).send().await.assert_status_is_ok();
+    let resp = app
+        .client
+        .post("/api/v2/users")
+        .api_key(&read)
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(body(resp).await["msg"], "API密钥缺少 write 授权范围");
+
+    let resp = app
+        .client
+        .post("/api/v2/users")
+        .api_key(&write)
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+
+    // 管理密钥需要 admin 范围，避免低权限的密钥创建高权限的密钥
+    let create = json!({ "name": "escalated", "scopes": ["admin"] });
+    let resp = app.client.post("/api/v1/api-keys").api_key(&write).body_json(&create).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(body(resp).await["code"], 403);
+    let resp = app.client.get("/api/v1/api-keys").api_key(&read).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(body(resp).await["code"], 403);
+    let resp = app.client.post("/api/v1/api-keys").api_key(&admin).body_json(&create).send().await;
+    assert_eq!(body(resp).await["code"], 200);
+}
+
+#[tokio::test]
+async fn admin_endpoints_reject_anonymous_and_unprivileged_callers() {
+    let app = TestApp::new().await;
+    let (id, _) = create_key(&app, &[ApiKeyScope::Read]).await;
+    let (_, write) = create_key(&app, &[ApiKeyScope::Write]).await;
+
+    // 未认证的调用方不能创建、查看或吊销密钥
+    let create = json!({ "name": "anonymous", "scopes": ["admin"] });
+    let resp = app.client.post("/api/v1/api-keys").body_json(&create).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(body(resp).await["code"], 401);
+    let resp = app.client.get("/api/v1/api-keys").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(body(resp).await["code"], 401);
+    let resp = app.client.get(format!("/api/v1/api-keys/{}", id)).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(body(resp).await["code"], 401);
+    let resp = app.client.delete(format!("/api/v1/api-keys/{}", id)).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(body(resp).await["code"], 401);
+    assert_eq!(app.state.api_keys.list(&test_actor().actor).await.unwrap().len(), 2);
+    assert!(app.state.api_keys.get(&test_actor().actor, id).await.unwrap().revoked_at.is_none());
+
+    // 其他管理接口同样需要管理权限
+    for path in ["/api/v1/jobs", "/api/v2/scheduler/tasks"] {
+        app.client.get(path).send().await.assert_status(StatusCode::UNAUTHORIZED);
+        let resp = app.client.get(path).api_key(&write).send().await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        assert_eq!(body(resp).await["msg"], "API密钥缺少 admin 授权范围");
+    }
+    let resp = app.client.post("/api/v1/scheduler/tasks/purge_deleted_users/run").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn changes_are_audited_as_the_key() {
+    let app = TestApp::new().await;
+    let (id, key) = create_key(&app, &[ApiKeyScope::Write]).await;
+
+    let resp = app
+        .client
+        .post("/api/v2/users")
+        .header(header::AUTHORIZATION, format!("apikey {}", key))
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+
+    let events = app.state.audit.list(&Default::default(), 1, 10).await.unwrap().0;
+    assert_eq!(events[0].entity_type, "user");
+    assert_eq!(events[0].actor_type, "api_key");
+    assert_eq!(events[0].actor_id.as_deref(), Some(id.to_string().as_str()));
+    assert_eq!(events[0].actor_name.as_deref(), Some("billing-service"));
+}
+
+#[tokio::test]
+async fn graphql_checks_operation_scope() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let (_, read) = create_key(&app, &[ApiKeyScope::Read]).await;
+    let (_, admin) = create_key(&app, &[ApiKeyScope::Admin]).await;
+
+    let send = |query: &str, variables: Value, key: &str| {
+        app.client
+            .post("/graphql/query")
+            .api_key(key)
+            .body_json(&json!({ "query": query, "variables": variables }))
+            .send()
+    };
+
+    // 查询只需要 read 授权范围，列出用户另需要 admin 授权范围
+    let resp = body(send("{ users { id } }", json!({}), &read).await).await;
+    assert_eq!(resp["errors"][0]["message"], "API密钥缺少 admin 授权范围");
+    let resp = body(send("{ users { id } }", json!({}), &admin).await).await;
+    assert_eq!(resp["data"]["users"].as_array().unwrap().len(), 1);
+
+    let mutation = "mutation($id: Int!) { deleteUser(id: $id) }";
+    let resp = body(send(mutation, json!({ "id": user.id }), &read).await).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    assert_eq!(resp["errors"][0]["message"], "API密钥缺少 write 授权范围");
+    assert!(app.state.users.get(user.id.unwrap()).await.is_ok());
+
+    send("{ users { id } }", json!({}), "pak_unknown_key").await.assert_status(StatusCode::UNAUTHORIZED);
+
+    // 关闭后密钥被忽略，按匿名调用方处理
+    let app = TestApp::with_config(|config| config.api_keys.enabled = false).await;
+    let resp = app.client.get("/api/v1/users").api_key("pak_unknown_key").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(resp.0.headers().get(header::WWW_AUTHENTICATE).is_none());
+    assert_eq!(body(resp).await["msg"], "未登录或缺少认证信息");
+}
@@ -0,0 +1,374 @@
+//! 应用组装测试：文档、健康检查、GraphQL及版本协商
+
+mod common;
+
+use common::{body, free_port, test_actor, RequestExt, TestApp, UserFixture};
+use futures::{SinkExt, StreamExt};
+use poem::{http::StatusCode, listener::TcpListener, Server};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+use {{crate_name}}::app;
+use {{crate_name}}::config::{DocsServer, DocsUi};
+use {{crate_name}}::models::api_key::{ApiKeyScope, CreateApiKeyRequest};
+
+#[tokio::test]
+async fn serves_docs_and_specs() {
+    let app = TestApp::new().await;
+
+    for uri in ["/api/docs", "/api/docs/v2"] {
+        app.client.get(uri).send().await.assert_status_is_ok();
+    }
+
+    let spec = body(app.client.get("/api/docs/v2/json").send().await).await;
+    assert!(spec["paths"]["/users"].is_object());
+
+    let resp = app.client.get("/api/docs/yaml").send().await;
+    resp.assert_status_is_ok();
+    assert!(resp.0.into_body().into_string().await.unwrap().starts_with("openapi:"));
+}
+
+#[tokio::test]
+async fn serves_every_docs_ui() {
+    let app = TestApp::new().await;
+
+    for ui in DocsUi::ALL {
+        for prefix in ["/api/docs", "/api/docs/v1", "/api/docs/v2"] {
+            let resp = app.client.get(format!("{}/{}", prefix, ui.as_str())).send().await;
+            resp.assert_status_is_ok();
+            resp.assert_content_type("text/html; charset=utf-8");
+        }
+    }
+}
+
+#[tokio::test]
+async fn docs_ui_is_configurable() {
+    let app = TestApp::with_config(|config| config.docs.ui = vec![DocsUi::Scalar]).await;
+
+    app.client.get("/api/docs/scalar").send().await.assert_status_is_ok();
+    app.client.get("/api/docs/redoc").send().await.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn docs_can_be_disabled() {
+    let app = TestApp::with_config(|config| config.docs.enabled = false).await;
+
+    for uri in ["/api/docs", "/api/docs/json", "/api/docs/v2/yaml", "/api/docs/redoc"] {
+        app.client.get(uri).send().await.assert_status(StatusCode::NOT_FOUND);
+    }
+    app.client.get("/health").send().await.assert_status_is_ok();
+}
+
+#[tokio::test]
+async fn spec_lists_configured_servers() {
+    let app = TestApp::with_config(|config| {
+        config.docs.servers = vec![
+            DocsServer {
+                url: "https://api.example.com/".to_string(),
+                description: Some("生产环境".to_string()),
+            },
+            DocsServer {
+                url: "https://staging.example.com".to_string(),
+                description: None,
+            },
+        ];
+    })
+    .await;
+
+    let spec = body(app.client.get("/api/docs/v2/json").send().await).await;
+    assert_eq!(
+        spec["servers"],
+        json!([
+            { "url": "https://api.example.com/api/v2", "description": "生产环境" },
+            { "url": "https://staging.example.com/api/v2" }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn spec_documents_examples_and_headers() {
+    let app = TestApp::new().await;
+
+    let spec = body(app.client.get("/api/docs/v1/json").send().await).await;
+    assert_eq!(spec["components"]["schemas"]["CreateUserRequest"]["example"]["username"], "alice");
+    assert!(spec["components"]["schemas"]["UserListResponse"]["example"].is_object());
+
+    let headers = &spec["paths"]["/users/{id}"]["get"]["responses"]["200"]["headers"];
+    for name in ["X-REQUEST-ID", "ETAG"] {
+        assert!(headers[name].is_object(), "缺少响应头 {}", name);
+    }
+    let user_tag = spec["tags"].as_array().unwrap().iter().find(|tag| tag["name"] == "User").unwrap();
+    assert!(user_tag["description"].is_string());
+
+    // 以412返回的错误使用错误响应的示例
+    let precondition_failed = &spec["paths"]["/users/{id}"]["put"]["responses"]["412"]["content"];
+    let schema = precondition_failed["application/json; charset=utf-8"]["schema"]["$ref"].as_str().unwrap();
+    assert_eq!(schema, "#/components/schemas/ErrorResponse");
+    let example = &spec["components"]["schemas"]["ErrorResponse"]["example"];
+    assert_eq!(example["code"], 412);
+    assert!(example["data"].is_null());
+}
+
+#[tokio::test]
+async fn responses_carry_request_id() {
+    let app = TestApp::new().await;
+
+    let resp = app.client.get("/api/v1/users").send().await;
+    resp.assert_header_exist("x-request-id");
+
+    let resp = app.client.get("/api/v1/users").header("x-request-id", "req-123").send().await;
+    resp.assert_header("x-request-id", "req-123");
+}
+
+#[tokio::test]
+async fn health_reports_database() {
+    let app = TestApp::new().await;
+
+    let resp = app.client.get("/health").send().await;
+    resp.assert_status_is_ok();
+    let body = body(resp).await;
+    assert_eq!(body["status"], "up");
+    assert_eq!(body["checks"]["database"]["status"], "up");
+
+    app.client.get("/health/ready").send().await.assert_status_is_ok();
+}
+
+#[tokio::test]
+async fn live_reports_up() {
+    let app = TestApp::new().await;
+
+    let resp = app.client.get("/health/live").send().await;
+    resp.assert_status_is_ok();
+    assert_eq!(body(resp).await["status"], "up");
+}
+
+#[tokio::test]
+async fn ready_fails_while_shutting_down() {
+    let app = TestApp::new().await;
+    app.state.health.mark_shutting_down();
+
+    let resp = app.client.get("/health/ready").send().await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    let body = body(resp).await;
+    assert_eq!(body["status"], "down");
+    assert_eq!(body["reason"], "shutting down");
+
+    // 关闭流程中进程仍然存活
+    app.client.get("/health/live").send().await.assert_status_is_ok();
+}
+
+#[tokio::test]
+async fn admin_routes_move_to_admin_listener() {
+    let app = TestApp::with_config(|config| {
+        config.server.admin.listen_addr = Some("127.0.0.1:0".to_string());
+    })
+    .await;
+
+    app.client.get("/health").send().await.assert_status(StatusCode::NOT_FOUND);
+    app.client.get("/api/docs").send().await.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn negotiates_version_from_header() {
+    let app = TestApp::new().await;
+
+    let key = app.admin_key().await;
+    let resp = app.client.get("/api/users").api_key(&key).header("accept-version", "2").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_header("api-version", "v2");
+
+    let resp = app.client.get("/api/users").header("accept-version", "9").send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn graphql_query() {
+    let app = TestApp::new().await;
+
+    let user = app.create_user(UserFixture::new()).await;
+    let key = app.admin_key().await;
+
+    let body = app.graphql_as(&key, "query { users { id name email } }", json!({})).await;
+    assert!(body.get("errors").is_none());
+    assert_eq!(body["data"]["users"][0]["email"], user.email);
+}
+
+/// 通过WebSocket（`graphql-transport-ws` 协议）执行一次操作，返回第一个结果
+async fn graphql_ws(port: u16, headers: &[(&'static str, String)], query: &str) -> Value {
+    let mut req = format!("ws://127.0.0.1:{}/graphql/ws", port).into_client_request().unwrap();
+    req.headers_mut().insert("sec-websocket-protocol", "graphql-transport-ws".parse().unwrap());
+    for (name, value) in headers {
+        req.headers_mut().insert(*name, value.parse().unwrap());
+    }
+    let (mut socket, _) = tokio_tungstenite::connect_async(req).await.unwrap();
+    socket.send(Message::text(json!({ "type": "connection_init" }).to_string())).await.unwrap();
+
+    while let Some(msg) = socket.next().await {
+        let msg: Value = serde_json::from_str(msg.unwrap().to_text().unwrap()).unwrap();
+        match msg["type"].as_str() {
+            Some("connection_ack") => {
+                let subscribe = json!({ "id": "1", "type": "subscribe", "payload": { "query": query } });
+                socket.send(Message::text(subscribe.to_string())).await.unwrap();
+            }
+            Some("next") => return msg["payload"].clone(),
+            _ => panic!("意外的消息: {}", msg),
+        }
+    }
+    panic!("连接已关闭")
+}
+
+#[tokio::test]
+async fn graphql_websocket_uses_request_context() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+    let (username, password) = (fixture.username.clone(), fixture.password.clone());
+    let user = app.create_user(fixture).await;
+    let admin = app.admin_key().await;
+    let req = CreateApiKeyRequest {
+        name: "reader".to_string(),
+        scopes: vec![ApiKeyScope::Read],
+        expires_at: None,
+    };
+    let read = app.state.api_keys.create(&test_actor(), req).await.unwrap().key;
+    let session = app.login(&username, &password, None).await;
+
+    let port = free_port();
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", port));
+    let server = tokio::spawn(Server::new(listener).run(app::build(&app.config, &app.state)));
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    // 与HTTP请求一样使用业务服务及调用方身份
+    let resp = graphql_ws(port, &[("x-api-key", admin.clone())], "{ users { email } }").await;
+    assert_eq!(resp["data"]["users"][0]["email"], user.email, "{}", resp);
+    let resp = graphql_ws(port, &[], "{ users { email } }").await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "UNAUTHORIZED");
+
+    // API密钥需要满足授权范围
+    let delete = format!("mutation {{ deleteUser(id: {}) }}", user.id.unwrap());
+    let resp = graphql_ws(port, &[("x-api-key", read)], &delete).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    assert_eq!(resp["errors"][0]["message"], "API密钥缺少 write 授权范围");
+
+    // 会话Cookie认证的连接不校验CSRF令牌，不能执行变更
+    let cookie = format!("sid={}; csrf_token={}", session.session, session.csrf);
+    let resp = graphql_ws(port, &[("cookie", cookie.clone())], &delete).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "METHOD_NOT_ALLOWED");
+    let query = format!("{{ user(id: {}) {{ name }} }}", user.id.unwrap());
+    let resp = graphql_ws(port, &[("cookie", cookie)], &query).await;
+    assert_eq!(resp["data"]["user"]["name"], user.username);
+
+    let resp = graphql_ws(port, &[("x-api-key", admin)], &delete).await;
+    assert_eq!(resp["data"]["deleteUser"], true, "{}", resp);
+    server.abort();
+}
+
+#[tokio::test]
+async fn graphql_validation_error() {
+    let app = TestApp::new().await;
+
+    let body = app
+        .graphql(
+            "mutation($input: CreateUserInput!) { createUser(input: $input) { id } }",
+            json!({ "input": { "username": "ab", "email": "ab@example.com", "password": "password123" } }),
+        )
+        .await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "VALIDATION_ERROR");
+}
+
+#[tokio::test]
+async fn graphql_create_and_update_user() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+
+    let create = "mutation($input: CreateUserInput!) { createUser(input: $input) { id name email version } }";
+    let body = app.graphql(create, json!({ "input": fixture.json() })).await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    assert_eq!(body["data"]["createUser"]["name"], fixture.username);
+    let id = body["data"]["createUser"]["id"].as_u64().unwrap();
+    assert_eq!(app.state.users.get(id).await.unwrap().email, fixture.email);
+
+    // 用户名重复
+    let body = app.graphql(create, json!({ "input": fixture.json() })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "CONFLICT");
+
+    let update = "mutation($id: Int!, $input: UpdateUserInput!) { updateUser(id: $id, input: $input) { email version } }";
+    let key = app.admin_key().await;
+    let body = app.graphql_as(&key, update, json!({ "id": id, "input": { "email": "updated@example.com" } })).await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    assert_eq!(body["data"]["updateUser"]["email"], "updated@example.com");
+    assert_eq!(body["data"]["updateUser"]["version"], 2);
+
+    let body = app.graphql_as(&key, update, json!({ "id": id, "input": { "password": "short" } })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "VALIDATION_ERROR");
+    let body = app.graphql_as(&key, update, json!({ "id": 404, "input": {} })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn graphql_patch_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let query = "mutation($id: Int!, $input: PatchUserInput!) { patchUser(id: $id, input: $input) { id name email } }";
+    let key = app.admin_key().await;
+
+    let body = app
+        .graphql_as(&key, query, json!({ "id": user.id, "input": { "email": "patched@example.com" } }))
+        .await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    assert_eq!(body["data"]["patchUser"]["email"], "patched@example.com");
+    assert_eq!(body["data"]["patchUser"]["name"], user.username);
+
+    // 显式传入 null 与不传字段语义不同，必填字段不允许清空
+    let body = app.graphql_as(&key, query, json!({ "id": user.id, "input": { "email": null } })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "VALIDATION_ERROR");
+
+    let body = app.graphql_as(&key, query, json!({ "id": 404, "input": {} })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn graphql_mutations_check_expected_version() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let patch = "mutation($id: Int!, $version: Int) { patchUser(id: $id, input: { email: \"patched@example.com\" }, expectedVersion: $version) { version } }";
+    let delete = "mutation($id: Int!, $version: Int) { deleteUser(id: $id, expectedVersion: $version) }";
+    let key = app.admin_key().await;
+
+    let body = app.graphql_as(&key, patch, json!({ "id": user.id, "version": 1 })).await;
+    assert_eq!(body["data"]["patchUser"]["version"], 2);
+
+    let body = app.graphql_as(&key, patch, json!({ "id": user.id, "version": 1 })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "PRECONDITION_FAILED");
+
+    let body = app.graphql_as(&key, delete, json!({ "id": user.id, "version": 1 })).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "PRECONDITION_FAILED");
+
+    let body = app.graphql_as(&key, delete, json!({ "id": user.id, "version": 2 })).await;
+    assert_eq!(body["data"]["deleteUser"], true);
+}
+
+#[tokio::test]
+async fn graphql_soft_delete_and_restore() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let vars = json!({ "id": user.id });
+    let key = app.admin_key().await;
+
+    let body = app.graphql_as(&key, "mutation($id: Int!) { deleteUser(id: $id) }", vars.clone()).await;
+    assert_eq!(body["data"]["deleteUser"], true);
+
+    let body = app.graphql_as(&key, "query($id: Int!) { user(id: $id) { id } }", vars.clone()).await;
+    assert!(body["data"]["user"].is_null());
+    let body = app.graphql_as(&key, "query { users { id } }", json!({})).await;
+    assert_eq!(body["data"]["users"], json!([]));
+    let body = app.graphql_as(&key, "query { users(includeDeleted: true) { id deletedAt } }", json!({})).await;
+    assert!(body["data"]["users"][0]["deletedAt"].is_string());
+
+    let body = app
+        .graphql_as(&key, "mutation($id: Int!) { restoreUser(id: $id) { id deletedAt } }", vars.clone())
+        .await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    assert!(body["data"]["restoreUser"]["deletedAt"].is_null());
+
+    let body = app.graphql_as(&key, "mutation($id: Int!) { restoreUser(id: $id) { id } }", vars).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "CONFLICT");
+}
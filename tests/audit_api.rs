@@ -0,0 +1,278 @@
+//! 审计日志测试
+
+mod common;
+
+use common::{body, test_actor, RequestExt, TestApp, UserFixture};
+use serde_json::{json, Value};
+use {{crate_name}}::audit::AuditContext;
+use {{crate_name}}::auth::Principal;
+use {{crate_name}}::models::user::{UpdateUserRequest, UserRole};
+
+/// 使用管理密钥查询审计事件，返回当前页数据
+async fn audit_events(app: &TestApp, key: &str, query: &str) -> Vec<Value> {
+    let resp = app.client.get(format!("/api/audit?{}", query)).api_key(key).send().await;
+    resp.assert_status_is_ok();
+
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    body["data"]["items"].as_array().unwrap().clone()
+}
+
+/// 读取响应体中的业务状态码
+async fn body_code(resp: poem::test::TestResponse) -> u64 {
+    body(resp).await["code"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn rest_mutations_are_audited() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .header("X-Request-Id", "req-create")
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    let id = body(resp).await["data"]["id"].as_u64().unwrap();
+
+    app.client
+        .put(format!("/api/v2/users/{}", id))
+        .api_key(&key)
+        .body_json(&json!({ "email": "changed@example.com", "password": "new-password" }))
+        .send()
+        .await
+        .assert_status_is_ok();
+    app.client
+        .delete(format!("/api/v2/users/{}", id))
+        .api_key(&key)
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    // 按时间倒序
+    let events = audit_events(&app, &key, &format!("entity_type=user&entity_id={}", id)).await;
+    let actions: Vec<_> = events.iter().map(|event| event["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, ["delete", "update", "create"]);
+
+    let create = &events[2];
+    assert_eq!(create["actor_type"], "anonymous");
+    assert_eq!(create["request_id"], "req-create");
+    assert!(create["changes"]["username"]["before"].is_null());
+    assert!(create["changes"].get("password").is_none());
+
+    let update = &events[1];
+    assert_eq!(update["changes"]["email"]["after"], "changed@example.com");
+    assert_eq!(update["changes"]["version"], json!({ "before": 1, "after": 2 }));
+    // 密码只记录发生了变化，不记录取值
+    assert_eq!(update["changes"]["password"]["after"], "[REDACTED]");
+    assert!(update["changes"].get("updated_at").is_none());
+
+    let delete = &events[0];
+    assert!(delete["changes"]["deleted_at"]["before"].is_null());
+    assert!(delete["changes"]["deleted_at"]["after"].is_string());
+}
+
+#[tokio::test]
+async fn failed_mutation_is_not_audited() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    let user = app.create_user(UserFixture::new()).await;
+
+    let resp = app
+        .client
+        .put(format!("/api/v1/users/{}", user.id.unwrap()))
+        .api_key(&key)
+        .header("If-Match", "\"9\"")
+        .body_json(&json!({ "email": "changed@example.com" }))
+        .send()
+        .await;
+    resp.assert_status(poem::http::StatusCode::PRECONDITION_FAILED);
+
+    let events = audit_events(&app, &key, "action=update").await;
+    assert!(events.is_empty());
+}
+
+#[tokio::test]
+async fn audit_events_are_filtered_and_paginated() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    let users = app.create_users(3).await;
+    let actor = AuditContext {
+        actor: Principal::User {
+            id: users[0].id.unwrap(),
+            username: users[0].username.clone(),
+            role: UserRole::Admin,
+        },
+        request_id: Some("req-admin".to_string()),
+        ip: Some("203.0.113.7".to_string()),
+    };
+    let req = UpdateUserRequest {
+        email: Some("updated@example.com".to_string()),
+        password: None,
+    };
+    app.state.users.update(&actor, users[1].id.unwrap(), req, None).await.unwrap();
+
+    let events = audit_events(&app, &key, &format!("actor_id={}", users[0].id.unwrap())).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["actor_type"], "user");
+    assert_eq!(events[0]["actor_name"], users[0].username);
+    assert_eq!(events[0]["entity_id"], users[1].id.unwrap().to_string());
+    assert_eq!(events[0]["ip"], "203.0.113.7");
+
+    let events = audit_events(&app, &key, "request_id=req-admin").await;
+    assert_eq!(events.len(), 1);
+
+    let resp = app
+        .client
+        .get("/api/audit?entity_type=user&action=create&page=2&page_size=2")
+        .api_key(&key)
+        .send()
+        .await;
+    let body = body(resp).await;
+    assert_eq!(body["data"]["total"], 3);
+    assert_eq!(body["data"]["total_pages"], 2);
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 1);
+
+    let events = audit_events(&app, &key, "since=2000-01-01T00:00:00Z&until=2000-01-02T00:00:00%2B08:00").await;
+    assert!(events.is_empty());
+
+    let resp = app.client.get("/api/audit?since=yesterday").api_key(&key).send().await;
+    assert_eq!(body_code(resp).await, 400);
+}
+
+#[tokio::test]
+async fn purge_is_audited_as_system() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    let user = app.create_user(UserFixture::new()).await;
+    app.state.users.delete(&test_actor(), user.id.unwrap(), None).await.unwrap();
+
+    let purged = app
+        .state
+        .users
+        .purge_deleted(&AuditContext::system("retention"), chrono::Utc::now() + chrono::Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    let events = audit_events(&app, &key, "action=purge").await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["actor_type"], "system");
+    assert_eq!(events[0]["actor_name"], "retention");
+    assert_eq!(events[0]["changes"]["username"]["before"], user.username);
+    assert!(events[0]["changes"]["username"]["after"].is_null());
+}
+
+#[tokio::test]
+async fn audit_storage_is_append_only() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    app.create_user(UserFixture::new()).await;
+
+    let updated = sqlx::query("UPDATE audit_events SET actor_name = 'someone else'")
+        .execute(&app.state.pool)
+        .await;
+    assert!(updated.is_err());
+
+    let deleted = sqlx::query("DELETE FROM audit_events").execute(&app.state.pool).await;
+    assert!(deleted.is_err());
+
+    assert_eq!(audit_events(&app, &key, "entity_type=user").await.len(), 1);
+}
+
+#[tokio::test]
+async fn graphql_mutations_are_audited_and_listed() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let vars = json!({ "id": user.id });
+
+    app.graphql_as(
+        &key,
+        r#"mutation($id: Int!) { patchUser(id: $id, input: { email: "patched@example.com" }) { id } }"#,
+        vars.clone(),
+    )
+    .await;
+    app.graphql_as(&key, "mutation($id: Int!) { deleteUser(id: $id) }", vars.clone()).await;
+    app.graphql_as(&key, "mutation($id: Int!) { restoreUser(id: $id) { id } }", vars).await;
+
+    let query = r#"
+        query($after: String) {
+            auditEvents(filter: { entityType: "user" }, first: 2, after: $after) {
+                totalCount
+                pageInfo { hasNextPage hasPreviousPage endCursor }
+                nodes { action changes }
+            }
+        }
+    "#;
+    let body = app.graphql_as(&key, query, json!({})).await;
+    assert!(body.get("errors").is_none(), "{}", body);
+    let page = &body["data"]["auditEvents"];
+    assert_eq!(page["totalCount"], 4);
+    assert_eq!(page["pageInfo"]["hasNextPage"], true);
+    assert_eq!(page["nodes"][0]["action"], "RESTORE");
+    assert_eq!(page["nodes"][1]["action"], "DELETE");
+
+    let body = app.graphql_as(&key, query, json!({ "after": page["pageInfo"]["endCursor"] })).await;
+    let page = &body["data"]["auditEvents"];
+    assert_eq!(page["pageInfo"]["hasNextPage"], false);
+    assert_eq!(page["pageInfo"]["hasPreviousPage"], true);
+    assert_eq!(page["nodes"][0]["action"], "UPDATE");
+    assert_eq!(page["nodes"][0]["changes"]["email"]["after"], "patched@example.com");
+    assert_eq!(page["nodes"][1]["action"], "CREATE");
+}
+
+#[tokio::test]
+async fn graphql_create_and_update_are_audited() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    let fixture = UserFixture::new();
+
+    let body = app
+        .graphql(
+            "mutation($input: CreateUserInput!) { createUser(input: $input) { id } }",
+            json!({ "input": fixture.json() }),
+        )
+        .await;
+    let id = body["data"]["createUser"]["id"].as_u64().unwrap();
+    app.graphql_as(
+        &key,
+        r#"mutation($id: Int!) { updateUser(id: $id, input: { email: "updated@example.com" }) { id } }"#,
+        json!({ "id": id }),
+    )
+    .await;
+
+    let events = audit_events(&app, &key, &format!("entity_type=user&entity_id={}", id)).await;
+    let actions: Vec<_> = events.iter().map(|event| event["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, ["update", "create"]);
+    assert_eq!(events[1]["changes"]["username"]["after"], fixture.username);
+    assert_eq!(events[0]["changes"]["email"]["after"], "updated@example.com");
+
+    // 领域事件同时写入发件箱
+    assert_eq!(app.state.outbox.pending().await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn audit_events_require_admin() {
+    let app = TestApp::new().await;
+    let (username, password) = {
+        let fixture = UserFixture::new();
+        let credentials = (fixture.username.clone(), fixture.password.clone());
+        app.create_user(fixture).await;
+        credentials
+    };
+    let session = app.login(&username, &password, None).await;
+
+    let resp = app.client.get("/api/audit").send().await;
+    resp.assert_status(poem::http::StatusCode::UNAUTHORIZED);
+    let resp = app.client.get("/api/v1/audit").session(&session).send().await;
+    resp.assert_status(poem::http::StatusCode::FORBIDDEN);
+
+    let query = "query { auditEvents { totalCount } }";
+    let resp = app.graphql(query, json!({})).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "UNAUTHORIZED");
+    let resp = body(app.graphql_request(query, json!({})).session(&session).send().await).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+}
@@ -0,0 +1,244 @@
+//! 邮箱验证及密码重置测试
+
+mod common;
+
+use chrono::Utc;
+use common::{body, test_actor, RequestExt, TestApp, UserFixture};
+use serde_json::json;
+use {{crate_name}}::accounts::{TokenPurpose, TokenSigner};
+use {{crate_name}}::mailer::Email;
+use {{crate_name}}::models::user::UpdateUserRequest;
+
+/// 投递领域事件并执行发送任务，返回至今发送的所有邮件
+async fn deliver(app: &TestApp) -> Vec<Email> {
+    app.state.outbox.relay_once().await.unwrap();
+    app.state.jobs.run_once().await.unwrap();
+    app.state.mailer.memory().expect("测试应使用内存发送方式").sent()
+}
+
+/// 发给 `to` 的最后一封标题以 `subject` 开头的邮件中的令牌
+fn token(emails: &[Email], to: &str, subject: &str) -> String {
+    let email = emails
+        .iter()
+        .rev()
+        .find(|email| email.to == to && email.subject.starts_with(subject))
+        .unwrap_or_else(|| panic!("没有发给 {} 的邮件：{}", to, subject));
+    let (_, rest) = email.text.split_once("token=").expect("邮件中没有令牌");
+    rest.split_whitespace().next().unwrap().to_string()
+}
+
+async fn password_hash(app: &TestApp, id: u64) -> String {
+    sqlx::query_scalar("SELECT password_hash FROM users WHERE id = ?")
+        .bind(id as i64)
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn new_users_verify_email_with_single_use_token() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+    let resp = app.client.post("/api/v1/users").body_json(&fixture.json()).send().await;
+    let created = body(resp).await;
+    assert!(created["data"]["email_verified_at"].is_null());
+
+    let emails = deliver(&app).await;
+    let token = token(&emails, &fixture.email, "验证您在");
+
+    let resp = app
+        .client
+        .post("/api/v2/auth/verify-email")
+        .body_json(&json!({ "token": token }))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let verified = body(resp).await;
+    assert!(verified["data"]["email_verified_at"].is_string());
+    assert_eq!(verified["data"]["version"], 2);
+
+    // 令牌只能使用一次
+    let resp = app
+        .client
+        .post("/api/v2/auth/verify-email")
+        .body_json(&json!({ "token": token }))
+        .send()
+        .await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 400);
+    assert_eq!(body["msg"], "令牌无效或已过期");
+}
+
+#[tokio::test]
+async fn changing_email_requires_verification_again() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let id = user.id.unwrap();
+    let old_token = token(&deliver(&app).await, &user.email, "验证您在");
+
+    let req = UpdateUserRequest {
+        email: Some("changed@example.com".to_string()),
+        password: None,
+    };
+    app.state.users.update(&test_actor(), id, req, None).await.unwrap();
+
+    // 签发后邮箱被修改的令牌失效，新邮箱收到新的验证邮件
+    assert!(app.state.accounts.verify_email(&test_actor(), &old_token).await.is_err());
+    let new_token = token(&deliver(&app).await, "changed@example.com", "验证您在");
+    let verified = app.state.accounts.verify_email(&test_actor(), &new_token).await.unwrap();
+    assert!(verified.email_verified_at.is_some());
+
+    // 再次修改邮箱时清空验证时间
+    let req = UpdateUserRequest {
+        email: Some("again@example.com".to_string()),
+        password: None,
+    };
+    let updated = app.state.users.update(&test_actor(), id, req, None).await.unwrap();
+    assert!(updated.email_verified_at.is_none());
+}
+
+#[tokio::test]
+async fn forgotten_password_can_be_reset_once() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+    let password = fixture.password.clone();
+    let user = app.create_user(fixture).await;
+    let id = user.id.unwrap();
+    let before = password_hash(&app, id).await;
+    let session = app.login(&user.username, &password, None).await;
+
+    for email in [user.email.as_str(), "nobody@example.com"] {
+        let resp = app
+            .client
+            .post("/api/v1/auth/forgot-password")
+            .body_json(&json!({ "email": email }))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+    }
+
+    // 不存在的邮箱不发送邮件
+    let emails = deliver(&app).await;
+    assert!(emails.iter().all(|email| email.to != "nobody@example.com"));
+    let token = token(&emails, &user.email, "重置您在");
+
+    let reset = json!({ "token": token, "password": "new-password" });
+    let resp = app.client.post("/api/v1/auth/reset-password").body_json(&reset).send().await;
+    resp.assert_status_is_ok();
+    let after = password_hash(&app, id).await;
+    assert_ne!(before, after);
+    assert!(bcrypt::verify("new-password", &after).unwrap());
+
+    // 重置密码后已有会话失效
+    let resp = app.client.get("/api/v1/sessions/current").session(&session).send().await;
+    assert_eq!(body(resp).await["code"], 401);
+
+    let resp = app.client.post("/api/v1/auth/reset-password").body_json(&reset).send().await;
+    assert_eq!(body(resp).await["code"], 400);
+
+    // 审计记录中密码被脱敏
+    let events = app.state.audit.list(&Default::default(), 1, 10).await.unwrap().0;
+    assert_eq!(events[0].changes["password"]["after"], "[REDACTED]");
+}
+
+#[tokio::test]
+async fn reset_invalidates_other_reset_tokens() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+
+    app.state.accounts.forgot_password(&user.email).await.unwrap();
+    let first = token(&deliver(&app).await, &user.email, "重置您在");
+    app.state.accounts.forgot_password(&user.email).await.unwrap();
+    let second = token(&deliver(&app).await, &user.email, "重置您在");
+    assert_ne!(first, second);
+
+    let reset = |token: &str| json!({ "token": token, "password": "new-password" });
+    let resp = app.client.post("/api/v1/auth/reset-password").body_json(&reset(&second)).send().await;
+    assert_eq!(body(resp).await["code"], 200);
+    let resp = app.client.post("/api/v1/auth/reset-password").body_json(&reset(&first)).send().await;
+    assert_eq!(body(resp).await["code"], 400);
+}
+
+#[tokio::test]
+async fn forged_expired_and_mismatched_tokens_are_rejected() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let id = user.id.unwrap();
+    let verify = token(&deliver(&app).await, &user.email, "验证您在");
+
+    let mut tampered = verify.clone();
+    let last = if tampered.ends_with('0') { "1" } else { "0" };
+    tampered.replace_range(tampered.len() - 1.., last);
+
+    let signer = TokenSigner::new(Some("another-secret"));
+    let forged = signer.issue(TokenPurpose::VerifyEmail, id, Utc::now().timestamp() + 3600);
+
+    for token in [tampered.as_str(), forged.as_str(), "", "not.a.token"] {
+        let resp = app
+            .client
+            .post("/api/v1/auth/verify-email")
+            .body_json(&json!({ "token": token }))
+            .send()
+            .await;
+        assert_eq!(body(resp).await["code"], 400, "令牌 {:?}", token);
+    }
+
+    // 验证令牌不能用于重置密码
+    let reset = json!({ "token": verify, "password": "new-password" });
+    let resp = app.client.post("/api/v1/auth/reset-password").body_json(&reset).send().await;
+    assert_eq!(body(resp).await["code"], 400);
+    assert!(app.state.accounts.verify_email(&test_actor(), &verify).await.is_ok());
+
+    // 签名正确但已过期
+    let signer = TokenSigner::new(Some("secret"));
+    let expired = signer.issue(TokenPurpose::ResetPassword, id, Utc::now().timestamp() - 1);
+    assert!(signer.verify(TokenPurpose::ResetPassword, &expired).is_some());
+    assert!(signer.verify(TokenPurpose::VerifyEmail, &expired).is_none());
+    let app = TestApp::with_config(|config| config.security.token_secret = Some("secret".to_string())).await;
+    let reset = json!({ "token": expired, "password": "new-password" });
+    let resp = app.client.post("/api/v1/auth/reset-password").body_json(&reset).send().await;
+    assert_eq!(body(resp).await["code"], 400);
+}
+
+#[tokio::test]
+async fn verification_email_can_be_disabled() {
+    let app = TestApp::with_config(|config| {
+        config.mailer.welcome_enabled = false;
+        config.accounts.send_verification = false;
+    })
+    .await;
+    app.create_user(UserFixture::new()).await;
+    assert!(deliver(&app).await.is_empty());
+}
+
+#[tokio::test]
+async fn graphql_flows_match_rest() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let verify = token(&deliver(&app).await, &user.email, "验证您在");
+
+    let resp = app
+        .graphql(
+            "mutation($token: String!) { verifyEmail(token: $token) { email emailVerifiedAt } }",
+            json!({ "token": verify }),
+        )
+        .await;
+    assert_eq!(resp["data"]["verifyEmail"]["email"], user.email);
+    assert!(resp["data"]["verifyEmail"]["emailVerifiedAt"].is_string());
+
+    let resp = app
+        .graphql("mutation($email: String!) { forgotPassword(email: $email) }", json!({ "email": user.email }))
+        .await;
+    assert_eq!(resp["data"]["forgotPassword"], true);
+    let reset = token(&deliver(&app).await, &user.email, "重置您在");
+
+    // 密码校验规则与REST一致
+    let mutation = "mutation($token: String!, $password: String!) { resetPassword(token: $token, password: $password) }";
+    let resp = app.graphql(mutation, json!({ "token": reset, "password": "123" })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "VALIDATION_ERROR");
+
+    let resp = app.graphql(mutation, json!({ "token": reset, "password": "new-password" })).await;
+    assert_eq!(resp["data"]["resetPassword"], true);
+    let resp = app.graphql(mutation, json!({ "token": reset, "password": "new-password" })).await;
+    assert_eq!(resp["errors"][0]["message"], "令牌无效或已过期");
+}
@@ -0,0 +1,262 @@
+//! 集成测试公共工具
+//!
+//! - `TestApp`：基于 `app::build` 组装完整应用（REST、GraphQL、文档、健康检查），
+//!   使用SQLite内存数据库，每个测试互相隔离
+//! - `UserFixture`：生成唯一的测试用户
+//! - `admin`、`principal`：调用管理接口使用的管理员身份及用户本人身份
+//! - `TestApp::admin_key`：调用管理接口使用的API密钥，`TestApp::graphql_as` 使用密钥调用GraphQL
+//! - `TestApp::login`：以用户身份登录，返回浏览器会话
+//! - `RequestExt`：附加认证信息等常用请求设置
+//! - `free_port`：获取空闲端口，用于启动本地HTTP服务
+
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use poem::{
+    endpoint::BoxEndpoint,
+    http::header,
+    test::{TestClient, TestRequestBuilder, TestResponse},
+    EndpointExt, Response,
+};
+use serde_json::{json, Value};
+use {{crate_name}}::app::{self, AppState};
+use {{crate_name}}::audit::AuditContext;
+use {{crate_name}}::auth::Principal;
+use {{crate_name}}::config::{AppConfig, MailTransport};
+use {{crate_name}}::models::api_key::{ApiKeyScope, CreateApiKeyRequest};
+use {{crate_name}}::models::user::{CreateUserRequest, User, UserRole};
+
+/// 测试应用
+pub struct TestApp {
+    /// 测试客户端，请求直接调用端点，不经过网络
+    pub client: TestClient<BoxEndpoint<'static, Response>>,
+    /// 共享状态，可直接调用服务准备数据
+    pub state: AppState,
+    /// 应用配置
+    pub config: AppConfig,
+}
+
+impl TestApp {
+    /// 使用默认测试配置创建应用
+    pub async fn new() -> Self {
+        Self::with_config(|_| {}).await
+    }
+
+    /// 在默认测试配置基础上调整配置后创建应用
+    pub async fn with_config(customize: impl FnOnce(&mut AppConfig)) -> Self {
+        let mut config = test_config();
+        customize(&mut config);
+
+        let state = AppState::new(&config).await.expect("创建测试状态失败");
+        let client = TestClient::new(app::build(&config, &state).boxed());
+
+        Self {
+            client,
+            state,
+            config,
+        }
+    }
+
+    /// 通过用户服务创建用户
+    pub async fn create_user(&self, fixture: UserFixture) -> User {
+        let req = fixture.request();
+        match fixture.role {
+            UserRole::Admin => self.state.users.create_admin(&test_actor(), req).await,
+            UserRole::User => self.state.users.create(&test_actor(), req).await,
+        }
+        .expect("创建测试用户失败")
+    }
+
+    /// 批量创建用户
+    pub async fn create_users(&self, count: usize) -> Vec<User> {
+        let mut users = Vec::with_capacity(count);
+        for _ in 0..count {
+            users.push(self.create_user(UserFixture::new()).await);
+        }
+        users
+    }
+
+    /// 构造GraphQL请求，可在发送前附加调用方身份
+    pub fn graphql_request(&self, query: &str, variables: Value) -> TestRequestBuilder<'_, BoxEndpoint<'static, Response>> {
+        self.client
+            .post("/graphql/query")
+            .body_json(&json!({ "query": query, "variables": variables }))
+    }
+
+    /// 创建具有 `admin` 授权范围的API密钥，返回密钥原文，用于调用管理接口
+    pub async fn admin_key(&self) -> String {
+        let req = CreateApiKeyRequest {
+            name: "test-admin".to_string(),
+            scopes: vec![ApiKeyScope::Admin],
+            expires_at: None,
+        };
+        self.state.api_keys.create(&test_actor(), req).await.expect("创建测试密钥失败").key
+    }
+
+    /// 使用用户名及密码登录，返回浏览器会话；已启用两步验证的用户需要提供验证码
+    pub async fn login(&self, username: &str, password: &str, mfa_code: Option<&str>) -> BrowserSession {
+        let payload = json!({ "username": username, "password": password, "mfa_code": mfa_code });
+        let resp = self.client.post("/api/v1/auth/login").body_json(&payload).send().await;
+        let cookie = |name: &str| {
+            resp.0
+                .headers()
+                .get_all(header::SET_COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok()?.split(';').next()?.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+                .expect("登录后应写入会话Cookie")
+        };
+        BrowserSession {
+            session: cookie(&self.config.sessions.cookie_name),
+            csrf: cookie(&self.config.sessions.csrf_cookie_name),
+        }
+    }
+
+    /// 执行GraphQL请求，返回完整的响应体（包含 `data` 与 `errors`）
+    pub async fn graphql(&self, query: &str, variables: Value) -> Value {
+        let resp = self.graphql_request(query, variables).send().await;
+        resp.assert_status_is_ok();
+        body(resp).await
+    }
+
+    /// 使用API密钥执行GraphQL请求，返回完整的响应体
+    pub async fn graphql_as(&self, key: &str, query: &str, variables: Value) -> Value {
+        let resp = self.graphql_request(query, variables).api_key(key).send().await;
+        resp.assert_status_is_ok();
+        body(resp).await
+    }
+}
+
+/// 浏览器会话：登录后写入的会话Cookie及CSRF令牌
+pub struct BrowserSession {
+    pub session: String,
+    pub csrf: String,
+}
+
+/// 测试代码直接调用服务时使用的审计上下文
+pub fn test_actor() -> AuditContext {
+    AuditContext::system("test")
+}
+
+/// 管理员身份，通过 `.data(admin())` 附加到请求上，代替认证中间件写入请求扩展
+pub fn admin() -> Principal {
+    Principal::User {
+        id: u64::MAX,
+        username: "test-admin".to_string(),
+        role: UserRole::Admin,
+    }
+}
+
+/// 用户本人的身份，用法同 `admin`
+pub fn principal(user: &User) -> Principal {
+    Principal::User {
+        id: user.id.unwrap(),
+        username: user.username.clone(),
+        role: user.role,
+    }
+}
+
+/// 测试配置：内存数据库，降低bcrypt成本以加快测试，邮件保存在内存中
+pub fn test_config() -> AppConfig {
+    let mut config = AppConfig::default();
+    config.database.url = "sqlite::memory:".to_string();
+    config.security.bcrypt_cost = 4;
+    config.mailer.transport = MailTransport::Memory;
+    config
+}
+
+/// 测试用户
+pub struct UserFixture {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+    pub role: UserRole,
+}
+
+impl UserFixture {
+    /// 生成用户名、邮箱唯一的普通用户
+    pub fn new() -> Self {
+        static SEQ: AtomicU32 = AtomicU32::new(1);
+        let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+
+        Self {
+            username: format!("user_{}", seq),
+            email: format!("user_{}@example.com", seq),
+            password: "password123".to_string(),
+            role: UserRole::User,
+        }
+    }
+
+    /// 指定用户名，邮箱随之变化
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = username.to_string();
+        self.email = format!("{}@example.com", username);
+        self
+    }
+
+    /// 设为管理员
+    pub fn admin(mut self) -> Self {
+        self.role = UserRole::Admin;
+        self
+    }
+
+    /// 转换为创建请求
+    pub fn request(&self) -> CreateUserRequest {
+        CreateUserRequest {
+            username: self.username.clone(),
+            email: self.email.clone(),
+            password: self.password.clone(),
+        }
+    }
+
+    /// 转换为REST请求体
+    pub fn json(&self) -> Value {
+        json!({
+            "username": self.username,
+            "email": self.email,
+            "password": self.password,
+        })
+    }
+}
+
+impl Default for UserFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 请求构造扩展
+pub trait RequestExt {
+    /// 通过 `X-API-Key` 请求头附加API密钥
+    fn api_key(self, key: &str) -> Self;
+
+    /// 附加默认配置下的会话Cookie及CSRF请求头
+    fn session(self, session: &BrowserSession) -> Self;
+}
+
+impl<E> RequestExt for TestRequestBuilder<'_, E> {
+    fn api_key(self, key: &str) -> Self {
+        self.header("X-API-Key", key)
+    }
+
+    fn session(self, session: &BrowserSession) -> Self {
+        self.header(header::COOKIE, format!("sid={}; csrf_token={}", session.session, session.csrf))
+            .header("X-CSRF-Token", &session.csrf)
+    }
+}
+
+/// 获取一个空闲端口，用于启动测试中的本地服务
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// 读取JSON响应体
+pub async fn body(resp: TestResponse) -> Value {
+    resp.json().await.value().deserialize()
+}
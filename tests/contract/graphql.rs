@@ -0,0 +1,238 @@
+//! GraphQL SDL差异分类
+
+use std::collections::BTreeMap;
+
+use async_graphql::parser::{
+    parse_schema,
+    types::{BaseType, FieldDefinition, InputValueDefinition, Type, TypeKind, TypeSystemDefinition},
+    Positioned,
+};
+
+use crate::Change;
+
+/// 类型定义的简化表示，便于按名称比较
+#[derive(Default)]
+struct TypeShape {
+    /// 类型种类，例如 object、input、enum
+    kind: &'static str,
+    /// 输出字段：字段名 -> （类型，参数）
+    fields: BTreeMap<String, (Type, BTreeMap<String, Argument>)>,
+    /// 输入字段
+    inputs: BTreeMap<String, Argument>,
+    /// 枚举值或联合类型成员
+    members: Vec<String>,
+}
+
+/// 参数或输入字段
+struct Argument {
+    ty: Type,
+    has_default: bool,
+}
+
+impl Argument {
+    /// 非空且没有默认值时调用方必须提供
+    fn required(&self) -> bool {
+        !self.ty.nullable && !self.has_default
+    }
+}
+
+/// 比较两个版本的GraphQL SDL
+pub fn diff(old: &str, new: &str) -> Vec<Change> {
+    let old = shapes(old);
+    let new = shapes(new);
+    let mut changes = Vec::new();
+
+    for (name, old_type) in &old {
+        let Some(new_type) = new.get(name) else {
+            changes.push(Change::breaking(format!("删除了类型 {}", name)));
+            continue;
+        };
+        if old_type.kind != new_type.kind {
+            changes.push(Change::breaking(format!(
+                "类型 {} 由 {} 改为 {}",
+                name, old_type.kind, new_type.kind
+            )));
+            continue;
+        }
+        diff_fields(name, old_type, new_type, &mut changes);
+        diff_inputs(name, &old_type.inputs, &new_type.inputs, &mut changes);
+
+        for member in old_type.members.iter().filter(|m| !new_type.members.contains(m)) {
+            changes.push(Change::breaking(format!("{} 删除了 {}", name, member)));
+        }
+        for member in new_type.members.iter().filter(|m| !old_type.members.contains(m)) {
+            // 输出端新增枚举值可能导致旧客户端无法识别，这里按兼容处理并提示
+            changes.push(Change::compatible(format!("{} 新增了 {}", name, member)));
+        }
+    }
+
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        changes.push(Change::compatible(format!("新增了类型 {}", name)));
+    }
+
+    changes
+}
+
+fn diff_fields(type_name: &str, old: &TypeShape, new: &TypeShape, changes: &mut Vec<Change>) {
+    for (field, (old_ty, old_args)) in &old.fields {
+        let at = format!("{}.{}", type_name, field);
+        let Some((new_ty, new_args)) = new.fields.get(field) else {
+            changes.push(Change::breaking(format!("删除了字段 {}", at)));
+            continue;
+        };
+        if !output_compatible(old_ty, new_ty) {
+            changes.push(Change::breaking(format!("{} 的类型由 {} 改为 {}", at, old_ty, new_ty)));
+        }
+        diff_inputs(&at, old_args, new_args, changes);
+    }
+    for field in new.fields.keys().filter(|f| !old.fields.contains_key(*f)) {
+        changes.push(Change::compatible(format!("新增了字段 {}.{}", type_name, field)));
+    }
+}
+
+/// 比较参数或输入对象字段
+fn diff_inputs(
+    at: &str,
+    old: &BTreeMap<String, Argument>,
+    new: &BTreeMap<String, Argument>,
+    changes: &mut Vec<Change>,
+) {
+    for (name, old_arg) in old {
+        let Some(new_arg) = new.get(name) else {
+            changes.push(Change::breaking(format!("{} 删除了参数 {}", at, name)));
+            continue;
+        };
+        if !input_compatible(&old_arg.ty, &new_arg.ty) {
+            changes.push(Change::breaking(format!(
+                "{} 的参数 {} 类型由 {} 改为 {}",
+                at, name, old_arg.ty, new_arg.ty
+            )));
+        } else if !old_arg.required() && new_arg.required() {
+            changes.push(Change::breaking(format!("{} 的参数 {} 改为必填", at, name)));
+        }
+    }
+    for (name, new_arg) in new.iter().filter(|(name, _)| !old.contains_key(*name)) {
+        if new_arg.required() {
+            changes.push(Change::breaking(format!("{} 新增了必填参数 {}", at, name)));
+        } else {
+            changes.push(Change::compatible(format!("{} 新增了可选参数 {}", at, name)));
+        }
+    }
+}
+
+/// 输出类型兼容：基础类型不变，允许由可空收紧为非空
+fn output_compatible(old: &Type, new: &Type) -> bool {
+    (old.nullable || !new.nullable)
+        && match (&old.base, &new.base) {
+            (BaseType::Named(old), BaseType::Named(new)) => old == new,
+            (BaseType::List(old), BaseType::List(new)) => output_compatible(old, new),
+            _ => false,
+        }
+}
+
+/// 输入类型兼容：基础类型不变，允许由非空放宽为可空
+fn input_compatible(old: &Type, new: &Type) -> bool {
+    output_compatible(new, old)
+}
+
+/// 解析SDL，按类型名索引
+fn shapes(sdl: &str) -> BTreeMap<String, TypeShape> {
+    let document = parse_schema(sdl).expect("SDL解析失败");
+    let mut shapes = BTreeMap::new();
+
+    for definition in document.definitions {
+        let TypeSystemDefinition::Type(definition) = definition else { continue };
+        let definition = definition.node;
+        let mut shape = TypeShape::default();
+
+        match definition.kind {
+            TypeKind::Scalar => shape.kind = "scalar",
+            TypeKind::Object(object) => {
+                shape.kind = "object";
+                shape.fields = output_fields(object.fields);
+            }
+            TypeKind::Interface(interface) => {
+                shape.kind = "interface";
+                shape.fields = output_fields(interface.fields);
+            }
+            TypeKind::Union(union) => {
+                shape.kind = "union";
+                shape.members = union.members.into_iter().map(|m| format!("成员 {}", m.node)).collect();
+            }
+            TypeKind::Enum(enumeration) => {
+                shape.kind = "enum";
+                shape.members = enumeration
+                    .values
+                    .into_iter()
+                    .map(|v| format!("枚举值 {}", v.node.value.node))
+                    .collect();
+            }
+            TypeKind::InputObject(input) => {
+                shape.kind = "input";
+                shape.inputs = arguments(input.fields.into_iter().map(|f| f.node));
+            }
+        }
+
+        shapes.insert(definition.name.node.to_string(), shape);
+    }
+
+    shapes
+}
+
+fn output_fields(
+    fields: Vec<Positioned<FieldDefinition>>,
+) -> BTreeMap<String, (Type, BTreeMap<String, Argument>)> {
+    fields
+        .into_iter()
+        .map(|field| {
+            let field = field.node;
+            let args = arguments(field.arguments.into_iter().map(|a| a.node));
+            (field.name.node.to_string(), (field.ty.node, args))
+        })
+        .collect()
+}
+
+fn arguments(values: impl Iterator<Item = InputValueDefinition>) -> BTreeMap<String, Argument> {
+    values
+        .map(|value| {
+            let argument = Argument {
+                ty: value.ty.node,
+                has_default: value.default_value.is_some(),
+            };
+            (value.name.node.to_string(), argument)
+        })
+        .collect()
+}
+
+// 分类规则自身的测试
+
+fn breaking(old: &str, new: &str) -> Vec<String> {
+    diff(old, new)
+        .into_iter()
+        .filter(|c| c.breaking)
+        .map(|c| c.description)
+        .collect()
+}
+
+#[test]
+fn removed_field_is_breaking() {
+    let old = "type Query { user(id: Int!): User }\ntype User { id: Int! name: String! }";
+    let new = "type Query { user(id: Int!): User }\ntype User { id: Int! }";
+    assert_eq!(breaking(old, new), ["删除了字段 User.name"]);
+}
+
+#[test]
+fn nullable_output_and_new_required_argument_are_breaking() {
+    let old = "type Query { users: [User!]! }\ntype User { id: Int! }";
+    let new = "type Query { users(tenant: String!): [User!] }\ntype User { id: Int! }";
+    assert_eq!(breaking(old, new).len(), 2);
+}
+
+#[test]
+fn additions_are_compatible() {
+    let old = "type Query { users: [User!]! }\ntype User { id: Int! }\nenum Role { USER }";
+    let new = "type Query { users(page: Int = 1): [User!]! }\ntype User { id: Int! email: String }\nenum Role { USER ADMIN }\ntype Post { id: Int! }";
+    let changes = diff(old, new);
+    assert!(changes.iter().all(|c| !c.breaking));
+    assert_eq!(changes.len(), 4);
+}
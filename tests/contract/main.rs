@@ -0,0 +1,131 @@
+//! API契约快照测试
+//!
+//! 将各版本的OpenAPI规范和GraphQL SDL与 `snapshots/` 下提交的快照比较，
+//! 并对差异分类：新增接口、字段等兼容变更只输出提示，删除接口、字段，收窄类型，
+//! 新增必填参数等破坏性变更会导致测试失败。
+//!
+//! - 确认变更后执行 `UPDATE_SNAPSHOTS=1 cargo test --test contract` 更新快照，快照缺失时测试同样失败
+//! - 有意引入的破坏性变更需要先写入 `accepted-breaking-changes.txt`（每行一条，
+//!   内容与测试输出一致），评审通过后再更新快照
+
+mod graphql;
+mod openapi;
+
+use std::fmt;
+use std::path::PathBuf;
+
+use {{crate_name}}::api::{self, ApiVersion};
+
+/// 契约变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// 是否为破坏性变更
+    pub breaking: bool,
+    /// 变更描述，同时作为接受破坏性变更时的标识
+    pub description: String,
+}
+
+impl Change {
+    /// 破坏性变更
+    pub fn breaking(description: impl Into<String>) -> Self {
+        Self {
+            breaking: true,
+            description: description.into(),
+        }
+    }
+
+    /// 兼容变更
+    pub fn compatible(description: impl Into<String>) -> Self {
+        Self {
+            breaking: false,
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.breaking { "破坏性" } else { "兼容" };
+        write!(f, "[{}] {}", kind, self.description)
+    }
+}
+
+/// 快照目录
+fn contract_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/contract")
+}
+
+/// 已接受的破坏性变更，格式为 `<快照名>: <变更描述>`，`#` 开头的行为注释
+fn accepted_breaking_changes() -> Vec<String> {
+    std::fs::read_to_string(contract_dir().join("accepted-breaking-changes.txt"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 与快照比较，只有未被接受的破坏性变更会导致失败
+///
+/// 快照缺失时失败，避免快照被误删后契约检查静默通过；新增快照需要设置 `UPDATE_SNAPSHOTS=1`
+fn assert_contract(name: &str, current: &str, diff: fn(&str, &str) -> Vec<Change>) {
+    let path = contract_dir().join("snapshots").join(name);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, current).unwrap();
+        eprintln!("已写入快照 {}", path.display());
+        return;
+    }
+    assert!(
+        path.exists(),
+        "缺少快照 {}，确认契约无误后执行 UPDATE_SNAPSHOTS=1 cargo test --test contract 生成",
+        path.display()
+    );
+
+    let snapshot = std::fs::read_to_string(&path).unwrap();
+    if snapshot == current {
+        return;
+    }
+
+    let changes = diff(&snapshot, current);
+    let accepted = accepted_breaking_changes();
+    let rejected = changes
+        .iter()
+        .filter(|change| change.breaking)
+        .filter(|change| !accepted.contains(&format!("{}: {}", name, change.description)))
+        .collect::<Vec<_>>();
+
+    for change in &changes {
+        eprintln!("{}: {}", name, change);
+    }
+    if rejected.is_empty() {
+        eprintln!("{} 与快照不一致，但不包含未接受的破坏性变更，请执行 UPDATE_SNAPSHOTS=1 更新快照", name);
+        return;
+    }
+
+    panic!(
+        "{} 包含破坏性变更:\n{}\n如确需引入，请将以下内容加入 tests/contract/accepted-breaking-changes.txt:\n{}",
+        name,
+        rejected.iter().map(|c| format!("  - {}", c.description)).collect::<Vec<_>>().join("\n"),
+        rejected.iter().map(|c| format!("{}: {}", name, c.description)).collect::<Vec<_>>().join("\n"),
+    );
+}
+
+#[test]
+fn openapi_contract() {
+    for version in ApiVersion::ALL {
+        assert_contract(
+            &format!("openapi-{}.json", version),
+            &api::export_spec(version, false, &[]),
+            openapi::diff,
+        );
+    }
+}
+
+#[test]
+fn graphql_contract() {
+    let sdl = {{crate_name}}::graphql::build_schema().sdl();
+    assert_contract("schema.graphql", &sdl, graphql::diff);
+}
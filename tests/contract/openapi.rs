@@ -0,0 +1,272 @@
+//! OpenAPI规范差异分类
+
+use serde_json::{json, Map, Value};
+
+use crate::Change;
+
+/// HTTP方法，用于区分路径项中的操作
+const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// 比较两个版本的OpenAPI规范（JSON）
+pub fn diff(old: &str, new: &str) -> Vec<Change> {
+    let old: Value = serde_json::from_str(old).expect("快照不是合法的JSON");
+    let new: Value = serde_json::from_str(new).expect("规范不是合法的JSON");
+
+    let mut changes = Vec::new();
+    diff_paths(&old["paths"], &new["paths"], &mut changes);
+    diff_schemas(&old["components"]["schemas"], &new["components"]["schemas"], &mut changes);
+    changes
+}
+
+fn diff_paths(old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    for (path, old_item) in entries(old) {
+        for method in METHODS {
+            let Some(old_op) = old_item.get(method) else { continue };
+            let op = format!("{} {}", method.to_uppercase(), path);
+            match new.get(path).and_then(|item| item.get(method)) {
+                None => changes.push(Change::breaking(format!("删除了接口 {}", op))),
+                Some(new_op) => diff_operation(&op, old_op, new_op, changes),
+            }
+        }
+    }
+
+    for (path, new_item) in entries(new) {
+        for method in METHODS {
+            if new_item.get(method).is_some() && old.get(path).and_then(|item| item.get(method)).is_none() {
+                changes.push(Change::compatible(format!("新增了接口 {} {}", method.to_uppercase(), path)));
+            }
+        }
+    }
+}
+
+fn diff_operation(op: &str, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    if old["operationId"] != new["operationId"] {
+        changes.push(Change::breaking(format!(
+            "{} 的 operationId 由 {} 改为 {}",
+            op, old["operationId"], new["operationId"]
+        )));
+    }
+    if old["deprecated"] != new["deprecated"] && new["deprecated"] == Value::Bool(true) {
+        changes.push(Change::compatible(format!("{} 被标记为弃用", op)));
+    }
+
+    let params = |op: &Value| {
+        op["parameters"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| (format!("{} 参数 {}", p["in"].as_str().unwrap_or(""), p["name"].as_str().unwrap_or("")), p))
+            .collect::<Vec<_>>()
+    };
+    let old_params = params(old);
+    let new_params = params(new);
+
+    for (name, old_param) in &old_params {
+        match new_params.iter().find(|(n, _)| n == name) {
+            None => changes.push(Change::breaking(format!("{} 删除了{}", op, name))),
+            Some((_, new_param)) => {
+                if !is_required(old_param) && is_required(new_param) {
+                    changes.push(Change::breaking(format!("{} 的{}改为必填", op, name)));
+                }
+                diff_schema(&format!("{} 的{}", op, name), &old_param["schema"], &new_param["schema"], changes);
+            }
+        }
+    }
+    for (name, new_param) in &new_params {
+        if old_params.iter().all(|(n, _)| n != name) {
+            if is_required(new_param) {
+                changes.push(Change::breaking(format!("{} 新增了必填{}", op, name)));
+            } else {
+                changes.push(Change::compatible(format!("{} 新增了可选{}", op, name)));
+            }
+        }
+    }
+
+    if !is_required(&old["requestBody"]) && is_required(&new["requestBody"]) {
+        changes.push(Change::breaking(format!("{} 的请求体改为必填", op)));
+    }
+    for content_type in keys(&old["requestBody"]["content"]) {
+        if new["requestBody"]["content"].get(content_type).is_none() {
+            changes.push(Change::breaking(format!("{} 不再接受 {} 请求体", op, content_type)));
+        }
+    }
+    for status in keys(&old["responses"]) {
+        if new["responses"].get(status).is_none() {
+            changes.push(Change::breaking(format!("{} 删除了 {} 响应", op, status)));
+        }
+    }
+}
+
+fn diff_schemas(old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    for (name, old_schema) in entries(old) {
+        match new.get(name) {
+            None => changes.push(Change::breaking(format!("删除了模型 {}", name))),
+            Some(new_schema) => diff_schema(&format!("模型 {}", name), old_schema, new_schema, changes),
+        }
+    }
+    for name in keys(new) {
+        if old.get(name).is_none() {
+            changes.push(Change::compatible(format!("新增了模型 {}", name)));
+        }
+    }
+}
+
+/// 比较两个JSON Schema
+///
+/// 模型同时用于请求和响应，因此删除字段、新增必填字段、修改类型、收窄取值范围都视为破坏性变更
+fn diff_schema(at: &str, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    for key in ["type", "format", "$ref"] {
+        if old[key] != new[key] {
+            changes.push(Change::breaking(format!("{} 的 {} 由 {} 改为 {}", at, key, old[key], new[key])));
+        }
+    }
+
+    narrowed(at, "maxLength", old, new, |old, new| new < old, changes);
+    narrowed(at, "maxItems", old, new, |old, new| new < old, changes);
+    narrowed(at, "maximum", old, new, |old, new| new < old, changes);
+    narrowed(at, "minLength", old, new, |old, new| new > old, changes);
+    narrowed(at, "minItems", old, new, |old, new| new > old, changes);
+    narrowed(at, "minimum", old, new, |old, new| new > old, changes);
+
+    if old["pattern"] != new["pattern"] && !new["pattern"].is_null() {
+        changes.push(Change::breaking(format!("{} 的 pattern 由 {} 改为 {}", at, old["pattern"], new["pattern"])));
+    }
+    if let (Some(old_values), Some(new_values)) = (old["enum"].as_array(), new["enum"].as_array()) {
+        for value in old_values.iter().filter(|v| !new_values.contains(v)) {
+            changes.push(Change::breaking(format!("{} 删除了枚举值 {}", at, value)));
+        }
+    }
+
+    for key in ["items", "additionalProperties"] {
+        if old[key].is_object() && new[key].is_object() {
+            diff_schema(&format!("{}.{}", at, key), &old[key], &new[key], changes);
+        }
+    }
+    for key in ["allOf", "oneOf", "anyOf"] {
+        if old[key] != new[key] {
+            changes.push(Change::breaking(format!("{} 的 {} 发生变化", at, key)));
+        }
+    }
+
+    let required = |schema: &Value| {
+        schema["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Value::as_str).map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    let old_required = required(old);
+    let new_required = required(new);
+
+    for (field, old_field) in entries(&old["properties"]) {
+        match new["properties"].get(field) {
+            None => changes.push(Change::breaking(format!("{} 删除了字段 {}", at, field))),
+            Some(new_field) => {
+                if !old_required.contains(field) && new_required.contains(field) {
+                    changes.push(Change::breaking(format!("{} 的字段 {} 改为必填", at, field)));
+                }
+                diff_schema(&format!("{}.{}", at, field), old_field, new_field, changes);
+            }
+        }
+    }
+    for field in keys(&new["properties"]) {
+        if old["properties"].get(field).is_none() {
+            if new_required.contains(field) && !is_read_only(&new["properties"][field]) {
+                changes.push(Change::breaking(format!("{} 新增了必填字段 {}", at, field)));
+            } else {
+                changes.push(Change::compatible(format!("{} 新增了字段 {}", at, field)));
+            }
+        }
+    }
+}
+
+/// 数值约束被收窄，或由无约束变为有约束
+fn narrowed(
+    at: &str,
+    key: &str,
+    old: &Value,
+    new: &Value,
+    is_narrower: fn(f64, f64) -> bool,
+    changes: &mut Vec<Change>,
+) {
+    let narrower = match (old[key].as_f64(), new[key].as_f64()) {
+        (None, Some(_)) => true,
+        (Some(old), Some(new)) => is_narrower(old, new),
+        _ => false,
+    };
+    if narrower {
+        changes.push(Change::breaking(format!("{} 的 {} 由 {} 收窄为 {}", at, key, old[key], new[key])));
+    }
+}
+
+fn is_required(value: &Value) -> bool {
+    value["required"] == Value::Bool(true)
+}
+
+fn is_read_only(value: &Value) -> bool {
+    value["readOnly"] == Value::Bool(true)
+}
+
+fn entries(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flat_map(Map::iter)
+}
+
+fn keys(value: &Value) -> impl Iterator<Item = &String> {
+    value.as_object().into_iter().flat_map(Map::keys)
+}
+
+// 分类规则自身的测试
+
+fn breaking(old: Value, new: Value) -> Vec<String> {
+    diff(&old.to_string(), &new.to_string())
+        .into_iter()
+        .filter(|c| c.breaking)
+        .map(|c| c.description)
+        .collect()
+}
+
+#[test]
+fn removed_operation_is_breaking() {
+    let old = json!({ "paths": { "/users": { "get": { "operationId": "listUsers" } } } });
+    let new = json!({ "paths": {} });
+    assert_eq!(breaking(old, new), ["删除了接口 GET /users"]);
+}
+
+#[test]
+fn renamed_operation_id_is_breaking() {
+    let old = json!({ "paths": { "/users": { "post": { "operationId": "createUser" } } } });
+    let new = json!({ "paths": { "/users": { "post": { "operationId": "addUser" } } } });
+    assert_eq!(breaking(old, new).len(), 1);
+}
+
+#[test]
+fn new_required_param_is_breaking_but_optional_is_not() {
+    let old = json!({ "paths": { "/users": { "get": { "operationId": "listUsers" } } } });
+    let new = json!({ "paths": { "/users": { "get": {
+        "operationId": "listUsers",
+        "parameters": [
+            { "name": "page", "in": "query", "required": false, "schema": { "type": "integer" } },
+            { "name": "tenant", "in": "header", "required": true, "schema": { "type": "string" } }
+        ]
+    } } } });
+    assert_eq!(breaking(old, new), ["GET /users 新增了必填header 参数 tenant"]);
+}
+
+#[test]
+fn narrowed_and_removed_fields_are_breaking() {
+    let old = json!({ "components": { "schemas": { "User": {
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer", "format": "uint64" },
+            "username": { "type": "string", "maxLength": 50 }
+        }
+    } } } });
+    let new = json!({ "components": { "schemas": { "User": {
+        "type": "object",
+        "properties": {
+            "username": { "type": "string", "maxLength": 20 },
+            "nickname": { "type": "string" }
+        }
+    } } } });
+    assert_eq!(breaking(old, new).len(), 2);
+}
@@ -0,0 +1,192 @@
+//! 领域事件与发件箱测试
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use common::{body, test_actor, RequestExt, TestApp, UserFixture};
+use serde_json::json;
+use {{crate_name}}::events::{DomainEvent, EventEnvelope, EventHandler, RelayStats};
+
+/// 记录收到的事件，可指定前若干次投递失败
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<EventEnvelope>>>,
+    failures: Arc<AtomicU32>,
+}
+
+impl Recorder {
+    /// 前 `count` 次投递失败
+    fn failing(count: u32) -> Self {
+        let recorder = Self::default();
+        recorder.failures.store(count, Ordering::SeqCst);
+        recorder
+    }
+
+    fn events(&self) -> Vec<EventEnvelope> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl EventHandler for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    async fn handle(&self, event: &EventEnvelope) -> anyhow::Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        let failing = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| count.checked_sub(1))
+            .is_ok();
+        if failing {
+            anyhow::bail!("下游暂时不可用");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn user_changes_are_published_through_outbox() {
+    let app = TestApp::new().await;
+    let recorder = Recorder::default();
+    app.state.events.subscribe(recorder.clone());
+    let key = app.admin_key().await;
+
+    let resp = app.client.post("/api/v1/users").body_json(&UserFixture::new().json()).send().await;
+    let id = body(resp).await["data"]["id"].as_u64().unwrap();
+    app.client
+        .put(format!("/api/v1/users/{}", id))
+        .api_key(&key)
+        .body_json(&json!({ "password": "new-password" }))
+        .send()
+        .await
+        .assert_status_is_ok();
+    app.client.delete(format!("/api/v1/users/{}", id)).api_key(&key).send().await.assert_status_is_ok();
+
+    // 事件在投递任务运行前只存在于发件箱中
+    assert!(recorder.events().is_empty());
+    assert_eq!(app.state.outbox.pending().await.unwrap(), 3);
+
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats, RelayStats { delivered: 3, ..Default::default() });
+    assert_eq!(app.state.outbox.pending().await.unwrap(), 0);
+
+    let events = recorder.events();
+    assert!(matches!(&events[0].event, DomainEvent::UserCreated(created) if created.user.id == Some(id)));
+    assert!(matches!(&events[1].event, DomainEvent::UserUpdated(updated) if updated.password_changed));
+    assert!(matches!(
+        &events[2].event,
+        DomainEvent::UserDeleted(deleted) if !deleted.purged && deleted.user.deleted_at.is_some()
+    ));
+    assert!(events.iter().all(|event| event.attempt == 1));
+}
+
+#[tokio::test]
+async fn failed_changes_publish_nothing() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let key = app.admin_key().await;
+    app.state.outbox.relay_once().await.unwrap();
+
+    let resp = app
+        .client
+        .delete(format!("/api/v1/users/{}", user.id.unwrap()))
+        .api_key(&key)
+        .header("If-Match", "\"9\"")
+        .send()
+        .await;
+    resp.assert_status(poem::http::StatusCode::PRECONDITION_FAILED);
+
+    assert_eq!(app.state.outbox.pending().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn failed_delivery_is_retried_with_same_event_id() {
+    let app = TestApp::with_config(|config| config.events.retry_base_secs = 0).await;
+    let recorder = Recorder::failing(1);
+    app.state.events.subscribe(recorder.clone());
+    app.create_user(UserFixture::new()).await;
+
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats, RelayStats { retried: 1, ..Default::default() });
+    assert_eq!(app.state.outbox.pending().await.unwrap(), 1);
+
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats, RelayStats { delivered: 1, ..Default::default() });
+
+    let events = recorder.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].id, events[1].id);
+    assert_eq!((events[0].attempt, events[1].attempt), (1, 2));
+}
+
+#[tokio::test]
+async fn retries_wait_for_backoff() {
+    let app = TestApp::new().await;
+    app.state.events.subscribe(Recorder::failing(1));
+    app.create_user(UserFixture::new()).await;
+
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats.retried, 1);
+
+    // 未到重试时间，不会被再次领取
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats, RelayStats::default());
+}
+
+#[tokio::test]
+async fn exhausted_events_move_to_dead_letters_and_can_be_requeued() {
+    let app = TestApp::with_config(|config| {
+        config.events.max_attempts = 2;
+        config.events.retry_base_secs = 0;
+    })
+    .await;
+    let recorder = Recorder::failing(2);
+    app.state.events.subscribe(recorder.clone());
+    let user = app.create_user(UserFixture::new()).await;
+
+    app.state.outbox.relay_once().await.unwrap();
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats, RelayStats { dead_lettered: 1, ..Default::default() });
+    assert_eq!(app.state.outbox.pending().await.unwrap(), 0);
+
+    let dead = app.state.outbox.dead_letters(10).await.unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].event_type, "user.created");
+    assert_eq!(dead[0].attempts, 2);
+    assert_eq!(dead[0].payload["data"]["user"]["username"], user.username);
+    assert!(dead[0].last_error.as_deref().unwrap().contains("recorder"));
+
+    assert!(app.state.outbox.requeue(dead[0].id).await.unwrap());
+    assert!(!app.state.outbox.requeue(dead[0].id).await.unwrap());
+    assert!(app.state.outbox.dead_letters(10).await.unwrap().is_empty());
+
+    let stats = app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(stats.delivered, 1);
+    let events = recorder.events();
+    assert_eq!(events.last().unwrap().id, dead[0].id);
+    assert_eq!(events.last().unwrap().attempt, 1);
+}
+
+#[tokio::test]
+async fn purge_publishes_permanent_deletion() {
+    let app = TestApp::new().await;
+    let recorder = Recorder::default();
+    app.state.events.subscribe(recorder.clone());
+    let user = app.create_user(UserFixture::new()).await;
+    app.state.users.delete(&test_actor(), user.id.unwrap(), None).await.unwrap();
+    app.state
+        .users
+        .purge_deleted(&test_actor(), chrono::Utc::now() + chrono::Duration::seconds(1))
+        .await
+        .unwrap();
+
+    app.state.outbox.relay_once().await.unwrap();
+    let events = recorder.events();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(&events[2].event, DomainEvent::UserDeleted(deleted) if deleted.purged));
+}
@@ -0,0 +1,283 @@
+//! 后台任务队列测试
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::{admin, body, TestApp};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use {{crate_name}}::jobs::{self, Job, JobContext, JobHandler, JobOptions, RunStats};
+
+#[derive(Serialize, Deserialize)]
+struct Echo {
+    message: String,
+}
+
+impl Job for Echo {
+    const KIND: &'static str = "echo";
+}
+
+/// 只执行一次的任务
+#[derive(Serialize, Deserialize)]
+struct OneShot;
+
+impl Job for OneShot {
+    const KIND: &'static str = "one_shot";
+    const MAX_ATTEMPTS: Option<u32> = Some(1);
+}
+
+/// 没有注册处理器的任务
+#[derive(Serialize, Deserialize)]
+struct Orphan;
+
+impl Job for Orphan {
+    const KIND: &'static str = "orphan";
+}
+
+/// 记录收到的任务，可指定前若干次执行失败
+#[derive(Clone, Default)]
+struct EchoHandler {
+    received: Arc<Mutex<Vec<(String, u32)>>>,
+    failures: Arc<AtomicU32>,
+}
+
+impl EchoHandler {
+    fn failing(count: u32) -> Self {
+        let handler = Self::default();
+        handler.failures.store(count, Ordering::SeqCst);
+        handler
+    }
+
+    fn received(&self) -> Vec<(String, u32)> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl JobHandler for EchoHandler {
+    type Job = Echo;
+
+    async fn handle(&self, job: Echo, ctx: &JobContext) -> anyhow::Result<()> {
+        self.received.lock().unwrap().push((job.message, ctx.attempt));
+        let failing = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| count.checked_sub(1))
+            .is_ok();
+        if failing {
+            anyhow::bail!("下游暂时不可用");
+        }
+        Ok(())
+    }
+}
+
+struct OneShotHandler;
+
+#[async_trait]
+impl JobHandler for OneShotHandler {
+    type Job = OneShot;
+
+    async fn handle(&self, _job: OneShot, _ctx: &JobContext) -> anyhow::Result<()> {
+        panic!("处理器出错");
+    }
+}
+
+fn echo(message: &str) -> Echo {
+    Echo {
+        message: message.to_string(),
+    }
+}
+
+/// 查询任务详情
+async fn job(app: &TestApp, id: u64) -> Value {
+    let resp = app.client.get(format!("/api/v1/jobs/{}", id)).data(admin()).send().await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    body["data"].clone()
+}
+
+/// 调用任务管理操作（retry、cancel），返回完整响应体
+async fn job_action(app: &TestApp, id: u64, action: &str) -> Value {
+    let resp = app.client.post(format!("/api/v1/jobs/{}/{}", id, action)).data(admin()).send().await;
+    resp.assert_status_is_ok();
+    body(resp).await
+}
+
+#[tokio::test]
+async fn enqueued_jobs_are_executed() {
+    let app = TestApp::new().await;
+    let handler = EchoHandler::default();
+    app.state.jobs.register(handler.clone());
+
+    let id = app.state.jobs.enqueue(&echo("hello"), JobOptions::new()).await.unwrap().unwrap();
+    app.state.jobs.enqueue(&echo("world"), JobOptions::new()).await.unwrap();
+    assert_eq!(job(&app, id).await["status"], "scheduled");
+
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { succeeded: 2, ..Default::default() });
+    assert_eq!(handler.received(), [("hello".to_string(), 1), ("world".to_string(), 1)]);
+
+    let record = job(&app, id).await;
+    assert_eq!(record["kind"], "echo");
+    assert_eq!(record["payload"]["message"], "hello");
+    assert_eq!(record["status"], "succeeded");
+    assert_eq!(record["attempts"], 1);
+    assert_eq!(record["max_attempts"], 5);
+    assert!(record["finished_at"].is_string());
+
+    let resp = app.client.get("/api/v2/jobs?status=succeeded&kind=echo").data(admin()).send().await;
+    assert_eq!(body(resp).await["data"]["total"], 2);
+
+    // 已结束的任务不会再次执行
+    assert_eq!(app.state.jobs.run_once().await.unwrap(), RunStats::default());
+}
+
+#[tokio::test]
+async fn failed_jobs_are_retried_with_backoff() {
+    let app = TestApp::new().await;
+    let handler = EchoHandler::failing(1);
+    app.state.jobs.register(handler.clone());
+    let id = app.state.jobs.enqueue(&echo("retry"), JobOptions::new()).await.unwrap().unwrap();
+
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { retried: 1, ..Default::default() });
+
+    let record = job(&app, id).await;
+    assert_eq!(record["status"], "scheduled");
+    assert!(record["last_error"].as_str().unwrap().contains("下游暂时不可用"));
+
+    // 未到重试时间，不会被再次领取
+    assert_eq!(app.state.jobs.run_once().await.unwrap(), RunStats::default());
+}
+
+#[tokio::test]
+async fn exhausted_jobs_fail_and_can_be_retried() {
+    let app = TestApp::with_config(|config| config.jobs.retry_base_secs = 0).await;
+    let handler = EchoHandler::failing(2);
+    app.state.jobs.register(handler.clone());
+    let id = app
+        .state
+        .jobs
+        .enqueue(&echo("flaky"), JobOptions::new().max_attempts(2))
+        .await
+        .unwrap()
+        .unwrap();
+
+    app.state.jobs.run_once().await.unwrap();
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { failed: 1, ..Default::default() });
+
+    let record = job(&app, id).await;
+    assert_eq!(record["status"], "failed");
+    assert_eq!(record["attempts"], 2);
+    assert_eq!(record["max_attempts"], 2);
+
+    // 手动重试，执行次数从零开始
+    let body = job_action(&app, id, "retry").await;
+    assert_eq!(body["data"]["status"], "scheduled");
+    assert_eq!(body["data"]["attempts"], 0);
+    assert!(body["data"]["finished_at"].is_null());
+
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { succeeded: 1, ..Default::default() });
+    assert_eq!(handler.received().last().unwrap().1, 1);
+
+    // 成功的任务不能重试
+    assert_eq!(job_action(&app, id, "retry").await["code"], 409);
+}
+
+#[tokio::test]
+async fn delayed_jobs_wait_and_can_be_cancelled() {
+    let app = TestApp::new().await;
+    app.state.jobs.register(EchoHandler::default());
+    let id = app
+        .state
+        .jobs
+        .enqueue(&echo("later"), JobOptions::new().delay(Duration::from_secs(3600)))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(app.state.jobs.run_once().await.unwrap(), RunStats::default());
+
+    let cancelled = job_action(&app, id, "cancel").await;
+    assert_eq!(cancelled["data"]["status"], "cancelled");
+    assert_eq!(job_action(&app, id, "cancel").await["code"], 409);
+
+    // 重试已取消的任务时立即执行
+    job_action(&app, id, "retry").await;
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats.succeeded, 1);
+
+    // 任务管理需要管理权限
+    let resp = app.client.post("/api/v1/jobs/9999/cancel").send().await;
+    resp.assert_status(poem::http::StatusCode::UNAUTHORIZED);
+
+    let resp = app.client.post("/api/v1/jobs/9999/cancel").data(admin()).send().await;
+    assert_eq!(body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn unique_jobs_are_enqueued_once_while_active() {
+    let app = TestApp::new().await;
+    app.state.jobs.register(EchoHandler::default());
+    let options = || JobOptions::new().unique("echo:report");
+
+    let first = app.state.jobs.enqueue(&echo("a"), options()).await.unwrap();
+    assert!(first.is_some());
+    assert_eq!(app.state.jobs.enqueue(&echo("b"), options()).await.unwrap(), None);
+    // 不同唯一键、无唯一键的任务不受影响
+    assert!(app.state.jobs.enqueue(&echo("c"), JobOptions::new().unique("other")).await.unwrap().is_some());
+    assert!(app.state.jobs.enqueue(&echo("d"), JobOptions::new()).await.unwrap().is_some());
+
+    app.state.jobs.run_once().await.unwrap();
+    // 之前的任务结束后可再次入队
+    assert!(app.state.jobs.enqueue(&echo("e"), options()).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn concurrency_limits_jobs_per_round() {
+    let app = TestApp::with_config(|config| config.jobs.concurrency = 2).await;
+    app.state.jobs.register(EchoHandler::default());
+    for message in ["1", "2", "3"] {
+        app.state.jobs.enqueue(&echo(message), JobOptions::new()).await.unwrap();
+    }
+
+    assert_eq!(app.state.jobs.run_once().await.unwrap().succeeded, 2);
+    assert_eq!(app.state.jobs.run_once().await.unwrap().succeeded, 1);
+}
+
+#[tokio::test]
+async fn unrunnable_jobs_fail_without_retry() {
+    let app = TestApp::new().await;
+    app.state.jobs.register(OneShotHandler);
+    let orphan = app.state.jobs.enqueue(&Orphan, JobOptions::new()).await.unwrap().unwrap();
+    let one_shot = app.state.jobs.enqueue(&OneShot, JobOptions::new()).await.unwrap().unwrap();
+
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { failed: 2, ..Default::default() });
+
+    let record = job(&app, orphan).await;
+    assert_eq!(record["attempts"], 1);
+    assert!(record["last_error"].as_str().unwrap().contains("orphan"));
+
+    // 处理器panic视为执行失败，该类型只执行一次
+    let record = job(&app, one_shot).await;
+    assert_eq!(record["status"], "failed");
+    assert_eq!(record["max_attempts"], 1);
+}
+
+#[tokio::test]
+async fn jobs_enqueued_in_rolled_back_transaction_are_discarded() {
+    let app = TestApp::new().await;
+
+    let mut tx = app.state.pool.begin().await.unwrap();
+    jobs::enqueue(&mut *tx, &echo("discarded"), JobOptions::new()).await.unwrap();
+    tx.rollback().await.unwrap();
+
+    let resp = app.client.get("/api/v1/jobs").data(admin()).send().await;
+    assert_eq!(body(resp).await["data"]["total"], 0);
+}
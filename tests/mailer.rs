@@ -0,0 +1,173 @@
+//! 邮件发送测试
+
+mod common;
+
+use common::{TestApp, UserFixture};
+use serde_json::json;
+use {{crate_name}}::config::{MailTransport, MailerConfig};
+use {{crate_name}}::jobs::{JobOptions, RunStats};
+use {{crate_name}}::mailer::{Email, FileTransport, Mailer, SendEmail, Templates, Transport, Welcome};
+
+fn welcome(username: &str) -> Welcome {
+    Welcome {
+        username: username.to_string(),
+    }
+}
+
+/// 内存发送方式已发送的邮件
+fn sent(app: &TestApp) -> Vec<Email> {
+    app.state.mailer.memory().expect("测试应使用内存发送方式").sent()
+}
+
+#[tokio::test]
+async fn templates_are_localized_with_fallback() {
+    let templates = Templates::new(&MailerConfig::default()).unwrap();
+
+    let zh = templates.render(None, &welcome("alice")).unwrap();
+    assert_eq!(zh.locale, "zh-CN");
+    assert!(zh.subject.starts_with("欢迎加入"));
+    assert!(zh.text.contains("alice，您好"));
+    assert!(zh.html.contains("<html lang=\"zh-CN\">"));
+
+    let en = templates.render(Some("en-US"), &welcome("alice")).unwrap();
+    assert_eq!(en.locale, "en");
+    assert!(en.subject.starts_with("Welcome to"));
+    assert!(en.text.starts_with("Hi alice,"));
+
+    // 不支持的语言使用默认语言
+    assert_eq!(templates.render(Some("fr"), &welcome("alice")).unwrap().locale, "zh-CN");
+    assert!(templates.render_value("missing", None, &json!({})).is_err());
+
+    let config = MailerConfig {
+        default_locale: "fr".to_string(),
+        ..Default::default()
+    };
+    assert!(Templates::new(&config).is_err());
+}
+
+#[tokio::test]
+async fn html_bodies_escape_parameters() {
+    let templates = Templates::new(&MailerConfig::default()).unwrap();
+    let rendered = templates.render(None, &welcome("<b>alice</b>")).unwrap();
+
+    assert!(rendered.html.contains("&lt;b&gt;alice&lt;&#x2f;b&gt;"));
+    assert!(rendered.text.contains("<b>alice</b>"));
+}
+
+#[tokio::test]
+async fn queued_emails_are_sent_by_jobs() {
+    let app = TestApp::new().await;
+    let job = SendEmail::new("bob@example.com", Some("en".to_string()), &welcome("bob")).unwrap();
+    app.state.jobs.enqueue(&job, JobOptions::new()).await.unwrap();
+    assert!(sent(&app).is_empty());
+
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { succeeded: 1, ..Default::default() });
+
+    let emails = sent(&app);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].to, "bob@example.com");
+    assert_eq!(emails[0].from, MailerConfig::default().from);
+    assert!(emails[0].subject.starts_with("Welcome to"));
+}
+
+#[tokio::test]
+async fn invalid_recipients_are_retried() {
+    let app = TestApp::new().await;
+    let job = SendEmail::new("not an address", None, &welcome("bob")).unwrap();
+    app.state.jobs.enqueue(&job, JobOptions::new()).await.unwrap();
+
+    let stats = app.state.jobs.run_once().await.unwrap();
+    assert_eq!(stats, RunStats { retried: 1, ..Default::default() });
+    assert!(sent(&app).is_empty());
+}
+
+#[tokio::test]
+async fn new_users_receive_welcome_email() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+    let email = fixture.email.clone();
+    app.client.post("/api/v1/users").body_json(&fixture.json()).send().await;
+
+    app.state.outbox.relay_once().await.unwrap();
+    app.state.jobs.run_once().await.unwrap();
+
+    // 同时还会发送邮箱验证邮件
+    let emails = sent(&app);
+    let welcome: Vec<_> = emails.iter().filter(|email| email.subject.starts_with("欢迎加入")).collect();
+    assert_eq!(welcome.len(), 1);
+    assert_eq!(welcome[0].to, email);
+    assert!(welcome[0].text.contains(&fixture.username));
+}
+
+#[tokio::test]
+async fn welcome_email_can_be_disabled() {
+    let app = TestApp::with_config(|config| {
+        config.mailer.welcome_enabled = false;
+        config.accounts.send_verification = false;
+    })
+    .await;
+    app.create_user(UserFixture::new()).await;
+
+    app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(app.state.jobs.run_once().await.unwrap(), RunStats::default());
+    assert!(sent(&app).is_empty());
+}
+
+#[tokio::test]
+async fn file_transport_appends_mbox_entries() {
+    let path = std::env::temp_dir().join(format!("mailer-test-{}.mbox", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let transport = FileTransport::new(&path);
+
+    let email = Email {
+        from: "noreply@example.com".to_string(),
+        to: "carol@example.com".to_string(),
+        subject: "Hello".to_string(),
+        html: "<p>Hello</p>".to_string(),
+        text: "From the team".to_string(),
+    };
+    transport.send(&email).await.unwrap();
+    transport.send(&email).await.unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(content.matches("From MAILER-DAEMON ").count(), 2);
+    assert!(content.starts_with("From MAILER-DAEMON "));
+    assert!(content.contains("To: carol@example.com"));
+    // 正文中以 "From " 开头的行被转义
+    assert!(content.contains("\n>From the team"));
+}
+
+#[tokio::test]
+async fn templates_can_be_previewed_in_development() {
+    let app = TestApp::with_config(|config| config.mailer.preview_enabled = true).await;
+
+    let resp = app.client.get("/dev/mail").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({ "templates": ["welcome", "verify_email", "reset_password"], "locales": ["zh-CN", "en"] })).await;
+
+    let resp = app.client.get("/dev/mail/welcome").query("locale", &"en").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("text/html; charset=utf-8");
+    assert!(resp.0.into_body().into_string().await.unwrap().contains("Hi alice,"));
+
+    let resp = app.client.get("/dev/mail/welcome").query("format", &"text").send().await;
+    assert!(resp.0.into_body().into_string().await.unwrap().starts_with("Subject: 欢迎加入"));
+
+    app.client.get("/dev/mail/missing").send().await.assert_status(poem::http::StatusCode::NOT_FOUND);
+
+    // 默认不提供预览
+    let app = TestApp::new().await;
+    app.client.get("/dev/mail/welcome").send().await.assert_status(poem::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn smtp_transport_is_created_from_config() {
+    let config = MailerConfig {
+        transport: MailTransport::Smtp,
+        ..Default::default()
+    };
+    let mailer = Mailer::new(&config).unwrap();
+    assert!(mailer.memory().is_none());
+}
@@ -0,0 +1,234 @@
+//! 两步验证测试
+
+mod common;
+
+use chrono::Utc;
+use common::{admin, body, principal, test_actor, TestApp, UserFixture};
+use poem::http::StatusCode;
+use serde_json::{json, Value};
+use {{crate_name}}::auth::totp::Totp;
+use {{crate_name}}::auth::Principal;
+use {{crate_name}}::services::{MfaMethod, MfaRequirement};
+
+/// 当前时间之后第 `offset` 步的验证码，同一步的验证码只能使用一次
+fn code(secret: &str, offset: u64) -> String {
+    Totp::from_base32(secret).unwrap().code_at(Totp::step(Utc::now().timestamp()) + offset)
+}
+
+/// 开始绑定并启用，返回密钥及恢复码
+async fn enable(app: &TestApp, id: u64) -> (String, Vec<String>) {
+    let secret = app.state.mfa.enroll(&test_actor().actor, id).await.unwrap().secret;
+    let codes = app.state.mfa.enable(&test_actor(), id, &code(&secret, 0)).await.unwrap().codes;
+    (secret, codes)
+}
+
+async fn post(app: &TestApp, caller: &Principal, path: &str, payload: Value) -> Value {
+    body(app.client.post(path).data(caller.clone()).body_json(&payload).send().await).await
+}
+
+async fn graphql(app: &TestApp, caller: &Principal, query: &str, variables: Value) -> Value {
+    body(app.graphql_request(query, variables).data(caller.clone()).send().await).await
+}
+
+#[tokio::test]
+async fn enroll_and_enable_with_totp() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let id = user.id.unwrap();
+    let me = principal(&user);
+    let base = format!("/api/v2/users/{}/mfa", id);
+
+    let status = body(app.client.get(&base).data(me.clone()).send().await).await;
+    assert_eq!(status["data"]["enabled"], false);
+    assert_eq!(status["data"]["required"], false);
+
+    // 启用前必须先绑定
+    let resp = post(&app, &me, &format!("{}/enable", base), json!({ "code": "123456" })).await;
+    assert_eq!(resp["code"], 409);
+
+    let enrollment = post(&app, &me, &format!("{}/enroll", base), json!({})).await;
+    let secret = enrollment["data"]["secret"].as_str().unwrap().to_string();
+    let uri = enrollment["data"]["otpauth_uri"].as_str().unwrap();
+    assert!(uri.starts_with("otpauth://totp/"));
+    assert!(uri.contains(&format!(":{}?secret={}", user.username, secret)));
+
+    let resp = post(&app, &me, &format!("{}/enable", base), json!({ "code": "000000x" })).await;
+    assert_eq!(resp["code"], 400);
+    assert_eq!(resp["msg"], "验证码无效");
+
+    let code = code(&secret, 0);
+    let resp = post(&app, &me, &format!("{}/enable", base), json!({ "code": code })).await;
+    assert_eq!(resp["code"], 200);
+    assert_eq!(resp["data"]["codes"].as_array().unwrap().len(), app.config.mfa.recovery_codes as usize);
+
+    let status = body(app.client.get(&base).data(me.clone()).send().await).await;
+    assert_eq!(status["data"]["enabled"], true);
+    assert!(status["data"]["enabled_at"].is_string());
+    assert_eq!(status["data"]["recovery_codes_remaining"], 10);
+
+    // 已启用时不能重新绑定，已使用的验证码不能再次使用
+    let resp = post(&app, &me, &format!("{}/enroll", base), json!({})).await;
+    assert_eq!(resp["code"], 409);
+    let resp = post(&app, &me, &format!("{}/disable", base), json!({ "code": code })).await;
+    assert_eq!(resp["code"], 400);
+
+    // 审计记录只包含启用状态，恢复码被脱敏
+    let events = app.state.audit.list(&Default::default(), 1, 10).await.unwrap().0;
+    assert_eq!(events[0].changes["mfa_enabled"]["after"], true);
+    assert_eq!(events[0].changes["mfa_recovery_codes"]["after"], "[REDACTED]");
+}
+
+#[tokio::test]
+async fn enrollment_can_be_restarted_before_enabling() {
+    let app = TestApp::new().await;
+    let id = app.create_user(UserFixture::new()).await.id.unwrap();
+
+    let first = app.state.mfa.enroll(&test_actor().actor, id).await.unwrap().secret;
+    let second = app.state.mfa.enroll(&test_actor().actor, id).await.unwrap().secret;
+    assert_ne!(first, second);
+
+    // 替换后原密钥的验证码无效
+    assert!(app.state.mfa.enable(&test_actor(), id, &code(&first, 0)).await.is_err());
+    assert!(app.state.mfa.enable(&test_actor(), id, &code(&second, 0)).await.is_ok());
+}
+
+#[tokio::test]
+async fn recovery_codes_are_single_use() {
+    let app = TestApp::new().await;
+    let id = app.create_user(UserFixture::new()).await.id.unwrap();
+    let (secret, codes) = enable(&app, id).await;
+
+    // 忽略大小写及分隔符
+    let normalized = codes[0].replace('-', "").to_uppercase();
+    assert_eq!(app.state.mfa.verify(id, &normalized).await.unwrap(), MfaMethod::RecoveryCode);
+    assert!(app.state.mfa.verify(id, &codes[0]).await.is_err());
+    assert_eq!(app.state.mfa.status(&test_actor().actor, id).await.unwrap().recovery_codes_remaining, 9);
+
+    // 重新生成后原有的恢复码全部失效
+    let new_codes = app
+        .state
+        .mfa
+        .regenerate_recovery_codes(&test_actor(), id, &code(&secret, 1))
+        .await
+        .unwrap()
+        .codes;
+    assert_eq!(new_codes.len(), 10);
+    assert!(app.state.mfa.verify(id, &codes[1]).await.is_err());
+    assert_eq!(app.state.mfa.verify(id, &new_codes[0]).await.unwrap(), MfaMethod::RecoveryCode);
+
+    // 停用后恢复码随之删除
+    app.state.mfa.disable(&test_actor(), id, &new_codes[1]).await.unwrap();
+    let status = app.state.mfa.status(&test_actor().actor, id).await.unwrap();
+    assert!(!status.enabled);
+    assert_eq!(status.recovery_codes_remaining, 0);
+}
+
+#[tokio::test]
+async fn login_verification_rejects_replayed_codes() {
+    let app = TestApp::new().await;
+    let id = app.create_user(UserFixture::new()).await.id.unwrap();
+    let (secret, _) = enable(&app, id).await;
+
+    let next = code(&secret, 1);
+    assert_eq!(app.state.mfa.verify(id, &next).await.unwrap(), MfaMethod::Totp);
+    assert!(app.state.mfa.verify(id, &next).await.is_err());
+    // 早于已使用验证码的验证码同样失效
+    assert!(app.state.mfa.verify(id, &code(&secret, 0)).await.is_err());
+}
+
+#[tokio::test]
+async fn roles_can_require_mfa() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(UserFixture::new().admin()).await;
+    let user = app.create_user(UserFixture::new()).await;
+    let admin_id = admin.id.unwrap();
+
+    assert_eq!(app.state.mfa.requirement(&user).await.unwrap(), MfaRequirement::NotRequired);
+    assert_eq!(app.state.mfa.requirement(&admin).await.unwrap(), MfaRequirement::Enroll);
+    assert!(app.state.mfa.status(&test_actor().actor, admin_id).await.unwrap().required);
+
+    let (_, codes) = enable(&app, admin_id).await;
+    assert_eq!(app.state.mfa.requirement(&admin).await.unwrap(), MfaRequirement::Verify);
+
+    // 管理员同样不能绕过角色的要求
+    let path = format!("/api/v1/users/{}/mfa/disable", admin_id);
+    let resp = post(&app, &principal(&admin), &path, json!({ "code": codes[0] })).await;
+    assert_eq!(resp["code"], 409);
+
+    // 不要求启用的角色
+    let app = TestApp::with_config(|config| config.mfa.required_roles.clear()).await;
+    let admin = app.create_user(UserFixture::new().admin()).await;
+    assert_eq!(app.state.mfa.requirement(&admin).await.unwrap(), MfaRequirement::NotRequired);
+}
+
+#[tokio::test]
+async fn graphql_flows_match_rest() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let (id, me) = (user.id.unwrap(), principal(&user));
+    let vars = |code: &str| json!({ "userId": id, "code": code });
+
+    let enroll = "mutation($userId: Int!) { enrollMfa(userId: $userId) { secret otpauthUri digits period } }";
+    let resp = graphql(&app, &me, enroll, json!({ "userId": id })).await;
+    assert_eq!(resp["data"]["enrollMfa"]["digits"], 6);
+    assert_eq!(resp["data"]["enrollMfa"]["period"], 30);
+    let secret = resp["data"]["enrollMfa"]["secret"].as_str().unwrap().to_string();
+
+    let enable = "mutation($userId: Int!, $code: String!) { enableMfa(userId: $userId, code: $code) }";
+    let resp = graphql(&app, &me, enable, vars("999999x")).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "VALIDATION_ERROR");
+    let resp = graphql(&app, &me, enable, vars(&code(&secret, 0))).await;
+    let codes = resp["data"]["enableMfa"].as_array().unwrap().clone();
+    assert_eq!(codes.len(), 10);
+
+    let query = "query($userId: Int!) { mfaStatus(userId: $userId) { enabled recoveryCodesRemaining } }";
+    let resp = graphql(&app, &me, query, json!({ "userId": id })).await;
+    assert_eq!(resp["data"]["mfaStatus"]["enabled"], true);
+
+    let regenerate = "mutation($userId: Int!, $code: String!) { regenerateRecoveryCodes(userId: $userId, code: $code) }";
+    let resp = graphql(&app, &me, regenerate, vars(codes[0].as_str().unwrap())).await;
+    assert_eq!(resp["data"]["regenerateRecoveryCodes"].as_array().unwrap().len(), 10);
+
+    let disable = "mutation($userId: Int!, $code: String!) { disableMfa(userId: $userId, code: $code) }";
+    let resp = graphql(&app, &me, disable, vars(&code(&secret, 1))).await;
+    assert_eq!(resp["data"]["disableMfa"], true);
+    let resp = graphql(&app, &me, disable, vars(&code(&secret, 1))).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "CONFLICT");
+}
+
+#[tokio::test]
+async fn only_the_user_or_an_admin_manages_mfa() {
+    let app = TestApp::new().await;
+    let id = app.create_user(UserFixture::new()).await.id.unwrap();
+    let other = principal(&app.create_user(UserFixture::new()).await);
+    let base = format!("/api/v1/users/{}/mfa", id);
+
+    // 未认证时返回401，其他用户返回403
+    let resp = app.client.get(&base).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(body(resp).await["code"], 401);
+    let resp = app.client.post(format!("{}/enroll", base)).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    let resp = app.client.post(format!("{}/enroll", base)).data(other.clone()).body_json(&json!({})).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(body(resp).await["code"], 403);
+    let resp = post(&app, &other, &format!("{}/enable", base), json!({ "code": "123456" })).await;
+    assert_eq!(resp["code"], 403);
+
+    let enroll = "mutation($userId: Int!) { enrollMfa(userId: $userId) { secret } }";
+    let resp = app.graphql(enroll, json!({ "userId": id })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "UNAUTHORIZED");
+    let resp = graphql(&app, &other, enroll, json!({ "userId": id })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    let query = "query($userId: Int!) { mfaStatus(userId: $userId) { enabled } }";
+    let resp = graphql(&app, &other, query, json!({ "userId": id })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+
+    // 被拒绝的请求没有开始绑定
+    let err = app.state.mfa.enable(&test_actor(), id, "123456").await.unwrap_err();
+    assert_eq!(err.to_string(), "请先开始绑定两步验证");
+
+    // 管理员可以查看其他用户的状态
+    let resp = app.client.get(&base).data(admin()).send().await;
+    assert_eq!(body(resp).await["data"]["enabled"], false);
+}
@@ -0,0 +1,590 @@
+//! 外部身份提供方登录（OpenID Connect）测试
+//!
+//! 身份提供方为测试中启动的本地模拟服务，提供发现文档、JWKS及令牌端点，ID令牌使用ES256签名
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use common::{body, free_port, test_actor, TestApp, UserFixture};
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use poem::{
+    get, handler,
+    http::{header, StatusCode},
+    listener::TcpListener,
+    post,
+    web::{Data, Form, Json},
+    EndpointExt, IntoResponse, Response, Route, Server,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use {{crate_name}}::auth::totp::Totp;
+use {{crate_name}}::config::OidcProviderConfig;
+
+const CLIENT_ID: &str = "poem-api";
+const CLIENT_SECRET: &str = "client-secret";
+const REDIRECT_URL: &str = "http://localhost:3000/login/callback";
+
+/// 签名密钥
+struct SigningKey {
+    kid: String,
+    encoding: EncodingKey,
+    jwk: Value,
+}
+
+impl SigningKey {
+    fn generate(kid: &str) -> Self {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        // 未压缩的椭圆曲线点：0x04 || X || Y
+        let point = key_pair.public_key_raw();
+        Self {
+            kid: kid.to_string(),
+            encoding: EncodingKey::from_ec_pem(key_pair.serialize_pem().as_bytes()).unwrap(),
+            jwk: json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": BASE64URL_NOPAD.encode(&point[1..33]),
+                "y": BASE64URL_NOPAD.encode(&point[33..65]),
+                "kid": kid,
+                "use": "sig",
+                "alg": "ES256",
+            }),
+        }
+    }
+}
+
+/// 用户在身份提供方登录后签发的授权码
+struct Grant {
+    claims: Value,
+    code_challenge: String,
+    /// 使用不在JWKS中的密钥签名
+    forged: bool,
+}
+
+#[derive(Clone)]
+struct IdpState {
+    issuer: String,
+    key: Arc<Mutex<SigningKey>>,
+    grants: Arc<Mutex<HashMap<String, Grant>>>,
+    next_code: Arc<AtomicU32>,
+    jwks_requests: Arc<Mutex<u32>>,
+}
+
+/// 本地模拟的身份提供方
+struct MockIdp {
+    issuer: String,
+    state: IdpState,
+    server: tokio::task::JoinHandle<std::io::Result<()>>,
+}
+
+impl MockIdp {
+    async fn start() -> Self {
+        let port = free_port();
+        let issuer = format!("http://127.0.0.1:{}/realms/test", port);
+        let state = IdpState {
+            issuer: issuer.clone(),
+            key: Arc::new(Mutex::new(SigningKey::generate("key-1"))),
+            grants: Arc::default(),
+            next_code: Arc::default(),
+            jwks_requests: Arc::default(),
+        };
+        let app = Route::new()
+            .at("/realms/test/.well-known/openid-configuration", get(discovery))
+            .at("/realms/test/jwks", get(jwks))
+            .at("/realms/test/token", post(token))
+            .data(state.clone());
+        let server = tokio::spawn(Server::new(TcpListener::bind(format!("127.0.0.1:{}", port))).run(app));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        Self { issuer, state, server }
+    }
+
+    /// 提供方配置
+    fn provider(&self) -> OidcProviderConfig {
+        OidcProviderConfig {
+            name: Some("Mock IdP".to_string()),
+            issuer: self.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: Some(CLIENT_SECRET.to_string()),
+            redirect_url: REDIRECT_URL.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 模拟用户在授权地址完成登录，返回授权码；`claims` 覆盖ID令牌中的默认声明
+    fn approve(&self, authorization_url: &str, claims: Value) -> String {
+        self.grant(authorization_url, claims, false)
+    }
+
+    /// 同 `approve`，但ID令牌使用身份提供方之外的密钥签名
+    fn approve_forged(&self, authorization_url: &str, claims: Value) -> String {
+        self.grant(authorization_url, claims, true)
+    }
+
+    fn grant(&self, authorization_url: &str, overrides: Value, forged: bool) -> String {
+        let params = query_params(authorization_url);
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["redirect_uri"], REDIRECT_URL);
+        assert_eq!(params["scope"], "openid email profile");
+        assert_eq!(params["code_challenge_method"], "S256");
+
+        let now = Utc::now().timestamp();
+        let mut claims = json!({
+            "iss": self.issuer,
+            "aud": CLIENT_ID,
+            "sub": "248289761001",
+            "iat": now,
+            "exp": now + 300,
+            "nonce": params["nonce"],
+            "email": "jane.doe@example.com",
+            "email_verified": true,
+            "preferred_username": "jane.doe",
+        });
+        for (name, value) in overrides.as_object().unwrap() {
+            claims[name] = value.clone();
+        }
+
+        let code = format!("code-{}", self.state.next_code.fetch_add(1, Ordering::Relaxed));
+        let grant = Grant {
+            claims,
+            code_challenge: params["code_challenge"].clone(),
+            forged,
+        };
+        self.state.grants.lock().unwrap().insert(code.clone(), grant);
+        code
+    }
+
+    /// 轮换签名密钥，JWKS中只保留新密钥
+    fn rotate_key(&self) {
+        *self.state.key.lock().unwrap() = SigningKey::generate("key-2");
+    }
+
+    fn jwks_requests(&self) -> u32 {
+        *self.state.jwks_requests.lock().unwrap()
+    }
+}
+
+impl Drop for MockIdp {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+#[handler]
+fn discovery(state: Data<&IdpState>) -> Json<Value> {
+    Json(json!({
+        "issuer": state.issuer,
+        "authorization_endpoint": format!("{}/auth", state.issuer),
+        "token_endpoint": format!("{}/token", state.issuer),
+        "jwks_uri": format!("{}/jwks", state.issuer),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["ES256"],
+    }))
+}
+
+#[handler]
+fn jwks(state: Data<&IdpState>) -> Json<Value> {
+    *state.jwks_requests.lock().unwrap() += 1;
+    Json(json!({ "keys": [state.key.lock().unwrap().jwk] }))
+}
+
+#[handler]
+fn token(form: Form<HashMap<String, String>>, state: Data<&IdpState>) -> Response {
+    let invalid_grant = || {
+        Json(json!({ "error": "invalid_grant", "error_description": "授权码无效" }))
+            .with_status(StatusCode::BAD_REQUEST)
+            .into_response()
+    };
+
+    let form = form.0;
+    assert_eq!(form["grant_type"], "authorization_code");
+    assert_eq!(form["client_id"], CLIENT_ID);
+    assert_eq!(form["client_secret"], CLIENT_SECRET);
+    assert_eq!(form["redirect_uri"], REDIRECT_URL);
+
+    // 授权码只能使用一次，且必须提供与挑战码对应的PKCE校验码
+    let Some(grant) = state.grants.lock().unwrap().remove(&form["code"]) else {
+        return invalid_grant();
+    };
+    if BASE64URL_NOPAD.encode(&Sha256::digest(form["code_verifier"].as_bytes())) != grant.code_challenge {
+        return invalid_grant();
+    }
+
+    let forged_key;
+    let key = state.key.lock().unwrap();
+    let (kid, encoding) = if grant.forged {
+        forged_key = SigningKey::generate(&key.kid);
+        (&forged_key.kid, &forged_key.encoding)
+    } else {
+        (&key.kid, &key.encoding)
+    };
+    let mut header = Header::new(Algorithm::ES256);
+    header.kid = Some(kid.clone());
+    let id_token = jsonwebtoken::encode(&header, &grant.claims, encoding).unwrap();
+
+    Json(json!({ "access_token": "access-token", "token_type": "Bearer", "id_token": id_token })).into_response()
+}
+
+fn query_params(url: &str) -> HashMap<String, String> {
+    reqwest::Url::parse(url).unwrap().query_pairs().into_owned().collect()
+}
+
+/// 启动模拟身份提供方及配置了该提供方的应用
+async fn setup(customize: impl FnOnce(&mut OidcProviderConfig)) -> (MockIdp, TestApp) {
+    let idp = MockIdp::start().await;
+    let mut provider = idp.provider();
+    customize(&mut provider);
+    let app = TestApp::with_config(|config| {
+        config.oidc.providers.insert("mock".to_string(), provider);
+    })
+    .await;
+    (idp, app)
+}
+
+/// 发起登录，返回授权地址及 `state`；`state` 同时写入浏览器的Cookie
+async fn authorize(app: &TestApp) -> (String, String) {
+    let resp = app.client.get("/api/v1/auth/oidc/mock/authorize").send().await;
+    let set_cookie = resp.0.headers().get(header::SET_COOKIE).unwrap().to_str().unwrap().to_string();
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    let state = body["data"]["state"].as_str().unwrap().to_string();
+    assert!(set_cookie.starts_with(&state_cookie(&state)), "{}", set_cookie);
+    assert!(set_cookie.contains("HttpOnly"), "{}", set_cookie);
+    (body["data"]["authorization_url"].as_str().unwrap().to_string(), state)
+}
+
+/// 发起登录的浏览器在回调时携带的Cookie
+fn state_cookie(state: &str) -> String {
+    format!("oidc_state={}", state)
+}
+
+/// 在发起登录的浏览器中回调
+async fn callback(app: &TestApp, code: &str, state: &str) -> Value {
+    let resp = app
+        .client
+        .get(format!("/api/v1/auth/oidc/mock/callback?code={}&state={}", code, state))
+        .header(header::COOKIE, state_cookie(state))
+        .send()
+        .await;
+    body(resp).await
+}
+
+/// 完成一次登录
+async fn login(idp: &MockIdp, app: &TestApp, claims: Value) -> Value {
+    let (url, state) = authorize(app).await;
+    let code = idp.approve(&url, claims);
+    callback(app, &code, &state).await
+}
+
+#[tokio::test]
+async fn providers_are_listed() {
+    let (_idp, app) = setup(|_| {}).await;
+    let resp = app.client.get("/api/v2/auth/oidc/providers").send().await;
+    let providers = body(resp).await;
+    assert_eq!(providers["data"]["items"], json!([{ "id": "mock", "name": "Mock IdP" }]));
+
+    let resp = app.client.get("/api/v2/auth/oidc/unknown/authorize").send().await;
+    assert_eq!(body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn first_login_creates_user_and_later_logins_reuse_it() {
+    let (idp, app) = setup(|_| {}).await;
+    let (url, state) = authorize(&app).await;
+    assert!(url.starts_with(&format!("{}/auth?", idp.issuer)));
+    assert_eq!(query_params(&url)["state"], state);
+
+    let code = idp.approve(&url, json!({}));
+    let first = callback(&app, &code, &state).await;
+    assert_eq!(first["code"], 200, "{}", first);
+    assert_eq!(first["data"]["created"], true);
+    assert_eq!(first["data"]["user"]["username"], "jane.doe");
+    assert_eq!(first["data"]["user"]["email"], "jane.doe@example.com");
+    assert!(first["data"]["user"]["email_verified_at"].is_string());
+    assert_eq!(first["data"]["identity"]["provider"], "mock");
+    assert_eq!(first["data"]["identity"]["subject"], "248289761001");
+
+    let second = login(&idp, &app, json!({ "email": "jane@example.com" })).await;
+    assert_eq!(second["data"]["created"], false);
+    assert_eq!(second["data"]["user"]["id"], first["data"]["user"]["id"]);
+    assert_eq!(second["data"]["identity"]["id"], first["data"]["identity"]["id"]);
+    assert_eq!(second["data"]["identity"]["email"], "jane@example.com");
+
+    // 发现文档及JWKS已缓存
+    assert_eq!(idp.jwks_requests(), 1);
+
+    let events = app.state.audit.list(&Default::default(), 1, 10).await.unwrap().0;
+    assert!(events.iter().any(|event| event.entity_type == "user_identity"));
+}
+
+#[tokio::test]
+async fn login_starts_browser_session() {
+    let (idp, app) = setup(|_| {}).await;
+    let (url, state) = authorize(&app).await;
+    let code = idp.approve(&url, json!({}));
+    let resp = app
+        .client
+        .get(format!("/api/v1/auth/oidc/mock/callback?code={}&state={}", code, state))
+        .header(header::COOKIE, state_cookie(&state))
+        .send()
+        .await;
+    let cookie = resp
+        .0
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().split(';').next().unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let login = body(resp).await;
+    assert_eq!(login["code"], 200, "{}", login);
+    assert_eq!(login["data"]["session"]["user_id"], login["data"]["user"]["id"]);
+    assert!(cookie.contains("sid="), "{}", cookie);
+
+    let resp = app
+        .client
+        .get("/api/v1/sessions/current")
+        .header(header::COOKIE, cookie)
+        .send()
+        .await;
+    let current = body(resp).await;
+    assert_eq!(current["code"], 200, "{}", current);
+    assert_eq!(current["data"]["id"], login["data"]["session"]["id"]);
+}
+
+#[tokio::test]
+async fn mfa_is_enforced_before_starting_session() {
+    let (idp, app) = setup(|_| {}).await;
+    let first = login(&idp, &app, json!({})).await;
+    let id = first["data"]["user"]["id"].as_u64().unwrap();
+    let secret = app.state.mfa.enroll(&test_actor().actor, id).await.unwrap().secret;
+    let totp = Totp::from_base32(&secret).unwrap();
+    let step = Totp::step(Utc::now().timestamp());
+    app.state.mfa.enable(&test_actor(), id, &totp.code_at(step)).await.unwrap();
+
+    let resp = login(&idp, &app, json!({})).await;
+    assert_eq!(resp["code"], 401);
+    assert_eq!(resp["msg"], "需要两步验证码");
+
+    let (url, state) = authorize(&app).await;
+    let code = idp.approve(&url, json!({}));
+    let resp = app
+        .client
+        .get(format!(
+            "/api/v1/auth/oidc/mock/callback?code={}&state={}&mfa_code={}",
+            code,
+            state,
+            totp.code_at(step + 1)
+        ))
+        .header(header::COOKIE, state_cookie(&state))
+        .send()
+        .await;
+    assert!(resp.0.headers().contains_key(header::SET_COOKIE));
+    let resp = body(resp).await;
+    assert_eq!(resp["code"], 200, "{}", resp);
+    assert_eq!(resp["data"]["session"]["user_id"], id);
+    assert_eq!(resp["data"]["mfa_enrollment_required"], false);
+}
+
+#[tokio::test]
+async fn unenrolled_mfa_roles_only_get_an_enrollment_session() {
+    let (idp, app) = setup(|_| {}).await;
+    let first = login(&idp, &app, json!({})).await;
+    let id = first["data"]["user"]["id"].as_u64().unwrap();
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = ?")
+        .bind(id as i64)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+
+    let (url, state) = authorize(&app).await;
+    let code = idp.approve(&url, json!({}));
+    let resp = app
+        .client
+        .get(format!("/api/v1/auth/oidc/mock/callback?code={}&state={}", code, state))
+        .header(header::COOKIE, state_cookie(&state))
+        .send()
+        .await;
+    let cookie = resp
+        .0
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().split(';').next().unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let login = body(resp).await;
+    assert_eq!(login["data"]["mfa_enrollment_required"], true, "{}", login);
+
+    // 完成绑定前只能访问本人的两步验证接口
+    let resp = app.client.get(format!("/api/v1/users/{}", id)).header(header::COOKIE, &cookie).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    let resp = app.client.get(format!("/api/v1/users/{}/mfa", id)).header(header::COOKIE, &cookie).send().await;
+    resp.assert_status_is_ok();
+    assert_eq!(body(resp).await["data"]["required"], true);
+}
+
+#[tokio::test]
+async fn username_conflicts_get_a_suffix() {
+    let (idp, app) = setup(|_| {}).await;
+    app.create_user(UserFixture::new().username("jane.doe")).await;
+
+    let resp = login(&idp, &app, json!({ "email": "jane.d@example.org", "email_verified": false })).await;
+    assert_eq!(resp["data"]["created"], true);
+    let username = resp["data"]["user"]["username"].as_str().unwrap();
+    assert!(username.starts_with("jane.doe-"), "{}", username);
+    assert!(resp["data"]["user"]["email_verified_at"].is_null());
+}
+
+#[tokio::test]
+async fn verified_email_links_existing_user() {
+    let (idp, app) = setup(|_| {}).await;
+    let user = app.create_user(UserFixture::new()).await;
+
+    // 身份提供方未验证邮箱时不关联，邮箱已被使用不能创建用户
+    let resp = login(&idp, &app, json!({ "email": user.email, "email_verified": false })).await;
+    assert_eq!(resp["code"], 409);
+
+    // 本地用户尚未验证邮箱时同样不关联，避免接管他人以该邮箱注册的账户
+    let resp = login(&idp, &app, json!({ "email": user.email })).await;
+    assert_eq!(resp["code"], 409);
+
+    sqlx::query("UPDATE users SET email_verified_at = '2025-01-01T00:00:00Z' WHERE id = ?")
+        .bind(user.id.unwrap() as i64)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+
+    let resp = login(&idp, &app, json!({ "email": user.email })).await;
+    assert_eq!(resp["data"]["created"], false);
+    assert_eq!(resp["data"]["user"]["id"], user.id.unwrap());
+    assert_eq!(resp["data"]["identity"]["user_id"], user.id.unwrap());
+}
+
+#[tokio::test]
+async fn signup_and_linking_follow_provider_config() {
+    let (idp, app) = setup(|provider| {
+        provider.allow_signup = false;
+        provider.link_by_email = false;
+    })
+    .await;
+    let user = app.create_user(UserFixture::new()).await;
+
+    let resp = login(&idp, &app, json!({})).await;
+    assert_eq!(resp["code"], 403);
+    let resp = login(&idp, &app, json!({ "email": user.email })).await;
+    assert_eq!(resp["code"], 403);
+}
+
+#[tokio::test]
+async fn state_is_single_use() {
+    let (idp, app) = setup(|_| {}).await;
+    let (url, state) = authorize(&app).await;
+    let code = idp.approve(&url, json!({}));
+    assert_eq!(callback(&app, &code, &state).await["code"], 200);
+
+    let resp = callback(&app, &code, &state).await;
+    assert_eq!(resp["code"], 400);
+    assert_eq!(resp["msg"], "登录状态无效或已过期，请重新登录");
+
+    let resp = callback(&app, &code, "unknown-state").await;
+    assert_eq!(resp["code"], 400);
+
+    // 已使用的授权码被身份提供方拒绝
+    let (_, state) = authorize(&app).await;
+    let resp = callback(&app, &code, &state).await;
+    assert_eq!(resp["code"], 400);
+    assert!(resp["msg"].as_str().unwrap().starts_with("身份提供方拒绝了授权码"), "{}", resp);
+}
+
+#[tokio::test]
+async fn state_is_bound_to_the_browser() {
+    let (idp, app) = setup(|_| {}).await;
+    let (url, state) = authorize(&app).await;
+    let code = idp.approve(&url, json!({}));
+    let uri = format!("/api/v1/auth/oidc/mock/callback?code={}&state={}", code, state);
+
+    // 攻击者诱导其他浏览器使用自己发起的登录完成回调
+    let resp = body(app.client.get(&uri).send().await).await;
+    assert_eq!(resp["code"], 400);
+    assert_eq!(resp["msg"], "登录状态与当前浏览器不匹配，请在同一浏览器中重新登录");
+
+    let (_, other) = authorize(&app).await;
+    let resp = app.client.get(&uri).header(header::COOKIE, state_cookie(&other)).send().await;
+    assert_eq!(body(resp).await["code"], 400);
+
+    // 回调成功后删除Cookie
+    let resp = app.client.get(&uri).header(header::COOKIE, state_cookie(&state)).send().await;
+    let removed = resp
+        .0
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .any(|value| value.to_str().unwrap().starts_with("oidc_state=;"));
+    assert!(removed);
+    assert_eq!(body(resp).await["code"], 200);
+}
+
+#[tokio::test]
+async fn provider_errors_are_reported() {
+    let (_idp, app) = setup(|_| {}).await;
+    let resp = app
+        .client
+        .get("/api/v1/auth/oidc/mock/callback?error=access_denied&error_description=denied")
+        .send()
+        .await;
+    let denied = body(resp).await;
+    assert_eq!(denied["code"], 400);
+    assert_eq!(denied["msg"], "身份提供方返回错误: access_denied（denied）");
+
+    let resp = app.client.get("/api/v1/auth/oidc/mock/callback?code=abc").send().await;
+    assert_eq!(body(resp).await["code"], 400);
+}
+
+#[tokio::test]
+async fn invalid_id_tokens_are_rejected() {
+    let (idp, app) = setup(|_| {}).await;
+    let now = Utc::now().timestamp();
+    let cases = [
+        (json!({ "nonce": "another-nonce" }), "ID令牌无效: nonce不匹配"),
+        (json!({ "aud": "another-client" }), "ID令牌无效: 受众不匹配"),
+        (json!({ "iss": "https://evil.example.com" }), "ID令牌无效: 签发方不匹配"),
+        (json!({ "exp": now - 3600 }), "ID令牌无效: 已过期"),
+    ];
+    for (claims, msg) in cases {
+        let resp = login(&idp, &app, claims).await;
+        assert_eq!(resp["code"], 400, "{}", resp);
+        assert_eq!(resp["msg"], msg);
+    }
+
+    let (url, state) = authorize(&app).await;
+    let code = idp.approve_forged(&url, json!({}));
+    let resp = callback(&app, &code, &state).await;
+    assert_eq!(resp["msg"], "ID令牌无效: 签名无效");
+
+    // 校验失败时不创建用户
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn rotated_signing_keys_are_fetched() {
+    let (idp, app) = setup(|_| {}).await;
+    assert_eq!(login(&idp, &app, json!({})).await["code"], 200);
+    assert_eq!(idp.jwks_requests(), 1);
+
+    idp.rotate_key();
+    let resp = login(&idp, &app, json!({})).await;
+    assert_eq!(resp["code"], 200, "{}", resp);
+    assert_eq!(idp.jwks_requests(), 2);
+}
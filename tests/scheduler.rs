@@ -0,0 +1,207 @@
+//! 定时任务测试
+
+mod common;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::{admin, body, TestApp};
+use serde_json::Value;
+use {{crate_name}}::config::ScheduleConfig;
+use {{crate_name}}::models::scheduler::TaskRunStatus;
+use {{crate_name}}::scheduler::{parse_cron, ScheduledTask, Scheduler};
+
+/// 记录执行次数，可指定执行失败或执行耗时
+#[derive(Clone, Default)]
+struct Counter {
+    runs: Arc<AtomicU32>,
+    fail: bool,
+    delay: Option<Duration>,
+}
+
+impl Counter {
+    fn runs(&self) -> u32 {
+        self.runs.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for Counter {
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        if self.fail {
+            anyhow::bail!("磁盘已满");
+        }
+        Ok(())
+    }
+}
+
+/// 只包含测试任务执行计划的应用，`cron` 为空时测试任务只能手动触发
+async fn test_app(cron: Option<&str>) -> TestApp {
+    TestApp::with_config(|config| {
+        config.scheduler.tasks.clear();
+        if let Some(cron) = cron {
+            config.scheduler.tasks.insert(
+                "counter".to_string(),
+                ScheduleConfig {
+                    cron: cron.to_string(),
+                    enabled: true,
+                },
+            );
+        }
+    })
+    .await
+}
+
+/// 查询定时任务状态
+async fn task(app: &TestApp, name: &str) -> Value {
+    let resp = app.client.get("/api/v1/scheduler/tasks").data(admin()).send().await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    body["data"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["name"] == name)
+        .cloned()
+        .unwrap_or_else(|| panic!("没有定时任务 {}", name))
+}
+
+#[tokio::test]
+async fn due_tasks_run_and_record_outcome() {
+    let app = test_app(Some("* * * * * *")).await;
+    let counter = Counter::default();
+    app.state.scheduler.register(counter.clone());
+
+    // 首次查询时写入执行计划
+    let status = task(&app, "counter").await;
+    assert_eq!(status["cron"], "* * * * * *");
+    assert!(status["next_run_at"].is_string());
+    assert!(status["last_status"].is_null());
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let runs = app.state.scheduler.run_due().await.unwrap();
+    assert_eq!(runs, [("counter".to_string(), TaskRunStatus::Succeeded)]);
+    assert_eq!(counter.runs(), 1);
+
+    let status = task(&app, "counter").await;
+    assert_eq!(status["last_status"], "succeeded");
+    assert_eq!(status["running"], false);
+    assert!(status["last_finished_at"].is_string());
+    assert!(status["last_duration_ms"].is_u64());
+}
+
+#[tokio::test]
+async fn failed_runs_record_error() {
+    let app = test_app(None).await;
+    app.state.scheduler.register(Counter {
+        fail: true,
+        ..Default::default()
+    });
+
+    let resp = app.client.post("/api/v1/scheduler/tasks/counter/run").data(admin()).send().await;
+    assert_eq!(body(resp).await["code"], 200);
+    let runs = app.state.scheduler.run_due().await.unwrap();
+    assert_eq!(runs, [("counter".to_string(), TaskRunStatus::Failed)]);
+
+    let status = task(&app, "counter").await;
+    assert_eq!(status["last_status"], "failed");
+    assert!(status["last_error"].as_str().unwrap().contains("磁盘已满"));
+}
+
+#[tokio::test]
+async fn unscheduled_tasks_only_run_when_triggered() {
+    let app = test_app(None).await;
+    let counter = Counter::default();
+    app.state.scheduler.register(counter.clone());
+
+    assert!(app.state.scheduler.run_due().await.unwrap().is_empty());
+    let status = task(&app, "counter").await;
+    assert!(status["cron"].is_null());
+    assert!(status["next_run_at"].is_null());
+
+    let resp = app.client.post("/api/v2/scheduler/tasks/counter/run").data(admin()).send().await;
+    assert!(body(resp).await["data"]["next_run_at"].is_string());
+    app.state.scheduler.run_due().await.unwrap();
+    assert_eq!(counter.runs(), 1);
+
+    // 执行后不会再次自动执行
+    assert!(task(&app, "counter").await["next_run_at"].is_null());
+    assert!(app.state.scheduler.run_due().await.unwrap().is_empty());
+
+    // 手动触发需要管理权限
+    let resp = app.client.post("/api/v1/scheduler/tasks/unknown/run").send().await;
+    resp.assert_status(poem::http::StatusCode::UNAUTHORIZED);
+
+    let resp = app.client.post("/api/v1/scheduler/tasks/unknown/run").data(admin()).send().await;
+    assert_eq!(body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn running_tasks_are_locked_across_instances() {
+    let app = test_app(None).await;
+    let counter = Counter {
+        delay: Some(Duration::from_millis(300)),
+        ..Default::default()
+    };
+    // 另一个实例：共用数据库的调度器
+    let other = Scheduler::new(app.state.pool.clone(), app.config.scheduler.clone()).unwrap();
+    app.state.scheduler.register(counter.clone());
+    other.register(counter.clone());
+
+    app.state.scheduler.trigger("counter").await.unwrap();
+    let (first, second) = tokio::join!(app.state.scheduler.run_due(), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // 执行期间：另一实例抢不到锁，也不能手动触发
+        let status = task(&app, "counter").await;
+        assert_eq!(status["running"], true);
+        assert!(status["locked_by"].is_string());
+        assert!(other.trigger("counter").await.is_err());
+        other.run_due().await
+    });
+
+    assert_eq!(first.unwrap().len(), 1);
+    assert!(second.unwrap().is_empty());
+    assert_eq!(counter.runs(), 1);
+    assert_eq!(task(&app, "counter").await["running"], false);
+}
+
+#[tokio::test]
+async fn retention_purge_is_registered_by_default() {
+    let app = TestApp::new().await;
+
+    let status = task(&app, "purge_deleted_users").await;
+    assert_eq!(status["cron"], "0 0 * * * *");
+
+    let resp = app.client.post("/api/v1/scheduler/tasks/purge_deleted_users/run").data(admin()).send().await;
+    assert_eq!(body(resp).await["code"], 200);
+    let runs = app.state.scheduler.run_due().await.unwrap();
+    assert_eq!(runs, [("purge_deleted_users".to_string(), TaskRunStatus::Succeeded)]);
+}
+
+#[tokio::test]
+async fn invalid_cron_is_rejected() {
+    assert!(parse_cron("0 3 * * *").is_ok());
+    assert!(parse_cron("0 0 3 * * Mon-Fri").is_ok());
+    assert!(parse_cron("every day").is_err());
+
+    let app = TestApp::new().await;
+    let mut config = app.config.scheduler.clone();
+    config.tasks.insert(
+        "counter".to_string(),
+        ScheduleConfig {
+            cron: "61 * * * *".to_string(),
+            enabled: true,
+        },
+    );
+    assert!(Scheduler::new(app.state.pool.clone(), config).is_err());
+}
@@ -0,0 +1,582 @@
+//! 浏览器会话测试
+
+mod common;
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use common::{body, test_actor, RequestExt, TestApp, UserFixture};
+use poem::http::{header, StatusCode};
+use poem::test::TestResponse;
+use serde_json::{json, Value};
+use {{crate_name}}::auth::totp::Totp;
+use {{crate_name}}::config::{CookieSameSite, SessionStoreKind};
+use {{crate_name}}::models::api_key::{ApiKeyScope, CreateApiKeyRequest};
+use {{crate_name}}::models::user::UpdateUserRequest;
+
+/// 登录后浏览器保存的Cookie
+struct Browser {
+    session: String,
+    csrf: String,
+}
+
+impl Browser {
+    /// 请求中携带的 `Cookie` 请求头
+    fn cookie(&self) -> String {
+        format!("sid={}; csrf_token={}", self.session, self.csrf)
+    }
+}
+
+/// 响应中的 `Set-Cookie`，键为Cookie名称，值为完整的响应头
+fn set_cookies(resp: &TestResponse) -> HashMap<String, String> {
+    resp.0
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| {
+            let value = value.to_str().unwrap().to_string();
+            let name = value.split('=').next().unwrap().to_string();
+            (name, value)
+        })
+        .collect()
+}
+
+/// `Set-Cookie` 响应头中的Cookie值
+fn cookie_value(set_cookie: &str) -> String {
+    set_cookie.split(';').next().unwrap().split_once('=').unwrap().1.to_string()
+}
+
+/// 创建用户，返回其登录信息
+async fn register(app: &TestApp, fixture: UserFixture) -> UserFixture {
+    let credentials = UserFixture {
+        username: fixture.username.clone(),
+        email: fixture.email.clone(),
+        password: fixture.password.clone(),
+        role: fixture.role,
+    };
+    app.create_user(fixture).await;
+    credentials
+}
+
+async fn try_login(app: &TestApp, payload: Value) -> (Value, Option<Browser>) {
+    let resp = app.client.post("/api/v1/auth/login").body_json(&payload).send().await;
+    let cookies = set_cookies(&resp);
+    let browser = match (cookies.get("sid"), cookies.get("csrf_token")) {
+        (Some(session), Some(csrf)) => Some(Browser {
+            session: cookie_value(session),
+            csrf: cookie_value(csrf),
+        }),
+        _ => None,
+    };
+    (body(resp).await, browser)
+}
+
+/// 使用测试用户的密码登录
+async fn login(app: &TestApp, fixture: &UserFixture) -> (Value, Browser) {
+    let (body, browser) = try_login(app, json!({ "username": fixture.username, "password": fixture.password })).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    (body, browser.expect("登录后应写入会话Cookie"))
+}
+
+async fn current(app: &TestApp, browser: &Browser) -> Value {
+    let resp = app
+        .client
+        .get("/api/v1/sessions/current")
+        .header(header::COOKIE, browser.cookie())
+        .send()
+        .await;
+    body(resp).await
+}
+
+#[tokio::test]
+async fn login_sets_cookies_and_authenticates_requests() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+
+    let resp = app
+        .client
+        .post("/api/v1/auth/login")
+        .header(header::USER_AGENT, "Mozilla/5.0 (X11; Linux x86_64)")
+        .body_json(&json!({ "username": fixture.username, "password": fixture.password }))
+        .send()
+        .await;
+    let cookies = set_cookies(&resp);
+    let login = body(resp).await;
+    assert_eq!(login["code"], 200, "{}", login);
+    assert_eq!(login["data"]["user"]["username"], fixture.username);
+    assert_eq!(login["data"]["session"]["current"], true);
+    assert_eq!(login["data"]["session"]["user_agent"], "Mozilla/5.0 (X11; Linux x86_64)");
+    assert_eq!(login["data"]["mfa_enrollment_required"], false);
+
+    // 会话Cookie禁止脚本读取，CSRF令牌Cookie需要被前端读取
+    let session_cookie = &cookies["sid"];
+    assert!(session_cookie.contains("HttpOnly"));
+    assert!(session_cookie.contains("Secure"));
+    assert!(session_cookie.contains("SameSite=Lax"));
+    assert!(session_cookie.contains("Path=/"));
+    assert!(session_cookie.contains("Max-Age=43200"));
+    let csrf_cookie = &cookies["csrf_token"];
+    assert!(!csrf_cookie.contains("HttpOnly"));
+    assert!(csrf_cookie.contains("Secure"));
+
+    let browser = Browser {
+        session: cookie_value(session_cookie),
+        csrf: cookie_value(csrf_cookie),
+    };
+    let session = current(&app, &browser).await;
+    assert_eq!(session["code"], 200, "{}", session);
+    assert_eq!(session["data"]["id"], login["data"]["session"]["id"]);
+
+    // 服务端只保存令牌摘要
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE token_hash = ?")
+        .bind(&browser.session)
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+
+    // 未携带会话Cookie时未登录
+    let resp = app.client.get("/api/v1/sessions/current").send().await;
+    assert_eq!(body(resp).await["code"], 401);
+}
+
+#[tokio::test]
+async fn login_accepts_email_and_rejects_wrong_password() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+
+    let (body, browser) = try_login(&app, json!({ "username": fixture.email, "password": fixture.password })).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    assert!(browser.is_some());
+
+    let (body, browser) = try_login(&app, json!({ "username": fixture.username, "password": "wrong-password" })).await;
+    assert_eq!(body["code"], 401);
+    assert_eq!(body["msg"], "用户名或密码错误");
+    assert!(browser.is_none());
+
+    let (body, _) = try_login(&app, json!({ "username": "nobody", "password": "password123" })).await;
+    assert_eq!(body["code"], 401);
+    assert_eq!(body["msg"], "用户名或密码错误");
+
+    // 已删除的用户不能登录
+    let user = app.state.users.verify_password(&fixture.username, &fixture.password).await.unwrap().unwrap();
+    app.state.users.delete(&test_actor(), user.id.unwrap(), None).await.unwrap();
+    let (body, _) = try_login(&app, json!({ "username": fixture.username, "password": fixture.password })).await;
+    assert_eq!(body["code"], 401);
+}
+
+#[tokio::test]
+async fn unsafe_requests_require_matching_csrf_token() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (_, browser) = login(&app, &fixture).await;
+
+    let logout = |cookie: String, csrf: Option<String>| {
+        let mut req = app.client.post("/api/v1/auth/logout").header(header::COOKIE, cookie);
+        if let Some(csrf) = csrf {
+            req = req.header("X-CSRF-Token", csrf);
+        }
+        req.send()
+    };
+
+    // 缺少请求头、请求头与Cookie不一致、Cookie中的令牌不属于该会话
+    let resp = logout(browser.cookie(), None).await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    let resp = logout(browser.cookie(), Some("forged".to_string())).await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    let resp = logout(format!("sid={}; csrf_token=forged", browser.session), Some("forged".to_string())).await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    let resp = logout(format!("sid={}", browser.session), Some(browser.csrf.clone())).await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(current(&app, &browser).await["code"], 200);
+
+    // 令牌一致时退出登录，并删除浏览器中的Cookie
+    let resp = logout(browser.cookie(), Some(browser.csrf.clone())).await;
+    resp.assert_status_is_ok();
+    let cookies = set_cookies(&resp);
+    assert!(cookies["sid"].starts_with("sid=;"));
+    assert!(cookies["sid"].contains("Max-Age=0"));
+    assert!(cookies["csrf_token"].contains("Max-Age=0"));
+    assert_eq!(body(resp).await["code"], 200);
+
+    // 已注销的会话按匿名调用方处理，同时删除浏览器中的Cookie
+    let resp = app
+        .client
+        .get("/api/v1/sessions/current")
+        .header(header::COOKIE, browser.cookie())
+        .send()
+        .await;
+    assert!(set_cookies(&resp)["sid"].contains("Max-Age=0"));
+    assert_eq!(body(resp).await["code"], 401);
+}
+
+#[tokio::test]
+async fn graphql_mutations_require_post() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (login, browser) = login(&app, &fixture).await;
+    let id = login["data"]["user"]["id"].as_u64().unwrap();
+
+    // GET请求不校验CSRF令牌，只携带会话Cookie的跨站GET不能执行变更
+    let mutation = format!("mutation {{ updateUser(id: {id}, input: {{ password: \"taken-over\" }}) {{ id }} }}");
+    let resp = app
+        .client
+        .get("/graphql/query")
+        .query("query", &mutation)
+        .header(header::COOKIE, browser.cookie())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    resp.assert_header(header::ALLOW, "POST");
+    let resp = body(resp).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "METHOD_NOT_ALLOWED");
+    assert!(app.state.users.verify_password(&fixture.username, "taken-over").await.unwrap().is_none());
+
+    // GET请求仍可执行查询
+    let resp = app
+        .client
+        .get("/graphql/query")
+        .query("query", &format!("{{ user(id: {id}) {{ id }} }}"))
+        .header(header::COOKIE, browser.cookie())
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    assert_eq!(body(resp).await["data"]["user"]["id"], id);
+}
+
+#[tokio::test]
+async fn sessions_are_listed_and_revoked_per_user() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (first, laptop) = login(&app, &fixture).await;
+    let (second, phone) = login(&app, &fixture).await;
+    let (_, tablet) = login(&app, &fixture).await;
+
+    let other = register(&app, UserFixture::new()).await;
+    let (foreign, _) = login(&app, &other).await;
+
+    let resp = app
+        .client
+        .get("/api/v2/sessions")
+        .header(header::COOKIE, laptop.cookie())
+        .send()
+        .await;
+    let list = body(resp).await;
+    let items = list["data"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    let current_items: Vec<_> = items.iter().filter(|item| item["current"] == true).collect();
+    assert_eq!(current_items.len(), 1);
+    assert_eq!(current_items[0]["id"], first["data"]["session"]["id"]);
+
+    let revoke = |id: &Value| {
+        app.client
+            .delete(format!("/api/v2/sessions/{}", id.as_str().unwrap()))
+            .header(header::COOKIE, laptop.cookie())
+            .header("X-CSRF-Token", &laptop.csrf)
+            .send()
+    };
+
+    // 不能注销其他用户的会话
+    let resp = revoke(&foreign["data"]["session"]["id"]).await;
+    assert_eq!(body(resp).await["code"], 404);
+
+    let resp = revoke(&second["data"]["session"]["id"]).await;
+    let revoked = body(resp).await;
+    assert_eq!(revoked["code"], 200, "{}", revoked);
+    assert_eq!(revoked["data"]["current"], false);
+    assert_eq!(current(&app, &phone).await["code"], 401);
+
+    let resp = revoke(&second["data"]["session"]["id"]).await;
+    assert_eq!(body(resp).await["code"], 404);
+
+    // 注销其他会话后只剩当前会话
+    let resp = app
+        .client
+        .delete("/api/v2/sessions")
+        .header(header::COOKIE, laptop.cookie())
+        .header("X-CSRF-Token", &laptop.csrf)
+        .send()
+        .await;
+    assert_eq!(body(resp).await["data"]["count"], 1);
+    assert_eq!(current(&app, &tablet).await["code"], 401);
+    assert_eq!(current(&app, &laptop).await["code"], 200);
+
+    // 注销当前会话时同时删除Cookie
+    let resp = revoke(&first["data"]["session"]["id"]).await;
+    assert!(set_cookies(&resp)["sid"].contains("Max-Age=0"));
+    assert_eq!(body(resp).await["data"]["current"], true);
+    assert_eq!(current(&app, &laptop).await["code"], 401);
+
+    // 登录及注销都记录审计事件，注销的操作者为会话所属的用户
+    let resp = app
+        .client
+        .get("/api/v1/audit?entity_type=session&page_size=100")
+        .api_key(&app.admin_key().await)
+        .send()
+        .await;
+    let events = body(resp).await;
+    let events = events["data"]["items"].as_array().unwrap();
+    assert_eq!(events.iter().filter(|event| event["action"] == "create").count(), 4);
+    let deletes: Vec<_> = events.iter().filter(|event| event["action"] == "delete").collect();
+    assert_eq!(deletes.len(), 3);
+    assert!(deletes.iter().all(|event| event["actor_type"] == "user" && event["actor_name"] == fixture.username));
+}
+
+#[tokio::test]
+async fn sessions_expire_after_idle_and_absolute_timeouts() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (idle, idle_browser) = login(&app, &fixture).await;
+    let (expired, expired_browser) = login(&app, &fixture).await;
+    let (_, active_browser) = login(&app, &fixture).await;
+
+    let now = Utc::now().timestamp();
+    sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE id = ?")
+        .bind(now - app.config.sessions.idle_timeout_secs as i64)
+        .bind(idle["data"]["session"]["id"].as_str().unwrap())
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE sessions SET expires_at = ? WHERE id = ?")
+        .bind(now)
+        .bind(expired["data"]["session"]["id"].as_str().unwrap())
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+
+    // 过期会话不出现在列表中，使用时被删除
+    let resp = app
+        .client
+        .get("/api/v1/sessions")
+        .header(header::COOKIE, active_browser.cookie())
+        .send()
+        .await;
+    assert_eq!(body(resp).await["data"]["items"].as_array().unwrap().len(), 1);
+    assert_eq!(current(&app, &idle_browser).await["code"], 401);
+    assert_eq!(app.state.sessions.purge_expired().await.unwrap(), 1);
+    assert_eq!(current(&app, &expired_browser).await["code"], 401);
+    assert_eq!(current(&app, &active_browser).await["code"], 200);
+}
+
+#[tokio::test]
+async fn activity_extends_idle_timeout() {
+    let app = TestApp::with_config(|config| config.sessions.touch_interval_secs = 0).await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (login, browser) = login(&app, &fixture).await;
+    let id = login["data"]["session"]["id"].as_str().unwrap().to_string();
+
+    let almost_idle = Utc::now().timestamp() - app.config.sessions.idle_timeout_secs as i64 + 60;
+    sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE id = ?")
+        .bind(almost_idle)
+        .bind(&id)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+
+    let session = current(&app, &browser).await;
+    assert_eq!(session["code"], 200);
+    let last_seen_at: i64 = sqlx::query_scalar("SELECT last_seen_at FROM sessions WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap();
+    assert!(last_seen_at > almost_idle);
+    assert!(session["data"]["idle_expires_at"].as_str().unwrap() <= session["data"]["expires_at"].as_str().unwrap());
+}
+
+#[tokio::test]
+async fn mfa_is_enforced_on_login() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let user = app.state.users.verify_password(&fixture.username, &fixture.password).await.unwrap().unwrap();
+    let id = user.id.unwrap();
+    let secret = app.state.mfa.enroll(&test_actor().actor, id).await.unwrap().secret;
+    let totp = Totp::from_base32(&secret).unwrap();
+    let step = Totp::step(Utc::now().timestamp());
+    app.state.mfa.enable(&test_actor(), id, &totp.code_at(step)).await.unwrap();
+
+    let credentials = json!({ "username": fixture.username, "password": fixture.password });
+    let (body, browser) = try_login(&app, credentials.clone()).await;
+    assert_eq!(body["code"], 401);
+    assert_eq!(body["msg"], "需要两步验证码");
+    assert!(browser.is_none());
+
+    let mut payload = credentials.clone();
+    payload["mfa_code"] = json!("000000x");
+    let (body, _) = try_login(&app, payload).await;
+    assert_eq!(body["code"], 400);
+
+    let mut payload = credentials;
+    payload["mfa_code"] = json!(totp.code_at(step + 1));
+    let (body, browser) = try_login(&app, payload).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    assert!(browser.is_some());
+
+    // 角色要求两步验证但尚未启用时提示绑定，完成绑定前会话只能访问本人的两步验证接口
+    let admin = register(&app, UserFixture::new().admin()).await;
+    let (login, browser) = login(&app, &admin).await;
+    assert_eq!(login["data"]["mfa_enrollment_required"], true);
+    let admin_id = login["data"]["user"]["id"].as_u64().unwrap();
+    let get = |path: String| app.client.get(path).header(header::COOKIE, browser.cookie()).send();
+    let post = |path: String, payload: Value| {
+        app.client
+            .post(path)
+            .header(header::COOKIE, browser.cookie())
+            .header("X-CSRF-Token", browser.csrf.clone())
+            .body_json(&payload)
+            .send()
+    };
+
+    for path in [format!("/api/v1/users/{}", admin_id), "/api/v2/users".to_string(), format!("/api/v1/users/{}/mfa", id)] {
+        let resp = get(path).await;
+        resp.assert_status(StatusCode::FORBIDDEN);
+        assert_eq!(common::body(resp).await["msg"], "该账户的角色要求启用两步验证，请先完成绑定");
+    }
+    let resp = post("/graphql/query".to_string(), json!({ "query": "{ users { id } }" })).await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(current(&app, &browser).await["code"], 200);
+
+    let resp = post(format!("/api/v1/users/{}/mfa/enroll", admin_id), json!({})).await;
+    let secret = common::body(resp).await["data"]["secret"].as_str().unwrap().to_string();
+    let code = Totp::from_base32(&secret).unwrap().code_at(step);
+    let resp = post(format!("/api/v1/users/{}/mfa/enable", admin_id), json!({ "code": code })).await;
+    assert_eq!(common::body(resp).await["code"], 200);
+
+    // 完成绑定后同一会话不再受限
+    let resp = get(format!("/api/v1/users/{}", admin_id)).await;
+    resp.assert_status_is_ok();
+    assert_eq!(common::body(resp).await["data"]["id"], admin_id);
+}
+
+#[tokio::test]
+async fn password_change_ends_existing_sessions() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (body, browser) = login(&app, &fixture).await;
+    let id = body["data"]["user"]["id"].as_u64().unwrap();
+
+    // 只修改邮箱时会话保持有效
+    let req = UpdateUserRequest {
+        email: Some("changed@example.com".to_string()),
+        password: None,
+    };
+    app.state.users.update(&test_actor(), id, req, None).await.unwrap();
+    assert_eq!(current(&app, &browser).await["code"], 200);
+
+    let req = UpdateUserRequest {
+        email: None,
+        password: Some("new-password".to_string()),
+    };
+    app.state.users.update(&test_actor(), id, req, None).await.unwrap();
+    assert_eq!(current(&app, &browser).await["code"], 401);
+}
+
+#[tokio::test]
+async fn memory_store_keeps_sessions_out_of_database() {
+    let app = TestApp::with_config(|config| config.sessions.store = SessionStoreKind::Memory).await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (_, first) = login(&app, &fixture).await;
+    let (second, _) = login(&app, &fixture).await;
+
+    let resp = app
+        .client
+        .get("/api/v1/sessions")
+        .header(header::COOKIE, first.cookie())
+        .send()
+        .await;
+    assert_eq!(body(resp).await["data"]["items"].as_array().unwrap().len(), 2);
+
+    let resp = app
+        .client
+        .delete(format!("/api/v1/sessions/{}", second["data"]["session"]["id"].as_str().unwrap()))
+        .header(header::COOKIE, first.cookie())
+        .header("X-CSRF-Token", &first.csrf)
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 200);
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn cookie_attributes_follow_config() {
+    let app = TestApp::with_config(|config| {
+        config.sessions.cookie_name = "admin_sid".to_string();
+        config.sessions.secure = false;
+        config.sessions.same_site = CookieSameSite::Strict;
+        config.sessions.domain = Some("example.com".to_string());
+        config.sessions.path = "/api".to_string();
+    })
+    .await;
+    let fixture = register(&app, UserFixture::new()).await;
+
+    let resp = app
+        .client
+        .post("/api/v1/auth/login")
+        .body_json(&json!({ "username": fixture.username, "password": fixture.password }))
+        .send()
+        .await;
+    let cookies = set_cookies(&resp);
+    let session_cookie = &cookies["admin_sid"];
+    assert!(!session_cookie.contains("Secure"));
+    assert!(session_cookie.contains("SameSite=Strict"));
+    assert!(session_cookie.contains("Domain=example.com"));
+    assert!(session_cookie.contains("Path=/api"));
+
+    let resp = app
+        .client
+        .get("/api/v1/sessions/current")
+        .header(header::COOKIE, format!("admin_sid={}", cookie_value(session_cookie)))
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 200);
+}
+
+#[tokio::test]
+async fn api_key_requests_ignore_session_cookie() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (_, browser) = login(&app, &fixture).await;
+    let created = app
+        .state
+        .api_keys
+        .create(
+            &test_actor(),
+            CreateApiKeyRequest {
+                name: "ci".to_string(),
+                scopes: vec![ApiKeyScope::Write],
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    // 通过API密钥认证时不检查CSRF令牌，也不使用会话
+    let resp = app
+        .client
+        .post("/api/v1/auth/logout")
+        .header(header::COOKIE, browser.cookie())
+        .api_key(&created.key)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    assert_eq!(current(&app, &browser).await["code"], 200);
+}
+
+#[tokio::test]
+async fn disabled_sessions_reject_login_and_ignore_cookies() {
+    let app = TestApp::new().await;
+    let fixture = register(&app, UserFixture::new()).await;
+    let (_, browser) = login(&app, &fixture).await;
+
+    let disabled = TestApp::with_config(|config| config.sessions.enabled = false).await;
+    register(&disabled, UserFixture::new().username(&fixture.username)).await;
+    let (body, cookies) = try_login(&disabled, json!({ "username": fixture.username, "password": fixture.password })).await;
+    assert_eq!(body["code"], 403);
+    assert!(cookies.is_none());
+    assert_eq!(current(&disabled, &browser).await["code"], 401);
+}
@@ -0,0 +1,132 @@
+//! TLS相关测试
+//!
+//! 测试中使用 rcgen 动态生成自签名证书，不依赖仓库中的证书文件
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use common::free_port;
+use futures::StreamExt;
+use poem::{
+    get, handler,
+    listener::{Listener, TcpListener},
+    EndpointExt, Route, Server,
+};
+use {{crate_name}}::config::TlsConfig;
+use {{crate_name}}::{middlewares, tls};
+
+/// 在临时目录中生成自签名证书，返回对应的TLS配置
+fn self_signed_config(name: &str) -> TlsConfig {
+    let dir = std::env::temp_dir().join(format!("tls-test-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    write_self_signed(&dir);
+
+    TlsConfig {
+        enabled: true,
+        cert_path: dir.join("server.crt").to_string_lossy().into_owned(),
+        key_path: dir.join("server.key").to_string_lossy().into_owned(),
+        reload_interval_secs: 1,
+        ..TlsConfig::default()
+    }
+}
+
+/// 写入（或覆盖）自签名证书及私钥
+fn write_self_signed(dir: &Path) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    std::fs::write(dir.join("server.crt"), certified.cert.pem()).unwrap();
+    std::fs::write(dir.join("server.key"), certified.key_pair.serialize_pem()).unwrap();
+}
+
+#[handler]
+fn hello() -> &'static str {
+    "hello"
+}
+
+#[test]
+fn load_rustls_config_reports_missing_files() {
+    let config = TlsConfig {
+        cert_path: "/nonexistent/server.crt".to_string(),
+        ..TlsConfig::default()
+    };
+
+    let err = tls::load_rustls_config(&config).err().expect("缺失证书时应返回错误");
+    assert!(err.to_string().contains("/nonexistent/server.crt"));
+}
+
+#[tokio::test]
+async fn config_stream_reloads_when_certificate_changes() {
+    let config = self_signed_config("reload");
+    let dir = PathBuf::from(&config.cert_path).parent().unwrap().to_path_buf();
+
+    let mut stream = tls::rustls_config_stream(config).unwrap();
+    assert!(stream.next().await.is_some(), "应立即产出初始配置");
+
+    write_self_signed(&dir);
+    let reloaded = tokio::time::timeout(Duration::from_secs(5), stream.next()).await;
+    assert!(matches!(reloaded, Ok(Some(_))), "证书变更后应产出新配置");
+}
+
+#[tokio::test]
+async fn serves_https_with_hsts_and_redirects_plain_http() {
+    let config = self_signed_config("serve");
+    let https_port = free_port();
+    let http_port = free_port();
+
+    let app = Route::new()
+        .at("/hello", get(hello))
+        .with(middlewares::hsts(&config))
+        .with(middlewares::HttpsRedirect::new(https_port));
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", https_port))
+        .rustls(tls::rustls_config_stream(config).unwrap())
+        .combine(TcpListener::bind(format!("127.0.0.1:{}", http_port)));
+    let server = tokio::spawn(Server::new(listener).run(app));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    // HTTPS请求：通过ALPN协商为HTTP/2，并带有HSTS响应头
+    let resp = client
+        .get(format!("https://localhost:{}/hello", https_port))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.version(), reqwest::Version::HTTP_2);
+    assert_eq!(
+        resp.headers()["strict-transport-security"],
+        "max-age=31536000; includeSubDomains"
+    );
+
+    // 明文HTTP请求：308重定向到HTTPS端口
+    let resp = client
+        .get(format!("http://localhost:{}/hello?a=1", http_port))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 308);
+    assert_eq!(
+        resp.headers()["location"],
+        format!("https://localhost:{}/hello?a=1", https_port).as_str()
+    );
+
+    // 不带端口的IPv6地址不能按最后一个冒号截断
+    let resp = client
+        .get(format!("http://127.0.0.1:{}/hello", http_port))
+        .header(reqwest::header::HOST, "[::1]")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 308);
+    assert_eq!(
+        resp.headers()["location"],
+        format!("https://[::1]:{}/hello", https_port).as_str()
+    );
+
+    server.abort();
+}
@@ -0,0 +1,799 @@
+//! 用户REST接口测试
+
+mod common;
+
+use common::{body, test_actor, RequestExt, TestApp, UserFixture};
+use poem::http::StatusCode;
+use serde_json::json;
+use {{crate_name}}::cli::CreateAdminArgs;
+use {{crate_name}}::services::UserFilter;
+
+#[tokio::test]
+async fn create_user() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+
+    let resp = app.client.post("/api/v1/users").body_json(&fixture.json()).send().await;
+    resp.assert_status_is_ok();
+
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200);
+    assert_eq!(body["data"]["username"], fixture.username);
+    assert_eq!(body["data"]["email"], fixture.email);
+    assert_eq!(body["data"]["role"], "user");
+    // 响应中不包含密码
+    assert!(body["data"].get("password").is_none());
+}
+
+#[tokio::test]
+async fn create_user_rejects_duplicate() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+    app.create_user(UserFixture::new().username(&fixture.username)).await;
+
+    let resp = app.client.post("/api/v1/users").body_json(&fixture.json()).send().await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 409);
+}
+
+#[tokio::test]
+async fn create_user_validates_body() {
+    let app = TestApp::new().await;
+
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .body_json(&json!({ "username": "ab", "email": "invalid", "password": "123" }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn create_admin_args_follow_create_user_rules() {
+    let args = |username: &str, email: &str, password: &str| CreateAdminArgs {
+        username: username.to_string(),
+        email: email.to_string(),
+        password: password.to_string(),
+        force: false,
+    };
+
+    assert!(args("admin", "admin@example.com", "password123").to_request().is_ok());
+    for invalid in [
+        args("ab", "admin@example.com", "password123"),
+        args("admin", "invalid", "password123"),
+        args("admin", "admin@example.com", "123"),
+    ] {
+        assert!(invalid.to_request().is_err(), "{:?}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn get_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let key = app.admin_key().await;
+
+    let resp = app.client.get(format!("/api/v1/users/{}", user.id.unwrap())).api_key(&key).send().await;
+    resp.assert_status_is_ok();
+
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200);
+    assert_eq!(body["data"]["id"], user.id.unwrap());
+    assert_eq!(body["data"]["username"], user.username);
+}
+
+#[tokio::test]
+async fn get_missing_user() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+
+    let body = body(app.client.get("/api/v1/users/404").api_key(&key).send().await).await;
+    assert_eq!(body["code"], 404);
+    assert!(body["data"].is_null());
+}
+
+#[tokio::test]
+async fn update_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .put(format!("/api/v1/users/{}", user.id.unwrap()))
+        .api_key(&key)
+        .body_json(&json!({ "email": "updated@example.com" }))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200);
+    assert_eq!(body["data"]["email"], "updated@example.com");
+    assert_eq!(body["data"]["username"], user.username);
+
+    let stored = app.state.users.get(user.id.unwrap()).await.unwrap();
+    assert_eq!(stored.email, "updated@example.com");
+}
+
+#[tokio::test]
+async fn update_missing_user() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .put("/api/v1/users/404")
+        .api_key(&key)
+        .body_json(&json!({ "email": "updated@example.com" }))
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn delete_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/v1/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+
+    let body = body(app.client.delete(&uri).api_key(&key).send().await).await;
+    assert_eq!(body["code"], 200);
+
+    let resp = app.client.get(&uri).api_key(&key).send().await;
+    assert_eq!(common::body(resp).await["code"], 404);
+
+    // 软删除后不能再次删除或修改
+    let resp = app.client.delete(&uri).api_key(&key).send().await;
+    assert_eq!(common::body(resp).await["code"], 404);
+    let resp = app.client.put(&uri).api_key(&key).body_json(&json!({ "email": "updated@example.com" })).send().await;
+    assert_eq!(common::body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn restore_deleted_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+
+    // 未删除的用户不能恢复
+    let resp = app.client.post(format!("{}/restore", uri)).api_key(&key).send().await;
+    assert_eq!(body(resp).await["code"], 409);
+
+    app.client.delete(&uri).api_key(&key).send().await.assert_status_is_ok();
+
+    let resp = app
+        .client
+        .post(format!("{}/restore", uri))
+        .api_key(&key)
+        .header("if-match", "\"2\"")
+        .send()
+        .await;
+    resp.assert_header("etag", "\"3\"");
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200);
+    assert!(body["data"]["deleted_at"].is_null());
+
+    let resp = app.client.get(&uri).api_key(&key).send().await;
+    assert_eq!(common::body(resp).await["data"]["username"], user.username);
+
+    let resp = app.client.post("/api/users/404/restore").api_key(&key).send().await;
+    assert_eq!(common::body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn list_users_excludes_deleted_by_default() {
+    let app = TestApp::new().await;
+    let users = app.create_users(3).await;
+    app.state.users.delete(&test_actor(), users[0].id.unwrap(), None).await.unwrap();
+    let key = app.admin_key().await;
+
+    let resp = app.client.get("/api/v2/users").api_key(&key).send().await;
+    let body = body(resp).await;
+    assert_eq!(body["data"]["total"], 2);
+
+    let resp = app.client.get("/api/v2/users").api_key(&key).query("include_deleted", &true).send().await;
+    let body = common::body(resp).await;
+    assert_eq!(body["data"]["total"], 3);
+    assert!(body["data"]["items"][0]["deleted_at"].is_string());
+
+    // v1 的弃用接口始终排除已删除的用户
+    let resp = app.client.get("/api/v1/users").api_key(&key).send().await;
+    assert_eq!(common::body(resp).await["data"]["total"], 2);
+}
+
+#[tokio::test]
+async fn purge_removes_users_deleted_before_retention() {
+    let app = TestApp::new().await;
+    let users = app.create_users(2).await;
+    app.state.users.delete(&test_actor(), users[0].id.unwrap(), None).await.unwrap();
+
+    // 保留期内不清除
+    let purged = app
+        .state
+        .users
+        .purge_deleted(&test_actor(), chrono::Utc::now() - chrono::Duration::days(1))
+        .await
+        .unwrap();
+    assert_eq!(purged, 0);
+
+    let purged = app
+        .state
+        .users
+        .purge_deleted(&test_actor(), chrono::Utc::now() + chrono::Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    let key = app.admin_key().await;
+    let resp = app.client.post(format!("/api/users/{}/restore", users[0].id.unwrap())).api_key(&key).send().await;
+    assert_eq!(body(resp).await["code"], 404);
+    let (_, total) = app
+        .state
+        .users
+        .list(&test_actor().actor, &UserFilter { include_deleted: true, ..Default::default() }, 1, 10)
+        .await
+        .unwrap();
+    assert_eq!(total, 1);
+}
+
+#[tokio::test]
+async fn list_users_v1_is_deprecated() {
+    let app = TestApp::new().await;
+    app.create_users(3).await;
+    let key = app.admin_key().await;
+
+    let resp = app.client.get("/api/v1/users").api_key(&key).query("page_size", &2).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_header_exist("deprecation");
+    resp.assert_header_exist("sunset");
+
+    let body = body(resp).await;
+    assert_eq!(body["data"]["total"], 3);
+    assert_eq!(body["data"]["users"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn list_users_v1_filters_by_username() {
+    let app = TestApp::new().await;
+    app.create_users(2).await;
+    app.create_user(UserFixture::new().username("alice")).await;
+    let key = app.admin_key().await;
+
+    let resp = app.client.get("/api/v1/users").api_key(&key).query("username", &"lic").send().await;
+    let body = body(resp).await;
+    assert_eq!(body["data"]["total"], 1);
+    assert_eq!(body["data"]["users"][0]["username"], "alice");
+}
+
+#[tokio::test]
+async fn list_users_v2_paginates() {
+    let app = TestApp::new().await;
+    app.create_users(5).await;
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .get("/api/v2/users")
+        .api_key(&key)
+        .query("page", &2)
+        .query("page_size", &2)
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_header("api-version", "v2");
+
+    let body = body(resp).await;
+    assert_eq!(body["data"]["total"], 5);
+    assert_eq!(body["data"]["total_pages"], 3);
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn list_users_v2_validates_page_size() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+
+    let resp = app.client.get("/api/v2/users").api_key(&key).query("page_size", &1000).send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn merge_patch_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .patch(format!("/api/users/{}", user.id.unwrap()))
+        .api_key(&key)
+        .content_type("application/merge-patch+json")
+        .body(json!({ "email": "patched@example.com" }).to_string())
+        .send()
+        .await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200);
+    assert_eq!(body["data"]["email"], "patched@example.com");
+    assert_eq!(body["data"]["username"], user.username);
+}
+
+#[tokio::test]
+async fn merge_patch_distinguishes_null_from_absent() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/v2/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+
+    // 显式置空必填字段会被拒绝，而不是像PUT那样被当作未提供
+    let resp = app
+        .client
+        .patch(&uri)
+        .api_key(&key)
+        .content_type("application/merge-patch+json")
+        .body(json!({ "email": null }).to_string())
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 400);
+
+    // 与更新接口使用相同的字段规则
+    let resp = app
+        .client
+        .patch(&uri)
+        .api_key(&key)
+        .content_type("application/merge-patch+json")
+        .body(json!({ "password": "123" }).to_string())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    let stored = app.state.users.get(user.id.unwrap()).await.unwrap();
+    assert_eq!(stored.email, user.email);
+}
+
+#[tokio::test]
+async fn json_patch_user() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/v2/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+    let patch = |ops: serde_json::Value| {
+        app.client
+            .patch(&uri)
+            .api_key(&key)
+            .content_type("application/json-patch+json")
+            .body(ops.to_string())
+            .send()
+    };
+
+    let resp = patch(json!([
+        { "op": "test", "path": "/email", "value": user.email },
+        { "op": "replace", "path": "/email", "value": "patched@example.com" },
+        { "op": "add", "path": "/password", "value": "new-password" }
+    ]))
+    .await;
+    let body = common::body(resp).await;
+    assert_eq!(body["code"], 200);
+    assert_eq!(body["data"]["email"], "patched@example.com");
+
+    // test 失败时整个补丁不生效
+    let resp = patch(json!([
+        { "op": "test", "path": "/email", "value": user.email },
+        { "op": "replace", "path": "/email", "value": "other@example.com" }
+    ]))
+    .await;
+    assert_eq!(common::body(resp).await["code"], 400);
+
+    // 只读字段不可修改
+    let resp = patch(json!([{ "op": "replace", "path": "/username", "value": "renamed" }])).await;
+    assert_eq!(common::body(resp).await["code"], 400);
+
+    // 修改后的值同样需要通过字段校验
+    let resp = patch(json!([{ "op": "replace", "path": "/email", "value": "invalid" }])).await;
+    assert_eq!(common::body(resp).await["code"], 400);
+
+    let stored = app.state.users.get(user.id.unwrap()).await.unwrap();
+    assert_eq!(stored.email, "patched@example.com");
+    assert_eq!(stored.username, user.username);
+}
+
+#[tokio::test]
+async fn get_user_supports_etag() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+
+    let resp = app.client.get(&uri).api_key(&key).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_header("etag", "\"1\"");
+
+    let resp = app.client.get(&uri).api_key(&key).header("if-none-match", "\"1\"").send().await;
+    resp.assert_status(StatusCode::NOT_MODIFIED);
+    resp.assert_header("etag", "\"1\"");
+
+    // 版本变化后重新返回完整响应
+    app.state.users.patch(&test_actor(), user.id.unwrap(), Default::default(), None).await.unwrap();
+    let resp = app.client.get(&uri).api_key(&key).header("if-none-match", "\"1\"").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_header("etag", "\"2\"");
+}
+
+#[tokio::test]
+async fn if_match_prevents_lost_updates() {
+    let app = TestApp::new().await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .put(&uri)
+        .api_key(&key)
+        .header("if-match", "\"1\"")
+        .body_json(&json!({ "email": "first@example.com" }))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_header("etag", "\"2\"");
+
+    // 基于旧版本的修改被拒绝
+    let resp = app
+        .client
+        .patch(&uri)
+        .api_key(&key)
+        .header("if-match", "\"1\"")
+        .content_type("application/merge-patch+json")
+        .body(json!({ "email": "second@example.com" }).to_string())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::PRECONDITION_FAILED);
+    assert_eq!(body(resp).await["code"], 412);
+
+    let resp = app.client.delete(&uri).api_key(&key).header("if-match", "\"1\"").send().await;
+    resp.assert_status(StatusCode::PRECONDITION_FAILED);
+
+    let stored = app.state.users.get(user.id.unwrap()).await.unwrap();
+    assert_eq!(stored.email, "first@example.com");
+    assert_eq!(stored.version, Some(2));
+
+    let resp = app.client.delete(&uri).api_key(&key).header("if-match", "\"2\"").send().await;
+    assert_eq!(body(resp).await["code"], 200);
+}
+
+#[tokio::test]
+async fn if_match_can_be_required() {
+    let app = TestApp::with_config(|config| config.api.require_if_match = true).await;
+    let user = app.create_user(UserFixture::new()).await;
+    let uri = format!("/api/users/{}", user.id.unwrap());
+    let key = app.admin_key().await;
+
+    let resp = app.client.put(&uri).api_key(&key).body_json(&json!({ "email": "updated@example.com" })).send().await;
+    resp.assert_status(StatusCode::PRECONDITION_REQUIRED);
+    assert_eq!(body(resp).await["code"], 428);
+
+    let resp = app
+        .client
+        .put(&uri)
+        .api_key(&key)
+        .header("if-match", "*")
+        .body_json(&json!({ "email": "updated@example.com" }))
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 200);
+}
+
+#[tokio::test]
+async fn graphql_expected_version_can_be_required() {
+    let app = TestApp::with_config(|config| config.api.require_if_match = true).await;
+    let id = app.create_user(UserFixture::new()).await.id.unwrap();
+    let key = app.admin_key().await;
+    let update = "mutation($id: Int!, $version: Int) { \
+        updateUser(id: $id, input: { email: \"updated@example.com\" }, expectedVersion: $version) { version } }";
+
+    let resp = app.graphql_as(&key, update, json!({ "id": id })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "PRECONDITION_REQUIRED");
+    let resp = app.graphql_as(&key, "mutation($id: Int!) { deleteUser(id: $id) }", json!({ "id": id })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "PRECONDITION_REQUIRED");
+
+    let resp = app.graphql_as(&key, update, json!({ "id": id, "version": 2 })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "PRECONDITION_FAILED");
+    let resp = app.graphql_as(&key, update, json!({ "id": id, "version": 1 })).await;
+    assert_eq!(resp["data"]["updateUser"]["version"], 2);
+}
+
+#[tokio::test]
+async fn users_can_only_manage_their_own_account() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(UserFixture::new().admin()).await;
+    let fixture = UserFixture::new();
+    let (username, password) = (fixture.username.clone(), fixture.password.clone());
+    let own = format!("/api/v1/users/{}", app.create_user(fixture).await.id.unwrap());
+    let other = format!("/api/v2/users/{}", admin.id.unwrap());
+    let session = app.login(&username, &password, None).await;
+    let update = json!({ "password": "taken-over" });
+
+    // 匿名调用方不能读取、修改或接管任何账户
+    let resp = app.client.put(&other).body_json(&update).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(body(resp).await["code"], 401);
+    app.client.get(&other).send().await.assert_status(StatusCode::UNAUTHORIZED);
+    app.client.delete(&own).send().await.assert_status(StatusCode::UNAUTHORIZED);
+    for list in ["/api/v1/users", "/api/v2/users"] {
+        app.client.get(list).send().await.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    // 普通用户不能操作其他用户，也不能查询用户列表、删除或恢复用户
+    let resp = app.client.put(&other).session(&session).body_json(&update).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    app.client.get(&other).session(&session).send().await.assert_status(StatusCode::FORBIDDEN);
+    let resp = app
+        .client
+        .patch(&other)
+        .session(&session)
+        .content_type("application/merge-patch+json")
+        .body(update.to_string())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    app.client.get("/api/v2/users").session(&session).send().await.assert_status(StatusCode::FORBIDDEN);
+    app.client.delete(&own).session(&session).send().await.assert_status(StatusCode::FORBIDDEN);
+    let resp = app.client.post(format!("{}/restore", own)).session(&session).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert!(app.state.users.verify_password(&admin.username, "password123").await.unwrap().is_some());
+
+    // 可以查看及修改自己的账户
+    let resp = app.client.get(&own).session(&session).send().await;
+    assert_eq!(body(resp).await["data"]["username"], username);
+    let resp = app
+        .client
+        .put(&own)
+        .session(&session)
+        .body_json(&json!({ "email": "self@example.com" }))
+        .send()
+        .await;
+    assert_eq!(body(resp).await["data"]["email"], "self@example.com");
+}
+
+#[tokio::test]
+async fn graphql_users_require_owner_or_admin() {
+    let app = TestApp::new().await;
+    let admin = app.create_user(UserFixture::new().admin()).await.id.unwrap();
+    let fixture = UserFixture::new();
+    let (username, password) = (fixture.username.clone(), fixture.password.clone());
+    let own = app.create_user(fixture).await.id.unwrap();
+    let session = app.login(&username, &password, None).await;
+    let as_user = |query: &'static str, variables: serde_json::Value| async {
+        body(app.graphql_request(query, variables).session(&session).send().await).await
+    };
+    let update = "mutation($id: Int!) { updateUser(id: $id, input: { password: \"taken-over\" }) { id } }";
+
+    // 匿名调用方不能列出用户或修改账户
+    let resp = app.graphql("{ users { email } }", json!({})).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "UNAUTHORIZED");
+    let resp = app.graphql("{ searchUsers(nameContains: \"user\") { email } }", json!({})).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "UNAUTHORIZED");
+    let resp = app.graphql(update, json!({ "id": admin })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "UNAUTHORIZED");
+
+    // 普通用户只能查看及修改自己的账户
+    let resp = as_user("{ users { email } }", json!({})).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    let resp = as_user("query($id: Int!) { user(id: $id) { email } }", json!({ "id": admin })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    let resp = as_user(update, json!({ "id": admin })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    let resp = as_user("mutation($id: Int!) { deleteUser(id: $id) }", json!({ "id": own })).await;
+    assert_eq!(resp["errors"][0]["extensions"]["code"], "FORBIDDEN");
+
+    let resp = as_user("query($id: Int!) { user(id: $id) { name } }", json!({ "id": own })).await;
+    assert_eq!(resp["data"]["user"]["name"], username);
+}
+
+#[tokio::test]
+async fn create_user_retry_with_idempotency_key_is_replayed() {
+    let app = TestApp::new().await;
+    let fixture = UserFixture::new();
+    let create = || {
+        app.client
+            .post("/api/v1/users")
+            .header("idempotency-key", "create-1")
+            .body_json(&fixture.json())
+            .send()
+    };
+
+    let first = create().await;
+    first.assert_status_is_ok();
+    let first = body(first).await;
+    assert_eq!(first["code"], 200);
+
+    let retry = create().await;
+    retry.assert_status_is_ok();
+    retry.assert_header("idempotent-replayed", "true");
+    assert_eq!(body(retry).await, first);
+
+    let (_, total) = app.state.users.list(&test_actor().actor, &Default::default(), 1, 10).await.unwrap();
+    assert_eq!(total, 1);
+}
+
+#[tokio::test]
+async fn idempotency_key_reused_with_different_body_is_rejected() {
+    let app = TestApp::new().await;
+
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .header("idempotency-key", "create-1")
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .header("idempotency-key", "create-1")
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body(resp).await["code"], 422);
+}
+
+#[tokio::test]
+async fn idempotency_key_in_flight_conflicts() {
+    let app = TestApp::new().await;
+
+    // 模拟另一个实例正在处理匿名调用方使用该键的请求，租约未到期
+    sqlx::query(
+        "INSERT INTO idempotency_keys (idempotency_key, fingerprint, created_at, expires_at, locked_until) \
+         VALUES ('anonymous:create-1', 'in-flight', '2025-01-01T00:00:00Z', 32503680000, 32503680000)",
+    )
+    .execute(&app.state.pool)
+    .await
+    .unwrap();
+
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .header("idempotency-key", "create-1")
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn idempotency_key_with_expired_lease_can_be_retried() {
+    let app = TestApp::new().await;
+
+    // 处理该请求的实例已崩溃，租约到期
+    sqlx::query(
+        "INSERT INTO idempotency_keys (idempotency_key, fingerprint, created_at, expires_at, locked_until) \
+         VALUES ('anonymous:create-1', 'crashed', '2025-01-01T00:00:00Z', 32503680000, 0)",
+    )
+    .execute(&app.state.pool)
+    .await
+    .unwrap();
+
+    let fixture = UserFixture::new();
+    let create = || {
+        app.client
+            .post("/api/v1/users")
+            .header("idempotency-key", "create-1")
+            .body_json(&fixture.json())
+            .send()
+    };
+    let first = body(create().await).await;
+    assert_eq!(first["code"], 200, "{}", first);
+
+    let retry = create().await;
+    retry.assert_header("idempotent-replayed", "true");
+    assert_eq!(body(retry).await, first);
+}
+
+#[tokio::test]
+async fn idempotency_keys_are_scoped_to_the_caller() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .header("idempotency-key", "create-1")
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 200);
+
+    // 其他调用方使用相同的键不会重放或冲突
+    let resp = app
+        .client
+        .post("/api/v1/users")
+        .api_key(&key)
+        .header("idempotency-key", "create-1")
+        .body_json(&UserFixture::new().json())
+        .send()
+        .await;
+    assert!(resp.0.headers().get("idempotent-replayed").is_none());
+    assert_eq!(body(resp).await["code"], 200);
+
+    let (_, total) = app.state.users.list(&test_actor().actor, &Default::default(), 1, 10).await.unwrap();
+    assert_eq!(total, 2);
+}
+
+#[tokio::test]
+async fn idempotency_key_does_not_store_server_errors() {
+    let app = TestApp::new().await;
+    sqlx::query("CREATE TRIGGER fail_insert BEFORE INSERT ON users BEGIN SELECT RAISE(ABORT, 'unavailable'); END")
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+
+    let fixture = UserFixture::new();
+    let create = || {
+        app.client
+            .post("/api/v1/users")
+            .header("idempotency-key", "create-1")
+            .body_json(&fixture.json())
+            .send()
+    };
+
+    // 服务端错误以HTTP 200返回，状态码在响应体的 code 中，同样不保存
+    let resp = create().await;
+    resp.assert_status_is_ok();
+    assert_eq!(body(resp).await["code"], 500);
+
+    sqlx::query("DROP TRIGGER fail_insert").execute(&app.state.pool).await.unwrap();
+    let retry = create().await;
+    assert!(retry.0.headers().get("idempotent-replayed").is_none());
+    assert_eq!(body(retry).await["code"], 200);
+}
+
+#[tokio::test]
+async fn idempotency_key_is_ignored_outside_user_creation() {
+    let app = TestApp::new().await;
+    let admin = app.admin_key().await;
+    let create = || {
+        app.client
+            .post("/api/v1/api-keys")
+            .api_key(&admin)
+            .header("idempotency-key", "key-1")
+            .body_json(&json!({ "name": "billing-service", "scopes": ["read"] }))
+            .send()
+    };
+
+    // 创建API密钥的响应包含密钥原文，不保存也不重放
+    let first = body(create().await).await;
+    let retry = create().await;
+    assert!(retry.0.headers().get("idempotent-replayed").is_none());
+    let retry = body(retry).await;
+    assert_eq!(retry["code"], 200);
+    assert_ne!(retry["data"]["key"], first["data"]["key"]);
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM idempotency_keys")
+        .fetch_one(&app.state.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn idempotency_key_expires() {
+    let app = TestApp::with_config(|config| config.idempotency.ttl_secs = 0).await;
+
+    for _ in 0..2 {
+        let resp = app
+            .client
+            .post("/api/v1/users")
+            .header("idempotency-key", "create-1")
+            .body_json(&UserFixture::new().json())
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        assert_eq!(body(resp).await["code"], 200);
+    }
+}
@@ -0,0 +1,442 @@
+//! Webhook测试
+//!
+//! 投递目标为测试中启动的本地HTTP接收方，可指定返回的状态码
+
+mod common;
+
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{body, free_port, RequestExt, TestApp, UserFixture};
+use poem::{
+    handler,
+    http::{HeaderMap, StatusCode},
+    listener::TcpListener,
+    post,
+    web::Data,
+    EndpointExt, Route, Server,
+};
+use serde_json::{json, Value};
+use {{crate_name}}::config::AppConfig;
+use {{crate_name}}::webhooks::{self, DeliveryStats};
+
+const SECRET: &str = "test-secret-0123456789";
+
+/// 接收方收到的请求
+#[derive(Clone)]
+struct Received {
+    headers: HeaderMap,
+    body: String,
+}
+
+impl Received {
+    fn header(&self, name: &str) -> &str {
+        self.headers[name].to_str().unwrap()
+    }
+
+    fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+#[derive(Clone)]
+struct ReceiverState {
+    requests: Arc<Mutex<Vec<Received>>>,
+    status: Arc<AtomicU16>,
+}
+
+/// 本地HTTP接收方
+struct Receiver {
+    url: String,
+    state: ReceiverState,
+    server: tokio::task::JoinHandle<std::io::Result<()>>,
+}
+
+impl Receiver {
+    async fn start() -> Self {
+        let port = free_port();
+        let state = ReceiverState {
+            requests: Arc::default(),
+            status: Arc::new(AtomicU16::new(200)),
+        };
+        let app = Route::new().at("/hook", post(receive)).data(state.clone());
+        let server = tokio::spawn(Server::new(TcpListener::bind(format!("127.0.0.1:{}", port))).run(app));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        Self {
+            url: format!("http://127.0.0.1:{}/hook", port),
+            state,
+            server,
+        }
+    }
+
+    /// 之后的请求返回指定状态码
+    fn respond_with(&self, status: u16) {
+        self.state.status.store(status, Ordering::SeqCst);
+    }
+
+    fn requests(&self) -> Vec<Received> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+#[handler]
+fn receive(headers: &HeaderMap, body: String, state: Data<&ReceiverState>) -> StatusCode {
+    state.requests.lock().unwrap().push(Received {
+        headers: headers.clone(),
+        body,
+    });
+    StatusCode::from_u16(state.status.load(Ordering::SeqCst)).unwrap()
+}
+
+/// 允许订阅本机地址的测试应用，以及调用订阅管理接口使用的管理密钥
+async fn webhook_app(customize: impl FnOnce(&mut AppConfig)) -> (TestApp, String) {
+    let app = TestApp::with_config(|config| {
+        config.webhooks.allow_private_targets = true;
+        customize(config);
+    })
+    .await;
+    let key = app.admin_key().await;
+    (app, key)
+}
+
+/// 通过API创建订阅，返回订阅ID
+async fn create_webhook(app: &TestApp, key: &str, url: &str, event_types: &[&str]) -> u64 {
+    let resp = app
+        .client
+        .post("/api/v1/webhooks")
+        .api_key(key)
+        .body_json(&json!({ "url": url, "event_types": event_types, "secret": SECRET }))
+        .send()
+        .await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    body["data"]["id"].as_u64().unwrap()
+}
+
+/// 将发件箱中的事件转为投递记录后投递一轮
+async fn dispatch_and_deliver(app: &TestApp) -> DeliveryStats {
+    app.state.outbox.relay_once().await.unwrap();
+    app.state.webhook_sender.deliver_due().await.unwrap()
+}
+
+/// 查询订阅的投递记录，按创建时间倒序
+async fn deliveries(app: &TestApp, key: &str, webhook_id: u64) -> Vec<Value> {
+    let resp = app.client.get(format!("/api/v1/webhooks/{}/deliveries", webhook_id)).api_key(key).send().await;
+    let body = body(resp).await;
+    assert_eq!(body["code"], 200, "{}", body);
+    body["data"]["items"].as_array().unwrap().clone()
+}
+
+/// 查询订阅详情
+async fn webhook(app: &TestApp, key: &str, id: u64) -> Value {
+    let resp = app.client.get(format!("/api/v2/webhooks/{}", id)).api_key(key).send().await;
+    body(resp).await["data"].clone()
+}
+
+#[tokio::test]
+async fn user_changes_are_delivered_with_signature() {
+    let (app, key) = webhook_app(|_| {}).await;
+    let receiver = Receiver::start().await;
+    let webhook_id = create_webhook(&app, &key, &receiver.url, &["user.created", "user.deleted"]).await;
+
+    let user = app.create_user(UserFixture::new()).await;
+    let stats = dispatch_and_deliver(&app).await;
+    assert_eq!(stats, DeliveryStats { succeeded: 1, ..Default::default() });
+
+    let requests = receiver.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.header("content-type"), "application/json");
+    assert_eq!(request.header(webhooks::EVENT_HEADER), "user.created");
+
+    // 接收方用订阅的密钥验证签名
+    let timestamp: i64 = request.header(webhooks::TIMESTAMP_HEADER).parse().unwrap();
+    let signature = request.header(webhooks::SIGNATURE_HEADER);
+    assert!(signature.starts_with("sha256="));
+    assert!(webhooks::verify(SECRET, timestamp, request.body.as_bytes(), signature));
+    assert!(!webhooks::verify("another-secret-0123", timestamp, request.body.as_bytes(), signature));
+    assert!(!webhooks::verify(SECRET, timestamp + 1, request.body.as_bytes(), signature));
+
+    let payload = request.json();
+    assert_eq!(payload["type"], "user.created");
+    assert_eq!(payload["id"].to_string(), request.header(webhooks::EVENT_ID_HEADER));
+    assert_eq!(payload["data"]["user"]["username"], user.username);
+
+    let history = deliveries(&app, &key, webhook_id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["id"].to_string(), request.header(webhooks::DELIVERY_ID_HEADER));
+    assert_eq!(history[0]["status"], "succeeded");
+    assert_eq!(history[0]["attempts"], 1);
+    assert_eq!(history[0]["response_status"], 200);
+    assert!(history[0]["delivered_at"].is_string());
+    assert!(history[0]["next_attempt_at"].is_null());
+
+    // 密钥不会返回
+    assert!(webhook(&app, &key, webhook_id).await.get("secret").is_none());
+}
+
+#[tokio::test]
+async fn only_subscribed_events_are_delivered() {
+    let (app, key) = webhook_app(|_| {}).await;
+    let receiver = Receiver::start().await;
+    let webhook_id = create_webhook(&app, &key, &receiver.url, &["user.deleted"]).await;
+
+    let user = app.create_user(UserFixture::new()).await;
+    app.client
+        .delete(format!("/api/v1/users/{}", user.id.unwrap()))
+        .api_key(&key)
+        .send()
+        .await
+        .assert_status_is_ok();
+
+    // 同一事件重复投递到分发器时不重复创建记录
+    app.state.outbox.relay_once().await.unwrap();
+    assert_eq!(deliveries(&app, &key, webhook_id).await.len(), 1);
+
+    app.state.webhook_sender.deliver_due().await.unwrap();
+    let requests = receiver.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].json()["type"], "user.deleted");
+    assert_eq!(requests[0].json()["data"]["purged"], false);
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_retried_after_backoff() {
+    let (app, key) = webhook_app(|_| {}).await;
+    let receiver = Receiver::start().await;
+    receiver.respond_with(500);
+    let webhook_id = create_webhook(&app, &key, &receiver.url, &["user.created"]).await;
+    app.create_user(UserFixture::new()).await;
+
+    let stats = dispatch_and_deliver(&app).await;
+    assert_eq!(stats, DeliveryStats { retried: 1, ..Default::default() });
+
+    // 未到重试时间，不会被再次领取
+    let stats = app.state.webhook_sender.deliver_due().await.unwrap();
+    assert_eq!(stats, DeliveryStats::default());
+    assert_eq!(receiver.requests().len(), 1);
+
+    let history = deliveries(&app, &key, webhook_id).await;
+    assert_eq!(history[0]["status"], "pending");
+    assert_eq!(history[0]["response_status"], 500);
+    assert!(history[0]["error"].as_str().unwrap().contains("500"));
+    assert!(history[0]["next_attempt_at"].is_string());
+    assert_eq!(webhook(&app, &key, webhook_id).await["consecutive_failures"], 1);
+}
+
+#[tokio::test]
+async fn exhausted_deliveries_are_marked_failed_and_can_be_redelivered() {
+    let (app, key) = webhook_app(|config| {
+        config.webhooks.max_attempts = 2;
+        config.webhooks.retry_base_secs = 0;
+    })
+    .await;
+    let receiver = Receiver::start().await;
+    receiver.respond_with(503);
+    let webhook_id = create_webhook(&app, &key, &receiver.url, &["user.created"]).await;
+    app.create_user(UserFixture::new()).await;
+
+    dispatch_and_deliver(&app).await;
+    let stats = app.state.webhook_sender.deliver_due().await.unwrap();
+    assert_eq!(stats, DeliveryStats { failed: 1, ..Default::default() });
+
+    let history = deliveries(&app, &key, webhook_id).await;
+    assert_eq!(history[0]["status"], "failed");
+    assert_eq!(history[0]["attempts"], 2);
+
+    let resp = app
+        .client
+        .get(format!("/api/v1/webhooks/{}/deliveries?status=failed", webhook_id))
+        .api_key(&key)
+        .send()
+        .await;
+    assert_eq!(body(resp).await["data"]["total"], 1);
+
+    // 接收方恢复后手动重新投递，事件ID不变
+    receiver.respond_with(204);
+    let original = history[0]["id"].as_u64().unwrap();
+    let resp = app
+        .client
+        .post(format!("/api/v1/webhooks/{}/deliveries/{}/redeliver", webhook_id, original))
+        .api_key(&key)
+        .send()
+        .await;
+    let redelivery = body(resp).await["data"].clone();
+    assert_eq!(redelivery["redelivery_of"], original);
+    assert_eq!(redelivery["status"], "pending");
+
+    let stats = app.state.webhook_sender.deliver_due().await.unwrap();
+    assert_eq!(stats, DeliveryStats { succeeded: 1, ..Default::default() });
+
+    let requests = receiver.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[2].header(webhooks::EVENT_ID_HEADER), requests[0].header(webhooks::EVENT_ID_HEADER));
+    assert_eq!(requests[2].header(webhooks::DELIVERY_ID_HEADER), redelivery["id"].to_string());
+
+    let history = deliveries(&app, &key, webhook_id).await;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["status"], "succeeded");
+    assert_eq!(history[1]["status"], "failed");
+    assert_eq!(webhook(&app, &key, webhook_id).await["consecutive_failures"], 0);
+}
+
+#[tokio::test]
+async fn repeated_failures_disable_webhook_until_reenabled() {
+    let (app, key) = webhook_app(|config| {
+        config.webhooks.retry_base_secs = 0;
+        config.webhooks.disable_after_failures = 2;
+    })
+    .await;
+    let receiver = Receiver::start().await;
+    receiver.respond_with(502);
+    let webhook_id = create_webhook(&app, &key, &receiver.url, &["user.created"]).await;
+    app.create_user(UserFixture::new()).await;
+
+    dispatch_and_deliver(&app).await;
+    let stats = app.state.webhook_sender.deliver_due().await.unwrap();
+    assert_eq!(stats, DeliveryStats { retried: 1, disabled: 1, ..Default::default() });
+
+    let disabled = webhook(&app, &key, webhook_id).await;
+    assert_eq!(disabled["enabled"], false);
+    assert_eq!(disabled["consecutive_failures"], 2);
+    assert!(disabled["disabled_reason"].as_str().unwrap().contains("502"));
+
+    // 停用期间不投递，也不为新事件创建投递记录
+    app.create_user(UserFixture::new()).await;
+    assert_eq!(dispatch_and_deliver(&app).await, DeliveryStats::default());
+    assert_eq!(deliveries(&app, &key, webhook_id).await.len(), 1);
+
+    let delivery_id = deliveries(&app, &key, webhook_id).await[0]["id"].as_u64().unwrap();
+    let resp = app
+        .client
+        .post(format!("/api/v1/webhooks/{}/deliveries/{}/redeliver", webhook_id, delivery_id))
+        .api_key(&key)
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 409);
+
+    // 重新启用后清零失败次数，积压的记录继续投递
+    receiver.respond_with(200);
+    let resp = app
+        .client
+        .put(format!("/api/v1/webhooks/{}", webhook_id))
+        .api_key(&key)
+        .body_json(&json!({ "enabled": true }))
+        .send()
+        .await;
+    let enabled = body(resp).await["data"].clone();
+    assert_eq!(enabled["enabled"], true);
+    assert_eq!(enabled["consecutive_failures"], 0);
+    assert!(enabled["disabled_reason"].is_null());
+
+    let stats = app.state.webhook_sender.deliver_due().await.unwrap();
+    assert_eq!(stats, DeliveryStats { succeeded: 1, ..Default::default() });
+}
+
+#[tokio::test]
+async fn unreachable_receiver_counts_as_failure() {
+    let (app, key) = webhook_app(|_| {}).await;
+    let url = format!("http://127.0.0.1:{}/hook", free_port());
+    let webhook_id = create_webhook(&app, &key, &url, &["user.created"]).await;
+    app.create_user(UserFixture::new()).await;
+
+    let stats = dispatch_and_deliver(&app).await;
+    assert_eq!(stats.retried, 1);
+
+    let history = deliveries(&app, &key, webhook_id).await;
+    assert!(history[0]["response_status"].is_null());
+    assert!(history[0]["error"].as_str().unwrap().starts_with("请求失败"));
+}
+
+#[tokio::test]
+async fn webhooks_are_managed_through_api() {
+    let (app, key) = webhook_app(|_| {}).await;
+    let id = create_webhook(&app, &key, "https://partner.example.com/hooks", &["user.created"]).await;
+
+    let resp = app
+        .client
+        .put(format!("/api/v1/webhooks/{}", id))
+        .api_key(&key)
+        .body_json(&json!({ "event_types": ["user.updated", "user.created", "user.updated"], "description": "CRM" }))
+        .send()
+        .await;
+    let updated = body(resp).await["data"].clone();
+    assert_eq!(updated["event_types"], json!(["user.created", "user.updated"]));
+    assert_eq!(updated["description"], "CRM");
+    assert_eq!(updated["url"], "https://partner.example.com/hooks");
+
+    let resp = app.client.get("/api/v1/webhooks").api_key(&key).send().await;
+    assert_eq!(body(resp).await["data"]["items"].as_array().unwrap().len(), 1);
+
+    let resp = app
+        .client
+        .post("/api/v1/webhooks")
+        .api_key(&key)
+        .body_json(&json!({ "url": "ftp://partner.example.com", "event_types": ["user.created"], "secret": SECRET }))
+        .send()
+        .await;
+    assert_eq!(body(resp).await["code"], 400);
+
+    let resp = app
+        .client
+        .post("/api/v1/webhooks")
+        .api_key(&key)
+        .body_json(&json!({ "url": "https://partner.example.com", "event_types": ["user.created"], "secret": "short" }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    app.client.delete(format!("/api/v1/webhooks/{}", id)).api_key(&key).send().await.assert_status_is_ok();
+    let resp = app.client.get(format!("/api/v1/webhooks/{}", id)).api_key(&key).send().await;
+    assert_eq!(body(resp).await["code"], 404);
+    let resp = app.client.get(format!("/api/v1/webhooks/{}/deliveries", id)).api_key(&key).send().await;
+    assert_eq!(body(resp).await["code"], 404);
+}
+
+#[tokio::test]
+async fn private_targets_and_unprivileged_callers_are_rejected() {
+    let app = TestApp::new().await;
+    let key = app.admin_key().await;
+    let create = |url: &'static str| {
+        app.client
+            .post("/api/v1/webhooks")
+            .api_key(&key)
+            .body_json(&json!({ "url": url, "event_types": ["user.created"], "secret": SECRET }))
+            .send()
+    };
+
+    for url in [
+        "http://127.0.0.1:8080/hook",
+        "http://localhost/hook",
+        "http://10.0.0.5/hook",
+        "http://192.168.1.10/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/hook",
+        "http://[fd00::1]/hook",
+        "http://[::ffff:127.0.0.1]/hook",
+    ] {
+        let body = body(create(url).await).await;
+        assert_eq!(body["code"], 400, "{}", url);
+        assert_eq!(body["msg"], "不允许投递到本机、链路本地或内网地址");
+    }
+    assert_eq!(body(create("https://partner.example.com/hooks").await).await["code"], 200);
+
+    // 订阅管理需要管理权限
+    let resp = app.client.get("/api/v1/webhooks").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    let resp = app
+        .client
+        .post("/api/v1/webhooks")
+        .body_json(&json!({ "url": "https://partner.example.com", "event_types": ["user.created"], "secret": SECRET }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+}